@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_messages_chat_sender_created;
+
+ALTER TABLE groups
+    DROP COLUMN thread_slow_mode_secs,
+    DROP COLUMN slow_mode_secs;
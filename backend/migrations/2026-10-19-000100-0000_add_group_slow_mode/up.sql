@@ -0,0 +1,7 @@
+ALTER TABLE groups
+    ADD COLUMN slow_mode_secs INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN thread_slow_mode_secs INTEGER NOT NULL DEFAULT 0;
+
+-- Slow mode looks up a member's latest message in a chat.
+CREATE INDEX idx_messages_chat_sender_created
+    ON messages (chat_id, sender_uid, created_at DESC);
@@ -0,0 +1,2 @@
+ALTER TABLE messages DROP COLUMN is_important;
+ALTER TABLE groups DROP COLUMN announcement_only;
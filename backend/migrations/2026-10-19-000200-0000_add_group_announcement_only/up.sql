@@ -0,0 +1,4 @@
+ALTER TABLE groups ADD COLUMN announcement_only BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Kept so pushes sent after deferred publishing (audio transcode) still bypass mutes.
+ALTER TABLE messages ADD COLUMN is_important BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+DROP TABLE message_mentions;
+DROP TABLE chat_folder_chats;
+DROP TABLE chat_folders;
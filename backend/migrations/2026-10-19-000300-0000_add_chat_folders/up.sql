@@ -0,0 +1,56 @@
+CREATE TABLE chat_folders (
+    id BIGINT PRIMARY KEY,
+    uid INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    include_unread BOOLEAN NOT NULL DEFAULT FALSE,
+    include_muted BOOLEAN NOT NULL DEFAULT FALSE,
+    include_mentions BOOLEAN NOT NULL DEFAULT FALSE,
+    include_admin_of BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_chat_folders_uid_sort_order
+    ON chat_folders (uid, sort_order, id);
+
+CREATE TABLE chat_folder_chats (
+    folder_id BIGINT NOT NULL REFERENCES chat_folders(id) ON DELETE CASCADE,
+    chat_id BIGINT NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (folder_id, chat_id)
+);
+
+-- Mentions per message, kept in sync on send and edit, so folder mention badges
+-- don't scan message text. An `@[all]` mention has `mentions_all` set and no uid.
+CREATE TABLE message_mentions (
+    message_id BIGINT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    chat_id BIGINT NOT NULL,
+    uid INTEGER NULL,
+    mentions_all BOOLEAN NOT NULL DEFAULT FALSE,
+    CHECK ((uid IS NULL) = mentions_all)
+);
+
+CREATE UNIQUE INDEX idx_message_mentions_message_uid
+    ON message_mentions (message_id, uid)
+    WHERE uid IS NOT NULL;
+CREATE UNIQUE INDEX idx_message_mentions_message_all
+    ON message_mentions (message_id)
+    WHERE mentions_all;
+CREATE INDEX idx_message_mentions_uid_chat
+    ON message_mentions (uid, chat_id, message_id DESC)
+    WHERE uid IS NOT NULL;
+CREATE INDEX idx_message_mentions_all_chat
+    ON message_mentions (chat_id, message_id DESC)
+    WHERE mentions_all;
+
+INSERT INTO message_mentions (message_id, chat_id, uid)
+SELECT DISTINCT m.id, m.chat_id, (match[1])::INTEGER
+FROM messages m
+CROSS JOIN LATERAL regexp_matches(m.message, '@\[uid:(\d{1,9})\]', 'g') AS match
+WHERE m.message IS NOT NULL;
+
+INSERT INTO message_mentions (message_id, chat_id, mentions_all)
+SELECT m.id, m.chat_id, TRUE
+FROM messages m
+WHERE m.message LIKE '%@[all]%';
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_group_membership_uid_pin_order;
+
+ALTER TABLE group_membership
+    DROP COLUMN pin_order;
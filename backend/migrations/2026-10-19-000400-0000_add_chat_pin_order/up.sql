@@ -0,0 +1,6 @@
+ALTER TABLE group_membership
+    ADD COLUMN pin_order INTEGER NULL;
+
+CREATE INDEX idx_group_membership_uid_pin_order
+    ON group_membership (uid, pin_order)
+    WHERE pin_order IS NOT NULL;
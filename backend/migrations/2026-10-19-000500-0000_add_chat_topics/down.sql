@@ -0,0 +1,8 @@
+DROP TABLE topic_user_states;
+
+DROP INDEX IF EXISTS idx_messages_topic_visible_top_level;
+
+ALTER TABLE messages
+    DROP COLUMN topic_id;
+
+DROP TABLE chat_topics;
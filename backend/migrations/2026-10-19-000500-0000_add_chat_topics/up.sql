@@ -0,0 +1,33 @@
+CREATE TABLE chat_topics (
+    id BIGINT PRIMARY KEY,
+    chat_id BIGINT NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    icon_emoji TEXT NULL,
+    closed BOOLEAN NOT NULL DEFAULT FALSE,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_by_uid INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_chat_topics_chat_sort_order
+    ON chat_topics (chat_id, sort_order, id);
+
+ALTER TABLE messages
+    ADD COLUMN topic_id BIGINT NULL REFERENCES chat_topics(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_messages_topic_visible_top_level
+    ON messages (topic_id, id DESC)
+    WHERE topic_id IS NOT NULL
+      AND reply_root_id IS NULL
+      AND deleted_at IS NULL
+      AND is_published = TRUE;
+
+CREATE TABLE topic_user_states (
+    topic_id BIGINT NOT NULL REFERENCES chat_topics(id) ON DELETE CASCADE,
+    uid INTEGER NOT NULL,
+    chat_id BIGINT NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    last_read_message_id BIGINT NULL,
+    muted_until TIMESTAMPTZ NULL,
+    PRIMARY KEY (topic_id, uid)
+);
@@ -0,0 +1,3 @@
+DROP TABLE group_audit_log;
+
+DROP TYPE group_audit_action;
@@ -0,0 +1,27 @@
+CREATE TYPE group_audit_action AS ENUM (
+    'member_add',
+    'member_remove',
+    'member_role_change',
+    'group_update',
+    'message_delete',
+    'message_pin',
+    'message_unpin',
+    'invite_update',
+    'invite_revoke'
+);
+
+-- `id` is a snowflake, so entries sort by time like the other ids in the API.
+CREATE TABLE group_audit_log (
+    id BIGINT PRIMARY KEY,
+    chat_id BIGINT NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    actor_uid INTEGER NOT NULL,
+    action group_audit_action NOT NULL,
+    target_uid INTEGER NULL,
+    target_id BIGINT NULL,
+    before JSONB NULL,
+    after JSONB NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_group_audit_log_chat_id
+    ON group_audit_log (chat_id, id DESC);
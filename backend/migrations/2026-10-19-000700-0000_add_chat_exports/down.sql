@@ -0,0 +1,6 @@
+DROP TABLE chat_exports;
+
+DROP TYPE chat_export_status;
+
+ALTER TABLE groups
+    DROP COLUMN member_export_enabled;
@@ -0,0 +1,21 @@
+ALTER TABLE groups
+    ADD COLUMN member_export_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TYPE chat_export_status AS ENUM ('pending', 'running', 'completed', 'failed');
+
+CREATE TABLE chat_exports (
+    id BIGINT PRIMARY KEY,
+    chat_id BIGINT NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    requester_uid INTEGER NOT NULL,
+    include_deleted BOOLEAN NOT NULL DEFAULT FALSE,
+    status chat_export_status NOT NULL DEFAULT 'pending',
+    json_key TEXT NULL,
+    html_key TEXT NULL,
+    message_count INTEGER NULL,
+    error TEXT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX idx_chat_exports_chat_requester
+    ON chat_exports (chat_id, requester_uid, created_at DESC);
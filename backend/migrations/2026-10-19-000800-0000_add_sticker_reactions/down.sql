@@ -0,0 +1,5 @@
+ALTER TABLE groups DROP COLUMN sticker_pack_id;
+
+DELETE FROM message_reactions WHERE sticker_id IS NOT NULL;
+DROP INDEX IF EXISTS idx_message_reactions_sticker_id;
+ALTER TABLE message_reactions DROP COLUMN sticker_id;
@@ -0,0 +1,12 @@
+-- Sticker reactions keep using `emoji` as the reaction key (`sticker:<id>`), so
+-- the primary key and per-key grouping are unchanged; `sticker_id` references
+-- the sticker for rendering and cleanup.
+ALTER TABLE message_reactions
+    ADD COLUMN sticker_id BIGINT NULL REFERENCES stickers(id) ON DELETE CASCADE;
+
+CREATE INDEX idx_message_reactions_sticker_id
+    ON message_reactions (sticker_id)
+    WHERE sticker_id IS NOT NULL;
+
+ALTER TABLE groups
+    ADD COLUMN sticker_pack_id BIGINT NULL REFERENCES sticker_packs(id) ON DELETE SET NULL;
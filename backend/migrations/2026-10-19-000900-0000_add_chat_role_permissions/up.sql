@@ -0,0 +1,10 @@
+-- Per-chat overrides of which chat-scoped actions each membership role may
+-- perform. Actions without a row fall back to the built-in role defaults.
+CREATE TABLE chat_role_permissions (
+    chat_id BIGINT NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    role group_role NOT NULL,
+    action TEXT NOT NULL,
+    allowed BOOLEAN NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chat_id, role, action)
+);
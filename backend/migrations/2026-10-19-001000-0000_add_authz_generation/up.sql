@@ -0,0 +1,9 @@
+-- Bumped on every policy, rule, assignment or chat role settings change; the new
+-- value is broadcast on the `authz_invalidation` channel so every node can drop
+-- affected cache entries and detect notifications it missed.
+CREATE TABLE authz_generation (
+    id SMALLINT PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    generation BIGINT NOT NULL
+);
+
+INSERT INTO authz_generation (id, generation) VALUES (1, 0);
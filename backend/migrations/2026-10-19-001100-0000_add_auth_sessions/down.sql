@@ -0,0 +1,3 @@
+ALTER TABLE user_extra DROP COLUMN tokens_valid_after;
+
+DROP TABLE IF EXISTS auth_sessions;
@@ -0,0 +1,21 @@
+-- One row per user and client. `generation` is embedded in access tokens as the
+-- `gen` claim; bumping it revokes every token issued to that client. The refresh
+-- token is stored as an HMAC and rotated on every use.
+CREATE TABLE auth_sessions (
+    uid INTEGER NOT NULL,
+    client_id VARCHAR(64) NOT NULL REFERENCES clients (client_id) ON DELETE CASCADE,
+    generation INTEGER NOT NULL DEFAULT 0,
+    refresh_token_hash TEXT,
+    refresh_expires_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (uid, client_id)
+);
+
+CREATE UNIQUE INDEX auth_sessions_refresh_token_hash_idx
+    ON auth_sessions (refresh_token_hash)
+    WHERE refresh_token_hash IS NOT NULL;
+
+-- Set by "log out all devices": tokens issued before it are rejected even for
+-- clients without a session row.
+ALTER TABLE user_extra ADD COLUMN tokens_valid_after TIMESTAMP;
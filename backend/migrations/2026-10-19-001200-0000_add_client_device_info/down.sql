@@ -0,0 +1,3 @@
+ALTER TABLE clients
+    DROP COLUMN app_version,
+    DROP COLUMN device_name;
@@ -0,0 +1,4 @@
+-- User-chosen label and the last reported `X-App-Version`, shown in the device list.
+ALTER TABLE clients
+    ADD COLUMN device_name VARCHAR(64),
+    ADD COLUMN app_version VARCHAR(128);
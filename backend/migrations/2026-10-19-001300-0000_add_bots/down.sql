@@ -0,0 +1,2 @@
+DROP TABLE bots;
+DROP SEQUENCE bot_uid_seq;
@@ -0,0 +1,14 @@
+-- Bot identities owned by a service token. Bot uids are negative so they can
+-- never collide with Discuz member uids, and are used as `sender_uid` and in
+-- `group_membership` like any other member.
+CREATE SEQUENCE bot_uid_seq;
+
+CREATE TABLE bots (
+    uid INTEGER PRIMARY KEY DEFAULT -nextval('bot_uid_seq')::INTEGER CHECK (uid < 0),
+    service_token_id BIGINT NOT NULL UNIQUE REFERENCES service_tokens (id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    avatar_url TEXT,
+    created_by_uid INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,47 @@
+-- Outgoing webhooks. A chat webhook is owned by the chat (`chat_id`) and receives
+-- that chat's events; a global webhook is owned by a service token and receives
+-- events from every chat. `events` lists subscribed event types; empty means all.
+CREATE TABLE webhooks (
+    id BIGINT PRIMARY KEY,
+    chat_id BIGINT REFERENCES groups (id) ON DELETE CASCADE,
+    service_token_id BIGINT REFERENCES service_tokens (id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT[] NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    disabled_at TIMESTAMPTZ,
+    created_by_uid INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((chat_id IS NULL) <> (service_token_id IS NULL))
+);
+
+CREATE INDEX idx_webhooks_chat ON webhooks (chat_id) WHERE chat_id IS NOT NULL;
+CREATE INDEX idx_webhooks_service_token ON webhooks (service_token_id)
+    WHERE service_token_id IS NOT NULL;
+
+CREATE TYPE webhook_delivery_status AS ENUM ('pending', 'succeeded', 'failed');
+
+-- Durable delivery queue and log. Workers claim due rows by pushing
+-- `next_attempt_at` forward, so a crashed attempt is retried after the lease.
+CREATE TABLE webhook_deliveries (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    webhook_id BIGINT NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status webhook_delivery_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_attempt_at TIMESTAMPTZ,
+    response_status INTEGER,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries (next_attempt_at)
+    WHERE status = 'pending';
+CREATE INDEX idx_webhook_deliveries_webhook ON webhook_deliveries (webhook_id, id DESC);
+CREATE INDEX idx_webhook_deliveries_completed ON webhook_deliveries (completed_at)
+    WHERE status <> 'pending';
@@ -0,0 +1,2 @@
+DROP TABLE message_sender_overrides;
+DROP TABLE incoming_webhooks;
@@ -0,0 +1,29 @@
+-- Incoming webhooks post into a single chat through a secret URL. Each one gets
+-- its own negative sender uid from the bot sequence and joins the chat as a
+-- member, so chat role permissions apply to it like any other sender.
+CREATE TABLE incoming_webhooks (
+    id BIGINT PRIMARY KEY,
+    chat_id BIGINT NOT NULL REFERENCES groups (id) ON DELETE CASCADE,
+    uid INTEGER NOT NULL UNIQUE DEFAULT -nextval('bot_uid_seq')::INTEGER CHECK (uid < 0),
+    name VARCHAR(64) NOT NULL,
+    avatar_url TEXT,
+    secret_hash TEXT NOT NULL,
+    rate_limit_per_minute INTEGER NOT NULL DEFAULT 30 CHECK (rate_limit_per_minute > 0),
+    -- Fixed one-minute window shared by every node.
+    window_started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    window_count INTEGER NOT NULL DEFAULT 0,
+    created_by_uid INTEGER NOT NULL,
+    last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX incoming_webhooks_chat_id_idx ON incoming_webhooks (chat_id);
+
+-- Per-message display overrides, e.g. the username an incoming webhook post
+-- asked to appear under.
+CREATE TABLE message_sender_overrides (
+    message_id BIGINT PRIMARY KEY REFERENCES messages (id) ON DELETE CASCADE,
+    name VARCHAR(64),
+    avatar_url TEXT
+);
@@ -0,0 +1,2 @@
+DROP TABLE bot_command_invocations;
+DROP TABLE bot_commands;
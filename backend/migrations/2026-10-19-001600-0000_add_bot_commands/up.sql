@@ -0,0 +1,32 @@
+-- Slash commands a bot declares for one chat. Names are unique per chat, so a
+-- `/name` message resolves to exactly one bot.
+CREATE TABLE bot_commands (
+    chat_id BIGINT NOT NULL REFERENCES groups (id) ON DELETE CASCADE,
+    name VARCHAR(32) NOT NULL,
+    bot_uid INTEGER NOT NULL REFERENCES bots (uid) ON DELETE CASCADE,
+    description VARCHAR(256) NOT NULL,
+    -- Ordered `[{name, description, required}]`; the last argument takes the rest of the text.
+    args JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chat_id, name)
+);
+
+CREATE INDEX bot_commands_bot_uid_idx ON bot_commands (bot_uid, chat_id);
+
+-- A command a member ran, kept so the bot can reply to it until it expires.
+CREATE TABLE bot_command_invocations (
+    id BIGINT PRIMARY KEY,
+    chat_id BIGINT NOT NULL REFERENCES groups (id) ON DELETE CASCADE,
+    bot_uid INTEGER NOT NULL REFERENCES bots (uid) ON DELETE CASCADE,
+    command VARCHAR(32) NOT NULL,
+    invoker_uid INTEGER NOT NULL,
+    topic_id BIGINT,
+    reply_to_id BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX bot_command_invocations_expires_at_idx ON bot_command_invocations (expires_at);
+-- Per-user invocation rate limit.
+CREATE INDEX bot_command_invocations_invoker_idx ON bot_command_invocations (invoker_uid, created_at);
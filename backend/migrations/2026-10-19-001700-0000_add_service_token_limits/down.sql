@@ -0,0 +1,7 @@
+DROP TABLE service_token_usage;
+ALTER TABLE service_tokens
+    DROP COLUMN window_count,
+    DROP COLUMN window_started_at,
+    DROP COLUMN rate_limit_per_minute,
+    DROP COLUMN allowed_chat_ids,
+    DROP COLUMN expires_at;
@@ -0,0 +1,21 @@
+-- Optional expiry, chat scoping and request rate limit per service token.
+-- `allowed_chat_ids` NULL means every chat the token's policies allow.
+ALTER TABLE service_tokens
+    ADD COLUMN expires_at TIMESTAMPTZ,
+    ADD COLUMN allowed_chat_ids BIGINT[],
+    ADD COLUMN rate_limit_per_minute INTEGER CHECK (rate_limit_per_minute > 0),
+    -- Fixed one-minute window shared by every node.
+    ADD COLUMN window_started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN window_count INTEGER NOT NULL DEFAULT 0;
+
+-- One row per authenticated request, pruned after a retention period.
+CREATE TABLE service_token_usage (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    service_token_id BIGINT NOT NULL REFERENCES service_tokens (id) ON DELETE CASCADE,
+    method VARCHAR(16) NOT NULL,
+    endpoint TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX service_token_usage_token_idx ON service_token_usage (service_token_id, id DESC);
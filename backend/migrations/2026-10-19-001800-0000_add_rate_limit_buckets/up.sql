@@ -0,0 +1,11 @@
+-- Token buckets for the API rate limiter when it is configured to share state
+-- between nodes (RATE_LIMIT_STORE=postgres). Keys are `group:kind:value`.
+CREATE TABLE rate_limit_buckets (
+    key TEXT PRIMARY KEY,
+    tokens DOUBLE PRECISION NOT NULL,
+    -- Whether the latest request took a token; read back by the same statement.
+    allowed BOOLEAN NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX rate_limit_buckets_updated_at_idx ON rate_limit_buckets (updated_at);
@@ -0,0 +1,27 @@
+-- External OpenID Connect subjects linked to local (Discuz) uids.
+CREATE TABLE oidc_identities (
+    id BIGINT PRIMARY KEY,
+    issuer TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    uid INTEGER NOT NULL,
+    email TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_login_at TIMESTAMPTZ,
+    UNIQUE (issuer, subject)
+);
+
+CREATE INDEX oidc_identities_uid_idx ON oidc_identities (uid);
+
+-- Pending authorization-code logins, consumed by the callback.
+CREATE TABLE oidc_login_states (
+    state TEXT PRIMARY KEY,
+    nonce TEXT NOT NULL,
+    code_verifier TEXT NOT NULL,
+    client_id VARCHAR(64) NOT NULL,
+    -- Set when a signed-in user is linking the identity to their account.
+    link_uid INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX oidc_login_states_expires_at_idx ON oidc_login_states (expires_at);
@@ -0,0 +1,48 @@
+DELETE FROM push_subscriptions
+WHERE provider = 'fcm';
+
+ALTER TABLE push_subscriptions
+    DROP CONSTRAINT IF EXISTS push_subscriptions_provider_shape_check;
+
+DROP INDEX IF EXISTS idx_push_subscriptions_web_user_endpoint;
+
+ALTER TABLE push_subscriptions
+    ALTER COLUMN provider DROP DEFAULT;
+
+ALTER TABLE push_subscriptions
+    ALTER COLUMN provider TYPE TEXT
+    USING provider::TEXT;
+
+DROP TYPE push_provider;
+
+CREATE TYPE push_provider AS ENUM ('web_push', 'apns');
+
+ALTER TABLE push_subscriptions
+    ALTER COLUMN provider TYPE push_provider
+    USING provider::push_provider;
+
+ALTER TABLE push_subscriptions
+    ALTER COLUMN provider SET DEFAULT 'web_push';
+
+CREATE UNIQUE INDEX idx_push_subscriptions_web_user_endpoint
+    ON push_subscriptions(user_id, endpoint)
+    WHERE provider = 'web_push';
+
+ALTER TABLE push_subscriptions
+    ADD CONSTRAINT push_subscriptions_provider_shape_check
+    CHECK (
+        (
+            provider = 'web_push'
+            AND endpoint IS NOT NULL
+            AND device_token IS NULL
+            AND apns_environment IS NULL
+            AND provider_data ? 'p256dh'
+            AND provider_data ? 'auth'
+        )
+        OR (
+            provider = 'apns'
+            AND endpoint IS NULL
+            AND device_token IS NOT NULL
+            AND apns_environment IS NOT NULL
+        )
+    );
@@ -0,0 +1 @@
+ALTER TYPE push_provider ADD VALUE IF NOT EXISTS 'fcm';
@@ -0,0 +1,32 @@
+ALTER TABLE push_subscriptions
+    DROP CONSTRAINT IF EXISTS push_subscriptions_provider_shape_check;
+
+ALTER TABLE push_subscriptions
+    ADD CONSTRAINT push_subscriptions_provider_shape_check
+    CHECK (
+        (
+            provider = 'web_push'
+            AND endpoint IS NOT NULL
+            AND device_token IS NULL
+            AND apns_environment IS NULL
+            AND provider_data ? 'p256dh'
+            AND provider_data ? 'auth'
+        )
+        OR (
+            provider = 'apns'
+            AND endpoint IS NULL
+            AND device_token IS NOT NULL
+            AND apns_environment IS NOT NULL
+        )
+        OR (
+            provider = 'fcm'
+            AND endpoint IS NULL
+            AND device_token IS NOT NULL
+            AND apns_environment IS NULL
+        )
+    );
+
+-- FCM registration tokens identify one app install, regardless of which user is signed in.
+CREATE UNIQUE INDEX idx_push_subscriptions_fcm_token
+    ON push_subscriptions(device_token)
+    WHERE provider = 'fcm';
@@ -0,0 +1,19 @@
+-- Durable push notification queue. Workers claim due rows by pushing
+-- `next_attempt_at` forward, so a job whose worker crashed is retried after the
+-- lease. Delivered jobs are deleted; exhausted ones are kept as dead letters.
+CREATE TABLE push_jobs (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    chat_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    payload JSONB NOT NULL,
+    -- Set on retries to redeliver only to subscriptions that failed transiently.
+    retry_subscription_ids BIGINT[],
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    dead_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_push_jobs_due ON push_jobs (next_attempt_at) WHERE dead_at IS NULL;
+CREATE INDEX idx_push_jobs_dead ON push_jobs (dead_at) WHERE dead_at IS NOT NULL;
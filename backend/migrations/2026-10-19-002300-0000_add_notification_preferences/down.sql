@@ -0,0 +1,5 @@
+ALTER TABLE push_jobs DROP COLUMN IF EXISTS silent_uids;
+ALTER TABLE group_membership DROP COLUMN IF EXISTS notification_level;
+DROP TABLE IF EXISTS notification_preferences;
+DROP TYPE IF EXISTS quiet_hours_mode;
+DROP TYPE IF EXISTS notification_level;
@@ -0,0 +1,35 @@
+CREATE TYPE notification_level AS ENUM ('all', 'mentions_and_replies', 'nothing');
+CREATE TYPE quiet_hours_mode AS ENUM ('hold', 'silent');
+
+-- Account-wide push preferences. Users without a row get every push.
+-- Quiet hours are minutes after local midnight in `quiet_hours_timezone`;
+-- a window whose end is before its start runs past midnight.
+CREATE TABLE notification_preferences (
+    uid INTEGER PRIMARY KEY,
+    level notification_level NOT NULL DEFAULT 'all',
+    -- Lowercased words that notify like a mention.
+    keywords TEXT[] NOT NULL DEFAULT '{}',
+    quiet_hours_start SMALLINT,
+    quiet_hours_end SMALLINT,
+    quiet_hours_timezone TEXT,
+    quiet_hours_mode quiet_hours_mode NOT NULL DEFAULT 'hold',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT notification_preferences_quiet_hours_shape CHECK (
+        (quiet_hours_start IS NULL AND quiet_hours_end IS NULL AND quiet_hours_timezone IS NULL)
+        OR (
+            quiet_hours_start BETWEEN 0 AND 1439
+            AND quiet_hours_end BETWEEN 0 AND 1439
+            AND quiet_hours_start <> quiet_hours_end
+            AND quiet_hours_timezone IS NOT NULL
+        )
+    )
+);
+
+-- Per-chat override; NULL follows the account level.
+ALTER TABLE group_membership
+    ADD COLUMN notification_level notification_level NULL;
+
+-- Owners of a retried job's subscriptions who were in silent quiet hours on the
+-- first attempt, so a retry stays silent for them.
+ALTER TABLE push_jobs
+    ADD COLUMN silent_uids INTEGER[] NOT NULL DEFAULT '{}';
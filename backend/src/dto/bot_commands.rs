@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BotCommandArg {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BotCommandDefinition {
+    /// Invoked as `/name`; 1–32 lowercase letters, digits or underscores.
+    pub name: String,
+    pub description: String,
+    /// Positional arguments; the last one receives the rest of the text.
+    #[serde(default)]
+    pub args: Vec<BotCommandArg>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SetBotCommandsRequest {
+    /// Replaces every command the bot declared in the chat.
+    pub commands: Vec<BotCommandDefinition>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BotCommandResponse {
+    pub name: String,
+    pub description: String,
+    pub args: Vec<BotCommandArg>,
+    pub bot_uid: i32,
+    pub bot_name: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListBotCommandsResponse {
+    pub commands: Vec<BotCommandResponse>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct ListBotCommandsQuery {
+    /// Command name prefix, without the leading `/`.
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+/// Returned instead of a message when the posted text ran a bot command.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandInvocationResponse {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub invocation_id: i64,
+    pub command: String,
+    pub bot_uid: i32,
+}
+
+/// Sent to the bot over WebSocket and as the `command.invoked` webhook payload.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandInvokedPayload {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub invocation_id: i64,
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub chat_id: i64,
+    pub command: String,
+    /// Declared arguments bound from the text.
+    pub args: BTreeMap<String, String>,
+    /// Everything after the command name.
+    pub text: String,
+    pub invoker_uid: i32,
+    #[serde(with = "crate::serde_i64_string::opt")]
+    #[schema(value_type = Option<String>)]
+    pub topic_id: Option<i64>,
+    #[serde(with = "crate::serde_i64_string::opt")]
+    #[schema(value_type = Option<String>)]
+    pub reply_to_id: Option<i64>,
+    /// Replies are accepted until this time.
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandReplyRequest {
+    pub text: String,
+    /// Show the reply only to the member who ran the command instead of posting it.
+    #[serde(default)]
+    pub ephemeral: bool,
+    /// Idempotency key for public replies; retrying with the same value returns the
+    /// original message.
+    #[serde(default)]
+    pub client_generated_id: Option<String>,
+}
+
+/// Ephemeral bot reply, delivered over WebSocket to the invoker only and never stored.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandReplyPayload {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub invocation_id: i64,
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub chat_id: i64,
+    pub command: String,
+    pub bot_uid: i32,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
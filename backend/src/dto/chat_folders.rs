@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Rule-based inclusion for a folder. A chat matches when any enabled rule matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatFolderRules {
+    #[serde(default)]
+    pub include_unread: bool,
+    #[serde(default)]
+    pub include_muted: bool,
+    #[serde(default)]
+    pub include_mentions: bool,
+    #[serde(default)]
+    pub include_admin_of: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateChatFolderRequest {
+    pub name: String,
+    #[serde(default)]
+    pub rules: ChatFolderRules,
+    #[serde(default)]
+    pub chat_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateChatFolderRequest {
+    pub name: Option<String>,
+    pub rules: Option<ChatFolderRules>,
+    /// Replaces the folder's explicit chat list when present.
+    pub chat_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderChatFoldersRequest {
+    pub folder_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatFolderResponse {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub id: i64,
+    pub name: String,
+    pub sort_order: i32,
+    pub rules: ChatFolderRules,
+    /// Explicitly added chats; rule matches are not listed here.
+    pub chat_ids: Vec<String>,
+    /// Unread messages across unmuted chats in the folder, capped like the global total.
+    pub unread_count: i64,
+    pub unread_chat_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListChatFoldersResponse {
+    pub folders: Vec<ChatFolderResponse>,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::GroupRole;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatRoleActions {
+    pub role: GroupRole,
+    /// Chat actions members with this role may perform, e.g. `chat.pin`.
+    pub allowed_actions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatPermissionsResponse {
+    pub roles: Vec<ChatRoleActions>,
+    /// Every action the role settings can grant.
+    pub available_actions: Vec<String>,
+    /// Actions the requester may perform in this chat, including policy grants.
+    pub my_actions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateChatPermissionsRequest {
+    /// Roles to replace; roles not listed keep their current settings.
+    pub roles: Vec<ChatRoleActions>,
+}
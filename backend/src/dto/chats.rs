@@ -19,6 +19,8 @@ pub struct ChatListItem {
     pub last_message: Option<MessagePreview>,
     pub muted_until: Option<DateTime<Utc>>,
     pub archived: bool,
+    /// Position among the user's pinned chats; `None` when not pinned.
+    pub pin_order: Option<i32>,
 }
 
 #[derive(Serialize, utoipa::ToSchema)]
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceResponse {
+    pub client_id: String,
+    pub name: Option<String>,
+    /// `ios`, `android`, `web`, ... derived from the last reported `X-App-Version`.
+    pub platform: String,
+    pub app_version: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_active_at: DateTime<Utc>,
+    pub has_push_subscription: bool,
+    /// Whether the device has a live WebSocket connection on this node.
+    pub online: bool,
+    /// Whether this is the device making the request.
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDevicesResponse {
+    pub devices: Vec<DeviceResponse>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDeviceRequest {
+    /// An empty string or null clears the name.
+    pub name: Option<String>,
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::GroupVisibility;
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalImportRequest {
+    /// Existing chat to import into. Mutually exclusive with `newChat`.
+    #[serde(
+        default,
+        deserialize_with = "crate::serde_i64_string::opt::deserialize"
+    )]
+    #[schema(value_type = Option<String>)]
+    pub chat_id: Option<i64>,
+    /// Create a new chat for the imported history.
+    pub new_chat: Option<ExternalImportNewChat>,
+    /// Export sender ids (Telegram `from_id`, e.g. `user123`) to Discuz uids.
+    #[serde(default)]
+    pub sender_map: HashMap<String, i32>,
+    /// Uid used for senders missing from `senderMap`; unmapped senders are rejected without it.
+    pub fallback_sender_uid: Option<i32>,
+    /// Import replies as thread replies under the root of their reply chain
+    /// instead of inline quote replies.
+    #[serde(default)]
+    pub replies_as_threads: bool,
+    /// Export media paths (`photo` / `file`) to objects already uploaded to attachment storage.
+    #[serde(default)]
+    pub media: HashMap<String, ExternalImportMedia>,
+    pub export: TelegramExport,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalImportNewChat {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub visibility: Option<GroupVisibility>,
+    /// Becomes the chat's first admin.
+    pub owner_uid: i32,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalImportMedia {
+    pub storage_key: String,
+    pub content_type: String,
+    pub size: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+/// Telegram Desktop "Export chat history" JSON (`result.json`); unknown fields are ignored.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TelegramExport {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<TelegramExportMessage>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TelegramExportMessage {
+    pub id: i64,
+    /// `message` or `service`; service entries (joins, pins, ...) are skipped.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub date_unixtime: String,
+    pub edited_unixtime: Option<String>,
+    pub from_id: Option<String>,
+    /// Either a plain string or an array of strings and `{type, text}` entities.
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub text: serde_json::Value,
+    pub reply_to_message_id: Option<i64>,
+    pub photo: Option<String>,
+    pub file: Option<String>,
+    pub file_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalImportResponse {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub chat_id: i64,
+    pub created_chat: bool,
+    pub imported_count: usize,
+    /// Messages already imported by an earlier run with the same source ids.
+    pub skipped_existing: usize,
+    pub skipped_service: usize,
+    /// Entries with no text whose media was absent or not supplied.
+    pub skipped_empty: usize,
+    /// Media paths referenced by the export but missing from `media`.
+    pub missing_media: Vec<String>,
+}
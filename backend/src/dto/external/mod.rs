@@ -1 +1,3 @@
+pub mod imports;
 pub mod invites;
+pub mod messages;
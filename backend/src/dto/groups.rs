@@ -1,8 +1,8 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-use crate::models::{GroupRole, GroupVisibility};
+use crate::models::{GroupRole, GroupVisibility, NotificationLevel};
 
 #[derive(Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -29,7 +29,21 @@ pub struct GroupInfoResponse {
     pub visibility: GroupVisibility,
     pub created_at: DateTime<Utc>,
     pub muted_until: Option<DateTime<Utc>>,
+    /// The caller's push level for this chat; null follows the account setting.
+    pub notification_level: Option<NotificationLevel>,
     pub my_role: Option<GroupRole>,
+    /// Seconds a non-admin member must wait between top-level messages; 0 when off.
+    pub slow_mode_secs: i32,
+    /// Seconds a non-admin member must wait between thread replies; 0 when off.
+    pub thread_slow_mode_secs: i32,
+    /// When set, only admins and holders of `chat.announce` may post top-level messages.
+    pub announcement_only: bool,
+    /// When set, non-admin members may export the chat history they can see.
+    pub member_export_enabled: bool,
+    /// Sticker pack every member may react with in this chat.
+    #[serde(with = "crate::serde_i64_string::opt")]
+    #[schema(value_type = Option<String>)]
+    pub sticker_pack_id: Option<i64>,
 }
 
 #[derive(Serialize, utoipa::ToSchema)]
@@ -67,3 +81,16 @@ pub struct AvatarUploadUrlResponse {
 pub struct MuteResponse {
     pub muted_until: DateTime<Utc>,
 }
+
+#[derive(Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SetChatNotificationLevelRequest {
+    /// Null clears the override and follows the account setting.
+    pub notification_level: Option<NotificationLevel>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatNotificationLevelResponse {
+    pub notification_level: Option<NotificationLevel>,
+}
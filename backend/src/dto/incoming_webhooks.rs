@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateIncomingWebhookRequest {
+    /// Sender name shown on posted messages.
+    pub name: String,
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+    /// Defaults to 30 posts per minute.
+    #[serde(default)]
+    pub rate_limit_per_minute: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateIncomingWebhookRequest {
+    pub name: Option<String>,
+    /// An empty string clears the avatar.
+    pub avatar_url: Option<String>,
+    pub rate_limit_per_minute: Option<i32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingWebhookResponse {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub id: i64,
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub chat_id: i64,
+    /// Negative uid the webhook posts as.
+    pub uid: i32,
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub rate_limit_per_minute: i32,
+    pub created_by_uid: i32,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingWebhookSecretResponse {
+    pub webhook: IncomingWebhookResponse,
+    /// Path to POST to, relative to the API root; contains the secret and is only returned once.
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListIncomingWebhooksResponse {
+    pub webhooks: Vec<IncomingWebhookResponse>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingWebhookAttachment {
+    /// Public http(s) URL the file is downloaded from.
+    pub url: String,
+    /// Defaults to the last segment of the URL path.
+    #[serde(default)]
+    pub file_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingWebhookMessageRequest {
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Overrides the webhook's name for this message only.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Overrides the webhook's avatar for this message only.
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<IncomingWebhookAttachment>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingWebhookMessageResponse {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub message_id: i64,
+}
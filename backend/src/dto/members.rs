@@ -13,6 +13,7 @@ pub struct MemberResponse {
     pub avatar_url: Option<String>,
     pub gender: i16,
     pub user_group: Option<UserGroupTagInfo>,
+    pub is_bot: bool,
 }
 
 #[derive(Serialize, utoipa::ToSchema)]
@@ -1,5 +1,5 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     dto::{attachments::AttachmentResponse, users::User},
@@ -51,6 +51,9 @@ pub struct MessageResponse {
     pub reactions: Vec<ReactionSummary>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub mentions: Vec<MentionInfo>,
+    #[serde(with = "crate::serde_i64_string::opt")]
+    #[schema(value_type = Option<String>)]
+    pub topic_id: Option<i64>,
 }
 
 #[derive(Serialize, utoipa::ToSchema)]
@@ -91,6 +94,9 @@ pub struct ReactionSummary {
     pub reacted_by_me: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reactors: Option<Vec<ReactionReactor>>,
+    /// Sticker media when `emoji` is a `sticker:<id>` reaction key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sticker: Option<MessageStickerResponse>,
 }
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -112,7 +118,7 @@ pub struct MessagePreviewSticker {
     pub emoji: String,
 }
 
-#[derive(Debug, Serialize, Clone, PartialEq, Eq, utoipa::ToSchema)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MessagePreviewAttachment {
     pub kind: String,
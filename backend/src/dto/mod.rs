@@ -1,15 +1,26 @@
 pub mod attachments;
+pub mod audit_log;
+pub mod bot_commands;
+pub mod chat_exports;
+pub mod chat_folders;
+pub mod chat_permissions;
 pub mod chats;
+pub mod devices;
 pub mod external;
 pub mod groups;
+pub mod incoming_webhooks;
 pub mod invites;
 pub mod members;
 pub mod messages;
+pub mod oidc;
 pub mod pins;
+pub mod policies;
 pub mod push;
 pub mod saved_messages;
 pub mod service_tokens;
 pub mod stickers;
 pub mod threads;
+pub mod topics;
 pub mod users;
+pub mod webhooks;
 pub mod ws;
@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::{GroupRole, PermissionResourceType, PolicySubjectType};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePolicyRequest {
+    pub name: String,
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub permissions: Vec<CreatePolicyPermissionRequest>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePolicyRequest {
+    pub name: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePolicyPermissionRequest {
+    /// Action string such as `chat.create` or `permission.all`.
+    pub action: String,
+    pub resource_type: PermissionResourceType,
+    /// Chat ID for `chat` rules; must be absent for `global` rules.
+    #[serde(default, with = "crate::serde_i64_string::opt")]
+    #[schema(value_type = Option<String>)]
+    pub resource_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePolicyAssignmentRequest {
+    pub subject_type: PolicySubjectType,
+    /// User uid, Discuz groupid or service token ID, depending on `subjectType`.
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub subject_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyPermissionResponse {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub id: i64,
+    pub action: String,
+    pub resource_type: PermissionResourceType,
+    #[serde(with = "crate::serde_i64_string::opt")]
+    #[schema(value_type = Option<String>)]
+    pub resource_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyAssignmentResponse {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub id: i64,
+    pub subject_type: PolicySubjectType,
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub subject_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyResponse {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub id: i64,
+    pub name: String,
+    #[schema(value_type = Object)]
+    pub metadata: serde_json::Value,
+    pub permissions: Vec<PolicyPermissionResponse>,
+    pub assignments: Vec<PolicyAssignmentResponse>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPoliciesResponse {
+    pub policies: Vec<PolicyResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionGrantResponse {
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub policy_id: i64,
+    pub policy_name: String,
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub permission_id: i64,
+    pub action: String,
+    pub resource_type: PermissionResourceType,
+    #[serde(with = "crate::serde_i64_string::opt")]
+    #[schema(value_type = Option<String>)]
+    pub resource_id: Option<i64>,
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub assignment_id: i64,
+    /// How the policy reaches the subject: directly, or through its Discuz group.
+    pub subject_type: PolicySubjectType,
+    #[serde(with = "crate::serde_i64_string")]
+    #[schema(value_type = String)]
+    pub subject_id: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatRoleDecisionResponse {
+    pub role: GroupRole,
+    pub allowed: bool,
+    /// Whether the chat overrides the built-in default for this role.
+    pub overridden: bool,
+}
+
+/// Why the request is refused even if a rule grants the action.
+#[derive(Debug, Clone, Copy, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ExplainDenyReason {
+    ServiceTokenRevoked,
+    ServiceTokenExpired,
+    /// The chat is outside the service token's allowed chats.
+    ChatNotAllowed,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainPermissionResponse {
+    pub allowed: bool,
+    pub action: String,
+    pub resource_type: PermissionResourceType,
+    #[serde(with = "crate::serde_i64_string::opt")]
+    #[schema(value_type = Option<String>)]
+    pub resource_id: Option<i64>,
+    pub discuz_group_id: Option<i32>,
+    /// Rules granting the action, directly or via `permission.all`.
+    pub grants: Vec<PermissionGrantResponse>,
+    /// The chat role check, for chat role actions when the user is a member.
+    pub chat_role: Option<ChatRoleDecisionResponse>,
+    pub deny_reasons: Vec<ExplainDenyReason>,
+}
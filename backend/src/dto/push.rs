@@ -1,6 +1,8 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::models::{NotificationLevel, QuietHoursMode};
+
 #[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct VapidPublicKeyResponse {
@@ -14,3 +16,35 @@ pub struct SubscriptionStatusResponse {
     pub has_matching_subscription: Option<bool>,
     pub has_matching_endpoint: Option<bool>,
 }
+
+/// A daily quiet-hours window; `end` before `start` runs past midnight.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHoursBody {
+    /// Local start time, `HH:MM`.
+    pub start: String,
+    /// Local end time, `HH:MM`.
+    pub end: String,
+    /// IANA timezone name, e.g. `Europe/Berlin`.
+    pub timezone: String,
+    /// `hold` delivers when quiet hours end; `silent` delivers without sound.
+    pub mode: QuietHoursMode,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNotificationPreferencesRequest {
+    pub level: NotificationLevel,
+    /// Words that notify like a mention; matched case-insensitively as whole words.
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    pub quiet_hours: Option<QuietHoursBody>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPreferencesResponse {
+    pub level: NotificationLevel,
+    pub keywords: Vec<String>,
+    pub quiet_hours: Option<QuietHoursBody>,
+}
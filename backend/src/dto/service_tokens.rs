@@ -1,5 +1,5 @@
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use utoipa::ToSchema;
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -8,6 +8,41 @@ pub struct CreateServiceTokenRequest {
     pub name: String,
     #[serde(default)]
     pub policy_ids: Vec<String>,
+    /// The token stops authenticating after this time.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Restrict the token to these chats; omit for no restriction.
+    #[serde(default)]
+    pub allowed_chat_ids: Option<Vec<String>>,
+    /// Requests per minute the token may make; omit for no limit.
+    #[serde(default)]
+    pub rate_limit_per_minute: Option<i32>,
+}
+
+/// Omitted fields are left unchanged; `null` removes the expiry, restriction or limit.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateServiceTokenRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub expires_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub allowed_chat_ids: Option<Option<Vec<String>>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<i32>)]
+    pub rate_limit_per_minute: Option<Option<i32>>,
+}
+
+/// Distinguishes an explicit `null` (`Some(None)`) from an omitted field (`None`).
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -23,6 +58,22 @@ pub struct ServiceTokenResponse {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub policy_ids: Vec<String>,
+    pub bot: Option<BotResponse>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub allowed_chat_ids: Option<Vec<String>>,
+    pub rate_limit_per_minute: Option<i32>,
+    /// Most recent requests made with the token, newest first.
+    pub recent_usage: Vec<ServiceTokenUsageResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceTokenUsageResponse {
+    pub method: String,
+    /// Route template, e.g. `/external/messages/{chat_id}`.
+    pub endpoint: String,
+    pub status: i32,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -44,3 +95,23 @@ pub struct RotateServiceTokenResponse {
     pub service_token: ServiceTokenResponse,
     pub credential: String,
 }
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertBotRequest {
+    pub name: String,
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BotResponse {
+    /// Negative uid the bot posts and joins groups as.
+    pub uid: i32,
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub created_by_uid: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -18,6 +18,9 @@ pub struct User {
     pub name: Option<String>,
     pub gender: i16,
     pub user_group: Option<UserGroupTagInfo>,
+    /// Sender is a bot posting through a service token.
+    #[serde(default)]
+    pub is_bot: bool,
 }
 
 #[derive(Debug, Clone, serde::Deserialize, Serialize, ToSchema)]
@@ -39,8 +42,20 @@ pub struct MeResponse {
 }
 
 #[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct AuthTokenResponse {
+    /// Short-lived access token, sent as `Authorization: Bearer`.
     pub token: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    /// Single-use token for `POST /users/auth-token/refresh`.
+    pub refresh_token: String,
+    pub refresh_expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshAuthTokenRequest {
+    pub refresh_token: String,
 }
 
 #[derive(Debug, Clone, Serialize, ToSchema)]
@@ -2,6 +2,9 @@ use chrono::{DateTime, Utc};
 use serde::Serialize;
 
 use crate::dto::{
+    bot_commands::{CommandInvokedPayload, CommandReplyPayload},
+    chat_exports::ChatExportResponse,
+    chat_folders::ChatFolderResponse,
     messages::{MessageResponse, ReactionSummary},
     pins::PinResponse,
     users::StickerPackOrderItem,
@@ -35,6 +38,11 @@ pub enum ServerWsMessage {
     PinAdded(PinUpdatePayload),
     PinRemoved(PinUpdatePayload),
     StickerPackOrderUpdated(StickerPackOrderUpdatePayload),
+    ChatFoldersUpdated(ChatFoldersUpdatedPayload),
+    ChatPinsChanged(ChatPinsChangedPayload),
+    ChatExportUpdated(ChatExportResponse),
+    CommandInvoked(CommandInvokedPayload),
+    CommandReply(CommandReplyPayload),
 }
 
 impl ServerWsMessage {
@@ -52,6 +60,11 @@ impl ServerWsMessage {
             Self::PinAdded(_) => "pinAdded",
             Self::PinRemoved(_) => "pinRemoved",
             Self::StickerPackOrderUpdated(_) => "stickerPackOrderUpdated",
+            Self::ChatFoldersUpdated(_) => "chatFoldersUpdated",
+            Self::ChatPinsChanged(_) => "chatPinsChanged",
+            Self::ChatExportUpdated(_) => "chatExportUpdated",
+            Self::CommandInvoked(_) => "commandInvoked",
+            Self::CommandReply(_) => "commandReply",
         }
     }
 }
@@ -109,6 +122,13 @@ pub struct ChatArchiveStateChangedPayload {
     pub muted_until: Option<DateTime<Utc>>,
 }
 
+/// The user's full pinned chat list, in display order, after any pin change.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatPinsChangedPayload {
+    pub pinned_chat_ids: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PinUpdatePayload {
@@ -131,6 +151,13 @@ pub struct StickerPackOrderUpdatePayload {
     pub order: Vec<StickerPackOrderItem>,
 }
 
+/// Full folder list after any folder change, so other devices can replace their copy.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatFoldersUpdatedPayload {
+    pub folders: Vec<ChatFolderResponse>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::{PresenceUpdatePayload, ServerWsMessage, ThreadMembershipChangedPayload};
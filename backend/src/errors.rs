@@ -1,11 +1,12 @@
+use axum::http::header::RETRY_AFTER;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 
 /// Unified error type for handler functions, replacing repetitive `.map_err()` boilerplate.
 ///
 /// Common database and pool errors implement `From`, so bare `?` works for the 500 case.
-/// Handlers can explicitly return `NotFound`, `Forbidden`, `BadRequest`, `Conflict`, or `Gone`
-/// for non-500 status codes.
+/// Handlers can explicitly return `NotFound`, `Forbidden`, `BadRequest`, `Conflict`, `Gone`, or
+/// `TooManyRequests` for non-500 status codes.
 #[derive(Debug)]
 pub enum AppError {
     /// r2d2 pool error (failed to acquire a DB connection).
@@ -24,6 +25,11 @@ pub enum AppError {
     Conflict(&'static str),
     /// 410 Gone with a static message.
     Gone(&'static str),
+    /// 429 Too Many Requests with a static message and a `Retry-After` hint in seconds.
+    TooManyRequests {
+        message: &'static str,
+        retry_after_secs: u64,
+    },
     /// 503 Service Unavailable with a static message.
     ServiceUnavailable(&'static str),
     /// Generic internal server error with a static message (for non-diesel/pool errors).
@@ -81,6 +87,15 @@ impl IntoResponse for AppError {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg).into_response(),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg).into_response(),
             AppError::Gone(msg) => (StatusCode::GONE, msg).into_response(),
+            AppError::TooManyRequests {
+                message,
+                retry_after_secs,
+            } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, retry_after_secs.to_string())],
+                message,
+            )
+                .into_response(),
             AppError::ServiceUnavailable(msg) => {
                 (StatusCode::SERVICE_UNAVAILABLE, msg).into_response()
             }
@@ -100,4 +115,21 @@ mod tests {
         let response = AppError::ServiceUnavailable("Message search unavailable").into_response();
         assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
     }
+
+    #[test]
+    fn too_many_requests_maps_to_429_with_retry_after() {
+        let response = AppError::TooManyRequests {
+            message: "Slow mode is enabled",
+            retry_after_secs: 12,
+        }
+        .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            response
+                .headers()
+                .get(axum::http::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok()),
+            Some("12")
+        );
+    }
 }
@@ -0,0 +1,110 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use utoipa_axum::router::OpenApiRouter;
+
+use crate::dto::audit_log::{AuditLogEntryResponse, ListAuditLogResponse};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::handlers::chats::build_sender;
+use crate::handlers::members::require_admin_role;
+use crate::models::GroupAuditAction;
+use crate::services::audit_log::{load_audit_log_page, AuditLogFilter};
+use crate::services::user::{lookup_user_avatars, lookup_user_profiles};
+use crate::utils::{auth::CurrentUid, pagination::validate_limit};
+use crate::AppState;
+
+const MAX_AUDIT_LOG_LIMIT: i64 = 100;
+
+#[derive(serde::Deserialize)]
+pub struct ChatIdPath {
+    pub chat_id: i64,
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[serde(rename_all = "camelCase")]
+struct ListAuditLogQuery {
+    /// Page size limit.
+    limit: Option<i64>,
+    /// Cursor: only entries older than this ID.
+    #[serde(
+        default,
+        deserialize_with = "crate::serde_i64_string::opt::deserialize"
+    )]
+    #[param(value_type = Option<String>)]
+    before: Option<i64>,
+    /// Only entries of this action.
+    action: Option<GroupAuditAction>,
+    /// Only entries performed by this user.
+    actor_uid: Option<i32>,
+    /// Only entries targeting this user.
+    target_uid: Option<i32>,
+}
+
+/// GET /group/:chat_id/audit-log — List administrative actions in a chat, newest first (admin only).
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ListAuditLogQuery,
+    ),
+    responses(
+        (status = OK, body = ListAuditLogResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn get_audit_log(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+    Query(q): Query<ListAuditLogQuery>,
+) -> Result<Json<ListAuditLogResponse>, AppError> {
+    let conn = &mut *conn;
+
+    require_admin_role(conn, chat_id, uid)?;
+
+    let limit = validate_limit(q.limit, MAX_AUDIT_LOG_LIMIT);
+    let filter = AuditLogFilter {
+        action: q.action,
+        actor_uid: q.actor_uid,
+        target_uid: q.target_uid,
+    };
+    let mut rows = load_audit_log_page(conn, chat_id, &filter, q.before, limit + 1)?;
+
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+    let next_cursor = has_more.then(|| rows.last().map(|row| row.id)).flatten();
+
+    let mut actor_uids: Vec<i32> = rows.iter().map(|row| row.actor_uid).collect();
+    actor_uids.sort_unstable();
+    actor_uids.dedup();
+    let profiles = lookup_user_profiles(conn, &actor_uids)?;
+    let avatars = lookup_user_avatars(&state, &actor_uids);
+
+    let entries = rows
+        .into_iter()
+        .map(|row| AuditLogEntryResponse {
+            id: row.id,
+            action: row.action,
+            actor: build_sender(row.actor_uid, &avatars, &profiles),
+            target_uid: row.target_uid,
+            target_id: row.target_id,
+            before: row.before,
+            after: row.after,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(Json(ListAuditLogResponse {
+        entries,
+        next_cursor,
+    }))
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new().routes(utoipa_axum::routes!(get_audit_log))
+}
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query},
+    Json,
+};
+use diesel::PgConnection;
+use serde::Deserialize;
+use utoipa_axum::router::OpenApiRouter;
+
+use crate::dto::bot_commands::{
+    CommandInvocationResponse, CommandInvokedPayload, ListBotCommandsQuery, ListBotCommandsResponse,
+};
+use crate::dto::ws::ServerWsMessage;
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::handlers::chats::{
+    enforce_send_policy, enforce_slow_mode, CreateMessageBody, PreparedMessageSend,
+};
+use crate::handlers::members::check_membership;
+use crate::models::MessageType;
+use crate::services::bot_commands as bot_command_service;
+use crate::services::webhooks::{self as webhook_service, WebhookEvent};
+use crate::utils::auth::CurrentUid;
+use crate::utils::ids;
+use crate::AppState;
+
+#[derive(Deserialize)]
+struct ChatIdPath {
+    chat_id: i64,
+}
+
+/// GET /chats/:chat_id/commands — Slash commands available in the chat, for autocomplete.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "chats",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ListBotCommandsQuery,
+    ),
+    responses(
+        (status = OK, body = ListBotCommandsResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn list_chat_commands(
+    CurrentUid(uid): CurrentUid,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    Query(query): Query<ListBotCommandsQuery>,
+    mut conn: DbConn,
+) -> Result<Json<ListBotCommandsResponse>, AppError> {
+    let conn = &mut *conn;
+    check_membership(conn, chat_id, uid)?;
+
+    let commands = bot_command_service::list_commands(conn, chat_id, query.prefix.as_deref())?
+        .into_iter()
+        .map(|(command, bot)| bot_command_service::command_to_response(command, &bot))
+        .collect();
+
+    Ok(Json(ListBotCommandsResponse { commands }))
+}
+
+/// If `body` is a `/command` registered in the chat, hand it to the owning bot instead of
+/// posting it. A bot with a live WebSocket session gets it there; otherwise it is queued
+/// for the bot's service-token webhooks. Returns `None` for ordinary messages, including
+/// unknown commands. Callers check membership first.
+pub(crate) async fn try_invoke_command(
+    conn: &mut PgConnection,
+    state: &AppState,
+    chat_id: i64,
+    uid: i32,
+    body: &CreateMessageBody,
+) -> Result<Option<CommandInvocationResponse>, AppError> {
+    if !matches!(body.message_type, MessageType::Text) || !body.attachment_ids.is_empty() {
+        return Ok(None);
+    }
+    let Some((name, text)) = body
+        .message
+        .as_deref()
+        .and_then(bot_command_service::parse_command_text)
+    else {
+        return Ok(None);
+    };
+    let Some((command, bot)) = bot_command_service::find_command(conn, chat_id, &name)? else {
+        return Ok(None);
+    };
+    let args = bot_command_service::bind_args(&bot_command_service::command_args(&command), text)?;
+
+    // The command stands in for a top-level post, so it is held to the same rules.
+    enforce_slow_mode(conn, chat_id, uid, false)?;
+    enforce_send_policy(
+        conn,
+        state,
+        &PreparedMessageSend {
+            chat_id,
+            sender_uid: uid,
+            message: body.message.clone(),
+            message_type: MessageType::Text,
+            sticker_id: None,
+            reply_to_id: body.reply_to_id,
+            reply_root_id: None,
+            client_generated_id: body.client_generated_id.clone(),
+            attachment_ids: Vec::new(),
+            publish_immediately: true,
+            important: body.important,
+            topic_id: body.topic_id,
+            slow_mode: true,
+        },
+    )?;
+    bot_command_service::check_invocation_rate(conn, uid)?;
+
+    let via_ws = state.ws_registry.is_connected(bot.uid);
+    if !via_ws
+        && !webhook_service::service_token_subscribes_to(
+            conn,
+            bot.service_token_id,
+            WebhookEvent::CommandInvoked,
+        )?
+    {
+        return Err(AppError::ServiceUnavailable("Bot is not reachable"));
+    }
+
+    let id = ids::next_id(state.id_gen.as_ref()).await.map_err(|e| {
+        tracing::error!("next_id for command invocation: {:?}", e);
+        AppError::Internal("ID generation failed")
+    })?;
+    let invocation = bot_command_service::create_invocation(
+        conn,
+        id,
+        &command,
+        uid,
+        body.topic_id,
+        body.reply_to_id,
+    )?;
+
+    let payload = CommandInvokedPayload {
+        invocation_id: invocation.id,
+        chat_id,
+        command: invocation.command.clone(),
+        args,
+        text: text.to_string(),
+        invoker_uid: uid,
+        topic_id: invocation.topic_id,
+        reply_to_id: invocation.reply_to_id,
+        expires_at: invocation.expires_at,
+    };
+    if via_ws {
+        state.ws_registry.broadcast_to_uids(
+            &[bot.uid],
+            Arc::new(ServerWsMessage::CommandInvoked(payload)),
+        );
+    } else {
+        state.webhook_service.publish_to_service_token(
+            bot.service_token_id,
+            chat_id,
+            WebhookEvent::CommandInvoked,
+            &payload,
+        );
+    }
+
+    Ok(Some(CommandInvocationResponse {
+        invocation_id: invocation.id,
+        command: invocation.command,
+        bot_uid: bot.uid,
+    }))
+}
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(utoipa_axum::routes!(list_chat_commands))
+}
@@ -0,0 +1,174 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::dto::chat_exports::{ChatExportResponse, CreateChatExportRequest};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::models::{ChatExportStatus, GroupRole, NewChatExport};
+use crate::schema::{chat_exports, group_membership, groups};
+use crate::services::background::BackgroundJob;
+use crate::services::chat_export::{
+    build_export_response, has_export_in_progress, load_chat_export, mark_export_failed,
+    reap_stale_exports,
+};
+use crate::utils::{auth::CurrentUid, ids};
+use crate::AppState;
+
+#[derive(serde::Deserialize)]
+pub struct ChatIdPath {
+    pub chat_id: i64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ExportPath {
+    chat_id: i64,
+    export_id: i64,
+}
+
+async fn export_response(
+    state: &AppState,
+    export: crate::models::ChatExport,
+) -> Result<ChatExportResponse, AppError> {
+    build_export_response(&state.s3_client, &state.s3_bucket_name, export)
+        .await
+        .map_err(|e| {
+            tracing::error!("presign chat export download: {}", e);
+            AppError::Internal("Failed to generate download links")
+        })
+}
+
+/// Membership and role check shared by export creation and status lookups, so a
+/// requester who left the chat or lost admin can no longer fetch download links.
+fn authorize_export(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    uid: i32,
+    include_deleted: bool,
+) -> Result<(), AppError> {
+    let role: GroupRole = group_membership::table
+        .filter(group_membership::chat_id.eq(chat_id))
+        .filter(group_membership::uid.eq(uid))
+        .select(group_membership::role)
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::Forbidden("Not a member of this chat"))?;
+    let is_admin = matches!(role, GroupRole::Admin);
+    if !is_admin {
+        let member_export_enabled: bool = groups::table
+            .filter(groups::id.eq(chat_id))
+            .select(groups::member_export_enabled)
+            .first(conn)?;
+        if !member_export_enabled {
+            return Err(AppError::Forbidden("Admin role required"));
+        }
+        if include_deleted {
+            return Err(AppError::Forbidden(
+                "Only admins can export deleted messages",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// POST /group/:chat_id/exports — Request an export of the chat history (admins, or members when enabled).
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    request_body = CreateChatExportRequest,
+    responses(
+        (status = ACCEPTED, body = ChatExportResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn post_chat_export(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+    Json(body): Json<CreateChatExportRequest>,
+) -> Result<(StatusCode, Json<ChatExportResponse>), AppError> {
+    let conn = &mut *conn;
+
+    authorize_export(conn, chat_id, uid, body.include_deleted)?;
+
+    reap_stale_exports(conn)?;
+    if has_export_in_progress(conn, chat_id, uid)? {
+        return Err(AppError::Conflict("An export is already in progress"));
+    }
+
+    let export_id = ids::next_id(state.id_gen.as_ref()).await.map_err(|e| {
+        tracing::error!("next_id for chat export: {:?}", e);
+        AppError::Internal("ID generation failed")
+    })?;
+    let export = diesel::insert_into(chat_exports::table)
+        .values(&NewChatExport {
+            id: export_id,
+            chat_id,
+            requester_uid: uid,
+            include_deleted: body.include_deleted,
+            status: ChatExportStatus::Pending,
+            created_at: Utc::now(),
+        })
+        .returning(crate::models::ChatExport::as_returning())
+        .get_result(conn)?;
+
+    if !state
+        .background_service
+        .enqueue(BackgroundJob::ExportChat { export_id })
+    {
+        mark_export_failed(conn, export_id, "Export queue is full")?;
+        return Err(AppError::ServiceUnavailable(
+            "Export queue is full, try again later",
+        ));
+    }
+
+    let response = export_response(&state, export).await?;
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+/// GET /group/:chat_id/exports/:export_id — Export status, with fresh download links once completed.
+#[utoipa::path(
+    get,
+    path = "/{export_id}",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("export_id" = i64, Path, description = "Export ID"),
+    ),
+    responses(
+        (status = OK, body = ChatExportResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn get_chat_export(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ExportPath { chat_id, export_id }): Path<ExportPath>,
+    mut conn: DbConn,
+) -> Result<Json<ChatExportResponse>, AppError> {
+    let conn = &mut *conn;
+
+    let export = load_chat_export(conn, chat_id, export_id)?
+        .filter(|export| export.requester_uid == uid)
+        .ok_or(AppError::NotFound("Export not found"))?;
+    authorize_export(conn, chat_id, uid, export.include_deleted)?;
+
+    Ok(Json(export_response(&state, export).await?))
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(post_chat_export))
+        .routes(routes!(get_chat_export))
+}
@@ -0,0 +1,411 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::dto::chat_folders::{
+    ChatFolderResponse, CreateChatFolderRequest, ListChatFoldersResponse,
+    ReorderChatFoldersRequest, UpdateChatFolderRequest,
+};
+use crate::dto::ws::{ChatFoldersUpdatedPayload, ServerWsMessage};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::models::{NewChatFolder, NewChatFolderChat, UpdateChatFolder};
+use crate::schema::{chat_folder_chats, chat_folders, group_membership};
+use crate::services::chat_folders::{
+    self as chat_folder_service, MAX_FOLDERS_PER_USER, MAX_FOLDER_CHATS, MAX_FOLDER_NAME_LEN,
+};
+use crate::utils::{auth::CurrentUid, ids};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct ChatFolderPath {
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    folder_id: i64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "chat-folders",
+    responses(
+        (status = 200, description = "Folders in display order", body = ListChatFoldersResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn get_chat_folders(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+) -> Result<Json<ListChatFoldersResponse>, AppError> {
+    let conn = &mut *conn;
+
+    let folders =
+        chat_folder_service::list_folder_responses(conn, state.unread_service.as_ref(), uid)?;
+
+    Ok(Json(ListChatFoldersResponse { folders }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "chat-folders",
+    request_body = CreateChatFolderRequest,
+    responses(
+        (status = 201, description = "Folder created", body = ChatFolderResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn post_chat_folder(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Json(body): Json<CreateChatFolderRequest>,
+) -> Result<(StatusCode, Json<ChatFolderResponse>), AppError> {
+    let conn = &mut *conn;
+
+    let name = normalize_name(&body.name)?;
+    let chat_ids = parse_chat_ids(&body.chat_ids)?;
+    ensure_member_of_chats(conn, uid, &chat_ids)?;
+
+    let existing: i64 = chat_folders::table
+        .filter(chat_folders::uid.eq(uid))
+        .count()
+        .get_result(conn)?;
+    if existing >= MAX_FOLDERS_PER_USER {
+        return Err(AppError::BadRequest("Too many folders"));
+    }
+    let next_sort_order: i32 = chat_folders::table
+        .filter(chat_folders::uid.eq(uid))
+        .select(diesel::dsl::max(chat_folders::sort_order))
+        .first::<Option<i32>>(conn)?
+        .map_or(0, |max| max + 1);
+
+    let folder_id = ids::next_id(state.id_gen.as_ref()).await.map_err(|e| {
+        tracing::error!("next_id for chat folder: {:?}", e);
+        AppError::Internal("ID generation failed")
+    })?;
+    let now = Utc::now();
+    let new_folder = NewChatFolder {
+        id: folder_id,
+        uid,
+        name,
+        sort_order: next_sort_order,
+        include_unread: body.rules.include_unread,
+        include_muted: body.rules.include_muted,
+        include_mentions: body.rules.include_mentions,
+        include_admin_of: body.rules.include_admin_of,
+        created_at: now,
+        updated_at: now,
+    };
+
+    conn.transaction::<(), AppError, _>(|conn| {
+        diesel::insert_into(chat_folders::table)
+            .values(&new_folder)
+            .execute(conn)?;
+        replace_folder_chats(conn, folder_id, &chat_ids)?;
+        Ok(())
+    })?;
+
+    let folders = broadcast_folders(conn, &state, uid)?;
+    let folder = folders
+        .into_iter()
+        .find(|f| f.id == folder_id)
+        .ok_or(AppError::Internal("Created folder missing"))?;
+
+    Ok((StatusCode::CREATED, Json(folder)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/{folder_id}",
+    tag = "chat-folders",
+    params(ChatFolderPath),
+    request_body = UpdateChatFolderRequest,
+    responses(
+        (status = 200, description = "Folder updated", body = ChatFolderResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn patch_chat_folder(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatFolderPath { folder_id }): Path<ChatFolderPath>,
+    mut conn: DbConn,
+    Json(body): Json<UpdateChatFolderRequest>,
+) -> Result<Json<ChatFolderResponse>, AppError> {
+    let conn = &mut *conn;
+
+    chat_folder_service::load_owned_folder(conn, uid, folder_id)?;
+
+    let name = body.name.as_deref().map(normalize_name).transpose()?;
+    let chat_ids = body.chat_ids.as_deref().map(parse_chat_ids).transpose()?;
+    if let Some(chat_ids) = &chat_ids {
+        ensure_member_of_chats(conn, uid, chat_ids)?;
+    }
+
+    let changeset = UpdateChatFolder {
+        name,
+        include_unread: body.rules.map(|r| r.include_unread),
+        include_muted: body.rules.map(|r| r.include_muted),
+        include_mentions: body.rules.map(|r| r.include_mentions),
+        include_admin_of: body.rules.map(|r| r.include_admin_of),
+        updated_at: Utc::now(),
+    };
+
+    conn.transaction::<(), AppError, _>(|conn| {
+        diesel::update(chat_folders::table.filter(chat_folders::id.eq(folder_id)))
+            .set(&changeset)
+            .execute(conn)?;
+        if let Some(chat_ids) = &chat_ids {
+            replace_folder_chats(conn, folder_id, chat_ids)?;
+        }
+        Ok(())
+    })?;
+
+    let folders = broadcast_folders(conn, &state, uid)?;
+    let folder = folders
+        .into_iter()
+        .find(|f| f.id == folder_id)
+        .ok_or(AppError::NotFound("Folder not found"))?;
+
+    Ok(Json(folder))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{folder_id}",
+    tag = "chat-folders",
+    params(ChatFolderPath),
+    responses(
+        (status = 204, description = "Folder deleted")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn delete_chat_folder(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatFolderPath { folder_id }): Path<ChatFolderPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+
+    let affected = diesel::delete(
+        chat_folders::table
+            .filter(chat_folders::id.eq(folder_id))
+            .filter(chat_folders::uid.eq(uid)),
+    )
+    .execute(conn)?;
+    if affected == 0 {
+        return Err(AppError::NotFound("Folder not found"));
+    }
+
+    broadcast_folders(conn, &state, uid)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/order",
+    tag = "chat-folders",
+    request_body = ReorderChatFoldersRequest,
+    responses(
+        (status = 200, description = "Folders in their new order", body = ListChatFoldersResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn put_chat_folder_order(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Json(body): Json<ReorderChatFoldersRequest>,
+) -> Result<Json<ListChatFoldersResponse>, AppError> {
+    let conn = &mut *conn;
+
+    let folder_ids = body
+        .folder_ids
+        .iter()
+        .map(|raw| {
+            raw.trim()
+                .parse::<i64>()
+                .map_err(|_| AppError::BadRequest("Folder id is invalid"))
+        })
+        .collect::<Result<Vec<i64>, AppError>>()?;
+
+    let existing: HashSet<i64> = chat_folders::table
+        .filter(chat_folders::uid.eq(uid))
+        .select(chat_folders::id)
+        .load::<i64>(conn)?
+        .into_iter()
+        .collect();
+    let requested: HashSet<i64> = folder_ids.iter().copied().collect();
+    if requested.len() != folder_ids.len() || requested != existing {
+        return Err(AppError::BadRequest(
+            "Folder order must list each folder exactly once",
+        ));
+    }
+
+    let now = Utc::now();
+    conn.transaction::<(), AppError, _>(|conn| {
+        for (index, folder_id) in folder_ids.iter().enumerate() {
+            diesel::update(chat_folders::table.filter(chat_folders::id.eq(*folder_id)))
+                .set((
+                    chat_folders::sort_order.eq(index as i32),
+                    chat_folders::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+        }
+        Ok(())
+    })?;
+
+    let folders = broadcast_folders(conn, &state, uid)?;
+
+    Ok(Json(ListChatFoldersResponse { folders }))
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_chat_folders, post_chat_folder))
+        .routes(routes!(put_chat_folder_order))
+        .routes(routes!(patch_chat_folder, delete_chat_folder))
+}
+
+/// Reload the user's folders and push them to all of the user's connections.
+fn broadcast_folders(
+    conn: &mut PgConnection,
+    state: &AppState,
+    uid: i32,
+) -> Result<Vec<ChatFolderResponse>, AppError> {
+    let folders =
+        chat_folder_service::list_folder_responses(conn, state.unread_service.as_ref(), uid)?;
+
+    state.ws_registry.broadcast_to_uids(
+        &[uid],
+        Arc::new(ServerWsMessage::ChatFoldersUpdated(
+            ChatFoldersUpdatedPayload {
+                folders: folders.clone(),
+            },
+        )),
+    );
+
+    Ok(folders)
+}
+
+fn replace_folder_chats(
+    conn: &mut PgConnection,
+    folder_id: i64,
+    chat_ids: &[i64],
+) -> Result<(), AppError> {
+    diesel::delete(chat_folder_chats::table.filter(chat_folder_chats::folder_id.eq(folder_id)))
+        .execute(conn)?;
+
+    if chat_ids.is_empty() {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let rows: Vec<NewChatFolderChat> = chat_ids
+        .iter()
+        .map(|chat_id| NewChatFolderChat {
+            folder_id,
+            chat_id: *chat_id,
+            added_at: now,
+        })
+        .collect();
+    diesel::insert_into(chat_folder_chats::table)
+        .values(&rows)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+fn ensure_member_of_chats(
+    conn: &mut PgConnection,
+    uid: i32,
+    chat_ids: &[i64],
+) -> Result<(), AppError> {
+    if chat_ids.is_empty() {
+        return Ok(());
+    }
+
+    let member_count: i64 = group_membership::table
+        .filter(group_membership::uid.eq(uid))
+        .filter(group_membership::chat_id.eq_any(chat_ids))
+        .count()
+        .get_result(conn)?;
+    if member_count != chat_ids.len() as i64 {
+        return Err(AppError::BadRequest("Folder can only contain your chats"));
+    }
+
+    Ok(())
+}
+
+fn normalize_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("Name is required"));
+    }
+    if name.chars().count() > MAX_FOLDER_NAME_LEN {
+        return Err(AppError::BadRequest("Name is too long"));
+    }
+    Ok(name.to_string())
+}
+
+/// Parse and de-duplicate chat ids, keeping first-seen order.
+fn parse_chat_ids(raw_chat_ids: &[String]) -> Result<Vec<i64>, AppError> {
+    let mut seen = HashSet::new();
+    let mut chat_ids = Vec::new();
+    for raw in raw_chat_ids {
+        let chat_id = raw
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| AppError::BadRequest("Chat id is invalid"))?;
+        if seen.insert(chat_id) {
+            chat_ids.push(chat_id);
+        }
+    }
+    if chat_ids.len() > MAX_FOLDER_CHATS {
+        return Err(AppError::BadRequest("Too many chats in folder"));
+    }
+    Ok(chat_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_name, parse_chat_ids};
+    use crate::errors::AppError;
+
+    #[test]
+    fn parse_chat_ids_deduplicates_preserving_order() {
+        let ids = parse_chat_ids(&["9".to_string(), "3".to_string(), "9".to_string()]).unwrap();
+        assert_eq!(ids, vec![9, 3]);
+    }
+
+    #[test]
+    fn parse_chat_ids_rejects_garbage() {
+        assert!(matches!(
+            parse_chat_ids(&["abc".to_string()]),
+            Err(AppError::BadRequest("Chat id is invalid"))
+        ));
+    }
+
+    #[test]
+    fn normalize_name_trims_and_rejects_blank() {
+        assert_eq!(normalize_name("  Work ").unwrap(), "Work");
+        assert!(matches!(
+            normalize_name("   "),
+            Err(AppError::BadRequest("Name is required"))
+        ));
+    }
+}
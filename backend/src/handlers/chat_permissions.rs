@@ -0,0 +1,300 @@
+use axum::extract::{Json, Path, State};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde_json::{json, Map, Value};
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::dto::chat_permissions::{
+    ChatPermissionsResponse, ChatRoleActions, UpdateChatPermissionsRequest,
+};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::handlers::members::{check_membership, require_admin_role};
+use crate::models::{ChatRolePermission, GroupAuditAction, GroupRole, NewGroupAuditLogEntry};
+use crate::schema::chat_role_permissions;
+use crate::services::audit_log::{next_audit_entry_id, record_audit_entry};
+use crate::services::authz::{
+    Action as AuthzAction, AuthzInvalidation, Resource as AuthzResource, CHAT_ROLE_ACTIONS,
+};
+use crate::utils::auth::CurrentUid;
+use crate::AppState;
+
+const ROLES: [GroupRole; 2] = [GroupRole::Member, GroupRole::Admin];
+
+#[derive(serde::Deserialize)]
+pub struct ChatIdPath {
+    pub chat_id: i64,
+}
+
+fn load_role_actions(
+    conn: &mut PgConnection,
+    state: &AppState,
+    chat_id: i64,
+) -> Result<Vec<ChatRoleActions>, AppError> {
+    ROLES
+        .iter()
+        .map(|role| {
+            let allowed_actions = state
+                .authz_service
+                .chat_role_actions(conn, chat_id, role)?
+                .into_iter()
+                .filter(|(_, allowed)| *allowed)
+                .map(|(action, _)| action.as_str().to_string())
+                .collect();
+            Ok(ChatRoleActions {
+                role: role.clone(),
+                allowed_actions,
+            })
+        })
+        .collect()
+}
+
+fn load_permissions_response(
+    conn: &mut PgConnection,
+    state: &AppState,
+    chat_id: i64,
+    uid: i32,
+) -> Result<ChatPermissionsResponse, AppError> {
+    let roles = load_role_actions(conn, state, chat_id)?;
+    let mut my_actions = Vec::new();
+    for action in CHAT_ROLE_ACTIONS {
+        if state
+            .authz_service
+            .has_permission(conn, uid, action, AuthzResource::Chat(chat_id))?
+        {
+            my_actions.push(action.as_str().to_string());
+        }
+    }
+
+    Ok(ChatPermissionsResponse {
+        roles,
+        available_actions: CHAT_ROLE_ACTIONS
+            .iter()
+            .map(|action| action.as_str().to_string())
+            .collect(),
+        my_actions,
+    })
+}
+
+/// Validate the requested role settings, returning the granted actions per role.
+fn parse_role_updates(
+    roles: &[ChatRoleActions],
+) -> Result<Vec<(GroupRole, Vec<AuthzAction>)>, AppError> {
+    let mut parsed: Vec<(GroupRole, Vec<AuthzAction>)> = Vec::with_capacity(roles.len());
+    for entry in roles {
+        if parsed.iter().any(|(role, _)| *role == entry.role) {
+            return Err(AppError::BadRequest("Each role may only be listed once"));
+        }
+        let actions = entry
+            .allowed_actions
+            .iter()
+            .map(|action| {
+                AuthzAction::from_chat_role_action(action)
+                    .ok_or(AppError::BadRequest("Unknown chat action"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        parsed.push((entry.role.clone(), actions));
+    }
+    Ok(parsed)
+}
+
+/// The role settings that result from applying `updates` on top of `current`.
+fn apply_role_updates(
+    current: &[ChatRoleActions],
+    updates: &[(GroupRole, Vec<AuthzAction>)],
+) -> Vec<ChatRoleActions> {
+    current
+        .iter()
+        .map(
+            |entry| match updates.iter().find(|(role, _)| *role == entry.role) {
+                Some((role, allowed)) => ChatRoleActions {
+                    role: role.clone(),
+                    allowed_actions: CHAT_ROLE_ACTIONS
+                        .iter()
+                        .filter(|action| allowed.contains(action))
+                        .map(|action| action.as_str().to_string())
+                        .collect(),
+                },
+                None => entry.clone(),
+            },
+        )
+        .collect()
+}
+
+fn role_actions_json(roles: &[ChatRoleActions]) -> Value {
+    let map: Map<String, Value> = roles
+        .iter()
+        .map(|entry| {
+            let key = match entry.role {
+                GroupRole::Member => "member",
+                GroupRole::Admin => "admin",
+            };
+            (key.to_string(), json!(entry.allowed_actions))
+        })
+        .collect();
+    json!({ "permissions": map })
+}
+
+/// GET /group/:chat_id/permissions — Chat actions each member role may perform.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    responses(
+        (status = OK, body = ChatPermissionsResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn get_chat_permissions(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+) -> Result<Json<ChatPermissionsResponse>, AppError> {
+    let conn = &mut *conn;
+
+    check_membership(conn, chat_id, uid)?;
+
+    Ok(Json(load_permissions_response(conn, &state, chat_id, uid)?))
+}
+
+/// PUT /group/:chat_id/permissions — Replace the allowed actions of the listed roles (admin only).
+#[utoipa::path(
+    put,
+    path = "/",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    request_body = UpdateChatPermissionsRequest,
+    responses(
+        (status = OK, body = ChatPermissionsResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn put_chat_permissions(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+    Json(body): Json<UpdateChatPermissionsRequest>,
+) -> Result<Json<ChatPermissionsResponse>, AppError> {
+    let conn = &mut *conn;
+
+    require_admin_role(conn, chat_id, uid)?;
+    let updates = parse_role_updates(&body.roles)?;
+    if updates.is_empty() {
+        return Ok(Json(load_permissions_response(conn, &state, chat_id, uid)?));
+    }
+
+    let before = load_role_actions(conn, &state, chat_id)?;
+    let after = apply_role_updates(&before, &updates);
+    let now = Utc::now();
+    let audit_id = next_audit_entry_id(state.id_gen.as_ref()).await?;
+    conn.transaction::<_, AppError, _>(|conn| {
+        use crate::schema::chat_role_permissions::dsl as crp_dsl;
+
+        for (role, allowed) in &updates {
+            diesel::delete(
+                chat_role_permissions::table
+                    .filter(crp_dsl::chat_id.eq(chat_id))
+                    .filter(crp_dsl::role.eq(role.clone())),
+            )
+            .execute(conn)?;
+            let rows: Vec<ChatRolePermission> = CHAT_ROLE_ACTIONS
+                .iter()
+                .map(|action| ChatRolePermission {
+                    chat_id,
+                    role: role.clone(),
+                    action: action.as_str().to_string(),
+                    allowed: allowed.contains(action),
+                    updated_at: now,
+                })
+                .collect();
+            diesel::insert_into(chat_role_permissions::table)
+                .values(&rows)
+                .execute(conn)?;
+        }
+        record_audit_entry(
+            conn,
+            &NewGroupAuditLogEntry::new(audit_id, chat_id, uid, GroupAuditAction::GroupUpdate)
+                .change(role_actions_json(&before), role_actions_json(&after)),
+        )?;
+        Ok(())
+    })?;
+    state
+        .authz_service
+        .publish_invalidation(conn, AuthzInvalidation::ChatRoles { chat_id })?;
+
+    Ok(Json(load_permissions_response(conn, &state, chat_id, uid)?))
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new().routes(routes!(get_chat_permissions, put_chat_permissions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_role_updates, parse_role_updates};
+    use crate::dto::chat_permissions::ChatRoleActions;
+    use crate::errors::AppError;
+    use crate::models::GroupRole;
+    use crate::services::authz::Action;
+
+    fn entry(role: GroupRole, actions: &[&str]) -> ChatRoleActions {
+        ChatRoleActions {
+            role,
+            allowed_actions: actions.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parse_role_updates_validates_actions_and_roles() {
+        let parsed =
+            parse_role_updates(&[entry(GroupRole::Member, &["chat.pin", "chat.sendMedia"])])
+                .unwrap();
+        assert_eq!(
+            parsed,
+            vec![(
+                GroupRole::Member,
+                vec![Action::ChatPin, Action::ChatSendMedia]
+            )]
+        );
+
+        assert!(matches!(
+            parse_role_updates(&[entry(GroupRole::Member, &["chat.create"])]),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            parse_role_updates(&[
+                entry(GroupRole::Admin, &[]),
+                entry(GroupRole::Admin, &["chat.pin"])
+            ]),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn apply_role_updates_replaces_only_listed_roles() {
+        let current = vec![
+            entry(GroupRole::Member, &["chat.pin"]),
+            entry(GroupRole::Admin, &["chat.pin", "chat.invite"]),
+        ];
+        let next = apply_role_updates(
+            &current,
+            &[(
+                GroupRole::Member,
+                vec![Action::ChatPin, Action::ChatSendMedia],
+            )],
+        );
+
+        assert_eq!(next[0].role, GroupRole::Member);
+        assert_eq!(next[0].allowed_actions, vec!["chat.sendMedia", "chat.pin"]);
+        assert_eq!(next[1].allowed_actions, vec!["chat.pin", "chat.invite"]);
+    }
+}
@@ -12,14 +12,16 @@ use utoipa_axum::router::OpenApiRouter;
 use crate::schema::messages::dsl;
 use crate::{
     dto::{
+        bot_commands::CommandInvocationResponse,
         messages::{ListMessagesResponse, MessageResponse, SearchMessagesResponse},
         ws::ServerWsMessage,
     },
     errors::AppError,
     extractors::DbConn,
-    handlers::{groups::load_requester_group_role, members::check_membership},
-    models::{GroupRole, Message, MessageType},
+    handlers::{bot_commands::try_invoke_command, members::check_membership},
+    models::{GroupAuditAction, Message, MessageType, NewGroupAuditLogEntry},
     schema::{attachments, group_membership, groups, messages},
+    services::authz::{Action as AuthzAction, Resource as AuthzResource},
     services::message_search::{
         filter_authoritative_hits_with_counts, validate_search_query, MessageSearchSort,
         SearchCandidateDropCounts,
@@ -62,6 +64,13 @@ pub struct ListMessagesQuery {
     )]
     #[schema(value_type = Option<String>)]
     thread_id: Option<i64>,
+    /// Restrict top-level messages to a forum topic; ignored for thread views.
+    #[serde(
+        default,
+        deserialize_with = "crate::serde_i64_string::opt::deserialize"
+    )]
+    #[schema(value_type = Option<String>)]
+    topic_id: Option<i64>,
 }
 
 #[derive(serde::Deserialize, utoipa::IntoParams)]
@@ -169,6 +178,25 @@ fn validate_message_payload(
     Ok(())
 }
 
+const SLOW_MODE_ACTIVE: &str = "Slow mode is enabled for this chat";
+
+/// Reject the send with 429 + `Retry-After` while the sender is still inside the
+/// group's slow-mode window. Admins are exempt.
+pub(crate) fn enforce_slow_mode(
+    conn: &mut diesel::PgConnection,
+    chat_id: i64,
+    uid: i32,
+    is_thread_reply: bool,
+) -> Result<(), AppError> {
+    match crate::services::chat::check_slow_mode(conn, chat_id, uid, is_thread_reply)? {
+        Some(retry_after_secs) => Err(AppError::TooManyRequests {
+            message: SLOW_MODE_ACTIVE,
+            retry_after_secs,
+        }),
+        None => Ok(()),
+    }
+}
+
 /// GET /chats/:chat_id/messages — List messages in a chat (cursor-based).
 #[utoipa::path(
     get,
@@ -181,6 +209,7 @@ fn validate_message_payload(
         ("after" = Option<String>, Query, description = "Cursor: fetch messages after this ID"),
         ("max" = Option<i64>, Query, description = "Max number of messages to return"),
         ("thread_id" = Option<String>, Query, description = "Thread root ID to filter by"),
+        ("topic_id" = Option<String>, Query, description = "Forum topic ID to filter by"),
     ),
     responses(
         (status = 200, description = "List of messages", body = ListMessagesResponse),
@@ -201,6 +230,10 @@ async fn get_messages(
     let max = validate_limit(q.max, MAX_MESSAGES_LIMIT);
 
     let q_thread_id = q.thread_id;
+    let q_topic_id = q.topic_id;
+    if let (None, Some(topic_id)) = (q_thread_id, q_topic_id) {
+        crate::services::topics::load_chat_topic(conn, chat_id, topic_id)?;
+    }
     macro_rules! base_query {
         () => {{
             let mut b = messages::table
@@ -219,6 +252,9 @@ async fn get_messages(
                         .is_null()
                         .and(dsl::deleted_at.is_null().or(dsl::has_thread.eq(true))),
                 );
+                if let Some(topic_id) = q_topic_id {
+                    b = b.filter(dsl::topic_id.eq(topic_id));
+                }
             }
             b
         }};
@@ -528,6 +564,10 @@ async fn get_message(
     request_body = CreateMessageBody,
     responses(
         (status = 201, description = "Message created", body = MessageResponse),
+        (status = 202, description = "Text was a bot command; handed to the bot instead of posted", body = CommandInvocationResponse),
+        (status = 403, description = "Group is announcement-only"),
+        (status = 429, description = "Slow mode is active; see Retry-After"),
+        (status = 503, description = "The command's bot is not reachable"),
     ),
     security(("uid_header" = []), ("bearer_jwt" = [])),
 )]
@@ -541,6 +581,23 @@ async fn post_message(
     let conn = &mut *conn;
 
     check_membership(conn, chat_id, uid)?;
+    if let Some(invocation) = try_invoke_command(conn, &state, chat_id, uid, &body).await? {
+        return Ok((StatusCode::ACCEPTED, Json(invocation)).into_response());
+    }
+    let response = create_message(conn, &state, chat_id, uid, body).await?;
+
+    Ok((StatusCode::CREATED, Json(response)).into_response())
+}
+
+/// Validate and send a top-level message as `uid`, advancing the sender's read position.
+/// Callers check membership first.
+pub(crate) async fn create_message(
+    conn: &mut diesel::PgConnection,
+    state: &AppState,
+    chat_id: i64,
+    uid: i32,
+    body: CreateMessageBody,
+) -> Result<MessageResponse, AppError> {
     validate_client_message_type(&body.message_type)?;
     let attachment_ids: Vec<i64> = body
         .attachment_ids
@@ -556,7 +613,7 @@ async fn post_message(
     let tx_result: Result<_, AppError> = async {
         let send_result = send_prepared_message(
             conn,
-            &state,
+            state,
             PreparedMessageSend {
                 chat_id,
                 sender_uid: uid,
@@ -572,12 +629,24 @@ async fn post_message(
                 client_generated_id: body.client_generated_id,
                 attachment_ids,
                 publish_immediately,
+                important: body.important,
+                topic_id: body.topic_id,
+                slow_mode: true,
             },
         )
         .await?;
 
         if let SendMessageOutcome::Created(send_result) = &send_result {
             crate::services::chat::mark_chat_as_read(conn, chat_id, uid, send_result.response.id)?;
+            if let Some(topic_id) = send_result.response.topic_id {
+                crate::services::topics::mark_topic_read(
+                    conn,
+                    chat_id,
+                    topic_id,
+                    uid,
+                    send_result.response.id,
+                )?;
+            }
         }
 
         Ok(send_result)
@@ -598,7 +667,7 @@ async fn post_message(
     let response = match send_result {
         SendMessageOutcome::Created(send_result) => {
             let send_result = *send_result;
-            send_result.side_effects.fire(&state);
+            send_result.side_effects.fire(state);
             if let Some(search_service) = state.message_search.clone() {
                 search_service.upsert_message_best_effort(send_result.inserted_message);
             }
@@ -610,7 +679,7 @@ async fn post_message(
         SendMessageOutcome::Duplicate(response) => *response,
     };
 
-    Ok((StatusCode::CREATED, Json(response)))
+    Ok(response)
 }
 
 /// POST /chats/:chat_id/threads/:thread_id/messages — Send a message in a thread.
@@ -625,6 +694,7 @@ async fn post_message(
     request_body = CreateMessageBody,
     responses(
         (status = 201, description = "Thread message created", body = MessageResponse),
+        (status = 429, description = "Slow mode is active; see Retry-After"),
     ),
     security(("uid_header" = []), ("bearer_jwt" = [])),
 )]
@@ -691,6 +761,9 @@ pub(super) async fn post_thread_message(
                 client_generated_id: body.client_generated_id,
                 attachment_ids,
                 publish_immediately,
+                important: body.important,
+                topic_id: body.topic_id,
+                slow_mode: true,
             },
         )
         .await?;
@@ -847,7 +920,36 @@ async fn patch_message(
     let conn = &mut *conn;
 
     check_membership(conn, chat_id, uid)?;
+    let attachment_ids: Vec<i64> = body
+        .attachment_ids
+        .iter()
+        .filter_map(|s| s.parse().ok())
+        .collect();
+    let response = edit_message(
+        conn,
+        &state,
+        chat_id,
+        message_id,
+        uid,
+        body.message,
+        attachment_ids,
+    )
+    .await?;
 
+    Ok(Json(response))
+}
+
+/// Replace the text and attachments of one of `uid`'s messages and broadcast the update.
+/// Callers check membership first.
+pub(crate) async fn edit_message(
+    conn: &mut diesel::PgConnection,
+    state: &AppState,
+    chat_id: i64,
+    message_id: i64,
+    uid: i32,
+    text: String,
+    attachment_ids: Vec<i64>,
+) -> Result<MessageResponse, AppError> {
     // Verify message exists and belongs to the user
 
     let message: Message = messages::table
@@ -868,21 +970,25 @@ async fn patch_message(
         return Err(AppError::BadRequest("Cannot edit unpublished message"));
     }
 
-    if body.message.trim().is_empty() && body.attachment_ids.is_empty() {
+    if text.trim().is_empty() && attachment_ids.is_empty() {
         return Err(AppError::BadRequest("Message cannot be empty"));
     }
 
-    let attachment_ids: Vec<i64> = body
-        .attachment_ids
-        .iter()
-        .filter_map(|s| s.parse().ok())
-        .collect();
-
     if attachment_ids.len() > MAX_ATTACHMENTS_PER_MESSAGE {
         return Err(AppError::BadRequest(
             "Too many attachments (maximum of 20 allowed)",
         ));
     }
+    // An edit must not add content the sender could not have sent.
+    super::enforce_content_permissions(
+        conn,
+        state,
+        chat_id,
+        uid,
+        &message.message_type,
+        !attachment_ids.is_empty(),
+        Some(&text),
+    )?;
 
     use crate::schema::attachments::dsl as a_dsl;
     diesel::update(attachments::table.filter(a_dsl::message_id.eq(message_id)))
@@ -899,18 +1005,19 @@ async fn patch_message(
     let now = Utc::now();
     let updated_message: Message = diesel::update(messages::table.filter(dsl::id.eq(message_id)))
         .set((
-            dsl::message.eq(&body.message),
+            dsl::message.eq(&text),
             dsl::has_attachments.eq(!attachment_ids.is_empty()),
             dsl::updated_at.eq(Some(now)),
         ))
         .returning(Message::as_returning())
         .get_result(conn)?;
+    super::sync_message_mentions(conn, message_id, chat_id, Some(&text))?;
 
     if let Some(search_service) = state.message_search.clone() {
         search_service.upsert_message_best_effort(updated_message.clone());
     }
 
-    let response = attach_metadata(conn, vec![updated_message], &state, uid)
+    let response = attach_metadata(conn, vec![updated_message], state, uid)
         .await
         .into_iter()
         .next()
@@ -925,9 +1032,10 @@ async fn patch_message(
             .load(conn)?
     };
     let ws_msg = std::sync::Arc::new(ServerWsMessage::MessageUpdated(response.clone()));
+    state.webhook_service.publish_ws_message(&ws_msg);
     state.ws_registry.broadcast_to_uids(&member_uids, ws_msg);
 
-    Ok(Json(response))
+    Ok(response)
 }
 
 /// DELETE /chats/:chat_id/messages/:message_id — Delete a message (soft delete).
@@ -956,7 +1064,20 @@ async fn delete_message(
     let conn = &mut *conn;
 
     check_membership(conn, chat_id, uid)?;
+    soft_delete_message(conn, &state, chat_id, message_id, uid).await?;
 
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Soft-delete a message on behalf of `uid`, who must be its sender or hold
+/// `chat.deleteOthers`. Callers check membership first.
+pub(crate) async fn soft_delete_message(
+    conn: &mut diesel::PgConnection,
+    state: &AppState,
+    chat_id: i64,
+    message_id: i64,
+    uid: i32,
+) -> Result<(), AppError> {
     // Verify message exists and belongs to the user
     let message: Message = messages::table
         .filter(dsl::id.eq(message_id).and(dsl::chat_id.eq(chat_id)))
@@ -965,10 +1086,15 @@ async fn delete_message(
         .optional()?
         .ok_or(AppError::NotFound("Message not found"))?;
 
-    if message.sender_uid != uid {
-        // Not the sender — allow if requester is an admin
-        let role = load_requester_group_role(conn, chat_id, uid)?;
-        if role != Some(GroupRole::Admin) {
+    let is_moderation = message.sender_uid != uid;
+    if is_moderation {
+        // Not the sender — allow if the requester may delete others' messages
+        if !state.authz_service.has_permission(
+            conn,
+            uid,
+            AuthzAction::ChatDeleteOthers,
+            AuthzResource::Chat(chat_id),
+        )? {
             return Err(AppError::Forbidden("You can only delete your own messages"));
         }
     }
@@ -982,6 +1108,7 @@ async fn delete_message(
 
     // Transaction: soft-delete + thread_meta + group last_message
     let now = Utc::now();
+    let audit_id = crate::services::audit_log::next_audit_entry_id(state.id_gen.as_ref()).await?;
     let deleted_message: Message = conn.transaction::<_, diesel::result::Error, _>(|conn| {
         let deleted_message: Message =
             diesel::update(messages::table.filter(dsl::id.eq(message_id)))
@@ -1020,6 +1147,25 @@ async fn delete_message(
             super::shift_chat_read_pointers_on_delete(conn, chat_id, &[message_id])?;
         }
 
+        if is_moderation {
+            crate::services::audit_log::record_audit_entry(
+                conn,
+                &NewGroupAuditLogEntry::new(
+                    audit_id,
+                    chat_id,
+                    uid,
+                    GroupAuditAction::MessageDelete,
+                )
+                .target_uid(message.sender_uid)
+                .target_id(message_id)
+                .before(serde_json::json!({
+                    "message": message.message,
+                    "messageType": message.message_type,
+                    "replyRootId": message.reply_root_id.map(|id| id.to_string()),
+                })),
+            )?;
+        }
+
         Ok(deleted_message)
     })?;
 
@@ -1034,7 +1180,7 @@ async fn delete_message(
             .observe_top_level_message_counted(chat_id, message_id, false);
     }
 
-    let response = attach_metadata(conn, vec![deleted_message], &state, uid)
+    let response = attach_metadata(conn, vec![deleted_message], state, uid)
         .await
         .into_iter()
         .next()
@@ -1049,6 +1195,7 @@ async fn delete_message(
             .load(conn)?
     };
     let ws_msg = std::sync::Arc::new(ServerWsMessage::MessageDeleted(response.clone()));
+    state.webhook_service.publish_ws_message(&ws_msg);
     state.ws_registry.broadcast_to_uids(&member_uids, ws_msg);
 
     if let Some(reply_root_id) = response.reply_root_id {
@@ -1067,7 +1214,7 @@ async fn delete_message(
         }
     }
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
 }
 
 pub fn router() -> OpenApiRouter<crate::AppState> {
@@ -22,13 +22,16 @@ use crate::{
             StickerMediaResponse, ThreadInfo,
         },
         users::User,
-        ws::{ChatArchiveStateChangedPayload, ServerWsMessage},
+        ws::{ChatArchiveStateChangedPayload, ChatPinsChangedPayload, ServerWsMessage},
     },
     errors::AppError,
     extractors::DbConn,
     handlers::members::check_membership,
     services::{
+        authz::{Action as AuthzAction, Resource as AuthzResource},
+        bots::is_bot_uid,
         chat,
+        incoming_webhooks::load_sender_overrides,
         media::build_public_object_url,
         push::{PushJob, PushMessagePreview, PushMessagePreviewSticker},
         user::{lookup_user_avatars, lookup_user_profiles, UserProfile},
@@ -36,7 +39,10 @@ use crate::{
     utils::{auth::CurrentUid, ids, pagination::validate_limit},
 };
 use crate::{
-    models::{Attachment, Media, Message, MessageType, NewMessage, Sticker, TranscodeStatus},
+    models::{
+        Attachment, GroupRole, Media, Message, MessageSenderOverride, MessageType, NewMessage,
+        Sticker, TranscodeStatus,
+    },
     schema::{
         attachments, group_membership, groups, media, message_reactions,
         messages as messages_schema, stickers, user_favorite_stickers,
@@ -48,12 +54,23 @@ use crate::{AppState, MAX_CHATS_LIMIT};
 // Re-exports for external consumers (pins.rs, threads.rs, invites.rs, ws/messages.rs)
 // ---------------------------------------------------------------------------
 pub use self::messages::router as messages_router;
+pub(crate) use self::messages::{
+    create_message, edit_message, enforce_slow_mode, soft_delete_message,
+};
 pub use self::reactions::router as reactions_router;
+pub(crate) use self::reactions::{add_reaction, remove_reaction, ReactionKey};
 
 // ---------------------------------------------------------------------------
 // Mention extraction
 // ---------------------------------------------------------------------------
 
+/// Mentions every member of the chat; requires `chat.mentionEveryone` to send.
+const MENTION_EVERYONE_TOKEN: &str = "@[all]";
+
+pub(crate) fn mentions_everyone(text: &str) -> bool {
+    text.contains(MENTION_EVERYONE_TOKEN)
+}
+
 fn parse_mention_token(text: &str, start: usize) -> Option<(i32, usize)> {
     let bytes = text.as_bytes();
     if bytes.get(start) != Some(&b'@') || bytes.get(start + 1) != Some(&b'[') {
@@ -90,6 +107,42 @@ pub fn extract_mention_uids(text: &str) -> Vec<i32> {
     uids
 }
 
+/// Replace a message's `message_mentions` rows; an `@[all]` mention is stored as its
+/// own row with `mentions_all` set.
+pub(crate) fn sync_message_mentions(
+    conn: &mut PgConnection,
+    message_id: i64,
+    chat_id: i64,
+    text: Option<&str>,
+) -> QueryResult<()> {
+    diesel::sql_query("DELETE FROM message_mentions WHERE message_id = $1")
+        .bind::<diesel::sql_types::BigInt, _>(message_id)
+        .execute(conn)?;
+
+    let text = text.unwrap_or_default();
+    let uids = extract_mention_uids(text);
+    if !uids.is_empty() {
+        diesel::sql_query(
+            "INSERT INTO message_mentions (message_id, chat_id, uid) \
+             SELECT $1, $2, uid FROM UNNEST($3) AS uid",
+        )
+        .bind::<diesel::sql_types::BigInt, _>(message_id)
+        .bind::<diesel::sql_types::BigInt, _>(chat_id)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Integer>, _>(uids)
+        .execute(conn)?;
+    }
+    if mentions_everyone(text) {
+        diesel::sql_query(
+            "INSERT INTO message_mentions (message_id, chat_id, mentions_all) \
+             VALUES ($1, $2, TRUE)",
+        )
+        .bind::<diesel::sql_types::BigInt, _>(message_id)
+        .bind::<diesel::sql_types::BigInt, _>(chat_id)
+        .execute(conn)?;
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Shared types
 // ---------------------------------------------------------------------------
@@ -111,6 +164,13 @@ pub(crate) struct PreparedMessageSend {
     pub client_generated_id: String,
     pub attachment_ids: Vec<i64>,
     pub publish_immediately: bool,
+    /// Announcement flagged as important; its push bypasses group mutes.
+    pub important: bool,
+    /// Forum topic for top-level messages; thread replies inherit the root's topic.
+    pub topic_id: Option<i64>,
+    /// Hold the send to the chat's slow mode. Set for member posts; the caller must
+    /// run the send inside a transaction.
+    pub slow_mode: bool,
 }
 
 pub(crate) struct SendMessageResult {
@@ -129,7 +189,8 @@ pub(crate) enum SendMessageOutcome {
 pub(crate) struct PendingSideEffects {
     pub(crate) ws_msg: std::sync::Arc<ServerWsMessage>,
     pub(crate) broadcast_uids: Vec<i32>,
-    pub(crate) push_job: Option<PushJob>,
+    /// A push job was queued in the message's transaction; wake the worker on fire.
+    pub(crate) push_queued: bool,
     pub(crate) unread_event: Option<TopLevelUnreadCacheEvent>,
 }
 
@@ -140,7 +201,7 @@ pub(crate) struct TopLevelUnreadCacheEvent {
 }
 
 impl PendingSideEffects {
-    /// Fire WS broadcast and push notification. Call after transaction commit.
+    /// Fire WS broadcast and webhooks and wake the push worker. Call after transaction commit.
     pub fn fire(self, state: &AppState) {
         if let Some(event) = self.unread_event {
             state.unread_service.observe_top_level_message(
@@ -149,11 +210,12 @@ impl PendingSideEffects {
                 event.countable,
             );
         }
+        state.webhook_service.publish_ws_message(&self.ws_msg);
         state
             .ws_registry
             .broadcast_to_uids(&self.broadcast_uids, self.ws_msg);
-        if let Some(job) = self.push_job {
-            state.push_service.enqueue(job);
+        if self.push_queued {
+            state.push_service.wake();
         }
     }
 }
@@ -178,6 +240,16 @@ pub struct CreateMessageBody {
     pub reply_to_id: Option<i64>,
     #[serde(default)]
     pub attachment_ids: Vec<String>,
+    /// Only honoured for top-level posts in announcement-only groups.
+    #[serde(default)]
+    pub important: bool,
+    /// Forum topic to post into; thread replies inherit the root message's topic.
+    #[serde(
+        default,
+        deserialize_with = "crate::serde_i64_string::opt::deserialize"
+    )]
+    #[schema(value_type = Option<String>)]
+    pub topic_id: Option<i64>,
 }
 
 // ---------------------------------------------------------------------------
@@ -317,10 +389,23 @@ pub(crate) fn build_sender(
 
     User {
         uid,
-        avatar_url: user_avatars.get(&uid).cloned().flatten(),
+        avatar_url: profile
+            .and_then(|profile| profile.avatar_url.clone())
+            .or_else(|| user_avatars.get(&uid).cloned().flatten()),
         name: profile.and_then(|profile| profile.username.clone()),
         gender: profile.map(|profile| profile.gender).unwrap_or(0),
         user_group: profile.and_then(|profile| profile.user_group.clone()),
+        is_bot: profile.is_some_and(|profile| profile.is_bot),
+    }
+}
+
+/// Show the per-message name and avatar an incoming webhook post asked for.
+pub(crate) fn apply_sender_override(sender: &mut User, sender_override: &MessageSenderOverride) {
+    if let Some(name) = &sender_override.name {
+        sender.name = Some(name.clone());
+    }
+    if let Some(avatar_url) = &sender_override.avatar_url {
+        sender.avatar_url = Some(avatar_url.clone());
     }
 }
 
@@ -495,9 +580,9 @@ struct PushPreviewBundle {
     mentioned_uids: Vec<i32>,
 }
 
-/// Replace `@[uid:N]` tokens with `@username` for human-readable previews.
+/// Replace `@[uid:N]` and `@[all]` tokens with `@username` / `@all` for human-readable previews.
 fn render_mentions_as_text(text: &str, mentions: &[MentionInfo]) -> String {
-    if mentions.is_empty() {
+    if mentions.is_empty() && !mentions_everyone(text) {
         return text.to_string();
     }
     let mention_map: std::collections::HashMap<i32, &str> = mentions
@@ -511,6 +596,13 @@ fn render_mentions_as_text(text: &str, mentions: &[MentionInfo]) -> String {
     let mut i = 0;
     let mut copied_until = 0;
     while i < len {
+        if bytes[i] == b'@' && text[i..].starts_with(MENTION_EVERYONE_TOKEN) {
+            result.push_str(&text[copied_until..i]);
+            result.push_str("@all");
+            i += MENTION_EVERYONE_TOKEN.len();
+            copied_until = i;
+            continue;
+        }
         if let Some((uid, next)) = parse_mention_token(text, i) {
             result.push_str(&text[copied_until..i]);
             let name = mention_map.get(&uid).copied().unwrap_or("Unknown User");
@@ -595,6 +687,7 @@ pub(crate) fn build_message_side_effects(
     sender_uid: i32,
     chat_id: i64,
     enqueue_push: bool,
+    important: bool,
 ) -> Result<PendingSideEffects, AppError> {
     let member_uids: Vec<i32> = {
         use crate::schema::group_membership as gm_dsl;
@@ -607,7 +700,7 @@ pub(crate) fn build_message_side_effects(
     let ws_msg = std::sync::Arc::new(ServerWsMessage::Message(response.clone()));
 
     let is_system_message = matches!(response.message_type, MessageType::System);
-    let push_job = if enqueue_push && !is_system_message {
+    let push_queued = if enqueue_push && !is_system_message {
         let sender_username =
             load_username_by_uid(conn, sender_uid)?.unwrap_or_else(|| "Someone".to_string());
         let chat_name = groups::table
@@ -615,8 +708,15 @@ pub(crate) fn build_message_side_effects(
             .select(groups::dsl::name)
             .first::<String>(conn)
             .unwrap_or_else(|_| "Chat".to_string());
-        let push_preview = build_push_preview_bundle(response);
-        Some(PushJob {
+        let mut push_preview = build_push_preview_bundle(response);
+        if response.message.as_deref().is_some_and(mentions_everyone) {
+            push_preview.mentioned_uids = member_uids
+                .iter()
+                .copied()
+                .filter(|uid| *uid != sender_uid)
+                .collect();
+        }
+        let job = PushJob {
             chat_id,
             sender_uid,
             sender_username,
@@ -630,15 +730,19 @@ pub(crate) fn build_message_side_effects(
                 .reply_to_message
                 .as_ref()
                 .map(|message| message.sender.uid),
-        })
+            important,
+            topic_id: response.topic_id,
+        };
+        crate::services::push::queue_job(conn, &job)?;
+        true
     } else {
-        None
+        false
     };
 
     Ok(PendingSideEffects {
         ws_msg,
         broadcast_uids: member_uids,
-        push_job,
+        push_queued,
         unread_event: response
             .reply_root_id
             .is_none()
@@ -662,6 +766,8 @@ pub(crate) async fn send_prepared_message(
     if let Some(reply_to_id) = prepared.reply_to_id {
         validate_reply_target(conn, prepared.chat_id, prepared.reply_root_id, reply_to_id)?;
     }
+    enforce_send_policy(conn, state, &prepared)?;
+    let topic_id = resolve_message_topic(conn, &prepared)?;
 
     let id = ids::next_message_id(state.id_gen.as_ref())
         .await
@@ -701,8 +807,36 @@ pub(crate) async fn send_prepared_message(
         has_reactions: false,
         is_published: prepared.publish_immediately,
         transcode_status,
+        topic_id,
+        is_important: prepared.important,
     };
 
+    if prepared.slow_mode {
+        // Lock the sender's membership so concurrent sends are checked one at a time, and
+        // answer a retried send with its original message rather than a 429.
+        group_membership::table
+            .filter(group_membership::chat_id.eq(prepared.chat_id))
+            .filter(group_membership::uid.eq(prepared.sender_uid))
+            .select(group_membership::uid)
+            .for_update()
+            .first::<i32>(conn)
+            .optional()?;
+        if let Some(existing) = messages_schema::table
+            .filter(messages_schema::client_generated_id.eq(&prepared.client_generated_id))
+            .select(Message::as_select())
+            .first::<Message>(conn)
+            .optional()?
+        {
+            return duplicate_send(conn, state, &prepared, existing).await;
+        }
+        enforce_slow_mode(
+            conn,
+            prepared.chat_id,
+            prepared.sender_uid,
+            prepared.reply_root_id.is_some(),
+        )?;
+    }
+
     let inserted_msg: Option<Message> = diesel::insert_into(messages_schema::table)
         .values(&new_msg)
         .on_conflict(messages_schema::client_generated_id)
@@ -717,16 +851,10 @@ pub(crate) async fn send_prepared_message(
             .first::<Message>(conn)
             .optional()?
             .ok_or(AppError::Conflict("Duplicate client generated id"))?;
-        let existing_attachment_ids = load_message_attachment_ids(conn, existing.id)?;
-        validate_idempotent_message_payload(&existing, &prepared, &existing_attachment_ids)?;
-        let response = attach_metadata(conn, vec![existing], state, prepared.sender_uid)
-            .await
-            .into_iter()
-            .next()
-            .ok_or(AppError::Internal("Failed to build message response"))?;
-        return Ok(SendMessageOutcome::Duplicate(Box::new(response)));
+        return duplicate_send(conn, state, &prepared, existing).await;
     };
     state.metrics.record_message(prepared.chat_id);
+    sync_message_mentions(conn, id, prepared.chat_id, prepared.message.as_deref())?;
 
     if prepared.publish_immediately && prepared.reply_root_id.is_none() {
         use crate::schema::groups::dsl as g_dsl;
@@ -759,6 +887,7 @@ pub(crate) async fn send_prepared_message(
             prepared.sender_uid,
             prepared.chat_id,
             !is_system_message,
+            prepared.important,
         )?;
         let member_uids = side_effects.broadcast_uids.clone();
         (member_uids, side_effects)
@@ -768,7 +897,7 @@ pub(crate) async fn send_prepared_message(
             PendingSideEffects {
                 ws_msg: std::sync::Arc::new(ServerWsMessage::Message(response.clone())),
                 broadcast_uids: Vec::new(),
-                push_job: None,
+                push_queued: false,
                 unread_event: prepared.reply_root_id.is_none().then_some(
                     TopLevelUnreadCacheEvent {
                         chat_id: prepared.chat_id,
@@ -788,6 +917,213 @@ pub(crate) async fn send_prepared_message(
     })))
 }
 
+/// Answer a send whose `client_generated_id` already exists with the original message,
+/// provided the payload matches.
+async fn duplicate_send(
+    conn: &mut PgConnection,
+    state: &AppState,
+    prepared: &PreparedMessageSend,
+    existing: Message,
+) -> Result<SendMessageOutcome, AppError> {
+    let existing_attachment_ids = load_message_attachment_ids(conn, existing.id)?;
+    validate_idempotent_message_payload(&existing, prepared, &existing_attachment_ids)?;
+    let response = attach_metadata(conn, vec![existing], state, prepared.sender_uid)
+        .await
+        .into_iter()
+        .next()
+        .ok_or(AppError::Internal("Failed to build message response"))?;
+    Ok(SendMessageOutcome::Duplicate(Box::new(response)))
+}
+
+/// Announcement-only and chat role checks every send must pass. Also run for text
+/// that is handed to a bot instead of posted, so commands cannot bypass them.
+pub(crate) fn enforce_send_policy(
+    conn: &mut PgConnection,
+    state: &AppState,
+    prepared: &PreparedMessageSend,
+) -> Result<(), AppError> {
+    enforce_announcement_policy(conn, state, prepared)?;
+    enforce_chat_role_permissions(conn, state, prepared)
+}
+
+/// Top-level posts in announcement-only groups are limited to admins and holders of
+/// `chat.announce`; system messages (joins, leaves, pins) are always allowed.
+fn enforce_announcement_policy(
+    conn: &mut PgConnection,
+    state: &AppState,
+    prepared: &PreparedMessageSend,
+) -> Result<(), AppError> {
+    let is_top_level =
+        prepared.reply_root_id.is_none() && !matches!(prepared.message_type, MessageType::System);
+    if !is_top_level {
+        if prepared.important {
+            return Err(AppError::BadRequest(
+                "Only top-level announcements can be marked important",
+            ));
+        }
+        return Ok(());
+    }
+
+    let announcement_only: bool = groups::table
+        .filter(groups::dsl::id.eq(prepared.chat_id))
+        .select(groups::dsl::announcement_only)
+        .first(conn)
+        .optional()?
+        .unwrap_or(false);
+    if !announcement_only {
+        if prepared.important {
+            return Err(AppError::BadRequest(
+                "Only announcement-only groups support important messages",
+            ));
+        }
+        return Ok(());
+    }
+
+    if can_post_announcement(conn, state, prepared.chat_id, prepared.sender_uid)? {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "Only admins can post top-level messages in this group",
+        ))
+    }
+}
+
+fn enforce_chat_role_permissions(
+    conn: &mut PgConnection,
+    state: &AppState,
+    prepared: &PreparedMessageSend,
+) -> Result<(), AppError> {
+    enforce_content_permissions(
+        conn,
+        state,
+        prepared.chat_id,
+        prepared.sender_uid,
+        &prepared.message_type,
+        !prepared.attachment_ids.is_empty(),
+        prepared.message.as_deref(),
+    )
+}
+
+/// Check the chat actions a message's content needs, for sends and edits alike.
+pub(crate) fn enforce_content_permissions(
+    conn: &mut PgConnection,
+    state: &AppState,
+    chat_id: i64,
+    uid: i32,
+    message_type: &MessageType,
+    has_attachments: bool,
+    text: Option<&str>,
+) -> Result<(), AppError> {
+    for (action, denied) in required_content_actions(message_type, has_attachments, text) {
+        if !state
+            .authz_service
+            .has_permission(conn, uid, action, AuthzResource::Chat(chat_id))?
+        {
+            return Err(AppError::Forbidden(denied));
+        }
+    }
+    Ok(())
+}
+
+/// Attachments and `@[all]` mentions need the matching chat actions; system messages
+/// are exempt.
+fn required_content_actions(
+    message_type: &MessageType,
+    has_attachments: bool,
+    text: Option<&str>,
+) -> Vec<(AuthzAction, &'static str)> {
+    let mut actions = Vec::new();
+    if matches!(message_type, MessageType::System) {
+        return actions;
+    }
+    if has_attachments {
+        actions.push((
+            AuthzAction::ChatSendMedia,
+            "Sending media is not allowed in this chat",
+        ));
+    }
+    if text.is_some_and(mentions_everyone) {
+        actions.push((
+            AuthzAction::ChatMentionEveryone,
+            "Mentioning everyone is not allowed in this chat",
+        ));
+    }
+    actions
+}
+
+/// Resolve the topic a new message lands in. Thread replies always follow their
+/// root message; top-level posts must name a topic of the same chat. Only admins
+/// post into a closed topic, thread replies included.
+fn resolve_message_topic(
+    conn: &mut PgConnection,
+    prepared: &PreparedMessageSend,
+) -> Result<Option<i64>, AppError> {
+    let topic_id = match prepared.reply_root_id {
+        Some(root_id) => {
+            let root_topic_id: Option<i64> = messages_schema::table
+                .filter(messages_schema::id.eq(root_id))
+                .select(messages_schema::topic_id)
+                .first(conn)
+                .optional()?
+                .flatten();
+            if prepared.topic_id.is_some() && prepared.topic_id != root_topic_id {
+                return Err(AppError::BadRequest(
+                    "Thread replies must stay in the root message's topic",
+                ));
+            }
+            root_topic_id
+        }
+        None => prepared.topic_id,
+    };
+
+    let Some(topic_id) = topic_id else {
+        return Ok(None);
+    };
+    let topic = crate::services::topics::load_chat_topic(conn, prepared.chat_id, topic_id)?;
+    if topic.closed && !matches!(prepared.message_type, MessageType::System) {
+        use crate::schema::group_membership::dsl as gm_dsl;
+
+        let role: Option<GroupRole> = group_membership::table
+            .filter(
+                gm_dsl::chat_id
+                    .eq(prepared.chat_id)
+                    .and(gm_dsl::uid.eq(prepared.sender_uid)),
+            )
+            .select(gm_dsl::role)
+            .first(conn)
+            .optional()?;
+        if role != Some(GroupRole::Admin) {
+            return Err(AppError::Forbidden("Topic is closed"));
+        }
+    }
+    Ok(Some(topic_id))
+}
+
+fn can_post_announcement(
+    conn: &mut PgConnection,
+    state: &AppState,
+    chat_id: i64,
+    uid: i32,
+) -> Result<bool, AppError> {
+    use crate::schema::group_membership::dsl as gm_dsl;
+
+    let role: Option<GroupRole> = group_membership::table
+        .filter(gm_dsl::chat_id.eq(chat_id).and(gm_dsl::uid.eq(uid)))
+        .select(gm_dsl::role)
+        .first(conn)
+        .optional()?;
+    if role == Some(GroupRole::Admin) {
+        return Ok(true);
+    }
+
+    state.authz_service.has_permission(
+        conn,
+        uid,
+        AuthzAction::ChatAnnounce,
+        AuthzResource::Chat(chat_id),
+    )
+}
+
 fn load_message_attachment_ids(conn: &mut PgConnection, message_id: i64) -> QueryResult<Vec<i64>> {
     use crate::schema::attachments::dsl as a_dsl;
     attachments::table
@@ -882,6 +1218,13 @@ pub async fn attach_metadata(
     let target_uids: Vec<i32> = avatar_uids.into_iter().collect();
     let mut user_avatars = lookup_user_avatars(state, &target_uids);
     let mut user_profiles = lookup_user_profiles(conn, &target_uids).unwrap_or_default();
+    let override_message_ids: Vec<i64> = messages_to_process
+        .iter()
+        .chain(reply_messages_map.values())
+        .filter(|message| is_bot_uid(message.sender_uid))
+        .map(|message| message.id)
+        .collect();
+    let sender_overrides = load_sender_overrides(conn, &override_message_ids).unwrap_or_default();
 
     let mut message_attachments_map: std::collections::HashMap<i64, Vec<Attachment>> =
         std::collections::HashMap::new();
@@ -1021,6 +1364,12 @@ pub async fn attach_metadata(
             .collect();
         let reactor_names = load_usernames_by_uids(conn, &all_reactor_uids);
         let reactor_avatars = lookup_user_avatars(state, &all_reactor_uids);
+        let reaction_stickers = self::reactions::load_reaction_stickers(
+            conn,
+            state,
+            counts.iter().map(|(_, emoji, _)| emoji.as_str()),
+            Some(current_user_uid),
+        );
 
         for (msg_id, emoji, count) in counts {
             let reacted_by_me = Some(my_reactions.contains(&(msg_id, emoji.clone())));
@@ -1038,6 +1387,7 @@ pub async fn attach_metadata(
                 .entry(msg_id)
                 .or_default()
                 .push(ReactionSummary {
+                    sticker: reaction_stickers.get(&emoji).cloned(),
                     emoji,
                     count,
                     reacted_by_me,
@@ -1085,6 +1435,14 @@ pub async fn attach_metadata(
         user_avatars.extend(lookup_user_avatars(state, &extra_mention_uids));
     }
 
+    let message_sender = |message: &Message| {
+        let mut sender = build_sender(message.sender_uid, &user_avatars, &user_profiles);
+        if let Some(sender_override) = sender_overrides.get(&message.id) {
+            apply_sender_override(&mut sender, sender_override);
+        }
+        sender
+    };
+
     let mut responses = Vec::with_capacity(messages_to_process.len());
     for (idx, m) in messages_to_process.into_iter().enumerate() {
         let reply_to_message = m
@@ -1123,7 +1481,7 @@ pub async fn attach_metadata(
                         id: reply_msg.id,
                         client_generated_id: reply_msg.client_generated_id.clone(),
                         created_at: reply_msg.created_at,
-                        sender: build_sender(reply_msg.sender_uid, &user_avatars, &user_profiles),
+                        sender: message_sender(reply_msg),
                         message: reply_msg.message.clone(),
                         message_type: reply_msg.message_type.clone(),
                         sticker_id: reply_msg.sticker_id,
@@ -1157,6 +1515,7 @@ pub async fn attach_metadata(
         }
 
         let is_deleted = m.deleted_at.is_some();
+        let sender = message_sender(&m);
         let mut response = MessageResponse {
             id: m.id,
             message: if is_deleted { None } else { m.message },
@@ -1177,7 +1536,7 @@ pub async fn attach_metadata(
             }),
             reply_root_id: m.reply_root_id,
             client_generated_id: m.client_generated_id,
-            sender: build_sender(m.sender_uid, &user_avatars, &user_profiles),
+            sender,
             chat_id: m.chat_id,
             created_at: m.created_at,
             is_edited: m.updated_at.is_some(),
@@ -1203,6 +1562,7 @@ pub async fn attach_metadata(
                     .map(|&uid| build_mention_info(uid, &user_avatars, &user_profiles))
                     .collect()
             },
+            topic_id: m.topic_id,
         };
         redact_deleted_message_response(&mut response);
         responses.push(response);
@@ -1227,9 +1587,15 @@ pub struct ListChatsQuery {
     after: Option<i64>,
     #[serde(default)]
     archived: Option<bool>,
+    #[serde(
+        default,
+        deserialize_with = "crate::serde_i64_string::opt::deserialize"
+    )]
+    #[schema(value_type = Option<String>)]
+    folder: Option<i64>,
 }
 
-/// GET /chats — List chats for the current user (cursor-based).
+/// GET /chats — List chats for the current user (cursor-based). Pinned chats lead the first page.
 #[utoipa::path(
     get,
     path = "/",
@@ -1238,6 +1604,7 @@ pub struct ListChatsQuery {
         ("limit" = Option<i64>, Query, description = "Max number of chats to return"),
         ("after" = Option<String>, Query, description = "Cursor for pagination"),
         ("archived" = Option<bool>, Query, description = "When true, list archived chats instead of active ones"),
+        ("folder" = Option<String>, Query, description = "List chats in this folder; overrides `archived`"),
     ),
     responses(
         (status = 200, description = "List of chats", body = ListChatsResponse),
@@ -1254,19 +1621,38 @@ async fn get_chats(
 
     let limit = validate_limit(q.limit, MAX_CHATS_LIMIT);
     let archived = q.archived.unwrap_or(false);
-
-    let base_query = groups::table
-        .inner_join(group_membership::table)
-        .left_join(
-            messages_schema::table.on(groups::last_message_id.eq(messages_schema::id.nullable())),
-        )
-        .left_join(
-            media::table.on(groups::avatar_image_id
-                .eq(media::id.nullable())
-                .and(media::deleted_at.is_null())),
-        )
-        .filter(group_membership::uid.eq(uid))
-        .filter(group_membership::archived.eq(archived));
+    let folder_chat_ids = q
+        .folder
+        .map(|folder_id| {
+            crate::services::chat_folders::load_folder_chat_ids(
+                conn,
+                state.unread_service.as_ref(),
+                uid,
+                folder_id,
+            )
+        })
+        .transpose()?;
+
+    // Rebuilt per query because boxed statements cannot be cloned.
+    let scoped_query = || {
+        let query = groups::table
+            .inner_join(group_membership::table)
+            .left_join(
+                messages_schema::table
+                    .on(groups::last_message_id.eq(messages_schema::id.nullable())),
+            )
+            .left_join(
+                media::table.on(groups::avatar_image_id
+                    .eq(media::id.nullable())
+                    .and(media::deleted_at.is_null())),
+            )
+            .filter(group_membership::uid.eq(uid))
+            .into_boxed::<diesel::pg::Pg>();
+        match &folder_chat_ids {
+            Some(chat_ids) => query.filter(groups::id.eq_any(chat_ids.clone())),
+            None => query.filter(group_membership::archived.eq(archived)),
+        }
+    };
 
     type RowType = (
         i64,
@@ -1277,8 +1663,31 @@ async fn get_chats(
         Option<crate::models::Message>,
         Option<DateTime<Utc>>,
         bool,
+        Option<i32>,
     );
 
+    // Pinned chats lead the first page; cursor pages only walk unpinned chats.
+    let pinned_rows: Vec<RowType> = if q.after.is_none() {
+        scoped_query()
+            .filter(group_membership::pin_order.is_not_null())
+            .select((
+                groups::id,
+                groups::name,
+                media::storage_key.nullable(),
+                groups::last_message_at,
+                group_membership::last_read_message_id,
+                messages_schema::all_columns.nullable(),
+                group_membership::muted_until,
+                group_membership::archived,
+                group_membership::pin_order,
+            ))
+            .order_by((group_membership::pin_order.asc(), groups::id.desc()))
+            .load(conn)?
+    } else {
+        Vec::new()
+    };
+
+    let base_query = scoped_query().filter(group_membership::pin_order.is_null());
     let rows: Vec<RowType> = match q.after {
         None => base_query
             .select((
@@ -1290,6 +1699,7 @@ async fn get_chats(
                 messages_schema::all_columns.nullable(),
                 group_membership::muted_until,
                 group_membership::archived,
+                group_membership::pin_order,
             ))
             .order_by((
                 groups::last_message_at.desc().nulls_last(),
@@ -1328,6 +1738,7 @@ async fn get_chats(
                         messages_schema::all_columns.nullable(),
                         group_membership::muted_until,
                         group_membership::archived,
+                        group_membership::pin_order,
                     ))
                     .filter(
                         groups::last_message_at
@@ -1353,6 +1764,7 @@ async fn get_chats(
                         messages_schema::all_columns.nullable(),
                         group_membership::muted_until,
                         group_membership::archived,
+                        group_membership::pin_order,
                     ))
                     .filter(
                         groups::last_message_at
@@ -1370,11 +1782,14 @@ async fn get_chats(
     };
 
     let has_more = rows.len() as i64 > limit;
-    let items_to_process: Vec<RowType> = rows.into_iter().take(limit as usize).collect();
+    let items_to_process: Vec<RowType> = pinned_rows
+        .into_iter()
+        .chain(rows.into_iter().take(limit as usize))
+        .collect();
 
     let messages_to_process: Vec<crate::models::Message> = items_to_process
         .iter()
-        .filter_map(|(_, _, _, _, _, msg, _, _)| msg.clone())
+        .filter_map(|(_, _, _, _, _, msg, _, _, _)| msg.clone())
         .collect();
 
     let memberships = items_to_process
@@ -1389,6 +1804,7 @@ async fn get_chats(
                 _msg,
                 muted_until,
                 archived,
+                _pin_order,
             )| crate::services::unread::ChatUnreadMembership {
                 chat_id: *id,
                 last_read_message_id: *last_read_message_id,
@@ -1421,6 +1837,7 @@ async fn get_chats(
                 msg,
                 muted_until,
                 archived,
+                pin_order,
             )| {
                 let unread_count = unread_counts.get(&id).copied().unwrap_or(0);
                 let mr = msg
@@ -1438,12 +1855,21 @@ async fn get_chats(
                     last_message: mr,
                     muted_until,
                     archived,
+                    pin_order,
                 }
             },
         )
         .collect();
 
-    let next_cursor = has_more.then(|| chats.last().map(|c| c.id)).flatten();
+    let next_cursor = has_more
+        .then(|| {
+            chats
+                .iter()
+                .rev()
+                .find(|c| c.pin_order.is_none())
+                .map(|c| c.id)
+        })
+        .flatten();
 
     Ok(Json(ListChatsResponse { chats, next_cursor }))
 }
@@ -1757,6 +2183,151 @@ async fn unarchive_chat(
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
 
+/// PUT /chats/:chat_id/pin — Pin a chat to the top of the user's chat list.
+#[utoipa::path(
+    put,
+    path = "/pin",
+    tag = "chats",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    responses(
+        (status = NO_CONTENT),
+        (status = 400, description = "Pinned chat limit reached"),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn pin_chat(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+) -> Result<axum::http::StatusCode, AppError> {
+    let conn = &mut *conn;
+
+    check_membership(conn, chat_id, uid)?;
+
+    let pinned = conn.transaction::<_, AppError, _>(|conn| {
+        let mut pinned = chat::lock_pinned_chat_ids(conn, uid)?;
+        if pinned.contains(&chat_id) {
+            return Ok(None);
+        }
+        if pinned.len() >= chat::MAX_PINNED_CHATS {
+            return Err(AppError::BadRequest("Pinned chat limit reached"));
+        }
+        pinned.push(chat_id);
+        chat::store_pinned_chat_order(conn, uid, &pinned)?;
+        Ok(Some(pinned))
+    })?;
+
+    if let Some(pinned) = pinned {
+        broadcast_chat_pins(&state, uid, &pinned);
+    }
+
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+/// DELETE /chats/:chat_id/pin — Unpin a chat.
+#[utoipa::path(
+    delete,
+    path = "/pin",
+    tag = "chats",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    responses(
+        (status = NO_CONTENT),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn unpin_chat(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+) -> Result<axum::http::StatusCode, AppError> {
+    let conn = &mut *conn;
+
+    check_membership(conn, chat_id, uid)?;
+
+    let pinned = conn.transaction::<_, AppError, _>(|conn| {
+        let mut pinned = chat::lock_pinned_chat_ids(conn, uid)?;
+        if !pinned.contains(&chat_id) {
+            return Ok(None);
+        }
+        pinned.retain(|id| *id != chat_id);
+        chat::store_pinned_chat_order(conn, uid, &pinned)?;
+        Ok(Some(pinned))
+    })?;
+
+    if let Some(pinned) = pinned {
+        broadcast_chat_pins(&state, uid, &pinned);
+    }
+
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedChatOrderBody {
+    chat_ids: Vec<String>,
+}
+
+/// PUT /chats/pin-order — Reorder the user's pinned chats.
+#[utoipa::path(
+    put,
+    path = "/pin-order",
+    tag = "chats",
+    request_body = PinnedChatOrderBody,
+    responses(
+        (status = NO_CONTENT),
+        (status = 400, description = "Order must list each pinned chat exactly once"),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn put_pinned_chat_order(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Json(body): Json<PinnedChatOrderBody>,
+) -> Result<axum::http::StatusCode, AppError> {
+    let conn = &mut *conn;
+
+    let requested = body
+        .chat_ids
+        .iter()
+        .map(|raw| {
+            raw.trim()
+                .parse::<i64>()
+                .map_err(|_| AppError::BadRequest("Chat id is invalid"))
+        })
+        .collect::<Result<Vec<i64>, AppError>>()?;
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let pinned = chat::lock_pinned_chat_ids(conn, uid)?;
+        if !chat::is_pin_reorder(&pinned, &requested) {
+            return Err(AppError::BadRequest(
+                "Order must list each pinned chat exactly once",
+            ));
+        }
+        chat::store_pinned_chat_order(conn, uid, &requested)?;
+        Ok(())
+    })?;
+
+    broadcast_chat_pins(&state, uid, &requested);
+
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+fn broadcast_chat_pins(state: &AppState, uid: i32, pinned_chat_ids: &[i64]) {
+    state.ws_registry.broadcast_to_uids(
+        &[uid],
+        std::sync::Arc::new(ServerWsMessage::ChatPinsChanged(ChatPinsChangedPayload {
+            pinned_chat_ids: pinned_chat_ids.iter().map(|id| id.to_string()).collect(),
+        })),
+    );
+}
+
 // ---------------------------------------------------------------------------
 // Shared helpers
 // ---------------------------------------------------------------------------
@@ -1860,10 +2431,12 @@ pub fn router() -> OpenApiRouter<crate::AppState> {
     OpenApiRouter::new()
         .routes(utoipa_axum::routes!(get_chats))
         .routes(utoipa_axum::routes!(get_unread_count))
+        .routes(utoipa_axum::routes!(put_pinned_chat_order))
         .nest(
             "/{chat_id}",
             OpenApiRouter::new()
                 .routes(utoipa_axum::routes!(archive_chat, unarchive_chat))
+                .routes(utoipa_axum::routes!(pin_chat, unpin_chat))
                 .nest(
                     "/messages",
                     messages_router().nest("/{message_id}/reactions", reactions_router()),
@@ -1878,7 +2451,8 @@ pub fn router() -> OpenApiRouter<crate::AppState> {
                     super::threads::subscribe_router(),
                 )
                 .nest("/saved-messages", self::saved_messages::router())
-                .nest("/pins", super::pins::router()),
+                .nest("/pins", super::pins::router())
+                .nest("/commands", super::bot_commands::router()),
         )
 }
 
@@ -1888,13 +2462,16 @@ pub fn router() -> OpenApiRouter<crate::AppState> {
 
 #[cfg(test)]
 mod tests {
+    use super::required_content_actions;
     use super::{
         attachment_preview_text, build_message_preview, build_push_preview_bundle,
-        extract_mention_uids, message_is_visible_in_thread_scope, redact_deleted_message_response,
-        render_mentions_as_text, sticker_preview_text, MentionInfo, MessagePreview,
-        MessagePreviewAttachment, MessagePreviewInput, MessageResponse, MessageStickerResponse,
-        PreparedMessageSend, ReactionSummary, StickerMediaResponse,
+        extract_mention_uids, mentions_everyone, message_is_visible_in_thread_scope,
+        redact_deleted_message_response, render_mentions_as_text, sticker_preview_text,
+        MentionInfo, MessagePreview, MessagePreviewAttachment, MessagePreviewInput,
+        MessageResponse, MessageStickerResponse, PreparedMessageSend, ReactionSummary,
+        StickerMediaResponse,
     };
+    use crate::services::authz::Action as AuthzAction;
     use crate::{
         dto::{attachments::AttachmentResponse, users::User},
         models::{Message, MessageType, TranscodeStatus},
@@ -1925,6 +2502,8 @@ mod tests {
             sticker_id: None,
             is_published: true,
             transcode_status: TranscodeStatus::None,
+            topic_id: None,
+            is_important: false,
         };
         patch(&mut message);
         message
@@ -1937,6 +2516,7 @@ mod tests {
             name: Some("Alice".to_string()),
             gender: 0,
             user_group: None,
+            is_bot: false,
         }
     }
 
@@ -2030,6 +2610,15 @@ mod tests {
         assert!(extract_mention_uids(text).is_empty());
     }
 
+    #[test]
+    fn render_mentions_as_text_renders_everyone_mention() {
+        let text = "@[all] 你好";
+
+        assert!(mentions_everyone(text));
+        assert_eq!(render_mentions_as_text(text, &[]), "@all 你好");
+        assert!(extract_mention_uids(text).is_empty());
+    }
+
     #[test]
     fn idempotent_message_payload_accepts_identical_top_level_message() {
         let existing = test_message();
@@ -2118,6 +2707,8 @@ mod tests {
             sticker_id: None,
             is_published: true,
             transcode_status: TranscodeStatus::None,
+            topic_id: None,
+            is_important: false,
         }
     }
 
@@ -2133,6 +2724,9 @@ mod tests {
             client_generated_id: "client-1".to_string(),
             attachment_ids: vec![10, 11],
             publish_immediately: true,
+            important: false,
+            topic_id: None,
+            slow_mode: false,
         }
     }
 
@@ -2151,6 +2745,7 @@ mod tests {
                 name: Some("Alice".to_string()),
                 gender: 0,
                 user_group: None,
+                is_bot: false,
             },
             chat_id: 10,
             created_at: Utc::now(),
@@ -2162,6 +2757,7 @@ mod tests {
             attachments: Vec::new(),
             reactions: Vec::new(),
             mentions: Vec::new(),
+            topic_id: None,
         };
 
         let preview = build_push_preview_bundle(&response);
@@ -2185,6 +2781,7 @@ mod tests {
                 name: Some("Alice".to_string()),
                 gender: 0,
                 user_group: None,
+                is_bot: false,
             },
             chat_id: 10,
             created_at: Utc::now(),
@@ -2204,6 +2801,7 @@ mod tests {
             }],
             reactions: Vec::new(),
             mentions: Vec::new(),
+            topic_id: None,
         };
 
         let preview = build_push_preview_bundle(&response);
@@ -2232,6 +2830,7 @@ mod tests {
                 name: Some("Alice".to_string()),
                 gender: 0,
                 user_group: None,
+                is_bot: false,
             },
             message: Some("voice".to_string()),
             message_type: MessageType::Audio,
@@ -2301,6 +2900,7 @@ mod tests {
                 count: 1,
                 reacted_by_me: Some(true),
                 reactors: None,
+                sticker: None,
             }],
             mentions: vec![MentionInfo {
                 uid: 9,
@@ -2309,6 +2909,7 @@ mod tests {
                 gender: 0,
                 user_group: None,
             }],
+            topic_id: None,
         };
 
         redact_deleted_message_response(&mut response);
@@ -2363,4 +2964,32 @@ mod tests {
         assert!(preview.attachments.is_empty());
         assert!(preview.mentions.is_empty());
     }
+
+    #[test]
+    fn edited_attachments_need_send_media() {
+        let actions = |has_attachments| -> Vec<AuthzAction> {
+            required_content_actions(&MessageType::Text, has_attachments, Some("edited"))
+                .into_iter()
+                .map(|(action, _)| action)
+                .collect()
+        };
+        assert_eq!(actions(true), vec![AuthzAction::ChatSendMedia]);
+        assert!(actions(false).is_empty());
+    }
+
+    #[test]
+    fn edited_everyone_mention_needs_mention_everyone() {
+        let actions = |text| -> Vec<AuthzAction> {
+            required_content_actions(&MessageType::Text, false, Some(text))
+                .into_iter()
+                .map(|(action, _)| action)
+                .collect()
+        };
+        assert_eq!(
+            actions("heads up @[all]"),
+            vec![AuthzAction::ChatMentionEveryone]
+        );
+        assert!(actions("heads up").is_empty());
+        assert!(required_content_actions(&MessageType::System, true, Some("@[all]")).is_empty());
+    }
 }
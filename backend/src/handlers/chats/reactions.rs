@@ -18,13 +18,137 @@ use crate::{
     extractors::DbConn,
     handlers::members::check_membership,
     models::{Message, MessageReaction},
-    schema::{group_membership, message_reactions, messages},
+    schema::{
+        group_membership, groups, message_reactions, messages, sticker_pack_stickers,
+        sticker_packs, user_sticker_pack_subscriptions,
+    },
     services::user::lookup_user_avatars,
     utils::auth::CurrentUid,
     AppState,
 };
 
-use super::load_usernames_by_uids;
+use super::{
+    build_message_sticker_response, load_favorited_sticker_ids, load_sticker_rows,
+    load_usernames_by_uids,
+};
+use crate::dto::messages::MessageStickerResponse;
+
+/// Reaction keys of this form react with a sticker instead of a Unicode emoji.
+pub(super) const STICKER_REACTION_PREFIX: &str = "sticker:";
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ReactionKey {
+    Emoji(String),
+    Sticker(i64),
+}
+
+impl ReactionKey {
+    pub(crate) fn parse(input: &str) -> Result<Self, AppError> {
+        match input.strip_prefix(STICKER_REACTION_PREFIX) {
+            Some(id) => sticker_reaction_id(input)
+                .filter(|_| !id.starts_with('+'))
+                .map(Self::Sticker)
+                .ok_or(AppError::BadRequest("Invalid sticker reaction")),
+            None => validate_emoji(input).map(Self::Emoji),
+        }
+    }
+
+    /// Value stored in `message_reactions.emoji`.
+    fn storage_key(&self) -> String {
+        match self {
+            Self::Emoji(emoji) => emoji.clone(),
+            Self::Sticker(sticker_id) => format!("{STICKER_REACTION_PREFIX}{sticker_id}"),
+        }
+    }
+
+    fn sticker_id(&self) -> Option<i64> {
+        match self {
+            Self::Emoji(_) => None,
+            Self::Sticker(sticker_id) => Some(*sticker_id),
+        }
+    }
+}
+
+pub(super) fn sticker_reaction_id(key: &str) -> Option<i64> {
+    key.strip_prefix(STICKER_REACTION_PREFIX)?
+        .parse()
+        .ok()
+        .filter(|id| *id > 0)
+}
+
+/// Stickers usable as reactions: the caller's subscribed or owned packs, plus the chat's own pack.
+fn ensure_sticker_reaction_allowed(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    uid: i32,
+    sticker_id: i64,
+) -> Result<(), AppError> {
+    let chat_pack_id: Option<i64> = groups::table
+        .filter(groups::id.eq(chat_id))
+        .select(groups::sticker_pack_id)
+        .first(conn)?;
+
+    let allowed: i64 = sticker_pack_stickers::table
+        .filter(sticker_pack_stickers::sticker_id.eq(sticker_id))
+        .filter(
+            sticker_pack_stickers::pack_id
+                .eq_any(
+                    user_sticker_pack_subscriptions::table
+                        .filter(user_sticker_pack_subscriptions::uid.eq(uid))
+                        .select(user_sticker_pack_subscriptions::pack_id),
+                )
+                .or(sticker_pack_stickers::pack_id.eq_any(
+                    sticker_packs::table
+                        .filter(sticker_packs::owner_uid.eq(uid))
+                        .select(sticker_packs::id),
+                ))
+                .or(sticker_pack_stickers::pack_id.nullable().eq(chat_pack_id)),
+        )
+        .count()
+        .get_result(conn)?;
+
+    if allowed == 0 {
+        return Err(AppError::Forbidden(
+            "Sticker is not available for reactions",
+        ));
+    }
+    Ok(())
+}
+
+/// Sticker media for every sticker reaction key in `keys`, keyed by reaction key.
+/// `viewer_uid` resolves `isFavorited`; broadcasts pass `None`.
+pub(super) fn load_reaction_stickers<'a>(
+    conn: &mut PgConnection,
+    state: &AppState,
+    keys: impl IntoIterator<Item = &'a str>,
+    viewer_uid: Option<i32>,
+) -> std::collections::HashMap<String, MessageStickerResponse> {
+    let mut sticker_ids: Vec<i64> = keys.into_iter().filter_map(sticker_reaction_id).collect();
+    sticker_ids.sort_unstable();
+    sticker_ids.dedup();
+    if sticker_ids.is_empty() {
+        return std::collections::HashMap::new();
+    }
+
+    let rows = load_sticker_rows(conn, &sticker_ids).unwrap_or_default();
+    let favorited = match viewer_uid {
+        Some(uid) => load_favorited_sticker_ids(conn, uid, &sticker_ids).unwrap_or_default(),
+        None => std::collections::HashSet::new(),
+    };
+    rows.iter()
+        .map(|(sticker_id, (sticker, media_row))| {
+            (
+                format!("{STICKER_REACTION_PREFIX}{sticker_id}"),
+                build_message_sticker_response(
+                    state,
+                    sticker,
+                    media_row,
+                    favorited.contains(sticker_id),
+                ),
+            )
+        })
+        .collect()
+}
 
 fn validate_emoji(input: &str) -> Result<String, AppError> {
     if input.is_empty() {
@@ -82,6 +206,12 @@ fn broadcast_reaction_update(
         .collect();
     let names = load_usernames_by_uids(conn, &all_uids);
     let avatars = lookup_user_avatars(state, &all_uids);
+    let stickers = load_reaction_stickers(
+        conn,
+        state,
+        counts.iter().map(|(key, _)| key.as_str()),
+        None,
+    );
 
     let reactions: Vec<ReactionSummary> = counts
         .into_iter()
@@ -97,6 +227,7 @@ fn broadcast_reaction_update(
                     .collect()
             });
             ReactionSummary {
+                sticker: stickers.get(&emoji).cloned(),
                 emoji,
                 count,
                 reacted_by_me: None,
@@ -116,6 +247,7 @@ fn broadcast_reaction_update(
         chat_id,
         reactions,
     }));
+    state.webhook_service.publish_ws_message(&ws_msg);
     state.ws_registry.broadcast_to_uids(&member_uids, ws_msg);
 }
 
@@ -210,7 +342,7 @@ async fn get_reaction_details(
     params(
         ("chat_id" = i64, Path, description = "Chat ID"),
         ("message_id" = i64, Path, description = "Message ID"),
-        ("emoji" = String, Path, description = "Emoji character"),
+        ("emoji" = String, Path, description = "Emoji character, or `sticker:<id>` for a sticker reaction"),
     ),
     responses(
         (status = 204, description = "Reaction added"),
@@ -224,8 +356,25 @@ async fn put_reaction(
     mut conn: DbConn,
 ) -> Result<StatusCode, AppError> {
     let conn = &mut *conn;
-    let emoji = validate_emoji(&emoji)?;
+    let key = ReactionKey::parse(&emoji)?;
     check_membership(conn, chat_id, uid)?;
+    add_reaction(conn, &state, chat_id, message_id, uid, &key)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Add `uid`'s reaction to a message and broadcast the new counts. Callers check membership first.
+pub(crate) fn add_reaction(
+    conn: &mut PgConnection,
+    state: &AppState,
+    chat_id: i64,
+    message_id: i64,
+    uid: i32,
+    key: &ReactionKey,
+) -> Result<(), AppError> {
+    if let Some(sticker_id) = key.sticker_id() {
+        ensure_sticker_reaction_allowed(conn, chat_id, uid, sticker_id)?;
+    }
 
     // Verify message exists and belongs to this chat
     let _message: Message = messages::table
@@ -242,8 +391,9 @@ async fn put_reaction(
         .values(&MessageReaction {
             message_id,
             user_uid: uid,
-            emoji,
+            emoji: key.storage_key(),
             created_at: Utc::now(),
+            sticker_id: key.sticker_id(),
         })
         .on_conflict_do_nothing()
         .execute(conn)?;
@@ -253,9 +403,9 @@ async fn put_reaction(
         .set(messages::has_reactions.eq(true))
         .execute(conn)?;
 
-    broadcast_reaction_update(conn, &state, chat_id, message_id);
+    broadcast_reaction_update(conn, state, chat_id, message_id);
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
 }
 
 #[utoipa::path(
@@ -265,7 +415,7 @@ async fn put_reaction(
     params(
         ("chat_id" = i64, Path, description = "Chat ID"),
         ("message_id" = i64, Path, description = "Message ID"),
-        ("emoji" = String, Path, description = "Emoji character"),
+        ("emoji" = String, Path, description = "Emoji character, or `sticker:<id>` for a sticker reaction"),
     ),
     responses(
         (status = 204, description = "Reaction removed"),
@@ -279,9 +429,24 @@ async fn delete_reaction(
     mut conn: DbConn,
 ) -> Result<StatusCode, AppError> {
     let conn = &mut *conn;
-    let emoji = validate_emoji(&emoji)?;
+    let key = ReactionKey::parse(&emoji)?;
     check_membership(conn, chat_id, uid)?;
+    remove_reaction(conn, &state, chat_id, message_id, uid, &key)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
 
+/// Remove `uid`'s reaction from a message, broadcasting if one existed. Callers check
+/// membership first.
+pub(crate) fn remove_reaction(
+    conn: &mut PgConnection,
+    state: &AppState,
+    chat_id: i64,
+    message_id: i64,
+    uid: i32,
+    key: &ReactionKey,
+) -> Result<(), AppError> {
+    let emoji = key.storage_key();
     let deleted = diesel::delete(
         message_reactions::table
             .filter(message_reactions::message_id.eq(message_id))
@@ -303,10 +468,10 @@ async fn delete_reaction(
                 .execute(conn)?;
         }
 
-        broadcast_reaction_update(conn, &state, chat_id, message_id);
+        broadcast_reaction_update(conn, state, chat_id, message_id);
     }
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
 }
 
 pub fn router() -> OpenApiRouter<crate::AppState> {
@@ -317,9 +482,35 @@ pub fn router() -> OpenApiRouter<crate::AppState> {
 
 #[cfg(test)]
 mod tests {
-    use super::validate_emoji;
+    use super::{validate_emoji, ReactionKey};
     use crate::errors::AppError;
 
+    #[test]
+    fn sticker_reaction_keys_round_trip() {
+        let key = ReactionKey::parse("sticker:42").unwrap();
+        assert_eq!(key, ReactionKey::Sticker(42));
+        assert_eq!(key.storage_key(), "sticker:42");
+        assert_eq!(key.sticker_id(), Some(42));
+
+        assert_eq!(
+            ReactionKey::parse("👍").unwrap(),
+            ReactionKey::Emoji("👍".to_string())
+        );
+
+        for invalid in [
+            "sticker:",
+            "sticker:abc",
+            "sticker:0",
+            "sticker:-4",
+            "sticker:+4",
+        ] {
+            assert!(matches!(
+                ReactionKey::parse(invalid),
+                Err(AppError::BadRequest("Invalid sticker reaction"))
+            ));
+        }
+    }
+
     #[test]
     fn reaction_emoji_must_be_exactly_one_grapheme() {
         for emoji in ["🙂", "👍🏽", "👨‍👩‍👧‍👦", "❤️"] {
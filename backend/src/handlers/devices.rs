@@ -0,0 +1,245 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde::Deserialize;
+use std::collections::HashSet;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::dto::devices::{DeviceResponse, ListDevicesResponse, UpdateDeviceRequest};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::models::ClientRecord;
+use crate::schema::{clients, push_subscriptions};
+use crate::utils::auth::{resolve_client_id, CurrentUid};
+use crate::AppState;
+
+const MAX_DEVICE_NAME_CHARS: usize = 64;
+/// Prefix the native app puts before its platform in `X-App-Version`, e.g. `f(ios)-1.4.0+12`.
+const NATIVE_APP_VERSION_PREFIX: &str = "f(";
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct DevicePath {
+    client_id: String,
+}
+
+/// GET /users/devices — Devices the caller is signed in on, most recently active first.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "users",
+    responses(
+        (status = OK, body = ListDevicesResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn get_devices(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut conn: DbConn,
+) -> Result<Json<ListDevicesResponse>, AppError> {
+    let conn = &mut *conn;
+    let current_client_id = resolve_client_id(&headers, &state)?;
+
+    let rows = clients::table
+        .filter(clients::last_active_uid.eq(uid))
+        .order(clients::last_active.desc())
+        .select(ClientRecord::as_select())
+        .load::<ClientRecord>(conn)?;
+    let client_ids: Vec<&str> = rows.iter().map(|row| row.client_id.as_str()).collect();
+    let subscribed: HashSet<String> = push_subscriptions::table
+        .filter(push_subscriptions::user_id.eq(uid))
+        .filter(push_subscriptions::client_id.eq_any(&client_ids))
+        .select(push_subscriptions::client_id.assume_not_null())
+        .distinct()
+        .load::<String>(conn)?
+        .into_iter()
+        .collect();
+    let online = state.ws_registry.connected_client_ids(uid);
+
+    let devices = rows
+        .into_iter()
+        .map(|row| {
+            let (platform, app_version) = parse_app_version(row.app_version.as_deref());
+            DeviceResponse {
+                platform: platform.to_string(),
+                app_version: app_version.map(str::to_string),
+                name: row.device_name,
+                created_at: row.created_at.and_utc(),
+                last_active_at: row.last_active.and_utc(),
+                has_push_subscription: subscribed.contains(&row.client_id),
+                online: online.contains(&row.client_id),
+                current: current_client_id.as_deref() == Some(row.client_id.as_str()),
+                client_id: row.client_id,
+            }
+        })
+        .collect();
+
+    Ok(Json(ListDevicesResponse { devices }))
+}
+
+/// PATCH /users/devices/:client_id — Rename one of the caller's devices.
+#[utoipa::path(
+    patch,
+    path = "/{client_id}",
+    tag = "users",
+    params(DevicePath),
+    request_body = UpdateDeviceRequest,
+    responses(
+        (status = 204, description = "Device renamed"),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn patch_device(
+    CurrentUid(uid): CurrentUid,
+    Path(DevicePath { client_id }): Path<DevicePath>,
+    mut conn: DbConn,
+    Json(body): Json<UpdateDeviceRequest>,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+
+    let name = normalize_device_name(body.name.as_deref())?;
+    let updated = diesel::update(
+        clients::table
+            .filter(clients::client_id.eq(&client_id))
+            .filter(clients::last_active_uid.eq(uid)),
+    )
+    .set(clients::device_name.eq(name))
+    .execute(conn)?;
+    if updated == 0 {
+        return Err(AppError::NotFound("Device not found"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /users/devices/:client_id — Sign a device out: revoke its tokens, drop its push
+/// subscriptions and close its WebSocket connections.
+#[utoipa::path(
+    delete,
+    path = "/{client_id}",
+    tag = "users",
+    params(DevicePath),
+    responses(
+        (status = 204, description = "Device revoked"),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn delete_device(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(DevicePath { client_id }): Path<DevicePath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+
+    ensure_own_device(conn, uid, &client_id)?;
+    state.auth_sessions.logout(conn, uid, &client_id)?;
+    let deleted_subscriptions = diesel::delete(
+        push_subscriptions::table
+            .filter(push_subscriptions::user_id.eq(uid))
+            .filter(push_subscriptions::client_id.eq(&client_id)),
+    )
+    .execute(conn)?;
+    let closed_connections = state.ws_registry.close_client_connections(uid, &client_id);
+
+    tracing::info!(
+        uid,
+        client_id,
+        deleted_subscriptions,
+        closed_connections,
+        "device revoked"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_devices))
+        .routes(routes!(patch_device, delete_device))
+}
+
+fn ensure_own_device(conn: &mut PgConnection, uid: i32, client_id: &str) -> Result<(), AppError> {
+    let exists = clients::table
+        .filter(clients::client_id.eq(client_id))
+        .filter(clients::last_active_uid.eq(uid))
+        .count()
+        .get_result::<i64>(conn)?;
+    if exists == 0 {
+        return Err(AppError::NotFound("Device not found"));
+    }
+    Ok(())
+}
+
+fn normalize_device_name(name: Option<&str>) -> Result<Option<String>, AppError> {
+    let Some(name) = name.map(str::trim).filter(|name| !name.is_empty()) else {
+        return Ok(None);
+    };
+    if name.chars().count() > MAX_DEVICE_NAME_CHARS {
+        return Err(AppError::BadRequest("Device name is too long"));
+    }
+    Ok(Some(name.to_string()))
+}
+
+/// Split `X-App-Version` into platform and version. The native app sends `f(<platform>)-<version>`;
+/// the web app sends a bare build version.
+fn parse_app_version(header: Option<&str>) -> (&str, Option<&str>) {
+    let Some(header) = header.map(str::trim).filter(|header| !header.is_empty()) else {
+        return ("unknown", None);
+    };
+    let native = header
+        .strip_prefix(NATIVE_APP_VERSION_PREFIX)
+        .and_then(|rest| rest.split_once(')'))
+        .filter(|(platform, _)| {
+            !platform.is_empty() && platform.bytes().all(|b| b.is_ascii_alphanumeric())
+        });
+    match native {
+        Some((platform, version)) => {
+            let version = version.strip_prefix('-').unwrap_or(version);
+            (platform, (!version.is_empty()).then_some(version))
+        }
+        None => ("web", Some(header)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_device_name, parse_app_version};
+    use crate::errors::AppError;
+
+    #[test]
+    fn parse_app_version_extracts_platform() {
+        assert_eq!(
+            parse_app_version(Some("f(ios)-1.4.0+12")),
+            ("ios", Some("1.4.0+12"))
+        );
+        assert_eq!(
+            parse_app_version(Some("f(android)-2.0.0")),
+            ("android", Some("2.0.0"))
+        );
+        assert_eq!(parse_app_version(Some("a1b2c3d")), ("web", Some("a1b2c3d")));
+        assert_eq!(parse_app_version(Some("f(bad platform)-1")).0, "web");
+        assert_eq!(parse_app_version(None), ("unknown", None));
+        assert_eq!(parse_app_version(Some("  ")), ("unknown", None));
+    }
+
+    #[test]
+    fn normalize_device_name_trims_and_clears() {
+        assert_eq!(
+            normalize_device_name(Some("  Work phone ")).unwrap(),
+            Some("Work phone".to_string())
+        );
+        assert_eq!(normalize_device_name(Some("   ")).unwrap(), None);
+        assert_eq!(normalize_device_name(None).unwrap(), None);
+        assert!(matches!(
+            normalize_device_name(Some(&"x".repeat(65))),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
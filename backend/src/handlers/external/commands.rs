@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use serde::Deserialize;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+use uuid::Uuid;
+
+use super::invites::require_service_token_principal;
+use super::messages::require_bot_in_chat;
+use crate::dto::bot_commands::{
+    CommandReplyPayload, CommandReplyRequest, ListBotCommandsResponse, SetBotCommandsRequest,
+};
+use crate::dto::messages::MessageResponse;
+use crate::dto::ws::ServerWsMessage;
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::handlers::chats::{create_message, CreateMessageBody};
+use crate::models::MessageType;
+use crate::services::authz::Action as AuthzAction;
+use crate::services::bot_commands as bot_command_service;
+use crate::services::bots as bot_service;
+use crate::utils::auth::Principal;
+use crate::AppState;
+
+const MAX_EPHEMERAL_REPLY_CHARS: usize = 4000;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct ChatPath {
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    chat_id: i64,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct InvocationPath {
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    invocation_id: i64,
+}
+
+/// GET /external/commands/:chat_id — Commands the bot declared in a chat.
+#[utoipa::path(
+    get,
+    path = "/{chat_id}",
+    tag = "external-commands",
+    params(ChatPath),
+    responses(
+        (status = 200, description = "The bot's commands", body = ListBotCommandsResponse),
+        (status = 403, description = "Missing `bot.post`, no bot, or bot is not a member"),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn get_external_commands(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(ChatPath { chat_id }): Path<ChatPath>,
+    mut conn: DbConn,
+) -> Result<Json<ListBotCommandsResponse>, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let bot = require_bot_in_chat(conn, &state, &service_token, AuthzAction::BotPost, chat_id)?;
+
+    Ok(Json(list_bot_commands(conn, chat_id, &bot)?))
+}
+
+/// PUT /external/commands/:chat_id — Replace the bot's commands in a chat.
+#[utoipa::path(
+    put,
+    path = "/{chat_id}",
+    tag = "external-commands",
+    params(ChatPath),
+    request_body = SetBotCommandsRequest,
+    responses(
+        (status = 200, description = "The bot's commands", body = ListBotCommandsResponse),
+        (status = 403, description = "Missing `bot.post`, no bot, or bot is not a member"),
+        (status = 409, description = "A command name is used by another bot in the chat"),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn put_external_commands(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(ChatPath { chat_id }): Path<ChatPath>,
+    mut conn: DbConn,
+    Json(body): Json<SetBotCommandsRequest>,
+) -> Result<Json<ListBotCommandsResponse>, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let bot = require_bot_in_chat(conn, &state, &service_token, AuthzAction::BotPost, chat_id)?;
+
+    let commands = bot_command_service::normalize_definitions(chat_id, bot.uid, body.commands)?;
+    bot_command_service::replace_commands(conn, chat_id, bot.uid, commands)?;
+
+    Ok(Json(list_bot_commands(conn, chat_id, &bot)?))
+}
+
+/// POST /external/commands/invocations/:invocation_id/reply — Answer a command invocation.
+#[utoipa::path(
+    post,
+    path = "/invocations/{invocation_id}/reply",
+    tag = "external-commands",
+    params(InvocationPath),
+    request_body = CommandReplyRequest,
+    responses(
+        (status = 201, description = "Public reply posted", body = MessageResponse),
+        (status = 204, description = "Ephemeral reply sent to the invoker's open sessions"),
+        (status = 404, description = "Invocation not found or addressed to another bot"),
+        (status = 410, description = "Invocation has expired"),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn post_command_reply(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(InvocationPath { invocation_id }): Path<InvocationPath>,
+    mut conn: DbConn,
+    Json(body): Json<CommandReplyRequest>,
+) -> Result<Response, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let bot = bot_service::find_bot_for_service_token(conn, service_token.id)?
+        .ok_or(AppError::Forbidden("Service token has no bot"))?;
+    let invocation = bot_command_service::load_invocation_for_bot(conn, invocation_id, bot.uid)?;
+    require_bot_in_chat(
+        conn,
+        &state,
+        &service_token,
+        AuthzAction::BotPost,
+        invocation.chat_id,
+    )?;
+
+    if body.ephemeral {
+        let text = body.text.trim();
+        if text.is_empty() {
+            return Err(AppError::BadRequest("Reply text is required"));
+        }
+        if text.chars().count() > MAX_EPHEMERAL_REPLY_CHARS {
+            return Err(AppError::BadRequest("Reply text is too long"));
+        }
+        state.ws_registry.broadcast_to_uids(
+            &[invocation.invoker_uid],
+            Arc::new(ServerWsMessage::CommandReply(CommandReplyPayload {
+                invocation_id: invocation.id,
+                chat_id: invocation.chat_id,
+                command: invocation.command,
+                bot_uid: bot.uid,
+                text: text.to_string(),
+                created_at: Utc::now(),
+            })),
+        );
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
+
+    let response = create_message(
+        conn,
+        &state,
+        invocation.chat_id,
+        bot.uid,
+        CreateMessageBody {
+            message: Some(body.text),
+            message_type: MessageType::Text,
+            sticker_id: None,
+            client_generated_id: body
+                .client_generated_id
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            reply_to_id: invocation.reply_to_id,
+            attachment_ids: Vec::new(),
+            important: false,
+            topic_id: invocation.topic_id,
+        },
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(response)).into_response())
+}
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_external_commands, put_external_commands))
+        .routes(routes!(post_command_reply))
+}
+
+fn list_bot_commands(
+    conn: &mut diesel::PgConnection,
+    chat_id: i64,
+    bot: &crate::models::Bot,
+) -> Result<ListBotCommandsResponse, AppError> {
+    let commands = bot_command_service::list_commands(conn, chat_id, None)?
+        .into_iter()
+        .filter(|(command, _)| command.bot_uid == bot.uid)
+        .map(|(command, bot)| bot_command_service::command_to_response(command, &bot))
+        .collect();
+    Ok(ListBotCommandsResponse { commands })
+}
@@ -0,0 +1,263 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use super::invites::require_service_token_principal;
+use crate::dto::external::imports::{ExternalImportRequest, ExternalImportResponse};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::models::{
+    GroupJoinReason, GroupRole, GroupVisibility, Message, NewGroup, NewGroupMembership,
+};
+use crate::schema::{group_membership, groups, messages};
+use crate::services::authz::{Action as AuthzAction, Resource as AuthzResource};
+use crate::services::chat_import::{
+    allocate_historical_ids, apply_import, load_imported_message_ids, plan_telegram_import,
+    prepare_import, MAX_IMPORT_BODY_BYTES,
+};
+use crate::utils::{auth::Principal, ids};
+use crate::AppState;
+
+fn validate_target(body: &ExternalImportRequest) -> Result<(), AppError> {
+    match (&body.chat_id, &body.new_chat) {
+        (Some(_), Some(_)) | (None, None) => Err(AppError::BadRequest(
+            "Exactly one of chatId or newChat is required",
+        )),
+        (None, Some(new_chat)) if new_chat.owner_uid <= 0 => {
+            Err(AppError::BadRequest("ownerUid is invalid"))
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn allocate_ids(state: &AppState, count: usize) -> Result<Vec<i64>, AppError> {
+    let mut allocated = Vec::with_capacity(count);
+    for _ in 0..count {
+        allocated.push(
+            ids::next_message_id(state.id_gen.as_ref())
+                .await
+                .map_err(|e| {
+                    tracing::error!("next_message_id for import: {:?}", e);
+                    AppError::Internal("ID generation failed")
+                })?,
+        );
+    }
+    Ok(allocated)
+}
+
+/// POST /external/imports — Import a Telegram-style JSON export into a new or existing chat.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "external-imports",
+    request_body = ExternalImportRequest,
+    responses(
+        (status = 200, description = "History imported into an existing chat", body = ExternalImportResponse),
+        (status = 201, description = "Chat created and history imported", body = ExternalImportResponse)
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn post_external_import(
+    principal: Principal,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Json(body): Json<ExternalImportRequest>,
+) -> Result<(StatusCode, Json<ExternalImportResponse>), AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    if !state.authz_service.has_service_token_permission(
+        conn,
+        service_token.id,
+        AuthzAction::ChatImport,
+        AuthzResource::Global,
+    )? {
+        return Err(AppError::Forbidden("Permission required"));
+    }
+
+    validate_target(&body)?;
+    let plan = plan_telegram_import(
+        &body.export,
+        &body.sender_map,
+        body.fallback_sender_uid,
+        body.replies_as_threads,
+    )?;
+    for media in body.media.values() {
+        if !media.storage_key.starts_with(&state.s3_attachment_prefix) || media.size < 0 {
+            return Err(AppError::BadRequest("Invalid media entry"));
+        }
+    }
+
+    let (chat_id, created_chat) = match body.chat_id {
+        Some(chat_id) => {
+            service_token.require_chat_allowed(chat_id)?;
+            let exists: i64 = groups::table
+                .filter(groups::id.eq(chat_id))
+                .count()
+                .get_result(conn)?;
+            if exists == 0 {
+                return Err(AppError::NotFound("Chat not found"));
+            }
+            (chat_id, false)
+        }
+        None => {
+            if service_token.allowed_chat_ids.is_some() {
+                return Err(AppError::Forbidden(
+                    "Service token is restricted to specific chats",
+                ));
+            }
+            let chat_id = ids::next_gid(state.id_gen.as_ref()).await.map_err(|e| {
+                tracing::error!("ferroid next_gid: {:?}", e);
+                AppError::Internal("ID generation failed")
+            })?;
+            (chat_id, true)
+        }
+    };
+
+    let source_ids: Vec<i64> = plan.messages.iter().map(|m| m.source_id).collect();
+    let already_imported = if created_chat {
+        Default::default()
+    } else {
+        load_imported_message_ids(conn, chat_id, &source_ids)?
+    };
+    let pending: Vec<_> = plan
+        .messages
+        .iter()
+        .filter(|m| !already_imported.contains_key(&m.source_id))
+        .collect();
+    let media_count = pending
+        .iter()
+        .filter(|m| {
+            m.media_path
+                .as_ref()
+                .is_some_and(|path| body.media.contains_key(path))
+        })
+        .count();
+    let attachment_ids = allocate_ids(&state, media_count).await?;
+
+    let mut missing_media = Vec::new();
+    let prepared = conn.transaction::<_, AppError, _>(|conn| {
+        let timestamps: Vec<_> = pending.iter().map(|m| m.created_at).collect();
+        let message_ids = allocate_historical_ids(conn, &timestamps)?;
+        let prepared = prepare_import(
+            chat_id,
+            &plan,
+            &already_imported,
+            &body.media,
+            &mut message_ids.into_iter(),
+            &mut attachment_ids.into_iter(),
+            &mut missing_media,
+        );
+
+        if let Some(new_chat) = &body.new_chat {
+            let now = Utc::now();
+            let name = new_chat
+                .name
+                .clone()
+                .or_else(|| body.export.name.clone())
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_default();
+            diesel::insert_into(groups::table)
+                .values(&NewGroup {
+                    id: chat_id,
+                    name,
+                    description: new_chat.description.clone(),
+                    avatar_image_id: None,
+                    created_at: now,
+                    visibility: new_chat.visibility.unwrap_or(GroupVisibility::Private),
+                })
+                .execute(conn)?;
+            diesel::insert_into(group_membership::table)
+                .values(&NewGroupMembership {
+                    chat_id,
+                    uid: new_chat.owner_uid,
+                    role: GroupRole::Admin,
+                    joined_at: now,
+                    join_reason: GroupJoinReason::Creator,
+                    join_reason_extra: None,
+                    last_read_message_id: prepared.messages.iter().map(|m| m.id).max(),
+                })
+                .execute(conn)?;
+        }
+        apply_import(conn, chat_id, &prepared)?;
+        Ok(prepared)
+    })?;
+
+    let imported_ids: Vec<i64> = prepared.messages.iter().map(|m| m.id).collect();
+    if !imported_ids.is_empty() {
+        state.unread_service.invalidate_chat(chat_id);
+        if let Some(search_service) = &state.message_search {
+            for chunk in imported_ids.chunks(1000) {
+                let rows: Vec<Message> = messages::table
+                    .filter(messages::id.eq_any(chunk))
+                    .select(Message::as_select())
+                    .load(conn)?;
+                search_service.upsert_messages_best_effort(rows);
+            }
+        }
+    }
+
+    tracing::info!(
+        chat_id,
+        service_token_id = service_token.id,
+        imported = imported_ids.len(),
+        "Chat history import completed"
+    );
+
+    Ok((
+        if created_chat {
+            StatusCode::CREATED
+        } else {
+            StatusCode::OK
+        },
+        Json(ExternalImportResponse {
+            chat_id,
+            created_chat,
+            imported_count: imported_ids.len(),
+            skipped_existing: already_imported.len(),
+            skipped_service: plan.skipped_service,
+            skipped_empty: plan.skipped_empty + prepared.skipped_empty,
+            missing_media,
+        }),
+    ))
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(post_external_import))
+        .layer(axum::extract::DefaultBodyLimit::max(MAX_IMPORT_BODY_BYTES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_target;
+    use crate::dto::external::imports::ExternalImportRequest;
+    use crate::errors::AppError;
+    use serde_json::json;
+
+    fn request(target: serde_json::Value) -> ExternalImportRequest {
+        let mut body = json!({ "export": { "messages": [] } });
+        body.as_object_mut()
+            .unwrap()
+            .extend(target.as_object().unwrap().clone());
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn target_must_be_exactly_one_of_chat_or_new_chat() {
+        assert!(matches!(
+            validate_target(&request(json!({}))),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            validate_target(&request(json!({"chatId": "5", "newChat": {"ownerUid": 1}}))),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(validate_target(&request(json!({"chatId": "5"}))).is_ok());
+        assert!(validate_target(&request(json!({"newChat": {"ownerUid": 1}}))).is_ok());
+    }
+}
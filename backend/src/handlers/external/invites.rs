@@ -41,6 +41,7 @@ async fn post_external_invite(
     let service_token = require_service_token_principal(principal)?;
     let conn = &mut *conn;
     require_invite_create_permission(conn, &state, service_token.id)?;
+    service_token.require_chat_allowed(body.chat_id)?;
 
     let now = Utc::now();
     validate_request(&body, now)?;
@@ -97,7 +98,7 @@ pub fn router() -> OpenApiRouter<crate::AppState> {
     OpenApiRouter::new().routes(routes!(post_external_invite))
 }
 
-fn require_service_token_principal(
+pub(super) fn require_service_token_principal(
     principal: Principal,
 ) -> Result<ServiceTokenPrincipal, AppError> {
     match principal {
@@ -230,6 +231,7 @@ mod tests {
         let service =
             require_service_token_principal(Principal::ServiceToken(ServiceTokenPrincipal {
                 id: 5,
+                allowed_chat_ids: None,
             }))
             .unwrap();
         assert_eq!(service.id, 5);
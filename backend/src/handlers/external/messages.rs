@@ -0,0 +1,267 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+};
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde::Deserialize;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use super::invites::require_service_token_principal;
+use crate::dto::external::messages::{ExternalEditMessageRequest, ExternalPostMessageRequest};
+use crate::dto::messages::MessageResponse;
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::handlers::chats::{
+    add_reaction, create_message, edit_message, remove_reaction, soft_delete_message,
+    CreateMessageBody, ReactionKey,
+};
+use crate::handlers::members::check_membership;
+use crate::models::{Bot, MessageType};
+use crate::schema::messages;
+use crate::services::authz::{Action as AuthzAction, Resource as AuthzResource};
+use crate::services::bots as bot_service;
+use crate::utils::auth::{Principal, ServiceTokenPrincipal};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct MessagePath {
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    message_id: i64,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct ReactionPath {
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    message_id: i64,
+    /// Emoji character, or `sticker:<id>` for a sticker reaction.
+    emoji: String,
+}
+
+/// POST /external/messages — Post a text message as the service token's bot.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "external-messages",
+    request_body = ExternalPostMessageRequest,
+    responses(
+        (status = 201, description = "Message created", body = MessageResponse),
+        (status = 403, description = "Missing `bot.post`, no bot, or bot is not a member"),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn post_external_message(
+    principal: Principal,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Json(body): Json<ExternalPostMessageRequest>,
+) -> Result<(StatusCode, Json<MessageResponse>), AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let bot = require_bot_in_chat(
+        conn,
+        &state,
+        &service_token,
+        AuthzAction::BotPost,
+        body.chat_id,
+    )?;
+
+    let response = create_message(
+        conn,
+        &state,
+        body.chat_id,
+        bot.uid,
+        CreateMessageBody {
+            message: Some(body.message),
+            message_type: MessageType::Text,
+            sticker_id: None,
+            client_generated_id: body.client_generated_id,
+            reply_to_id: body.reply_to_id,
+            attachment_ids: Vec::new(),
+            important: false,
+            topic_id: body.topic_id,
+        },
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// PATCH /external/messages/:message_id — Edit a message the bot posted.
+#[utoipa::path(
+    patch,
+    path = "/{message_id}",
+    tag = "external-messages",
+    params(MessagePath),
+    request_body = ExternalEditMessageRequest,
+    responses(
+        (status = 200, description = "Updated message", body = MessageResponse),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn patch_external_message(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(MessagePath { message_id }): Path<MessagePath>,
+    mut conn: DbConn,
+    Json(body): Json<ExternalEditMessageRequest>,
+) -> Result<Json<MessageResponse>, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let chat_id = load_message_chat_id(conn, message_id)?;
+    let bot = require_bot_in_chat(conn, &state, &service_token, AuthzAction::BotPost, chat_id)?;
+
+    let response = edit_message(
+        conn,
+        &state,
+        chat_id,
+        message_id,
+        bot.uid,
+        body.message,
+        Vec::new(),
+    )
+    .await?;
+
+    Ok(Json(response))
+}
+
+/// DELETE /external/messages/:message_id — Delete a message the bot posted.
+#[utoipa::path(
+    delete,
+    path = "/{message_id}",
+    tag = "external-messages",
+    params(MessagePath),
+    responses(
+        (status = 204, description = "Message deleted"),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn delete_external_message(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(MessagePath { message_id }): Path<MessagePath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let chat_id = load_message_chat_id(conn, message_id)?;
+    let bot = require_bot_in_chat(conn, &state, &service_token, AuthzAction::BotPost, chat_id)?;
+
+    // Bots only delete their own messages, even when their chat role could moderate.
+    let sender_uid: i32 = messages::table
+        .filter(messages::id.eq(message_id))
+        .select(messages::sender_uid)
+        .first(conn)?;
+    if sender_uid != bot.uid {
+        return Err(AppError::Forbidden("You can only delete your own messages"));
+    }
+    soft_delete_message(conn, &state, chat_id, message_id, bot.uid).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /external/messages/:message_id/reactions/:emoji — React to a message as the bot.
+#[utoipa::path(
+    put,
+    path = "/{message_id}/reactions/{emoji}",
+    tag = "external-messages",
+    params(ReactionPath),
+    responses(
+        (status = 204, description = "Reaction added"),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn put_external_reaction(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(ReactionPath { message_id, emoji }): Path<ReactionPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let key = ReactionKey::parse(&emoji)?;
+    let chat_id = load_message_chat_id(conn, message_id)?;
+    let bot = require_bot_in_chat(conn, &state, &service_token, AuthzAction::BotReact, chat_id)?;
+
+    add_reaction(conn, &state, chat_id, message_id, bot.uid, &key)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /external/messages/:message_id/reactions/:emoji — Remove the bot's reaction.
+#[utoipa::path(
+    delete,
+    path = "/{message_id}/reactions/{emoji}",
+    tag = "external-messages",
+    params(ReactionPath),
+    responses(
+        (status = 204, description = "Reaction removed"),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn delete_external_reaction(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(ReactionPath { message_id, emoji }): Path<ReactionPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let key = ReactionKey::parse(&emoji)?;
+    let chat_id = load_message_chat_id(conn, message_id)?;
+    let bot = require_bot_in_chat(conn, &state, &service_token, AuthzAction::BotReact, chat_id)?;
+
+    remove_reaction(conn, &state, chat_id, message_id, bot.uid, &key)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(post_external_message))
+        .routes(routes!(patch_external_message, delete_external_message))
+        .routes(routes!(put_external_reaction, delete_external_reaction))
+}
+
+/// Authorize `action` for the token (globally or for this chat, within its chat allowlist)
+/// and resolve the bot it posts as, which must be a member of the chat.
+pub(super) fn require_bot_in_chat(
+    conn: &mut PgConnection,
+    state: &AppState,
+    service_token: &ServiceTokenPrincipal,
+    action: AuthzAction,
+    chat_id: i64,
+) -> Result<Bot, AppError> {
+    service_token.require_chat_allowed(chat_id)?;
+    let service_token_id = service_token.id;
+    if !state.authz_service.has_service_token_permission(
+        conn,
+        service_token_id,
+        action,
+        AuthzResource::Global,
+    )? {
+        state.authz_service.require_service_token_permission(
+            conn,
+            service_token_id,
+            action,
+            AuthzResource::Chat(chat_id),
+        )?;
+    }
+
+    let bot = bot_service::find_bot_for_service_token(conn, service_token_id)?
+        .ok_or(AppError::Forbidden("Service token has no bot"))?;
+    check_membership(conn, chat_id, bot.uid)?;
+    Ok(bot)
+}
+
+fn load_message_chat_id(conn: &mut PgConnection, message_id: i64) -> Result<i64, AppError> {
+    messages::table
+        .filter(messages::id.eq(message_id))
+        .select(messages::chat_id)
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Message not found"))
+}
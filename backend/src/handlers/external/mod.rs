@@ -1,8 +1,17 @@
+pub mod commands;
+pub mod imports;
 pub mod invites;
+pub mod messages;
+pub mod webhooks;
 
 use crate::AppState;
 use utoipa_axum::router::OpenApiRouter;
 
 pub fn router() -> OpenApiRouter<AppState> {
-    OpenApiRouter::new().nest("/invites", invites::router())
+    OpenApiRouter::new()
+        .nest("/commands", commands::router())
+        .nest("/invites", invites::router())
+        .nest("/imports", imports::router())
+        .nest("/messages", messages::router())
+        .nest("/webhooks", webhooks::router())
 }
@@ -0,0 +1,247 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+};
+use diesel::PgConnection;
+use serde::Deserialize;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use super::invites::require_service_token_principal;
+use crate::dto::webhooks::{
+    CreateWebhookRequest, CreateWebhookResponse, ListWebhookDeliveriesQuery,
+    ListWebhookDeliveriesResponse, ListWebhooksResponse, UpdateWebhookRequest,
+    WebhookDeliveryResponse, WebhookResponse,
+};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::handlers::webhooks::MAX_DELIVERIES_LIMIT;
+use crate::services::authz::{Action as AuthzAction, Resource as AuthzResource};
+use crate::services::webhooks::{self as webhook_service, WebhookOwner};
+use crate::utils::auth::Principal;
+use crate::utils::{ids, pagination::validate_limit};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct WebhookPath {
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    webhook_id: i64,
+}
+
+/// GET /external/webhooks — List the service token's global webhooks.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "external-webhooks",
+    responses(
+        (status = 200, body = ListWebhooksResponse),
+        (status = 403, description = "Missing `webhook.manage`"),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn get_external_webhooks(
+    principal: Principal,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+) -> Result<Json<ListWebhooksResponse>, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let owner = require_webhook_manage(conn, &state, service_token.id)?;
+
+    let webhooks = webhook_service::list_webhooks(conn, owner)?
+        .into_iter()
+        .map(webhook_service::webhook_to_response)
+        .collect();
+    Ok(Json(ListWebhooksResponse { webhooks }))
+}
+
+/// POST /external/webhooks — Register a webhook that receives events from every chat.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "external-webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, description = "Webhook created; the secret is only shown once", body = CreateWebhookResponse),
+        (status = 403, description = "Missing `webhook.manage`"),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn post_external_webhook(
+    principal: Principal,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Json(body): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<CreateWebhookResponse>), AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let owner = require_webhook_manage(conn, &state, service_token.id)?;
+
+    let url = state.webhook_service.validate_url(&body.url)?;
+    let events = webhook_service::parse_event_types(&body.events)?;
+    let id = ids::next_id(state.id_gen.as_ref()).await.map_err(|e| {
+        tracing::error!("next_id for webhook: {:?}", e);
+        AppError::Internal("ID generation failed")
+    })?;
+    let webhook = webhook_service::create_webhook(conn, owner, id, url, events, None)?;
+
+    let secret = webhook.secret.clone();
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateWebhookResponse {
+            webhook: webhook_service::webhook_to_response(webhook),
+            secret,
+        }),
+    ))
+}
+
+/// PATCH /external/webhooks/:webhook_id — Change a webhook's URL, events or enabled state.
+#[utoipa::path(
+    patch,
+    path = "/{webhook_id}",
+    tag = "external-webhooks",
+    params(WebhookPath),
+    request_body = UpdateWebhookRequest,
+    responses(
+        (status = 200, body = WebhookResponse),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn patch_external_webhook(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(WebhookPath { webhook_id }): Path<WebhookPath>,
+    mut conn: DbConn,
+    Json(body): Json<UpdateWebhookRequest>,
+) -> Result<Json<WebhookResponse>, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let owner = require_webhook_manage(conn, &state, service_token.id)?;
+
+    let url = body
+        .url
+        .as_deref()
+        .map(|url| state.webhook_service.validate_url(url))
+        .transpose()?;
+    let events = body
+        .events
+        .as_deref()
+        .map(webhook_service::parse_event_types)
+        .transpose()?;
+    let webhook = webhook_service::load_webhook(conn, owner, webhook_id)?;
+    let webhook = webhook_service::update_webhook(conn, webhook, url, events, body.enabled)?;
+
+    Ok(Json(webhook_service::webhook_to_response(webhook)))
+}
+
+/// DELETE /external/webhooks/:webhook_id — Remove a webhook and its delivery log.
+#[utoipa::path(
+    delete,
+    path = "/{webhook_id}",
+    tag = "external-webhooks",
+    params(WebhookPath),
+    responses(
+        (status = 204, description = "Webhook deleted"),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn delete_external_webhook(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(WebhookPath { webhook_id }): Path<WebhookPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let owner = require_webhook_manage(conn, &state, service_token.id)?;
+
+    webhook_service::delete_webhook(conn, owner, webhook_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /external/webhooks/:webhook_id/ping — Queue a test delivery.
+#[utoipa::path(
+    post,
+    path = "/{webhook_id}/ping",
+    tag = "external-webhooks",
+    params(WebhookPath),
+    responses(
+        (status = 202, description = "Ping queued", body = WebhookDeliveryResponse),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn post_external_webhook_ping(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(WebhookPath { webhook_id }): Path<WebhookPath>,
+    mut conn: DbConn,
+) -> Result<(StatusCode, Json<WebhookDeliveryResponse>), AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let owner = require_webhook_manage(conn, &state, service_token.id)?;
+
+    let webhook = webhook_service::load_webhook(conn, owner, webhook_id)?;
+    let delivery = state.webhook_service.enqueue_ping(conn, &webhook)?;
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(webhook_service::delivery_to_response(delivery)),
+    ))
+}
+
+/// GET /external/webhooks/:webhook_id/deliveries — Recent delivery attempts, newest first.
+#[utoipa::path(
+    get,
+    path = "/{webhook_id}/deliveries",
+    tag = "external-webhooks",
+    params(WebhookPath, ListWebhookDeliveriesQuery),
+    responses(
+        (status = 200, body = ListWebhookDeliveriesResponse),
+    ),
+    security(("service_token_bearer" = []))
+)]
+async fn get_external_webhook_deliveries(
+    principal: Principal,
+    State(state): State<AppState>,
+    Path(WebhookPath { webhook_id }): Path<WebhookPath>,
+    Query(q): Query<ListWebhookDeliveriesQuery>,
+    mut conn: DbConn,
+) -> Result<Json<ListWebhookDeliveriesResponse>, AppError> {
+    let service_token = require_service_token_principal(principal)?;
+    let conn = &mut *conn;
+    let owner = require_webhook_manage(conn, &state, service_token.id)?;
+
+    let webhook = webhook_service::load_webhook(conn, owner, webhook_id)?;
+    let limit = validate_limit(q.limit, MAX_DELIVERIES_LIMIT);
+    let (deliveries, next_cursor) =
+        webhook_service::list_deliveries(conn, webhook.id, q.before, limit)?;
+    Ok(Json(ListWebhookDeliveriesResponse {
+        deliveries: deliveries
+            .into_iter()
+            .map(webhook_service::delivery_to_response)
+            .collect(),
+        next_cursor,
+    }))
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_external_webhooks, post_external_webhook))
+        .routes(routes!(patch_external_webhook, delete_external_webhook))
+        .routes(routes!(post_external_webhook_ping))
+        .routes(routes!(get_external_webhook_deliveries))
+}
+
+fn require_webhook_manage(
+    conn: &mut PgConnection,
+    state: &AppState,
+    service_token_id: i64,
+) -> Result<WebhookOwner, AppError> {
+    state.authz_service.require_service_token_permission(
+        conn,
+        service_token_id,
+        AuthzAction::WebhookManage,
+        AuthzResource::Global,
+    )?;
+    Ok(WebhookOwner::ServiceToken(service_token_id))
+}
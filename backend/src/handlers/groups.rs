@@ -10,18 +10,20 @@ use diesel::PgTextExpressionMethods;
 use utoipa_axum::router::OpenApiRouter;
 
 use crate::dto::groups::{
-    AvatarUploadUrlResponse, CreateChatResponse, GroupInfoResponse, GroupSelectorItem,
-    ListGroupsResponse, MuteResponse,
+    AvatarUploadUrlResponse, ChatNotificationLevelResponse, CreateChatResponse, GroupInfoResponse,
+    GroupSelectorItem, ListGroupsResponse, MuteResponse, SetChatNotificationLevelRequest,
 };
 use crate::errors::AppError;
 use crate::extractors::DbConn;
-use crate::handlers::members::{check_membership, require_admin_role};
+use crate::handlers::members::{check_membership, require_admin_role, require_chat_permission};
 use crate::models::{
-    GroupJoinReason, GroupRole, GroupVisibility, Media, MediaPurpose, NewGroup, NewGroupMembership,
-    NewMedia, UpdateGroup,
+    GroupAuditAction, GroupJoinReason, GroupRole, GroupVisibility, Media, MediaPurpose, NewGroup,
+    NewGroupAuditLogEntry, NewGroupMembership, NewMedia, NotificationLevel, UpdateGroup,
 };
-use crate::schema::{group_membership, groups, media};
+use crate::schema::{group_membership, groups, media, sticker_packs};
+use crate::services::audit_log::{group_update_diff, next_audit_entry_id, record_audit_entry};
 use crate::services::authz::{Action as AuthzAction, Resource as AuthzResource};
+use crate::services::chat::MAX_SLOW_MODE_SECS;
 use crate::services::media::{build_public_object_url, build_storage_key, presign_public_upload};
 use crate::utils::ids;
 use crate::utils::{auth::CurrentUid, pagination::validate_limit};
@@ -100,6 +102,33 @@ pub(super) struct UpdateChatBody {
     #[schema(value_type = Option<String>)]
     avatar_image_id: Option<Option<i64>>,
     visibility: Option<GroupVisibility>,
+    /// Minimum seconds between top-level messages from a non-admin member; 0 disables.
+    slow_mode_secs: Option<i32>,
+    /// Minimum seconds between thread replies from a non-admin member; 0 disables.
+    thread_slow_mode_secs: Option<i32>,
+    /// Restrict top-level posting to admins and holders of `chat.announce`.
+    announcement_only: Option<bool>,
+    /// Allow non-admin members to request chat history exports.
+    member_export_enabled: Option<bool>,
+    /// Sticker pack members may react with regardless of their own subscriptions; null clears it.
+    #[serde(
+        default,
+        deserialize_with = "crate::serde_i64_string::double_opt::deserialize"
+    )]
+    #[schema(value_type = Option<String>)]
+    sticker_pack_id: Option<Option<i64>>,
+}
+
+impl UpdateChatBody {
+    /// Whether the update touches anything beyond the chat's name, description or avatar.
+    fn changes_settings(&self) -> bool {
+        self.visibility.is_some()
+            || self.slow_mode_secs.is_some()
+            || self.thread_slow_mode_secs.is_some()
+            || self.announcement_only.is_some()
+            || self.member_export_enabled.is_some()
+            || self.sticker_pack_id.is_some()
+    }
 }
 
 #[derive(serde::Deserialize, utoipa::ToSchema)]
@@ -116,7 +145,20 @@ struct AvatarUploadUrlRequest {
 #[serde(rename_all = "camelCase")]
 pub(super) struct MuteBody {
     /// Duration in seconds, or null/absent for indefinite mute.
-    duration_seconds: Option<i64>,
+    pub(super) duration_seconds: Option<i64>,
+}
+
+/// Resolve a mute request into its expiry; non-positive or absent durations mute indefinitely.
+pub(super) fn resolve_mute_until(duration_seconds: Option<i64>) -> Result<DateTime<Utc>, AppError> {
+    match duration_seconds {
+        Some(secs) if secs > 0 && secs <= MAX_MUTE_DURATION_SECS => {
+            Ok(Utc::now() + chrono::Duration::seconds(secs))
+        }
+        Some(secs) if secs > MAX_MUTE_DURATION_SECS => {
+            Err(AppError::BadRequest("Duration exceeds 7 day maximum"))
+        }
+        _ => Ok(indefinite_mute_until()),
+    }
 }
 
 fn parse_group_search_query(
@@ -184,16 +226,20 @@ pub(super) fn load_group_info(
 
     let my_role = load_requester_group_role(conn, chat_id, requester_uid)?;
 
-    let muted_until: Option<DateTime<Utc>> = group_membership::table
-        .filter(
-            group_membership::chat_id
-                .eq(chat_id)
-                .and(group_membership::uid.eq(requester_uid)),
-        )
-        .select(group_membership::muted_until)
-        .first(conn)
-        .optional()?
-        .flatten();
+    let (muted_until, notification_level): (Option<DateTime<Utc>>, Option<NotificationLevel>) =
+        group_membership::table
+            .filter(
+                group_membership::chat_id
+                    .eq(chat_id)
+                    .and(group_membership::uid.eq(requester_uid)),
+            )
+            .select((
+                group_membership::muted_until,
+                group_membership::notification_level,
+            ))
+            .first(conn)
+            .optional()?
+            .unwrap_or_default();
 
     Ok(GroupInfoResponse {
         id: group.id,
@@ -207,7 +253,13 @@ pub(super) fn load_group_info(
         visibility: group.visibility,
         created_at: group.created_at,
         muted_until,
+        notification_level,
         my_role,
+        slow_mode_secs: group.slow_mode_secs,
+        thread_slow_mode_secs: group.thread_slow_mode_secs,
+        announcement_only: group.announcement_only,
+        member_export_enabled: group.member_export_enabled,
+        sticker_pack_id: group.sticker_pack_id,
     })
 }
 
@@ -447,7 +499,7 @@ async fn post_avatar_upload_url(
     if payload.size <= 0 || payload.size > MAX_GROUP_AVATAR_BYTES {
         return Err(AppError::BadRequest("Avatar size is invalid"));
     }
-    require_admin_role(conn, chat_id, uid)?;
+    require_chat_permission(conn, &state, chat_id, uid, AuthzAction::ChatChangeInfo)?;
 
     let id = ids::next_message_id(state.id_gen.as_ref())
         .await
@@ -494,7 +546,7 @@ async fn post_avatar_upload_url(
     ))
 }
 
-/// PATCH /group/:chat_id — Update chat metadata (admin only).
+/// PATCH /group/:chat_id — Update chat metadata (name, description and avatar need `chat.changeInfo`; other settings are admin only).
 #[utoipa::path(
     patch,
     path = "/{chat_id}",
@@ -517,7 +569,13 @@ async fn patch_group(
 ) -> Result<Json<GroupInfoResponse>, AppError> {
     let conn = &mut *conn;
 
-    require_admin_role(conn, chat_id, uid)?;
+    // Name, description and avatar follow the chat's role settings; everything
+    // else stays admin-only.
+    if body.changes_settings() {
+        require_admin_role(conn, chat_id, uid)?;
+    } else {
+        require_chat_permission(conn, &state, chat_id, uid, AuthzAction::ChatChangeInfo)?;
+    }
 
     let current_group: crate::models::Group = groups::table
         .filter(groups::id.eq(chat_id))
@@ -541,16 +599,49 @@ async fn patch_group(
         }
     }
 
+    if let Some(Some(pack_id)) = body.sticker_pack_id {
+        let pack_exists = sticker_packs::table
+            .filter(sticker_packs::id.eq(pack_id))
+            .count()
+            .get_result::<i64>(conn)?;
+        if pack_exists == 0 {
+            return Err(AppError::BadRequest("Invalid sticker pack"));
+        }
+    }
+
+    for interval in [body.slow_mode_secs, body.thread_slow_mode_secs]
+        .into_iter()
+        .flatten()
+    {
+        if !(0..=MAX_SLOW_MODE_SECS).contains(&interval) {
+            return Err(AppError::BadRequest(
+                "Slow mode interval must be between 0 and 3600 seconds",
+            ));
+        }
+    }
+
     use crate::schema::groups::dsl as groups_dsl;
     let changeset = UpdateGroup {
         name: body.name,
         description: body.description,
         visibility: body.visibility,
+        slow_mode_secs: body.slow_mode_secs,
+        thread_slow_mode_secs: body.thread_slow_mode_secs,
+        announcement_only: body.announcement_only,
+        member_export_enabled: body.member_export_enabled,
+        sticker_pack_id: body.sticker_pack_id,
     };
+    let audit_change = group_update_diff(&current_group, &changeset, body.avatar_image_id);
     let has_metadata_changes = changeset.name.is_some()
         || changeset.description.is_some()
-        || changeset.visibility.is_some();
-
+        || changeset.visibility.is_some()
+        || changeset.slow_mode_secs.is_some()
+        || changeset.thread_slow_mode_secs.is_some()
+        || changeset.announcement_only.is_some()
+        || changeset.member_export_enabled.is_some()
+        || changeset.sticker_pack_id.is_some();
+
+    let audit_id = next_audit_entry_id(state.id_gen.as_ref()).await?;
     conn.transaction::<_, diesel::result::Error, _>(|conn| {
         if has_metadata_changes {
             diesel::update(groups::table.filter(groups_dsl::id.eq(chat_id)))
@@ -572,6 +663,14 @@ async fn patch_group(
             }
         }
 
+        if let Some((before, after)) = audit_change {
+            record_audit_entry(
+                conn,
+                &NewGroupAuditLogEntry::new(audit_id, chat_id, uid, GroupAuditAction::GroupUpdate)
+                    .change(before, after),
+            )?;
+        }
+
         Ok(())
     })?;
 
@@ -602,15 +701,7 @@ async fn put_mute(
 
     check_membership(conn, chat_id, uid)?;
 
-    let muted_until = match body.duration_seconds {
-        Some(secs) if secs > 0 && secs <= MAX_MUTE_DURATION_SECS => {
-            Utc::now() + chrono::Duration::seconds(secs)
-        }
-        Some(secs) if secs > MAX_MUTE_DURATION_SECS => {
-            return Err(AppError::BadRequest("Duration exceeds 7 day maximum"));
-        }
-        _ => indefinite_mute_until(),
-    };
+    let muted_until = resolve_mute_until(body.duration_seconds)?;
 
     use crate::schema::group_membership::dsl as gm_dsl;
     diesel::update(
@@ -657,11 +748,63 @@ async fn delete_mute(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// PUT /group/:chat_id/notifications — Override the caller's push level for a chat.
+#[utoipa::path(
+    put,
+    path = "/{chat_id}/notifications",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    request_body = SetChatNotificationLevelRequest,
+    responses(
+        (status = OK, body = ChatNotificationLevelResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn put_notification_level(
+    CurrentUid(uid): CurrentUid,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+    Json(body): Json<SetChatNotificationLevelRequest>,
+) -> Result<Json<ChatNotificationLevelResponse>, AppError> {
+    let conn = &mut *conn;
+
+    check_membership(conn, chat_id, uid)?;
+
+    use crate::schema::group_membership::dsl as gm_dsl;
+    diesel::update(
+        group_membership::table.filter(gm_dsl::chat_id.eq(chat_id).and(gm_dsl::uid.eq(uid))),
+    )
+    .set(gm_dsl::notification_level.eq(body.notification_level))
+    .execute(conn)?;
+
+    Ok(Json(ChatNotificationLevelResponse {
+        notification_level: body.notification_level,
+    }))
+}
+
 pub fn router() -> OpenApiRouter<crate::AppState> {
     OpenApiRouter::new()
         .routes(utoipa_axum::routes!(get_groups, post_group))
         .routes(utoipa_axum::routes!(get_group, patch_group))
         .routes(utoipa_axum::routes!(post_avatar_upload_url))
         .routes(utoipa_axum::routes!(put_mute, delete_mute))
+        .routes(utoipa_axum::routes!(put_notification_level))
         .nest("/{chat_id}/members", crate::handlers::members::router())
+        .nest("/{chat_id}/topics", crate::handlers::topics::router())
+        .nest("/{chat_id}/audit-log", crate::handlers::audit_log::router())
+        .nest(
+            "/{chat_id}/permissions",
+            crate::handlers::chat_permissions::router(),
+        )
+        .nest(
+            "/{chat_id}/exports",
+            crate::handlers::chat_exports::router(),
+        )
+        .nest("/{chat_id}/webhooks", crate::handlers::webhooks::router())
+        .nest(
+            "/{chat_id}/incoming-webhooks",
+            crate::handlers::incoming_webhooks::router(),
+        )
 }
@@ -0,0 +1,586 @@
+use aws_sdk_s3::primitives::ByteStream;
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde_json::json;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::dto::incoming_webhooks::{
+    CreateIncomingWebhookRequest, IncomingWebhookMessageRequest, IncomingWebhookMessageResponse,
+    IncomingWebhookResponse, IncomingWebhookSecretResponse, ListIncomingWebhooksResponse,
+    UpdateIncomingWebhookRequest,
+};
+use crate::dto::ws::ServerWsMessage;
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::handlers::chats::{
+    apply_sender_override, send_prepared_message, PreparedMessageSend, SendMessageOutcome,
+};
+use crate::handlers::members::require_admin_role;
+use crate::models::{
+    GroupJoinReason, GroupRole, IncomingWebhook, MessageSenderOverride, MessageType, NewAttachment,
+    NewGroupMembership, NewIncomingWebhook,
+};
+use crate::schema::{
+    attachments, group_membership, groups, incoming_webhooks, message_sender_overrides,
+};
+use crate::services::bots::{normalize_bot_avatar_url, normalize_bot_name};
+use crate::services::incoming_webhooks::{
+    self as incoming_webhook_service, MAX_ATTACHMENTS_PER_POST, MAX_WEBHOOKS_PER_CHAT,
+};
+use crate::services::media::{build_storage_key, upload_public_object};
+use crate::services::webhooks::{MemberEventData, WebhookEvent};
+use crate::utils::{auth::CurrentUid, ids};
+use crate::AppState;
+
+const MAX_TEXT_CHARS: usize = 8000;
+
+#[derive(serde::Deserialize)]
+pub struct ChatIdPath {
+    pub chat_id: i64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct IncomingWebhookPath {
+    chat_id: i64,
+    webhook_id: i64,
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+pub struct IncomingWebhookTokenPath {
+    /// Incoming webhook ID.
+    #[param(value_type = String)]
+    webhook_id: i64,
+    /// Secret from the webhook's URL.
+    token: String,
+}
+
+/// GET /group/:chat_id/incoming-webhooks — List the chat's incoming webhooks (admins only).
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    responses(
+        (status = OK, body = ListIncomingWebhooksResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn get_incoming_webhooks(
+    CurrentUid(uid): CurrentUid,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+) -> Result<Json<ListIncomingWebhooksResponse>, AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    let webhooks = incoming_webhooks::table
+        .filter(incoming_webhooks::chat_id.eq(chat_id))
+        .order(incoming_webhooks::id.asc())
+        .select(IncomingWebhook::as_select())
+        .load(conn)?
+        .into_iter()
+        .map(incoming_webhook_service::incoming_webhook_to_response)
+        .collect();
+    Ok(Json(ListIncomingWebhooksResponse { webhooks }))
+}
+
+/// POST /group/:chat_id/incoming-webhooks — Create an incoming webhook; it joins the chat as a member.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    request_body = CreateIncomingWebhookRequest,
+    responses(
+        (status = CREATED, description = "Webhook created; the path is only shown once", body = IncomingWebhookSecretResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn post_incoming_webhook(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+    Json(body): Json<CreateIncomingWebhookRequest>,
+) -> Result<(StatusCode, Json<IncomingWebhookSecretResponse>), AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    let name = normalize_bot_name(&body.name)?;
+    let avatar_url = normalize_bot_avatar_url(body.avatar_url.as_deref())?;
+    let rate_limit_per_minute =
+        incoming_webhook_service::normalize_rate_limit(body.rate_limit_per_minute)?;
+    let id = ids::next_id(state.id_gen.as_ref()).await.map_err(|e| {
+        tracing::error!("next_id for incoming webhook: {:?}", e);
+        AppError::Internal("ID generation failed")
+    })?;
+    let (token, secret_hash) =
+        incoming_webhook_service::generate_token(&state.service_token_hash_key, id)?;
+
+    let webhook = conn.transaction::<_, AppError, _>(|conn| {
+        let existing: i64 = incoming_webhooks::table
+            .filter(incoming_webhooks::chat_id.eq(chat_id))
+            .count()
+            .get_result(conn)?;
+        if existing >= MAX_WEBHOOKS_PER_CHAT {
+            return Err(AppError::BadRequest("Too many incoming webhooks"));
+        }
+
+        let webhook: IncomingWebhook = diesel::insert_into(incoming_webhooks::table)
+            .values(&NewIncomingWebhook {
+                id,
+                chat_id,
+                name,
+                avatar_url,
+                secret_hash,
+                rate_limit_per_minute,
+                created_by_uid: uid,
+            })
+            .returning(IncomingWebhook::as_returning())
+            .get_result(conn)?;
+        let last_message_id: Option<i64> = groups::table
+            .filter(groups::id.eq(chat_id))
+            .select(groups::last_message_id)
+            .first(conn)?;
+        diesel::insert_into(group_membership::table)
+            .values(&NewGroupMembership {
+                chat_id,
+                uid: webhook.uid,
+                role: GroupRole::Member,
+                joined_at: Utc::now(),
+                join_reason: GroupJoinReason::Other,
+                join_reason_extra: Some(json!({ "incoming_webhook_id": id.to_string() })),
+                last_read_message_id: last_message_id,
+            })
+            .execute(conn)?;
+        Ok(webhook)
+    })?;
+
+    state.webhook_service.publish(
+        chat_id,
+        WebhookEvent::MemberJoined,
+        &MemberEventData {
+            uid: webhook.uid,
+            role: GroupRole::Member,
+            actor_uid: Some(uid),
+        },
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(IncomingWebhookSecretResponse {
+            path: incoming_webhook_service::webhook_path(webhook.id, &token),
+            webhook: incoming_webhook_service::incoming_webhook_to_response(webhook),
+        }),
+    ))
+}
+
+/// PATCH /group/:chat_id/incoming-webhooks/:webhook_id — Rename, change avatar or rate limit.
+#[utoipa::path(
+    patch,
+    path = "/{webhook_id}",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("webhook_id" = i64, Path, description = "Incoming webhook ID"),
+    ),
+    request_body = UpdateIncomingWebhookRequest,
+    responses(
+        (status = OK, body = IncomingWebhookResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn patch_incoming_webhook(
+    CurrentUid(uid): CurrentUid,
+    Path(IncomingWebhookPath {
+        chat_id,
+        webhook_id,
+    }): Path<IncomingWebhookPath>,
+    mut conn: DbConn,
+    Json(body): Json<UpdateIncomingWebhookRequest>,
+) -> Result<Json<IncomingWebhookResponse>, AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    let webhook = load_incoming_webhook(conn, chat_id, webhook_id)?;
+    let name = match body.name.as_deref() {
+        Some(name) => normalize_bot_name(name)?,
+        None => webhook.name,
+    };
+    let avatar_url = match body.avatar_url.as_deref() {
+        Some(avatar_url) => normalize_bot_avatar_url(Some(avatar_url))?,
+        None => webhook.avatar_url,
+    };
+    let rate_limit_per_minute = match body.rate_limit_per_minute {
+        Some(limit) => incoming_webhook_service::normalize_rate_limit(Some(limit))?,
+        None => webhook.rate_limit_per_minute,
+    };
+
+    let webhook =
+        diesel::update(incoming_webhooks::table.filter(incoming_webhooks::id.eq(webhook.id)))
+            .set((
+                incoming_webhooks::name.eq(name),
+                incoming_webhooks::avatar_url.eq(avatar_url),
+                incoming_webhooks::rate_limit_per_minute.eq(rate_limit_per_minute),
+                incoming_webhooks::updated_at.eq(Utc::now()),
+            ))
+            .returning(IncomingWebhook::as_returning())
+            .get_result(conn)?;
+
+    Ok(Json(
+        incoming_webhook_service::incoming_webhook_to_response(webhook),
+    ))
+}
+
+/// POST /group/:chat_id/incoming-webhooks/:webhook_id/rotate — Replace the secret URL.
+#[utoipa::path(
+    post,
+    path = "/{webhook_id}/rotate",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("webhook_id" = i64, Path, description = "Incoming webhook ID"),
+    ),
+    responses(
+        (status = OK, description = "New path; the old one stops working", body = IncomingWebhookSecretResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn post_rotate_incoming_webhook(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(IncomingWebhookPath {
+        chat_id,
+        webhook_id,
+    }): Path<IncomingWebhookPath>,
+    mut conn: DbConn,
+) -> Result<Json<IncomingWebhookSecretResponse>, AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    let webhook = load_incoming_webhook(conn, chat_id, webhook_id)?;
+    let (token, secret_hash) =
+        incoming_webhook_service::generate_token(&state.service_token_hash_key, webhook.id)?;
+    let webhook =
+        diesel::update(incoming_webhooks::table.filter(incoming_webhooks::id.eq(webhook.id)))
+            .set((
+                incoming_webhooks::secret_hash.eq(secret_hash),
+                incoming_webhooks::updated_at.eq(Utc::now()),
+            ))
+            .returning(IncomingWebhook::as_returning())
+            .get_result(conn)?;
+
+    Ok(Json(IncomingWebhookSecretResponse {
+        path: incoming_webhook_service::webhook_path(webhook.id, &token),
+        webhook: incoming_webhook_service::incoming_webhook_to_response(webhook),
+    }))
+}
+
+/// DELETE /group/:chat_id/incoming-webhooks/:webhook_id — Delete the webhook and remove it from the chat.
+/// Messages it already posted are kept.
+#[utoipa::path(
+    delete,
+    path = "/{webhook_id}",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("webhook_id" = i64, Path, description = "Incoming webhook ID"),
+    ),
+    responses(
+        (status = NO_CONTENT, description = "Webhook deleted"),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn delete_incoming_webhook(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(IncomingWebhookPath {
+        chat_id,
+        webhook_id,
+    }): Path<IncomingWebhookPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    let webhook = load_incoming_webhook(conn, chat_id, webhook_id)?;
+    let removed_role: Option<GroupRole> = conn.transaction::<_, AppError, _>(|conn| {
+        let removed_role = diesel::delete(
+            group_membership::table
+                .filter(group_membership::chat_id.eq(chat_id))
+                .filter(group_membership::uid.eq(webhook.uid)),
+        )
+        .returning(group_membership::role)
+        .get_result(conn)
+        .optional()?;
+        diesel::delete(incoming_webhooks::table.filter(incoming_webhooks::id.eq(webhook.id)))
+            .execute(conn)?;
+        Ok(removed_role)
+    })?;
+
+    if let Some(role) = removed_role {
+        state.webhook_service.publish(
+            chat_id,
+            WebhookEvent::MemberLeft,
+            &MemberEventData {
+                uid: webhook.uid,
+                role,
+                actor_uid: Some(uid),
+            },
+        );
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /incoming-webhooks/:webhook_id/:token — Post a message through an incoming webhook.
+/// Authenticated by the secret in the URL; no other credentials are needed.
+#[utoipa::path(
+    post,
+    path = "/{webhook_id}/{token}",
+    tag = "incoming-webhooks",
+    params(IncomingWebhookTokenPath),
+    request_body = IncomingWebhookMessageRequest,
+    responses(
+        (status = CREATED, body = IncomingWebhookMessageResponse),
+        (status = NOT_FOUND, description = "Unknown webhook or wrong secret"),
+        (status = TOO_MANY_REQUESTS, description = "Per-webhook rate limit exceeded"),
+    ),
+)]
+async fn post_incoming_webhook_message(
+    State(state): State<AppState>,
+    Path(IncomingWebhookTokenPath { webhook_id, token }): Path<IncomingWebhookTokenPath>,
+    Json(body): Json<IncomingWebhookMessageRequest>,
+) -> Result<(StatusCode, Json<IncomingWebhookMessageResponse>), AppError> {
+    // Connections are taken per phase so none is held while attachments download.
+    let webhook = incoming_webhook_service::authenticate(
+        &mut *state.db.get()?,
+        &state.service_token_hash_key,
+        webhook_id,
+        &token,
+    )?;
+
+    let text = body
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string);
+    if text.is_none() && body.attachments.is_empty() {
+        return Err(AppError::BadRequest("Text or attachments are required"));
+    }
+    if text
+        .as_deref()
+        .is_some_and(|text| text.chars().count() > MAX_TEXT_CHARS)
+    {
+        return Err(AppError::BadRequest("Text is too long"));
+    }
+    if body.attachments.len() > MAX_ATTACHMENTS_PER_POST {
+        return Err(AppError::BadRequest("Too many attachments"));
+    }
+    let sender_override = MessageSenderOverride {
+        message_id: 0,
+        name: body
+            .username
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(normalize_bot_name)
+            .transpose()?,
+        avatar_url: normalize_bot_avatar_url(body.avatar_url.as_deref())?,
+    };
+    let attachment_urls = body
+        .attachments
+        .iter()
+        .map(|attachment| state.webhook_service.validate_url(&attachment.url))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    incoming_webhook_service::consume_rate_limit(&mut *state.db.get()?, &webhook)?;
+
+    let mut uploads = Vec::with_capacity(attachment_urls.len());
+    for (url, attachment) in attachment_urls.iter().zip(&body.attachments) {
+        uploads.push(upload_attachment(&state, url, attachment.file_name.as_deref()).await?);
+    }
+
+    let mut conn = state.db.get()?;
+    let conn = &mut *conn;
+    let mut attachment_ids = Vec::with_capacity(uploads.len());
+    for (order, upload) in uploads.into_iter().enumerate() {
+        attachment_ids.push(record_attachment(&state, conn, upload, order).await?);
+    }
+
+    // The sender override and the queued push job commit with the message.
+    diesel::sql_query("BEGIN").execute(conn)?;
+    let tx_result: Result<_, AppError> = async {
+        let outcome = send_prepared_message(
+            conn,
+            &state,
+            PreparedMessageSend {
+                chat_id: webhook.chat_id,
+                sender_uid: webhook.uid,
+                message: text,
+                message_type: MessageType::Text,
+                sticker_id: None,
+                reply_to_id: None,
+                reply_root_id: None,
+                client_generated_id: uuid::Uuid::new_v4().to_string(),
+                attachment_ids,
+                publish_immediately: true,
+                important: false,
+                topic_id: None,
+                slow_mode: false,
+            },
+        )
+        .await?;
+        let SendMessageOutcome::Created(send_result) = outcome else {
+            return Err(AppError::Internal("Failed to post message"));
+        };
+        let mut send_result = *send_result;
+        let message_id = send_result.inserted_message.id;
+
+        if sender_override.name.is_some() || sender_override.avatar_url.is_some() {
+            let sender_override = MessageSenderOverride {
+                message_id,
+                ..sender_override
+            };
+            diesel::insert_into(message_sender_overrides::table)
+                .values(&sender_override)
+                .execute(conn)?;
+            apply_sender_override(&mut send_result.response.sender, &sender_override);
+            if let Some(name) = sender_override.name.as_deref() {
+                crate::services::push::rename_queued_sender(conn, message_id, name)?;
+            }
+            send_result.side_effects.ws_msg =
+                std::sync::Arc::new(ServerWsMessage::Message(send_result.response.clone()));
+        }
+        Ok(send_result)
+    }
+    .await;
+
+    let send_result = match tx_result {
+        Ok(send_result) => {
+            diesel::sql_query("COMMIT").execute(conn)?;
+            send_result
+        }
+        Err(err) => {
+            let _ = diesel::sql_query("ROLLBACK").execute(conn);
+            return Err(err);
+        }
+    };
+    let message_id = send_result.inserted_message.id;
+    send_result.side_effects.fire(&state);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(IncomingWebhookMessageResponse { message_id }),
+    ))
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_incoming_webhooks, post_incoming_webhook))
+        .routes(routes!(patch_incoming_webhook, delete_incoming_webhook))
+        .routes(routes!(post_rotate_incoming_webhook))
+}
+
+/// Unauthenticated routes addressed by the secret webhook URL.
+pub fn public_router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new().routes(routes!(post_incoming_webhook_message))
+}
+
+fn load_incoming_webhook(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    webhook_id: i64,
+) -> Result<IncomingWebhook, AppError> {
+    incoming_webhooks::table
+        .filter(incoming_webhooks::id.eq(webhook_id))
+        .filter(incoming_webhooks::chat_id.eq(chat_id))
+        .select(IncomingWebhook::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Incoming webhook not found"))
+}
+
+/// An attachment already copied into the attachment bucket.
+struct UploadedAttachment {
+    file_name: String,
+    content_type: String,
+    storage_key: String,
+    size: i64,
+}
+
+/// Download an attachment URL into the attachment bucket.
+async fn upload_attachment(
+    state: &AppState,
+    url: &str,
+    file_name: Option<&str>,
+) -> Result<UploadedAttachment, AppError> {
+    let fetched = incoming_webhook_service::fetch_attachment(
+        state.webhook_service.http_client(),
+        url,
+        file_name,
+    )
+    .await?;
+    let storage_key = build_storage_key(
+        &state.s3_attachment_prefix,
+        &fetched.file_name,
+        &uuid::Uuid::new_v4().to_string(),
+    );
+    let size = fetched.data.len() as i64;
+    upload_public_object(
+        &state.s3_client,
+        &state.s3_bucket_name,
+        &storage_key,
+        &fetched.content_type,
+        ByteStream::from(fetched.data),
+    )
+    .await?;
+    Ok(UploadedAttachment {
+        file_name: fetched.file_name,
+        content_type: fetched.content_type,
+        storage_key,
+        size,
+    })
+}
+
+/// Record an uploaded attachment, unlinked, for the post.
+async fn record_attachment(
+    state: &AppState,
+    conn: &mut PgConnection,
+    upload: UploadedAttachment,
+    order: usize,
+) -> Result<i64, AppError> {
+    let id = ids::next_message_id(state.id_gen.as_ref())
+        .await
+        .map_err(|e| {
+            tracing::error!("next_message_id for attachment: {:?}", e);
+            AppError::Internal("Failed to generate ID")
+        })?;
+    diesel::insert_into(attachments::table)
+        .values(&NewAttachment {
+            id,
+            message_id: None,
+            file_name: upload.file_name,
+            kind: upload.content_type,
+            external_reference: upload.storage_key,
+            size: upload.size,
+            created_at: Utc::now(),
+            deleted_at: None,
+            width: None,
+            height: None,
+            order: order as i16,
+        })
+        .execute(conn)?;
+    Ok(id)
+}
@@ -20,12 +20,16 @@ use crate::errors::AppError;
 use crate::extractors::DbConn;
 use crate::handlers::chats::{send_prepared_message, PreparedMessageSend, SendMessageOutcome};
 use crate::handlers::groups::load_group_info;
-use crate::handlers::members::{check_membership, require_admin_role};
+use crate::handlers::members::{check_membership, require_chat_permission};
 use crate::models::{
-    GroupJoinReason, GroupRole, Invite, InviteType, MessageType, NewGroupMembership,
+    GroupAuditAction, GroupJoinReason, GroupRole, Invite, InviteType, MessageType,
+    NewGroupAuditLogEntry, NewGroupMembership,
 };
 use crate::schema::{group_membership, invites};
+use crate::services::audit_log::{next_audit_entry_id, record_audit_entry};
+use crate::services::authz::Action as AuthzAction;
 use crate::services::invites as invite_service;
+use crate::services::webhooks::{MemberEventData, WebhookEvent};
 use crate::utils::auth::CurrentUid;
 use crate::AppState;
 
@@ -300,7 +304,7 @@ async fn post_invite(
 
     validate_create_body(&body)?;
 
-    require_admin_role(conn, body.chat_id, uid)?;
+    require_chat_permission(conn, &state, body.chat_id, uid, AuthzAction::ChatInvite)?;
     let invite = create_invite_from_body(conn, &state, uid, &body).await?;
 
     Ok((
@@ -327,13 +331,19 @@ async fn post_send_invite_message(
 ) -> Result<(StatusCode, Json<SendInviteMessageResponse>), AppError> {
     let conn = &mut *conn;
 
-    require_admin_role(conn, body.source_chat_id, uid)?;
+    require_chat_permission(
+        conn,
+        &state,
+        body.source_chat_id,
+        uid,
+        AuthzAction::ChatInvite,
+    )?;
     check_membership(conn, body.destination_chat_id, uid)?;
 
     let now = Utc::now();
     let invite = if let Some(invite_id) = body.invite_id {
         let invite = load_invite_by_id(conn, invite_id)?;
-        require_admin_role(conn, invite.chat_id, uid)?;
+        require_chat_permission(conn, &state, invite.chat_id, uid, AuthzAction::ChatInvite)?;
         if invite.chat_id != body.source_chat_id {
             return Err(AppError::BadRequest(
                 "Invite does not belong to source chat",
@@ -368,6 +378,9 @@ async fn post_send_invite_message(
             client_generated_id: body.client_generated_id,
             attachment_ids: vec![],
             publish_immediately: true,
+            important: false,
+            topic_id: None,
+            slow_mode: false,
         },
     )
     .await?;
@@ -401,6 +414,7 @@ async fn post_send_invite_message(
 )]
 async fn get_invites(
     CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
     mut conn: DbConn,
     Query(query): Query<ListInvitesQuery>,
 ) -> Result<Json<ListInvitesResponse>, AppError> {
@@ -412,7 +426,7 @@ async fn get_invites(
         .limit(invite_limit(query.limit));
 
     if let Some(group_id) = query.group_id {
-        require_admin_role(conn, group_id, uid)?;
+        require_chat_permission(conn, &state, group_id, uid, AuthzAction::ChatInvite)?;
         base_query = base_query.filter(invites::chat_id.eq(group_id));
     } else {
         base_query = base_query.filter(invites::creator_uid.eq(Some(uid)));
@@ -444,13 +458,14 @@ async fn get_invites(
 )]
 async fn get_invite(
     CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
     Path(InviteIdPath { invite_id }): Path<InviteIdPath>,
     mut conn: DbConn,
 ) -> Result<Json<InviteResponse>, AppError> {
     let conn = &mut *conn;
 
     let invite = load_invite_by_id(conn, invite_id)?;
-    require_admin_role(conn, invite.chat_id, uid)?;
+    require_chat_permission(conn, &state, invite.chat_id, uid, AuthzAction::ChatInvite)?;
 
     Ok(Json(invite_service::invite_to_response(invite)))
 }
@@ -522,6 +537,7 @@ async fn get_invite_by_code(
 )]
 async fn patch_invite(
     CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
     Path(InviteIdPath { invite_id }): Path<InviteIdPath>,
     mut conn: DbConn,
     Json(body): Json<PatchInviteBody>,
@@ -529,16 +545,34 @@ async fn patch_invite(
     let conn = &mut *conn;
 
     let invite = load_invite_by_id(conn, invite_id)?;
-    require_admin_role(conn, invite.chat_id, uid)?;
+    require_chat_permission(conn, &state, invite.chat_id, uid, AuthzAction::ChatInvite)?;
 
     let next_expires_at = body
         .expires_at
         .ok_or(AppError::BadRequest("expires_at is required"))?;
 
-    let updated = diesel::update(invites::table.filter(invites::id.eq(invite_id)))
-        .set(invites::expires_at.eq(next_expires_at))
-        .returning(Invite::as_returning())
-        .get_result::<Invite>(conn)?;
+    let audit_id = next_audit_entry_id(state.id_gen.as_ref()).await?;
+    let updated = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let updated = diesel::update(invites::table.filter(invites::id.eq(invite_id)))
+            .set(invites::expires_at.eq(next_expires_at))
+            .returning(Invite::as_returning())
+            .get_result::<Invite>(conn)?;
+        record_audit_entry(
+            conn,
+            &NewGroupAuditLogEntry::new(
+                audit_id,
+                invite.chat_id,
+                uid,
+                GroupAuditAction::InviteUpdate,
+            )
+            .target_id(invite_id)
+            .change(
+                json!({ "expiresAt": invite.expires_at }),
+                json!({ "expiresAt": updated.expires_at }),
+            ),
+        )?;
+        Ok(updated)
+    })?;
 
     Ok(Json(invite_service::invite_to_response(updated)))
 }
@@ -557,17 +591,37 @@ async fn patch_invite(
 )]
 async fn delete_invite(
     CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
     Path(InviteIdPath { invite_id }): Path<InviteIdPath>,
     mut conn: DbConn,
 ) -> Result<StatusCode, AppError> {
     let conn = &mut *conn;
 
     let invite = load_invite_by_id(conn, invite_id)?;
-    require_admin_role(conn, invite.chat_id, uid)?;
-
-    diesel::update(invites::table.filter(invites::id.eq(invite_id)))
-        .set(invites::revoked_at.eq(Utc::now()))
-        .execute(conn)?;
+    require_chat_permission(conn, &state, invite.chat_id, uid, AuthzAction::ChatInvite)?;
+
+    let revoked_at = Utc::now();
+    let audit_id = next_audit_entry_id(state.id_gen.as_ref()).await?;
+    let mut entry = NewGroupAuditLogEntry::new(
+        audit_id,
+        invite.chat_id,
+        uid,
+        GroupAuditAction::InviteRevoke,
+    )
+    .target_id(invite_id)
+    .change(
+        json!({ "code": invite.code, "revokedAt": invite.revoked_at }),
+        json!({ "revokedAt": revoked_at }),
+    );
+    if let Some(target_uid) = invite.target_uid {
+        entry = entry.target_uid(target_uid);
+    }
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::update(invites::table.filter(invites::id.eq(invite_id)))
+            .set(invites::revoked_at.eq(revoked_at))
+            .execute(conn)?;
+        record_audit_entry(conn, &entry)
+    })?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -709,6 +763,15 @@ async fn post_redeem_invite(
             return Err(AppError::Conflict("Already a member of this chat"));
         }
     };
+    state.webhook_service.publish(
+        chat_id,
+        WebhookEvent::MemberJoined,
+        &MemberEventData {
+            uid,
+            role: GroupRole::Member,
+            actor_uid: None,
+        },
+    );
 
     if let Ok(SendMessageOutcome::Created(send_result)) =
         crate::handlers::chats::send_prepared_message(
@@ -725,6 +788,9 @@ async fn post_redeem_invite(
                 client_generated_id: uuid::Uuid::new_v4().to_string(),
                 attachment_ids: vec![],
                 publish_immediately: true,
+                important: false,
+                topic_id: None,
+                slow_mode: false,
             },
         )
         .await
@@ -15,13 +15,20 @@ use crate::dto::members::{ListMembersResponse, MemberResponse};
 use crate::errors::AppError;
 use crate::extractors::DbConn;
 use crate::handlers::groups::load_requester_group_role;
-use crate::models::{GroupJoinReason, GroupMembership, GroupRole, NewGroupMembership};
+use crate::models::{
+    GroupAuditAction, GroupJoinReason, GroupMembership, GroupRole, NewGroupAuditLogEntry,
+    NewGroupMembership,
+};
 use crate::schema::{self, group_membership};
 
+use crate::services::audit_log::{next_audit_entry_id, record_audit_entry};
+use crate::services::authz::{Action as AuthzAction, Resource as AuthzResource};
+use crate::services::chat_folders::remove_chats_from_user_folders;
 use crate::services::user::{
     lookup_user_avatars, lookup_user_profiles, parse_user_search_query, search_group_member_uids,
-    UserSearchMode,
+    UserProfile, UserSearchMode,
 };
+use crate::services::webhooks::{MemberEventData, WebhookEvent};
 use crate::utils::{auth::CurrentUid, pagination::validate_limit};
 use crate::{AppState, MAX_MEMBERS_LIMIT};
 
@@ -71,20 +78,38 @@ fn build_member_responses(
     Ok(page_rows
         .into_iter()
         .map(|(uid, role, joined_at)| {
-            let profile = profiles.get(&uid);
-            MemberResponse {
-                avatar_url: avatars.remove(&uid).flatten(),
+            member_response(
                 uid,
                 role,
                 joined_at,
-                username: profile.and_then(|profile| profile.username.clone()),
-                gender: profile.map(|profile| profile.gender).unwrap_or(0),
-                user_group: profile.and_then(|profile| profile.user_group.clone()),
-            }
+                profiles.get(&uid),
+                avatars.remove(&uid).flatten(),
+            )
         })
         .collect())
 }
 
+fn member_response(
+    uid: i32,
+    role: GroupRole,
+    joined_at: DateTime<Utc>,
+    profile: Option<&UserProfile>,
+    avatar_url: Option<String>,
+) -> MemberResponse {
+    MemberResponse {
+        uid,
+        role,
+        joined_at,
+        username: profile.and_then(|profile| profile.username.clone()),
+        avatar_url: profile
+            .and_then(|profile| profile.avatar_url.clone())
+            .or(avatar_url),
+        gender: profile.map(|profile| profile.gender).unwrap_or(0),
+        user_group: profile.and_then(|profile| profile.user_group.clone()),
+        is_bot: profile.is_some_and(|profile| profile.is_bot),
+    }
+}
+
 /// Check if user is a member of the chat; return 403 if not.
 pub(super) fn check_membership(
     conn: &mut PgConnection,
@@ -126,6 +151,21 @@ pub(super) fn require_admin_role(
     }
 }
 
+/// Check that the user is a member allowed to perform `action` in the chat, either
+/// through the chat's role settings or a policy grant; return 403 otherwise.
+pub(super) fn require_chat_permission(
+    conn: &mut PgConnection,
+    state: &AppState,
+    chat_id: i64,
+    uid: i32,
+    action: AuthzAction,
+) -> Result<(), AppError> {
+    check_membership(conn, chat_id, uid)?;
+    state
+        .authz_service
+        .require_permission(conn, uid, action, AuthzResource::Chat(chat_id))
+}
+
 /// GET /group/:chat_id/members — List members of a chat.
 #[utoipa::path(
     get,
@@ -202,7 +242,7 @@ async fn get_members(
     }))
 }
 
-/// POST /group/:chat_id/members — Add a member to the chat (caller must be admin).
+/// POST /group/:chat_id/members — Add a member to the chat (caller needs `chat.invite`).
 #[utoipa::path(
     post,
     path = "/",
@@ -225,8 +265,7 @@ async fn post_add_member(
 ) -> Result<(StatusCode, Json<MemberResponse>), AppError> {
     let conn = &mut *conn;
 
-    // Check if requester is admin
-    require_admin_role(conn, chat_id, uid)?;
+    require_chat_permission(conn, &state, chat_id, uid, AuthzAction::ChatInvite)?;
 
     let profiles = lookup_user_profiles(conn, &[body.uid])?;
     let profile = profiles.get(&body.uid);
@@ -267,9 +306,27 @@ async fn post_add_member(
         last_read_message_id: last_message_id,
     };
 
-    diesel::insert_into(group_membership::table)
-        .values(&new_membership)
-        .execute(conn)?;
+    let audit_id = next_audit_entry_id(state.id_gen.as_ref()).await?;
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::insert_into(group_membership::table)
+            .values(&new_membership)
+            .execute(conn)?;
+        record_audit_entry(
+            conn,
+            &NewGroupAuditLogEntry::new(audit_id, chat_id, uid, GroupAuditAction::MemberAdd)
+                .target_uid(body.uid)
+                .after(json!({ "role": role })),
+        )
+    })?;
+    state.webhook_service.publish(
+        chat_id,
+        WebhookEvent::MemberJoined,
+        &MemberEventData {
+            uid: body.uid,
+            role: role.clone(),
+            actor_uid: Some(uid),
+        },
+    );
 
     let target_username = profile
         .and_then(|p| p.username.clone())
@@ -290,6 +347,9 @@ async fn post_add_member(
                 client_generated_id: uuid::Uuid::new_v4().to_string(),
                 attachment_ids: vec![],
                 publish_immediately: true,
+                important: false,
+                topic_id: None,
+                slow_mode: false,
             },
         )
         .await
@@ -304,15 +364,7 @@ async fn post_add_member(
 
     Ok((
         StatusCode::CREATED,
-        Json(MemberResponse {
-            uid: body.uid,
-            role,
-            joined_at: now,
-            username: profile.and_then(|profile| profile.username.clone()),
-            avatar_url,
-            gender: profile.map(|profile| profile.gender).unwrap_or(0),
-            user_group: profile.and_then(|profile| profile.user_group.clone()),
-        }),
+        Json(member_response(body.uid, role, now, profile, avatar_url)),
     ))
 }
 
@@ -394,10 +446,36 @@ async fn delete_remove_member(
         }
     }
 
-    diesel::delete(
-        group_membership::table.filter(gm_dsl::chat_id.eq(chat_id).and(gm_dsl::uid.eq(target_uid))),
-    )
-    .execute(conn)?;
+    let audit_id = next_audit_entry_id(state.id_gen.as_ref()).await?;
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::delete(
+            group_membership::table
+                .filter(gm_dsl::chat_id.eq(chat_id).and(gm_dsl::uid.eq(target_uid))),
+        )
+        .execute(conn)?;
+        remove_chats_from_user_folders(conn, target_uid, &[chat_id])?;
+        if is_admin_removing_other {
+            record_audit_entry(
+                conn,
+                &NewGroupAuditLogEntry::new(audit_id, chat_id, uid, GroupAuditAction::MemberRemove)
+                    .target_uid(target_uid)
+                    .change(
+                        json!({ "role": target_role }),
+                        json!({ "deleteMessages": query.delete_messages }),
+                    ),
+            )?;
+        }
+        Ok(())
+    })?;
+    state.webhook_service.publish(
+        chat_id,
+        WebhookEvent::MemberLeft,
+        &MemberEventData {
+            uid: target_uid,
+            role: target_role,
+            actor_uid: is_admin_removing_other.then_some(uid),
+        },
+    );
 
     let (sys_sender_uid, sys_msg) = if is_admin_removing_other {
         (uid, format!("removed {}", target_username))
@@ -420,6 +498,9 @@ async fn delete_remove_member(
                 client_generated_id: uuid::Uuid::new_v4().to_string(),
                 attachment_ids: vec![],
                 publish_immediately: true,
+                important: false,
+                topic_id: None,
+                slow_mode: false,
             },
         )
         .await
@@ -489,21 +570,40 @@ async fn patch_member(
 
     // Check if target is a member
     use crate::schema::group_membership::dsl as gm_dsl;
-    let is_member = group_membership::table
+    let previous_role: GroupRole = group_membership::table
         .filter(gm_dsl::chat_id.eq(chat_id).and(gm_dsl::uid.eq(target_uid)))
-        .count()
-        .get_result::<i64>(conn)?;
-
-    if is_member == 0 {
-        return Err(AppError::NotFound("Member not found"));
-    }
+        .select(gm_dsl::role)
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Member not found"))?;
 
+    let audit_id = next_audit_entry_id(state.id_gen.as_ref()).await?;
     // Update role
-    diesel::update(
-        group_membership::table.filter(gm_dsl::chat_id.eq(chat_id).and(gm_dsl::uid.eq(target_uid))),
-    )
-    .set(gm_dsl::role.eq(&body.role))
-    .execute(conn)?;
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::update(
+            group_membership::table
+                .filter(gm_dsl::chat_id.eq(chat_id).and(gm_dsl::uid.eq(target_uid))),
+        )
+        .set(gm_dsl::role.eq(&body.role))
+        .execute(conn)?;
+        if previous_role != body.role {
+            record_audit_entry(
+                conn,
+                &NewGroupAuditLogEntry::new(
+                    audit_id,
+                    chat_id,
+                    requester_uid,
+                    GroupAuditAction::MemberRoleChange,
+                )
+                .target_uid(target_uid)
+                .change(
+                    json!({ "role": previous_role }),
+                    json!({ "role": body.role }),
+                ),
+            )?;
+        }
+        Ok(())
+    })?;
 
     // Get updated member info
     let (role, joined_at): (GroupRole, DateTime<Utc>) = group_membership::table
@@ -518,15 +618,9 @@ async fn patch_member(
         .remove(&target_uid)
         .flatten();
 
-    Ok(Json(MemberResponse {
-        uid: target_uid,
-        role,
-        joined_at,
-        username: profile.and_then(|profile| profile.username.clone()),
-        avatar_url,
-        gender: profile.map(|profile| profile.gender).unwrap_or(0),
-        user_group: profile.and_then(|profile| profile.user_group.clone()),
-    }))
+    Ok(Json(member_response(
+        target_uid, role, joined_at, profile, avatar_url,
+    )))
 }
 
 pub fn router() -> OpenApiRouter<crate::AppState> {
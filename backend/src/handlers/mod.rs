@@ -1,16 +1,27 @@
 pub mod attachments;
+pub mod audit_log;
+pub mod bot_commands;
+pub mod chat_exports;
+pub mod chat_folders;
+pub mod chat_permissions;
 pub mod chats;
+pub mod devices;
 pub mod external;
 pub mod groups;
+pub mod incoming_webhooks;
 pub mod invites;
 pub mod members;
+pub mod oidc;
 pub mod pins;
+pub mod policies;
 pub mod push;
 mod saved_messages;
 pub mod service_tokens;
 pub mod stickers;
 pub mod threads;
+pub mod topics;
 pub mod users;
+pub mod webhooks;
 pub mod ws;
 
 use crate::AppState;
@@ -20,14 +31,18 @@ pub fn api_router() -> OpenApiRouter<AppState> {
     OpenApiRouter::new()
         .nest("/ws", ws::router())
         .nest("/chats", chats::router())
+        .nest("/chat-folders", chat_folders::router())
         .nest("/threads", threads::router())
         .nest("/group", groups::router())
         .nest("/invites", invites::router())
+        .nest("/incoming-webhooks", incoming_webhooks::public_router())
         .nest("/push", push::router())
         .nest("/saved-messages", saved_messages::router())
         .nest("/external", external::router())
         .nest("/service-tokens", service_tokens::router())
+        .nest("/policies", policies::router())
         .nest("/stickers", stickers::router())
         .nest("/users", users::router())
+        .nest("/auth/oidc", oidc::router())
         .nest("/attachments", attachments::router())
 }
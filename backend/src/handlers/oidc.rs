@@ -0,0 +1,276 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::dto::oidc::{
+    ListOidcIdentitiesResponse, OidcCallbackRequest, OidcIdentityResponse, OidcLoginStartResponse,
+};
+use crate::dto::users::AuthTokenResponse;
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::handlers::users::{auth_token_response, session_client_id};
+use crate::services::auth_sessions::{AuthSessionService, IssuedTokens};
+use crate::services::client_tracking::ClientTrackingService;
+use crate::services::oidc::{self as oidc_service, OidcClient};
+use crate::utils::auth::{extract_auth_context, required_client_id, CurrentUid, X_APP_VERSION};
+use crate::utils::ids;
+use crate::AppState;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct IdentityPath {
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    id: i64,
+}
+
+/// POST /auth/oidc/login — Start an OpenID Connect login for the `X-Client-Id` client.
+#[utoipa::path(
+    post,
+    path = "/login",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Authorization URL", body = OidcLoginStartResponse),
+        (status = 404, description = "OIDC login is not enabled")
+    )
+)]
+async fn post_oidc_login(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut conn: DbConn,
+) -> Result<Json<OidcLoginStartResponse>, AppError> {
+    let client = require_oidc(&state)?;
+    let client_id = required_client_id(&headers)?;
+    let conn = &mut *conn;
+
+    let login = oidc_service::create_login_state(conn, &client_id, None)?;
+    Ok(Json(OidcLoginStartResponse {
+        authorization_url: client.authorization_url(&login).await?,
+        state: login.state,
+        expires_at: login.expires_at,
+    }))
+}
+
+/// POST /auth/oidc/link — Start a login that links the identity to the caller's account.
+#[utoipa::path(
+    post,
+    path = "/link",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Authorization URL", body = OidcLoginStartResponse),
+        (status = 404, description = "OIDC login is not enabled")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn post_oidc_link(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut conn: DbConn,
+) -> Result<Json<OidcLoginStartResponse>, AppError> {
+    let client = require_oidc(&state)?;
+    let auth = extract_auth_context(&headers, &state)?;
+    let client_id = session_client_id(&auth, &headers)?;
+    let conn = &mut *conn;
+
+    let login = oidc_service::create_login_state(conn, &client_id, Some(auth.uid))?;
+    Ok(Json(OidcLoginStartResponse {
+        authorization_url: client.authorization_url(&login).await?,
+        state: login.state,
+        expires_at: login.expires_at,
+    }))
+}
+
+/// POST /auth/oidc/callback — Redeem the provider's code for an access and a refresh token.
+/// Must come from the `X-Client-Id` client that started the login.
+#[utoipa::path(
+    post,
+    path = "/callback",
+    tag = "auth",
+    request_body = OidcCallbackRequest,
+    responses(
+        (status = 200, description = "Auth token", body = AuthTokenResponse),
+        (status = 400, description = "Login state is invalid, expired or belongs to another client"),
+        (status = 401, description = "Code or ID token rejected"),
+        (status = 403, description = "No account is linked to this identity"),
+        (status = 409, description = "Identity is already linked to another account")
+    )
+)]
+async fn post_oidc_callback(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut conn: DbConn,
+    Json(body): Json<OidcCallbackRequest>,
+) -> Result<Json<AuthTokenResponse>, AppError> {
+    let client = require_oidc(&state)?;
+    let client_id = required_client_id(&headers)?;
+    let conn = &mut *conn;
+
+    let login = oidc_service::take_login_state(conn, body.state.trim())?;
+    // The state is bound to the client that started the login, so a state leaked
+    // from one client cannot be redeemed by another.
+    if login.client_id != client_id {
+        return Err(AppError::BadRequest(
+            "Login state belongs to another client",
+        ));
+    }
+    let claims = client.complete_login(body.code.trim(), &login).await?;
+    let identity_id = ids::next_id(state.id_gen.as_ref()).await.map_err(|e| {
+        tracing::error!("next_id for OIDC identity: {:?}", e);
+        AppError::Internal("ID generation failed")
+    })?;
+    let uid = oidc_service::resolve_uid(conn, client, &claims, login.link_uid, identity_id)?;
+
+    let app_version = headers.get(X_APP_VERSION).and_then(|v| v.to_str().ok());
+    let tokens = start_login_session(
+        conn,
+        &state.client_tracking,
+        &state.auth_sessions,
+        &state.jwt_signing_key,
+        uid,
+        &login.client_id,
+        app_version,
+    )?;
+    tracing::info!(uid, client_id = login.client_id, "OIDC login");
+
+    Ok(Json(auth_token_response(tokens)))
+}
+
+/// GET /auth/oidc/identities — External identities linked to the caller.
+#[utoipa::path(
+    get,
+    path = "/identities",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Linked identities", body = ListOidcIdentitiesResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn get_oidc_identities(
+    CurrentUid(uid): CurrentUid,
+    mut conn: DbConn,
+) -> Result<Json<ListOidcIdentitiesResponse>, AppError> {
+    let conn = &mut *conn;
+    let identities = oidc_service::list_identities(conn, uid)?
+        .into_iter()
+        .map(|identity| OidcIdentityResponse {
+            id: identity.id,
+            issuer: identity.issuer,
+            subject: identity.subject,
+            email: identity.email,
+            created_at: identity.created_at,
+            last_login_at: identity.last_login_at,
+        })
+        .collect();
+
+    Ok(Json(ListOidcIdentitiesResponse { identities }))
+}
+
+/// DELETE /auth/oidc/identities/:id — Unlink an external identity from the caller.
+#[utoipa::path(
+    delete,
+    path = "/identities/{id}",
+    tag = "auth",
+    params(IdentityPath),
+    responses(
+        (status = 204, description = "Identity unlinked"),
+        (status = 404, description = "Identity not found")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn delete_oidc_identity(
+    CurrentUid(uid): CurrentUid,
+    Path(IdentityPath { id }): Path<IdentityPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+    oidc_service::unlink_identity(conn, uid, id)?;
+    tracing::info!(uid, identity_id = id, "OIDC identity unlinked");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(post_oidc_login))
+        .routes(routes!(post_oidc_link))
+        .routes(routes!(post_oidc_callback))
+        .routes(routes!(get_oidc_identities))
+        .routes(routes!(delete_oidc_identity))
+}
+
+fn require_oidc(state: &AppState) -> Result<&Arc<OidcClient>, AppError> {
+    state
+        .oidc
+        .as_ref()
+        .ok_or(AppError::NotFound("OIDC login is not enabled"))
+}
+
+/// Start the session for a login. The callback is unauthenticated, so the activity
+/// tracker has not seen the client yet; record it first, as sessions reference it.
+fn start_login_session(
+    conn: &mut diesel::PgConnection,
+    client_tracking: &ClientTrackingService,
+    auth_sessions: &AuthSessionService,
+    jwt_signing_key: &[u8],
+    uid: i32,
+    client_id: &str,
+    app_version: Option<&str>,
+) -> Result<IssuedTokens, AppError> {
+    client_tracking.record_activity(uid, client_id, app_version)?;
+    auth_sessions.issue(conn, jwt_signing_key, uid, client_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+    use diesel::r2d2::{ConnectionManager, Pool};
+    use diesel_migrations::MigrationHarness;
+
+    use super::*;
+    use crate::schema::clients;
+
+    /// Runs against `TEST_DATABASE_URL` when set; there is no database otherwise.
+    #[tokio::test]
+    async fn login_session_starts_for_an_unseen_client() {
+        let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+            return;
+        };
+        let pool = Pool::builder()
+            .max_size(2)
+            .build(ConnectionManager::<diesel::PgConnection>::new(database_url))
+            .unwrap();
+        let conn = &mut pool.get().unwrap();
+        conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+
+        let client_tracking =
+            ClientTrackingService::start(pool.clone(), Arc::new(crate::metrics::Metrics::new()));
+        let auth_sessions = AuthSessionService::new(pool.clone());
+        let client_id = format!("oidc-test-{}", uuid::Uuid::new_v4().simple());
+
+        let tokens = start_login_session(
+            conn,
+            &client_tracking,
+            &auth_sessions,
+            b"01234567890123456789012345678901",
+            7,
+            &client_id,
+            None,
+        )
+        .unwrap();
+        assert!(!tokens.refresh_token.is_empty());
+        assert_eq!(
+            clients::table
+                .find(&client_id)
+                .select(clients::last_active_uid)
+                .first::<i32>(conn)
+                .unwrap(),
+            7
+        );
+    }
+}
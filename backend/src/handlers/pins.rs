@@ -17,9 +17,13 @@ use crate::dto::{
 use crate::errors::AppError;
 use crate::extractors::DbConn;
 use crate::handlers::chats::{attach_metadata, PreparedMessageSend, SendMessageOutcome};
-use crate::handlers::members::{check_membership, require_admin_role};
-use crate::models::{Message, MessageType, NewPinnedMessage, PinnedMessage};
+use crate::handlers::members::{check_membership, require_chat_permission};
+use crate::models::{
+    GroupAuditAction, Message, MessageType, NewGroupAuditLogEntry, NewPinnedMessage, PinnedMessage,
+};
 use crate::schema::{group_membership, messages, pinned_messages};
+use crate::services::audit_log::{next_audit_entry_id, record_audit_entry};
+use crate::services::authz::Action as AuthzAction;
 use crate::utils::auth::CurrentUid;
 use crate::utils::ids;
 use crate::AppState;
@@ -135,7 +139,7 @@ async fn create_pin(
 ) -> Result<(StatusCode, Json<PinResponse>), AppError> {
     let conn = &mut *conn;
 
-    require_admin_role(conn, path.chat_id, uid)?;
+    require_chat_permission(conn, &state, path.chat_id, uid, AuthzAction::ChatPin)?;
 
     // Verify message exists in this chat and is not deleted
     let msg: Message = messages::table
@@ -182,17 +186,28 @@ async fn create_pin(
         expires_at: None,
     };
 
-    let pin: PinnedMessage = diesel::insert_into(pinned_messages::table)
-        .values(&new_pin)
-        .returning(PinnedMessage::as_returning())
-        .get_result(conn)
-        .map_err(|e| {
-            if e.to_string().contains("unique") || e.to_string().contains("duplicate") {
-                return AppError::Conflict("Message is already pinned");
-            }
-            tracing::error!("insert pin: {:?}", e);
-            AppError::Internal("Database error")
-        })?;
+    let audit_id = next_audit_entry_id(state.id_gen.as_ref()).await?;
+    let pin: PinnedMessage = conn.transaction::<_, AppError, _>(|conn| {
+        let pin: PinnedMessage = diesel::insert_into(pinned_messages::table)
+            .values(&new_pin)
+            .returning(PinnedMessage::as_returning())
+            .get_result(conn)
+            .map_err(|e| {
+                if e.to_string().contains("unique") || e.to_string().contains("duplicate") {
+                    return AppError::Conflict("Message is already pinned");
+                }
+                tracing::error!("insert pin: {:?}", e);
+                AppError::Internal("Database error")
+            })?;
+        record_audit_entry(
+            conn,
+            &NewGroupAuditLogEntry::new(audit_id, path.chat_id, uid, GroupAuditAction::MessagePin)
+                .target_uid(msg.sender_uid)
+                .target_id(body.message_id)
+                .after(serde_json::json!({ "pinId": pin.id.to_string() })),
+        )?;
+        Ok(pin)
+    })?;
 
     let enriched = attach_metadata(conn, vec![msg], &state, uid).await;
     let msg_response = enriched
@@ -225,6 +240,9 @@ async fn create_pin(
                 client_generated_id: Uuid::new_v4().to_string(),
                 attachment_ids: vec![],
                 publish_immediately: true,
+                important: false,
+                topic_id: None,
+                slow_mode: false,
             },
         )
         .await
@@ -245,6 +263,7 @@ async fn create_pin(
         message_id: pin_response.message.id,
         pin: Some(pin_response.clone()),
     }));
+    state.webhook_service.publish_ws_message(&ws_msg);
     state.ws_registry.broadcast_to_uids(&member_uids, ws_msg);
 
     Ok((StatusCode::CREATED, Json(pin_response)))
@@ -271,7 +290,7 @@ async fn delete_pin(
 ) -> Result<StatusCode, AppError> {
     let conn = &mut *conn;
 
-    require_admin_role(conn, path.chat_id, uid)?;
+    require_chat_permission(conn, &state, path.chat_id, uid, AuthzAction::ChatPin)?;
 
     let pin: PinnedMessage = pinned_messages::table
         .filter(
@@ -283,8 +302,26 @@ async fn delete_pin(
         .optional()?
         .ok_or(AppError::NotFound("Pin not found"))?;
 
-    diesel::delete(pinned_messages::table.filter(pinned_messages::id.eq(path.pin_id)))
-        .execute(conn)?;
+    let audit_id = next_audit_entry_id(state.id_gen.as_ref()).await?;
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::delete(pinned_messages::table.filter(pinned_messages::id.eq(path.pin_id)))
+            .execute(conn)?;
+        record_audit_entry(
+            conn,
+            &NewGroupAuditLogEntry::new(
+                audit_id,
+                path.chat_id,
+                uid,
+                GroupAuditAction::MessageUnpin,
+            )
+            .target_id(pin.message_id)
+            .before(serde_json::json!({
+                "pinId": pin.id.to_string(),
+                "pinnedBy": pin.pinned_by,
+                "pinnedAt": pin.pinned_at,
+            })),
+        )
+    })?;
 
     // Send system message (best-effort — unpin already saved)
     if let Ok(SendMessageOutcome::Created(send_result)) =
@@ -302,6 +339,9 @@ async fn delete_pin(
                 client_generated_id: Uuid::new_v4().to_string(),
                 attachment_ids: vec![],
                 publish_immediately: true,
+                important: false,
+                topic_id: None,
+                slow_mode: false,
             },
         )
         .await
@@ -322,6 +362,7 @@ async fn delete_pin(
         message_id: pin.message_id,
         pin: None,
     }));
+    state.webhook_service.publish_ws_message(&ws_msg);
     state.ws_registry.broadcast_to_uids(&member_uids, ws_msg);
 
     Ok(StatusCode::NO_CONTENT)
@@ -0,0 +1,943 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde::Deserialize;
+use std::collections::HashMap;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::dto::policies::{
+    ChatRoleDecisionResponse, CreatePolicyAssignmentRequest, CreatePolicyPermissionRequest,
+    CreatePolicyRequest, ExplainDenyReason, ExplainPermissionResponse, ListPoliciesResponse,
+    PermissionGrantResponse, PolicyAssignmentResponse, PolicyPermissionResponse, PolicyResponse,
+    UpdatePolicyRequest,
+};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::models::{
+    NewPolicy, NewPolicyAssignment, NewPolicyPermission, PermissionResourceType, Policy,
+    PolicyAssignment, PolicyPermission, PolicySubjectType,
+};
+use crate::schema::discuz::discuz::{common_member, common_usergroup};
+use crate::schema::{groups, policies, policy_assignments, policy_permissions, service_tokens};
+use crate::services::authz::{
+    Action as AuthzAction, AuthzInvalidation, DenyReason, ExplainSubject, Resource as AuthzResource,
+};
+use crate::utils::{auth::CurrentUid, ids};
+use crate::AppState;
+
+const MAX_POLICY_NAME_LEN: usize = 120;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct PolicyPath {
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    id: i64,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct PolicyPermissionPath {
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    id: i64,
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    permission_id: i64,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct PolicyAssignmentPath {
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    id: i64,
+    #[serde(deserialize_with = "crate::serde_i64_string::deserialize")]
+    #[param(value_type = String)]
+    assignment_id: i64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "policies",
+    responses(
+        (status = 200, description = "Policies with their rules and assignments", body = ListPoliciesResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn get_policies(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+) -> Result<Json<ListPoliciesResponse>, AppError> {
+    let conn = &mut *conn;
+    require_permission_admin(conn, &state, uid)?;
+
+    let rows = policies::table
+        .order(policies::id.asc())
+        .select(Policy::as_select())
+        .load::<Policy>(conn)?;
+
+    Ok(Json(ListPoliciesResponse {
+        policies: build_policy_responses(conn, rows)?,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "policies",
+    request_body = CreatePolicyRequest,
+    responses(
+        (status = 201, description = "Policy created", body = PolicyResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn post_policy(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Json(body): Json<CreatePolicyRequest>,
+) -> Result<(StatusCode, Json<PolicyResponse>), AppError> {
+    let conn = &mut *conn;
+    require_permission_admin(conn, &state, uid)?;
+
+    let name = normalize_name(&body.name)?;
+    let metadata = normalize_metadata(body.metadata)?;
+    ensure_name_available(conn, &name, None)?;
+    let mut rules = Vec::with_capacity(body.permissions.len());
+    for rule in &body.permissions {
+        let rule = validate_permission_rule(rule)?;
+        ensure_rule_resource_exists(conn, &rule)?;
+        if !rules.contains(&rule) {
+            rules.push(rule);
+        }
+    }
+
+    let policy_id = next_id(&state, "policy").await?;
+    let mut rule_ids = Vec::with_capacity(rules.len());
+    for _ in &rules {
+        rule_ids.push(next_id(&state, "policy permission").await?);
+    }
+
+    let now = Utc::now();
+    conn.transaction::<(), AppError, _>(|conn| {
+        diesel::insert_into(policies::table)
+            .values(&NewPolicy {
+                id: policy_id,
+                name,
+                metadata,
+                created_at: now,
+                updated_at: now,
+            })
+            .execute(conn)?;
+
+        let new_rules: Vec<NewPolicyPermission> = rules
+            .iter()
+            .zip(&rule_ids)
+            .map(|(rule, rule_id)| NewPolicyPermission {
+                id: *rule_id,
+                policy_id,
+                action: rule.action.to_string(),
+                resource_type: rule.resource_type,
+                resource_id: rule.resource_id,
+                created_at: now,
+            })
+            .collect();
+        if !new_rules.is_empty() {
+            diesel::insert_into(policy_permissions::table)
+                .values(&new_rules)
+                .execute(conn)?;
+        }
+        Ok(())
+    })?;
+
+    tracing::info!(policy_id, created_by_uid = uid, "policy created");
+
+    Ok((
+        StatusCode::CREATED,
+        Json(load_policy_response(conn, policy_id)?),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "policies",
+    params(PolicyPath),
+    responses(
+        (status = 200, description = "Policy", body = PolicyResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn get_policy(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(PolicyPath { id }): Path<PolicyPath>,
+    mut conn: DbConn,
+) -> Result<Json<PolicyResponse>, AppError> {
+    let conn = &mut *conn;
+    require_permission_admin(conn, &state, uid)?;
+
+    Ok(Json(load_policy_response(conn, id)?))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    tag = "policies",
+    params(PolicyPath),
+    request_body = UpdatePolicyRequest,
+    responses(
+        (status = 200, description = "Policy updated", body = PolicyResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn patch_policy(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(PolicyPath { id }): Path<PolicyPath>,
+    mut conn: DbConn,
+    Json(body): Json<UpdatePolicyRequest>,
+) -> Result<Json<PolicyResponse>, AppError> {
+    let conn = &mut *conn;
+    require_permission_admin(conn, &state, uid)?;
+
+    let policy = load_policy(conn, id)?;
+    let name = match &body.name {
+        Some(name) => {
+            let name = normalize_name(name)?;
+            ensure_name_available(conn, &name, Some(id))?;
+            name
+        }
+        None => policy.name,
+    };
+    let metadata = match body.metadata {
+        Some(metadata) => normalize_metadata(Some(metadata))?,
+        None => policy.metadata,
+    };
+
+    diesel::update(policies::table.filter(policies::id.eq(id)))
+        .set((
+            policies::name.eq(name),
+            policies::metadata.eq(metadata),
+            policies::updated_at.eq(Utc::now()),
+        ))
+        .execute(conn)?;
+
+    tracing::info!(policy_id = id, updated_by_uid = uid, "policy updated");
+
+    Ok(Json(load_policy_response(conn, id)?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "policies",
+    params(PolicyPath),
+    responses(
+        (status = 204, description = "Policy deleted with its rules and assignments")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn delete_policy(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(PolicyPath { id }): Path<PolicyPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+    require_permission_admin(conn, &state, uid)?;
+
+    let policy = load_policy(conn, id)?;
+    if is_reserved(&policy) {
+        return Err(AppError::Conflict("Reserved policies cannot be deleted"));
+    }
+
+    diesel::delete(policies::table.filter(policies::id.eq(id))).execute(conn)?;
+    state
+        .authz_service
+        .publish_invalidation(conn, AuthzInvalidation::All)?;
+
+    tracing::info!(policy_id = id, deleted_by_uid = uid, "policy deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/permissions",
+    tag = "policies",
+    params(PolicyPath),
+    request_body = CreatePolicyPermissionRequest,
+    responses(
+        (status = 201, description = "Rule added", body = PolicyPermissionResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn post_policy_permission(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(PolicyPath { id }): Path<PolicyPath>,
+    mut conn: DbConn,
+    Json(body): Json<CreatePolicyPermissionRequest>,
+) -> Result<(StatusCode, Json<PolicyPermissionResponse>), AppError> {
+    let conn = &mut *conn;
+    require_permission_admin(conn, &state, uid)?;
+
+    load_policy(conn, id)?;
+    let rule = validate_permission_rule(&body)?;
+    ensure_rule_resource_exists(conn, &rule)?;
+
+    let mut existing_query = policy_permissions::table
+        .filter(policy_permissions::policy_id.eq(id))
+        .filter(policy_permissions::action.eq(rule.action))
+        .filter(policy_permissions::resource_type.eq(rule.resource_type))
+        .into_boxed();
+    existing_query = match rule.resource_id {
+        Some(resource_id) => existing_query.filter(policy_permissions::resource_id.eq(resource_id)),
+        None => existing_query.filter(policy_permissions::resource_id.is_null()),
+    };
+    let existing = existing_query.count().get_result::<i64>(conn)?;
+    if existing > 0 {
+        return Err(AppError::Conflict("Policy already has this rule"));
+    }
+
+    let rule_id = next_id(&state, "policy permission").await?;
+    let row = diesel::insert_into(policy_permissions::table)
+        .values(&NewPolicyPermission {
+            id: rule_id,
+            policy_id: id,
+            action: rule.action.to_string(),
+            resource_type: rule.resource_type,
+            resource_id: rule.resource_id,
+            created_at: Utc::now(),
+        })
+        .returning(PolicyPermission::as_returning())
+        .get_result::<PolicyPermission>(conn)?;
+    touch_policy(conn, id)?;
+    state
+        .authz_service
+        .publish_invalidation(conn, AuthzInvalidation::All)?;
+
+    tracing::info!(
+        policy_id = id,
+        permission_id = rule_id,
+        action = rule.action,
+        created_by_uid = uid,
+        "policy rule added"
+    );
+
+    Ok((StatusCode::CREATED, Json(permission_to_response(row))))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}/permissions/{permission_id}",
+    tag = "policies",
+    params(PolicyPermissionPath),
+    responses(
+        (status = 204, description = "Rule removed")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn delete_policy_permission(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(PolicyPermissionPath { id, permission_id }): Path<PolicyPermissionPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+    require_permission_admin(conn, &state, uid)?;
+
+    let affected = diesel::delete(
+        policy_permissions::table
+            .filter(policy_permissions::id.eq(permission_id))
+            .filter(policy_permissions::policy_id.eq(id)),
+    )
+    .execute(conn)?;
+    if affected == 0 {
+        return Err(AppError::NotFound("Policy rule not found"));
+    }
+    touch_policy(conn, id)?;
+    state
+        .authz_service
+        .publish_invalidation(conn, AuthzInvalidation::All)?;
+
+    tracing::info!(
+        policy_id = id,
+        permission_id,
+        deleted_by_uid = uid,
+        "policy rule removed"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/assignments",
+    tag = "policies",
+    params(PolicyPath),
+    request_body = CreatePolicyAssignmentRequest,
+    responses(
+        (status = 201, description = "Policy attached to the subject", body = PolicyAssignmentResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn post_policy_assignment(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(PolicyPath { id }): Path<PolicyPath>,
+    mut conn: DbConn,
+    Json(body): Json<CreatePolicyAssignmentRequest>,
+) -> Result<(StatusCode, Json<PolicyAssignmentResponse>), AppError> {
+    let conn = &mut *conn;
+    require_permission_admin(conn, &state, uid)?;
+
+    load_policy(conn, id)?;
+    ensure_subject_exists(conn, body.subject_type, body.subject_id)?;
+
+    let existing = policy_assignments::table
+        .filter(policy_assignments::policy_id.eq(id))
+        .filter(policy_assignments::subject_type.eq(body.subject_type))
+        .filter(policy_assignments::subject_id.eq(body.subject_id))
+        .count()
+        .get_result::<i64>(conn)?;
+    if existing > 0 {
+        return Err(AppError::Conflict(
+            "Policy is already assigned to this subject",
+        ));
+    }
+
+    let assignment_id = next_id(&state, "policy assignment").await?;
+    let now = Utc::now();
+    let row = diesel::insert_into(policy_assignments::table)
+        .values(&NewPolicyAssignment {
+            id: assignment_id,
+            subject_type: body.subject_type,
+            subject_id: body.subject_id,
+            policy_id: id,
+            created_at: now,
+            updated_at: now,
+        })
+        .returning(PolicyAssignment::as_returning())
+        .get_result::<PolicyAssignment>(conn)?;
+    state.authz_service.publish_invalidation(
+        conn,
+        AuthzInvalidation::for_subject(row.subject_type, row.subject_id),
+    )?;
+
+    tracing::info!(
+        policy_id = id,
+        assignment_id,
+        subject_type = ?body.subject_type,
+        subject_id = body.subject_id,
+        created_by_uid = uid,
+        "policy assigned"
+    );
+
+    Ok((StatusCode::CREATED, Json(assignment_to_response(row))))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}/assignments/{assignment_id}",
+    tag = "policies",
+    params(PolicyAssignmentPath),
+    responses(
+        (status = 204, description = "Policy detached from the subject")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn delete_policy_assignment(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(PolicyAssignmentPath { id, assignment_id }): Path<PolicyAssignmentPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+    require_permission_admin(conn, &state, uid)?;
+
+    let (subject_type, subject_id) = diesel::delete(
+        policy_assignments::table
+            .filter(policy_assignments::id.eq(assignment_id))
+            .filter(policy_assignments::policy_id.eq(id)),
+    )
+    .returning((
+        policy_assignments::subject_type,
+        policy_assignments::subject_id,
+    ))
+    .get_result::<(PolicySubjectType, i64)>(conn)
+    .optional()?
+    .ok_or(AppError::NotFound("Policy assignment not found"))?;
+    state.authz_service.publish_invalidation(
+        conn,
+        AuthzInvalidation::for_subject(subject_type, subject_id),
+    )?;
+
+    tracing::info!(
+        policy_id = id,
+        assignment_id,
+        deleted_by_uid = uid,
+        "policy unassigned"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[serde(rename_all = "camelCase")]
+struct ExplainPermissionQuery {
+    /// User to explain; mutually exclusive with `serviceTokenId`.
+    uid: Option<i32>,
+    /// Service token to explain; mutually exclusive with `uid`.
+    #[serde(
+        default,
+        deserialize_with = "crate::serde_i64_string::opt::deserialize"
+    )]
+    #[param(value_type = Option<String>)]
+    service_token_id: Option<i64>,
+    /// Action string such as `chat.pin`.
+    action: String,
+    /// Chat to check against; the global scope when absent.
+    #[serde(
+        default,
+        deserialize_with = "crate::serde_i64_string::opt::deserialize"
+    )]
+    #[param(value_type = Option<String>)]
+    chat_id: Option<i64>,
+}
+
+/// GET /policies/explain — Why a subject is or is not allowed an action on a resource.
+#[utoipa::path(
+    get,
+    path = "/explain",
+    tag = "policies",
+    params(ExplainPermissionQuery),
+    responses(
+        (status = OK, body = ExplainPermissionResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn get_permission_explanation(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Query(q): Query<ExplainPermissionQuery>,
+) -> Result<Json<ExplainPermissionResponse>, AppError> {
+    let conn = &mut *conn;
+    require_permission_admin(conn, &state, uid)?;
+
+    let subject = explain_subject(q.uid, q.service_token_id)?;
+    let action =
+        AuthzAction::parse(q.action.trim()).ok_or(AppError::BadRequest("Unknown action"))?;
+    let resource = match q.chat_id {
+        Some(chat_id) => AuthzResource::Chat(chat_id),
+        None => AuthzResource::Global,
+    };
+
+    match subject {
+        ExplainSubject::User(subject_uid) => {
+            ensure_subject_exists(conn, PolicySubjectType::User, i64::from(subject_uid))?
+        }
+        ExplainSubject::ServiceToken(token_id) => {
+            ensure_subject_exists(conn, PolicySubjectType::ServiceToken, token_id)?
+        }
+    }
+    if let Some(chat_id) = q.chat_id {
+        let exists = groups::table
+            .filter(groups::id.eq(chat_id))
+            .count()
+            .get_result::<i64>(conn)?;
+        if exists == 0 {
+            return Err(AppError::NotFound("Chat not found"));
+        }
+    }
+
+    let explanation = state
+        .authz_service
+        .explain(conn, subject, action, resource)?;
+
+    Ok(Json(ExplainPermissionResponse {
+        allowed: explanation.allowed,
+        action: action.as_str().to_string(),
+        resource_type: resource.resource_type(),
+        resource_id: resource.resource_id(),
+        discuz_group_id: explanation.discuz_group_id,
+        grants: explanation
+            .matching_grants
+            .into_iter()
+            .map(|grant| PermissionGrantResponse {
+                policy_id: grant.policy_id,
+                policy_name: grant.policy_name,
+                permission_id: grant.permission_id,
+                action: grant.action,
+                resource_type: grant.resource.resource_type(),
+                resource_id: grant.resource.resource_id(),
+                assignment_id: grant.assignment_id,
+                subject_type: grant.subject_type,
+                subject_id: grant.subject_id,
+            })
+            .collect(),
+        chat_role: explanation
+            .role_decision
+            .map(|decision| ChatRoleDecisionResponse {
+                role: decision.role,
+                allowed: decision.allowed,
+                overridden: decision.overridden,
+            }),
+        deny_reasons: explanation
+            .deny_reasons
+            .into_iter()
+            .map(|reason| match reason {
+                DenyReason::ServiceTokenRevoked => ExplainDenyReason::ServiceTokenRevoked,
+                DenyReason::ServiceTokenExpired => ExplainDenyReason::ServiceTokenExpired,
+                DenyReason::ChatNotAllowed => ExplainDenyReason::ChatNotAllowed,
+            })
+            .collect(),
+    }))
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_policies, post_policy))
+        .routes(routes!(get_permission_explanation))
+        .routes(routes!(get_policy, patch_policy, delete_policy))
+        .routes(routes!(post_policy_permission))
+        .routes(routes!(delete_policy_permission))
+        .routes(routes!(post_policy_assignment))
+        .routes(routes!(delete_policy_assignment))
+}
+
+fn require_permission_admin(
+    conn: &mut PgConnection,
+    state: &AppState,
+    uid: i32,
+) -> Result<(), AppError> {
+    state.authz_service.require_permission(
+        conn,
+        uid,
+        AuthzAction::PermissionAll,
+        AuthzResource::Global,
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ValidatedRule {
+    action: &'static str,
+    resource_type: PermissionResourceType,
+    resource_id: Option<i64>,
+}
+
+fn validate_permission_rule(
+    rule: &CreatePolicyPermissionRequest,
+) -> Result<ValidatedRule, AppError> {
+    let action =
+        AuthzAction::parse(rule.action.trim()).ok_or(AppError::BadRequest("Unknown action"))?;
+    match (rule.resource_type, rule.resource_id) {
+        (PermissionResourceType::Global, None) => {}
+        (PermissionResourceType::Global, Some(_)) => {
+            return Err(AppError::BadRequest(
+                "Global rules must not have a resource id",
+            ))
+        }
+        (PermissionResourceType::Chat, Some(chat_id)) if chat_id > 0 => {}
+        (PermissionResourceType::Chat, _) => {
+            return Err(AppError::BadRequest("Chat rules require a chat id"))
+        }
+    }
+
+    Ok(ValidatedRule {
+        action: action.as_str(),
+        resource_type: rule.resource_type,
+        resource_id: rule.resource_id,
+    })
+}
+
+fn ensure_rule_resource_exists(
+    conn: &mut PgConnection,
+    rule: &ValidatedRule,
+) -> Result<(), AppError> {
+    let Some(chat_id) = rule.resource_id else {
+        return Ok(());
+    };
+
+    let exists = groups::table
+        .filter(groups::id.eq(chat_id))
+        .count()
+        .get_result::<i64>(conn)?;
+    if exists == 0 {
+        return Err(AppError::BadRequest("Chat not found"));
+    }
+    Ok(())
+}
+
+fn explain_subject(
+    uid: Option<i32>,
+    service_token_id: Option<i64>,
+) -> Result<ExplainSubject, AppError> {
+    match (uid, service_token_id) {
+        (Some(uid), None) => Ok(ExplainSubject::User(uid)),
+        (None, Some(token_id)) => Ok(ExplainSubject::ServiceToken(token_id)),
+        _ => Err(AppError::BadRequest(
+            "Exactly one of uid or serviceTokenId is required",
+        )),
+    }
+}
+
+fn ensure_subject_exists(
+    conn: &mut PgConnection,
+    subject_type: PolicySubjectType,
+    subject_id: i64,
+) -> Result<(), AppError> {
+    let exists = match subject_type {
+        PolicySubjectType::User => {
+            let uid = i32::try_from(subject_id)
+                .map_err(|_| AppError::BadRequest("Subject id is invalid"))?;
+            common_member::table
+                .filter(common_member::uid.eq(uid))
+                .count()
+                .get_result::<i64>(conn)?
+        }
+        PolicySubjectType::DiscuzGroup => {
+            let group_id = i32::try_from(subject_id)
+                .map_err(|_| AppError::BadRequest("Subject id is invalid"))?;
+            common_usergroup::table
+                .filter(common_usergroup::groupid.eq(group_id))
+                .count()
+                .get_result::<i64>(conn)?
+        }
+        PolicySubjectType::ServiceToken => service_tokens::table
+            .filter(service_tokens::id.eq(subject_id))
+            .filter(service_tokens::revoked_at.is_null())
+            .count()
+            .get_result::<i64>(conn)?,
+    };
+
+    if exists == 0 {
+        return Err(AppError::BadRequest("Subject not found"));
+    }
+    Ok(())
+}
+
+fn normalize_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("Name is required"));
+    }
+    if name.len() > MAX_POLICY_NAME_LEN {
+        return Err(AppError::BadRequest("Name is too long"));
+    }
+    Ok(name.to_string())
+}
+
+fn normalize_metadata(metadata: Option<serde_json::Value>) -> Result<serde_json::Value, AppError> {
+    match metadata {
+        None | Some(serde_json::Value::Null) => Ok(serde_json::json!({})),
+        Some(value @ serde_json::Value::Object(_)) => Ok(value),
+        Some(_) => Err(AppError::BadRequest("Metadata must be an object")),
+    }
+}
+
+fn is_reserved(policy: &Policy) -> bool {
+    policy
+        .metadata
+        .get("reserved")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn ensure_name_available(
+    conn: &mut PgConnection,
+    name: &str,
+    except_id: Option<i64>,
+) -> Result<(), AppError> {
+    let mut query = policies::table.filter(policies::name.eq(name)).into_boxed();
+    if let Some(id) = except_id {
+        query = query.filter(policies::id.ne(id));
+    }
+    let taken = query.count().get_result::<i64>(conn)?;
+    if taken > 0 {
+        return Err(AppError::Conflict("Policy name already exists"));
+    }
+    Ok(())
+}
+
+fn load_policy(conn: &mut PgConnection, id: i64) -> Result<Policy, AppError> {
+    policies::table
+        .filter(policies::id.eq(id))
+        .select(Policy::as_select())
+        .first::<Policy>(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Policy not found"))
+}
+
+fn touch_policy(conn: &mut PgConnection, id: i64) -> Result<(), AppError> {
+    diesel::update(policies::table.filter(policies::id.eq(id)))
+        .set(policies::updated_at.eq(Utc::now()))
+        .execute(conn)?;
+    Ok(())
+}
+
+async fn next_id(state: &AppState, label: &'static str) -> Result<i64, AppError> {
+    ids::next_id(state.id_gen.as_ref()).await.map_err(|e| {
+        tracing::error!("next_id for {}: {:?}", label, e);
+        AppError::Internal("ID generation failed")
+    })
+}
+
+fn load_policy_response(conn: &mut PgConnection, id: i64) -> Result<PolicyResponse, AppError> {
+    let policy = load_policy(conn, id)?;
+    build_policy_responses(conn, vec![policy])?
+        .pop()
+        .ok_or(AppError::NotFound("Policy not found"))
+}
+
+fn build_policy_responses(
+    conn: &mut PgConnection,
+    rows: Vec<Policy>,
+) -> Result<Vec<PolicyResponse>, AppError> {
+    let policy_ids: Vec<i64> = rows.iter().map(|row| row.id).collect();
+    if policy_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut permissions: HashMap<i64, Vec<PolicyPermissionResponse>> = HashMap::new();
+    for row in policy_permissions::table
+        .filter(policy_permissions::policy_id.eq_any(&policy_ids))
+        .order(policy_permissions::id.asc())
+        .select(PolicyPermission::as_select())
+        .load::<PolicyPermission>(conn)?
+    {
+        permissions
+            .entry(row.policy_id)
+            .or_default()
+            .push(permission_to_response(row));
+    }
+
+    let mut assignments: HashMap<i64, Vec<PolicyAssignmentResponse>> = HashMap::new();
+    for row in policy_assignments::table
+        .filter(policy_assignments::policy_id.eq_any(&policy_ids))
+        .order(policy_assignments::id.asc())
+        .select(PolicyAssignment::as_select())
+        .load::<PolicyAssignment>(conn)?
+    {
+        assignments
+            .entry(row.policy_id)
+            .or_default()
+            .push(assignment_to_response(row));
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|row| PolicyResponse {
+            permissions: permissions.remove(&row.id).unwrap_or_default(),
+            assignments: assignments.remove(&row.id).unwrap_or_default(),
+            id: row.id,
+            name: row.name,
+            metadata: row.metadata,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+        .collect())
+}
+
+fn permission_to_response(row: PolicyPermission) -> PolicyPermissionResponse {
+    PolicyPermissionResponse {
+        id: row.id,
+        action: row.action,
+        resource_type: row.resource_type,
+        resource_id: row.resource_id,
+        created_at: row.created_at,
+    }
+}
+
+fn assignment_to_response(row: PolicyAssignment) -> PolicyAssignmentResponse {
+    PolicyAssignmentResponse {
+        id: row.id,
+        subject_type: row.subject_type,
+        subject_id: row.subject_id,
+        created_at: row.created_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{explain_subject, normalize_metadata, validate_permission_rule};
+    use crate::dto::policies::CreatePolicyPermissionRequest;
+    use crate::errors::AppError;
+    use crate::models::PermissionResourceType;
+    use crate::services::authz::ExplainSubject;
+
+    fn rule(
+        action: &str,
+        resource_type: PermissionResourceType,
+        resource_id: Option<i64>,
+    ) -> CreatePolicyPermissionRequest {
+        CreatePolicyPermissionRequest {
+            action: action.to_string(),
+            resource_type,
+            resource_id,
+        }
+    }
+
+    #[test]
+    fn validate_permission_rule_checks_action_and_scope() {
+        let validated =
+            validate_permission_rule(&rule("chat.pin", PermissionResourceType::Chat, Some(5)))
+                .unwrap();
+        assert_eq!(validated.action, "chat.pin");
+        assert_eq!(validated.resource_id, Some(5));
+
+        assert!(matches!(
+            validate_permission_rule(&rule("chat.fly", PermissionResourceType::Global, None)),
+            Err(AppError::BadRequest("Unknown action"))
+        ));
+        assert!(matches!(
+            validate_permission_rule(&rule("chat.pin", PermissionResourceType::Chat, None)),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            validate_permission_rule(&rule(
+                "permission.all",
+                PermissionResourceType::Global,
+                Some(1)
+            )),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn normalize_metadata_requires_an_object() {
+        assert_eq!(normalize_metadata(None).unwrap(), serde_json::json!({}));
+        assert!(matches!(
+            normalize_metadata(Some(serde_json::json!([1]))),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn explain_subject_requires_exactly_one_subject() {
+        assert!(matches!(
+            explain_subject(Some(7), None),
+            Ok(ExplainSubject::User(7))
+        ));
+        assert!(matches!(
+            explain_subject(None, Some(9)),
+            Ok(ExplainSubject::ServiceToken(9))
+        ));
+        assert!(matches!(
+            explain_subject(None, None),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            explain_subject(Some(7), Some(9)),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
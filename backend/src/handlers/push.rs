@@ -11,7 +11,10 @@ use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 
-use crate::dto::push::{SubscriptionStatusResponse, VapidPublicKeyResponse};
+use crate::dto::push::{
+    NotificationPreferencesResponse, SubscriptionStatusResponse,
+    UpdateNotificationPreferencesRequest, VapidPublicKeyResponse,
+};
 use crate::errors::AppError;
 use crate::extractors::DbConn;
 use crate::models::{
@@ -19,6 +22,7 @@ use crate::models::{
     WebPushSubscriptionData,
 };
 use crate::schema::push_subscriptions;
+use crate::services::notification_preferences as preference_service;
 use crate::utils::auth::{ClientId, CurrentUid};
 use crate::utils::ids;
 use crate::AppState;
@@ -43,6 +47,7 @@ pub enum ApiPushProvider {
     #[default]
     WebPush,
     Apns,
+    Fcm,
 }
 
 impl From<ApiPushProvider> for PushProvider {
@@ -50,6 +55,7 @@ impl From<ApiPushProvider> for PushProvider {
         match value {
             ApiPushProvider::WebPush => PushProvider::WebPush,
             ApiPushProvider::Apns => PushProvider::Apns,
+            ApiPushProvider::Fcm => PushProvider::Fcm,
         }
     }
 }
@@ -125,6 +131,21 @@ impl SubscribeBody {
                         .map_err(|_| AppError::Internal("failed to serialize APNs data"))?,
                 })
             }
+            ApiPushProvider::Fcm => {
+                let device_token = self
+                    .device_token
+                    .clone()
+                    .filter(|value| !value.trim().is_empty())
+                    .ok_or(AppError::BadRequest("deviceToken is required for fcm"))?;
+
+                Ok(ValidatedSubscription {
+                    provider: PushProvider::Fcm,
+                    endpoint: None,
+                    device_token: Some(device_token),
+                    apns_environment: None,
+                    provider_data: serde_json::json!({}),
+                })
+            }
         }
     }
 }
@@ -205,6 +226,13 @@ async fn post_subscribe(
                         );
                 }
             }
+            PushProvider::Fcm => {
+                if let Some(device_token) = &device_token {
+                    replace_scope = replace_scope.filter(
+                        push_subscriptions::dsl::device_token.ne(Some(device_token.clone())),
+                    );
+                }
+            }
         }
 
         let replace_ids = replace_scope
@@ -225,12 +253,21 @@ async fn post_subscribe(
                 .select(push_subscriptions::dsl::id)
                 .first::<i64>(conn)
                 .optional()?,
+            // A registration token belongs to one app install, so a new sign-in on the
+            // same device takes the subscription over from the previous user.
+            PushProvider::Fcm => push_subscriptions::table
+                .filter(push_subscriptions::dsl::provider.eq(provider))
+                .filter(push_subscriptions::dsl::device_token.eq(device_token.clone()))
+                .select(push_subscriptions::dsl::id)
+                .first::<i64>(conn)
+                .optional()?,
             PushProvider::Apns => None,
         };
 
         if let Some(existing_id) = existing_id {
             diesel::update(push_subscriptions::table.find(existing_id))
                 .set((
+                    push_subscriptions::user_id.eq(uid),
                     push_subscriptions::client_id.eq(&current_client_id),
                     push_subscriptions::created_at.eq(created_at),
                     push_subscriptions::provider_data.eq(&provider_data),
@@ -242,7 +279,7 @@ async fn post_subscribe(
                 .execute(conn)?;
         } else {
             match provider {
-                PushProvider::WebPush => {
+                PushProvider::WebPush | PushProvider::Fcm => {
                     diesel::insert_into(push_subscriptions::table)
                         .values(&new_sub)
                         .execute(conn)?;
@@ -332,6 +369,12 @@ async fn post_unsubscribe(
                 .filter(push_subscriptions::dsl::device_token.eq(Some(device_token)))
                 .filter(push_subscriptions::dsl::apns_environment.eq(Some(environment)));
         }
+        PushProvider::Fcm => {
+            let device_token = body.device_token.ok_or(AppError::BadRequest(
+                "deviceToken is required for fcm unsubscribe",
+            ))?;
+            query = query.filter(push_subscriptions::dsl::device_token.eq(Some(device_token)));
+        }
     }
 
     let delete_ids = query
@@ -415,6 +458,22 @@ async fn get_subscription_status(
             ),
             _ => None,
         },
+        PushProvider::Fcm => query
+            .device_token
+            .as_ref()
+            .map(|device_token| {
+                diesel::select(diesel::dsl::exists(
+                    push_subscriptions::table
+                        .filter(push_subscriptions::dsl::user_id.eq(uid))
+                        .filter(push_subscriptions::dsl::client_id.eq(Some(client_id.clone())))
+                        .filter(push_subscriptions::dsl::provider.eq(provider))
+                        .filter(
+                            push_subscriptions::dsl::device_token.eq(Some(device_token.clone())),
+                        ),
+                ))
+                .get_result::<bool>(conn)
+            })
+            .transpose()?,
     };
 
     Ok(Json(SubscriptionStatusResponse {
@@ -426,12 +485,71 @@ async fn get_subscription_status(
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/preferences",
+    tag = "push",
+    responses(
+        (status = 200, description = "Notification preferences", body = NotificationPreferencesResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn get_notification_preferences(
+    CurrentUid(uid): CurrentUid,
+    mut conn: DbConn,
+) -> Result<Json<NotificationPreferencesResponse>, AppError> {
+    let conn = &mut *conn;
+    let preferences = preference_service::load_preferences(conn, uid)?;
+    Ok(Json(preference_service::preferences_to_response(
+        preferences,
+    )))
+}
+
+#[utoipa::path(
+    put,
+    path = "/preferences",
+    tag = "push",
+    request_body = UpdateNotificationPreferencesRequest,
+    responses(
+        (status = 200, description = "Notification preferences", body = NotificationPreferencesResponse),
+        (status = 400, description = "Invalid keywords, times or timezone")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn put_notification_preferences(
+    CurrentUid(uid): CurrentUid,
+    mut conn: DbConn,
+    Json(body): Json<UpdateNotificationPreferencesRequest>,
+) -> Result<Json<NotificationPreferencesResponse>, AppError> {
+    let conn = &mut *conn;
+    let mut preferences = preference_service::default_preferences(uid);
+    preferences.level = body.level;
+    preferences.keywords = preference_service::normalize_keywords(body.keywords)?;
+    if let Some(quiet_hours) = &body.quiet_hours {
+        let (start, end, timezone) = preference_service::resolve_quiet_hours(quiet_hours)?;
+        preferences.quiet_hours_start = Some(start);
+        preferences.quiet_hours_end = Some(end);
+        preferences.quiet_hours_timezone = Some(timezone);
+        preferences.quiet_hours_mode = quiet_hours.mode;
+    }
+    preferences.updated_at = Utc::now();
+
+    preference_service::save_preferences(conn, &preferences)?;
+    Ok(Json(preference_service::preferences_to_response(
+        preferences,
+    )))
+}
+
 pub fn router() -> OpenApiRouter<crate::AppState> {
     OpenApiRouter::new()
         .routes(routes!(get_vapid_public_key))
         .routes(routes!(get_subscription_status))
         .routes(routes!(post_subscribe))
         .routes(routes!(post_unsubscribe))
+        .routes(routes!(
+            get_notification_preferences,
+            put_notification_preferences
+        ))
 }
 
 #[cfg(test)]
@@ -461,6 +579,30 @@ mod tests {
         assert_eq!(validated.apns_environment, Some(PushEnvironment::Sandbox));
     }
 
+    #[test]
+    fn subscribe_validates_fcm_payload() {
+        let body: SubscribeBody =
+            serde_json::from_str(r#"{"provider":"fcm","deviceToken":"fcm-token-1"}"#)
+                .expect("parse FCM body");
+
+        let validated = body.validate().expect("validate FCM body");
+        assert_eq!(validated.provider, PushProvider::Fcm);
+        assert_eq!(validated.endpoint, None);
+        assert_eq!(validated.device_token.as_deref(), Some("fcm-token-1"));
+        assert_eq!(validated.apns_environment, None);
+    }
+
+    #[test]
+    fn subscribe_rejects_fcm_without_device_token() {
+        let body: SubscribeBody = serde_json::from_str(r#"{"provider":"fcm","deviceToken":"  "}"#)
+            .expect("parse FCM body");
+
+        assert!(matches!(
+            body.validate(),
+            Err(AppError::BadRequest("deviceToken is required for fcm"))
+        ));
+    }
+
     #[test]
     fn subscribe_rejects_missing_web_keys() {
         let body: SubscribeBody =
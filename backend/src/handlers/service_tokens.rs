@@ -12,19 +12,29 @@ use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 
 use crate::dto::service_tokens::{
-    CreateServiceTokenRequest, CreateServiceTokenResponse, ListServiceTokensResponse,
-    RotateServiceTokenResponse, ServiceTokenResponse,
+    BotResponse, CreateServiceTokenRequest, CreateServiceTokenResponse, ListServiceTokensResponse,
+    RotateServiceTokenResponse, ServiceTokenResponse, ServiceTokenUsageResponse,
+    UpdateServiceTokenRequest, UpsertBotRequest,
 };
 use crate::errors::AppError;
 use crate::extractors::DbConn;
-use crate::models::{NewPolicyAssignment, NewServiceToken, PolicySubjectType, ServiceToken};
-use crate::schema::{policies, policy_assignments, service_tokens};
-use crate::services::authz::{Action as AuthzAction, Resource as AuthzResource};
+use crate::models::{
+    Bot, GroupRole, NewBot, NewPolicyAssignment, NewServiceToken, PolicySubjectType, ServiceToken,
+    ServiceTokenUsage,
+};
+use crate::schema::{bots, group_membership, groups, policies, policy_assignments, service_tokens};
+use crate::services::authz::{Action as AuthzAction, AuthzInvalidation, Resource as AuthzResource};
+use crate::services::bots as bot_service;
+use crate::services::chat_folders::remove_chats_from_user_folders;
 use crate::services::service_tokens as service_token_service;
+use crate::services::webhooks::{MemberEventData, WebhookEvent};
 use crate::utils::{auth::CurrentUid, ids};
 use crate::AppState;
 
 const MAX_SERVICE_TOKEN_NAME_LEN: usize = 120;
+const MAX_ALLOWED_CHATS: usize = 100;
+const MAX_RATE_LIMIT_PER_MINUTE: i32 = 6000;
+const RECENT_USAGE_PER_TOKEN: i64 = 20;
 
 #[derive(Debug, Deserialize, utoipa::IntoParams)]
 struct ServiceTokenPath {
@@ -55,6 +65,17 @@ async fn post_service_token(
     let name = normalize_name(&body.name)?;
     let policy_ids = parse_policy_ids(&body.policy_ids)?;
     ensure_policies_exist(conn, &policy_ids)?;
+    let now = Utc::now();
+    validate_expires_at(body.expires_at, now)?;
+    let allowed_chat_ids = body
+        .allowed_chat_ids
+        .as_deref()
+        .map(parse_chat_ids)
+        .transpose()?;
+    if let Some(chat_ids) = &allowed_chat_ids {
+        ensure_chats_exist(conn, chat_ids)?;
+    }
+    validate_rate_limit(body.rate_limit_per_minute)?;
 
     let service_token_id = ids::next_id(state.id_gen.as_ref()).await.map_err(|e| {
         tracing::error!("next_id for service token: {:?}", e);
@@ -64,7 +85,6 @@ async fn post_service_token(
         next_ids(&state, policy_ids.len(), "service token policy assignment").await?;
     let credential = service_token_service::generate_credential(&state.service_token_hash_key)?;
 
-    let now = Utc::now();
     let new_token = NewServiceToken {
         id: service_token_id,
         token: credential.token.clone(),
@@ -76,6 +96,9 @@ async fn post_service_token(
         metadata: serde_json::json!({}),
         created_at: now,
         updated_at: now,
+        expires_at: body.expires_at,
+        allowed_chat_ids,
+        rate_limit_per_minute: body.rate_limit_per_minute,
     };
 
     conn.transaction::<(), AppError, _>(|conn| {
@@ -101,7 +124,12 @@ async fn post_service_token(
     Ok((
         StatusCode::CREATED,
         Json(CreateServiceTokenResponse {
-            service_token: service_token_to_response(row, policy_ids_to_strings(policy_ids)),
+            service_token: service_token_to_response(
+                row,
+                policy_ids_to_strings(policy_ids),
+                None,
+                Vec::new(),
+            ),
             credential: credential.credential,
         }),
     ))
@@ -130,18 +158,127 @@ async fn get_service_tokens(
         .load::<ServiceToken>(conn)?;
     let token_ids: Vec<i64> = rows.iter().map(|row| row.id).collect();
     let policy_ids = load_policy_ids(conn, &token_ids)?;
+    let mut bots_by_token: HashMap<i64, Bot> = bots::table
+        .filter(bots::service_token_id.eq_any(&token_ids))
+        .select(Bot::as_select())
+        .load::<Bot>(conn)?
+        .into_iter()
+        .map(|bot| (bot.service_token_id, bot))
+        .collect();
+    let mut usage_by_token =
+        service_token_service::load_recent_usage(conn, &token_ids, RECENT_USAGE_PER_TOKEN)?;
 
     Ok(Json(ListServiceTokensResponse {
         service_tokens: rows
             .into_iter()
             .map(|row| {
                 let policy_ids = policy_ids.get(&row.id).cloned().unwrap_or_default();
-                service_token_to_response(row, policy_ids)
+                let bot = bots_by_token.remove(&row.id);
+                let usage = usage_by_token.remove(&row.id).unwrap_or_default();
+                service_token_to_response(row, policy_ids, bot, usage)
             })
             .collect(),
     }))
 }
 
+/// PATCH /service-tokens/:id — Rename the token or change its expiry, chat scope or rate limit.
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    tag = "service-tokens",
+    params(ServiceTokenPath),
+    request_body = UpdateServiceTokenRequest,
+    responses(
+        (status = 200, description = "Service token updated", body = ServiceTokenResponse),
+        (status = 404, description = "Service token not found"),
+        (status = 410, description = "Service token revoked")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn patch_service_token(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ServiceTokenPath { id }): Path<ServiceTokenPath>,
+    mut conn: DbConn,
+    Json(body): Json<UpdateServiceTokenRequest>,
+) -> Result<Json<ServiceTokenResponse>, AppError> {
+    let conn = &mut *conn;
+    require_manage_permission(conn, &state, uid)?;
+
+    let row = service_tokens::table
+        .filter(service_tokens::id.eq(id))
+        .select(ServiceToken::as_select())
+        .first::<ServiceToken>(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Service token not found"))?;
+    if row.revoked_at.is_some() {
+        return Err(AppError::Gone("Service token revoked"));
+    }
+
+    let now = Utc::now();
+    let row_expires_at = row.expires_at;
+    let row_allowed_chat_ids = row.allowed_chat_ids.clone();
+    let name = match body.name.as_deref() {
+        Some(name) => normalize_name(name)?,
+        None => row.name,
+    };
+    let expires_at = match body.expires_at {
+        Some(expires_at) => {
+            validate_expires_at(expires_at, now)?;
+            expires_at
+        }
+        None => row.expires_at,
+    };
+    let allowed_chat_ids = match body.allowed_chat_ids {
+        Some(Some(raw)) => {
+            let chat_ids = parse_chat_ids(&raw)?;
+            ensure_chats_exist(conn, &chat_ids)?;
+            Some(chat_ids)
+        }
+        Some(None) => None,
+        None => row.allowed_chat_ids,
+    };
+    let rate_limit_per_minute = match body.rate_limit_per_minute {
+        Some(limit) => {
+            validate_rate_limit(limit)?;
+            limit
+        }
+        None => row.rate_limit_per_minute,
+    };
+
+    let updated = diesel::update(service_tokens::table.filter(service_tokens::id.eq(id)))
+        .set((
+            service_tokens::name.eq(name),
+            service_tokens::expires_at.eq(expires_at),
+            service_tokens::allowed_chat_ids.eq(allowed_chat_ids),
+            service_tokens::rate_limit_per_minute.eq(rate_limit_per_minute),
+            service_tokens::updated_at.eq(now),
+        ))
+        .returning(ServiceToken::as_returning())
+        .get_result::<ServiceToken>(conn)?;
+    let policy_ids = load_policy_ids(conn, &[id])?
+        .remove(&id)
+        .unwrap_or_default();
+    let bot = bot_service::find_bot_for_service_token(conn, id)?;
+    // Live sessions were admitted under the old expiry and chat scope.
+    if updated.expires_at != row_expires_at || updated.allowed_chat_ids != row_allowed_chat_ids {
+        close_bot_sessions(&state, bot.as_ref(), id);
+    }
+    let usage = service_token_service::load_recent_usage(conn, &[id], RECENT_USAGE_PER_TOKEN)?
+        .remove(&id)
+        .unwrap_or_default();
+
+    tracing::info!(
+        service_token_id = id,
+        updated_by_uid = uid,
+        "service token updated"
+    );
+
+    Ok(Json(service_token_to_response(
+        updated, policy_ids, bot, usage,
+    )))
+}
+
 #[utoipa::path(
     delete,
     path = "/{id}",
@@ -172,6 +309,11 @@ async fn delete_service_token(
     if affected == 0 {
         return Err(AppError::NotFound("Service token not found"));
     }
+    state
+        .authz_service
+        .publish_invalidation(conn, AuthzInvalidation::ServiceToken { token_id: id })?;
+    let bot = bot_service::find_bot_for_service_token(conn, id)?;
+    close_bot_sessions(&state, bot.as_ref(), id);
 
     tracing::info!(
         service_token_id = id,
@@ -233,6 +375,11 @@ async fn post_rotate_service_token(
     let policy_ids = load_policy_ids(conn, &[id])?
         .remove(&id)
         .unwrap_or_default();
+    let bot = bot_service::find_bot_for_service_token(conn, id)?;
+    close_bot_sessions(&state, bot.as_ref(), id);
+    let usage = service_token_service::load_recent_usage(conn, &[id], RECENT_USAGE_PER_TOKEN)?
+        .remove(&id)
+        .unwrap_or_default();
 
     tracing::info!(
         service_token_id = id,
@@ -241,15 +388,157 @@ async fn post_rotate_service_token(
     );
 
     Ok(Json(RotateServiceTokenResponse {
-        service_token: service_token_to_response(updated, policy_ids),
+        service_token: service_token_to_response(updated, policy_ids, bot, usage),
         credential,
     }))
 }
 
+/// PUT /service-tokens/:id/bot — Create or update the bot identity the token posts as.
+#[utoipa::path(
+    put,
+    path = "/{id}/bot",
+    tag = "service-tokens",
+    params(ServiceTokenPath),
+    request_body = UpsertBotRequest,
+    responses(
+        (status = 200, description = "Bot updated", body = BotResponse),
+        (status = 201, description = "Bot created", body = BotResponse)
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn put_service_token_bot(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ServiceTokenPath { id }): Path<ServiceTokenPath>,
+    mut conn: DbConn,
+    Json(body): Json<UpsertBotRequest>,
+) -> Result<(StatusCode, Json<BotResponse>), AppError> {
+    let conn = &mut *conn;
+    require_manage_permission(conn, &state, uid)?;
+
+    let name = bot_service::normalize_bot_name(&body.name)?;
+    let avatar_url = bot_service::normalize_bot_avatar_url(body.avatar_url.as_deref())?;
+
+    let revoked_at = service_tokens::table
+        .filter(service_tokens::id.eq(id))
+        .select(service_tokens::revoked_at)
+        .first::<Option<DateTime<Utc>>>(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Service token not found"))?;
+    if revoked_at.is_some() {
+        return Err(AppError::Gone("Service token revoked"));
+    }
+
+    let (status, bot) = match bot_service::find_bot_for_service_token(conn, id)? {
+        Some(existing) => {
+            let bot = diesel::update(bots::table.filter(bots::uid.eq(existing.uid)))
+                .set((
+                    bots::name.eq(&name),
+                    bots::avatar_url.eq(&avatar_url),
+                    bots::updated_at.eq(Utc::now()),
+                ))
+                .returning(Bot::as_returning())
+                .get_result::<Bot>(conn)?;
+            (StatusCode::OK, bot)
+        }
+        None => {
+            let bot = diesel::insert_into(bots::table)
+                .values(&NewBot {
+                    service_token_id: id,
+                    name,
+                    avatar_url,
+                    created_by_uid: uid,
+                })
+                .returning(Bot::as_returning())
+                .get_result::<Bot>(conn)?;
+            (StatusCode::CREATED, bot)
+        }
+    };
+
+    tracing::info!(
+        service_token_id = id,
+        bot_uid = bot.uid,
+        updated_by_uid = uid,
+        "service token bot saved"
+    );
+
+    Ok((status, Json(bot_to_response(bot))))
+}
+
+/// DELETE /service-tokens/:id/bot — Remove the token's bot and its group memberships.
+/// Messages it already posted are kept.
+#[utoipa::path(
+    delete,
+    path = "/{id}/bot",
+    tag = "service-tokens",
+    params(ServiceTokenPath),
+    responses(
+        (status = 204, description = "Bot deleted")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn delete_service_token_bot(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ServiceTokenPath { id }): Path<ServiceTokenPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+    require_manage_permission(conn, &state, uid)?;
+
+    let bot = bot_service::find_bot_for_service_token(conn, id)?
+        .ok_or(AppError::NotFound("Bot not found"))?;
+    let removed: Vec<(i64, GroupRole)> = conn.transaction::<_, AppError, _>(|conn| {
+        let removed: Vec<(i64, GroupRole)> =
+            diesel::delete(group_membership::table.filter(group_membership::uid.eq(bot.uid)))
+                .returning((group_membership::chat_id, group_membership::role))
+                .get_results(conn)?;
+        let chat_ids: Vec<i64> = removed.iter().map(|(chat_id, _)| *chat_id).collect();
+        remove_chats_from_user_folders(conn, bot.uid, &chat_ids)?;
+        diesel::delete(bots::table.filter(bots::uid.eq(bot.uid))).execute(conn)?;
+        Ok(removed)
+    })?;
+    close_bot_sessions(&state, Some(&bot), id);
+
+    let removed_memberships = removed.len();
+    for (chat_id, role) in removed {
+        state.webhook_service.publish(
+            chat_id,
+            WebhookEvent::MemberLeft,
+            &MemberEventData {
+                uid: bot.uid,
+                role,
+                actor_uid: Some(uid),
+            },
+        );
+    }
+    tracing::info!(
+        service_token_id = id,
+        bot_uid = bot.uid,
+        removed_memberships,
+        deleted_by_uid = uid,
+        "service token bot deleted"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 pub fn router() -> OpenApiRouter<crate::AppState> {
     OpenApiRouter::new()
         .routes(routes!(post_service_token, get_service_tokens))
-        .routes(routes!(delete_service_token, post_rotate_service_token))
+        .routes(routes!(patch_service_token, delete_service_token))
+        .routes(routes!(post_rotate_service_token))
+        .routes(routes!(put_service_token_bot, delete_service_token_bot))
+}
+
+/// End the bot's WebSocket sessions opened with this token, so a revoked, rotated
+/// or re-scoped token stops receiving events right away.
+fn close_bot_sessions(state: &AppState, bot: Option<&Bot>, service_token_id: i64) {
+    if let Some(bot) = bot {
+        state
+            .ws_registry
+            .close_client_connections(bot.uid, &bot_service::ws_client_id(service_token_id));
+    }
 }
 
 fn require_manage_permission(
@@ -302,6 +591,56 @@ fn parse_policy_ids(raw_policy_ids: &[String]) -> Result<Vec<i64>, AppError> {
     Ok(policy_ids.into_iter().collect())
 }
 
+fn parse_chat_ids(raw_chat_ids: &[String]) -> Result<Vec<i64>, AppError> {
+    let mut chat_ids = BTreeSet::new();
+    for raw in raw_chat_ids {
+        let chat_id = raw
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| AppError::BadRequest("Chat id is invalid"))?;
+        chat_ids.insert(chat_id);
+    }
+    if chat_ids.is_empty() {
+        return Err(AppError::BadRequest("At least one chat id is required"));
+    }
+    if chat_ids.len() > MAX_ALLOWED_CHATS {
+        return Err(AppError::BadRequest("Too many chat ids"));
+    }
+    Ok(chat_ids.into_iter().collect())
+}
+
+fn ensure_chats_exist(conn: &mut PgConnection, chat_ids: &[i64]) -> Result<(), AppError> {
+    let existing_count = groups::table
+        .filter(groups::id.eq_any(chat_ids))
+        .count()
+        .get_result::<i64>(conn)?;
+
+    if existing_count != chat_ids.len() as i64 {
+        return Err(AppError::BadRequest("Chat id is invalid"));
+    }
+
+    Ok(())
+}
+
+fn validate_expires_at(
+    expires_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    if expires_at.is_some_and(|expires_at| expires_at <= now) {
+        return Err(AppError::BadRequest("Expiry must be in the future"));
+    }
+    Ok(())
+}
+
+fn validate_rate_limit(limit: Option<i32>) -> Result<(), AppError> {
+    if limit.is_some_and(|limit| !(1..=MAX_RATE_LIMIT_PER_MINUTE).contains(&limit)) {
+        return Err(AppError::BadRequest(
+            "Rate limit must be between 1 and 6000",
+        ));
+    }
+    Ok(())
+}
+
 fn ensure_policies_exist(conn: &mut PgConnection, policy_ids: &[i64]) -> Result<(), AppError> {
     if policy_ids.is_empty() {
         return Ok(());
@@ -407,7 +746,12 @@ fn policy_ids_to_strings(policy_ids: Vec<i64>) -> Vec<String> {
         .collect()
 }
 
-fn service_token_to_response(row: ServiceToken, policy_ids: Vec<String>) -> ServiceTokenResponse {
+fn service_token_to_response(
+    row: ServiceToken,
+    policy_ids: Vec<String>,
+    bot: Option<Bot>,
+    usage: Vec<ServiceTokenUsage>,
+) -> ServiceTokenResponse {
     ServiceTokenResponse {
         id: row.id,
         token: row.token,
@@ -418,12 +762,42 @@ fn service_token_to_response(row: ServiceToken, policy_ids: Vec<String>) -> Serv
         created_at: row.created_at,
         updated_at: row.updated_at,
         policy_ids,
+        bot: bot.map(bot_to_response),
+        expires_at: row.expires_at,
+        allowed_chat_ids: row
+            .allowed_chat_ids
+            .map(|chat_ids| chat_ids.iter().map(i64::to_string).collect()),
+        rate_limit_per_minute: row.rate_limit_per_minute,
+        recent_usage: usage
+            .into_iter()
+            .map(|usage| ServiceTokenUsageResponse {
+                method: usage.method,
+                endpoint: usage.endpoint,
+                status: usage.status,
+                created_at: usage.created_at,
+            })
+            .collect(),
+    }
+}
+
+fn bot_to_response(bot: Bot) -> BotResponse {
+    BotResponse {
+        uid: bot.uid,
+        name: bot.name,
+        avatar_url: bot.avatar_url,
+        created_by_uid: bot.created_by_uid,
+        created_at: bot.created_at,
+        updated_at: bot.updated_at,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{normalize_name, parse_policy_ids};
+    use chrono::{Duration, Utc};
+
+    use super::{
+        normalize_name, parse_chat_ids, parse_policy_ids, validate_expires_at, validate_rate_limit,
+    };
     use crate::errors::AppError;
 
     #[test]
@@ -440,4 +814,31 @@ mod tests {
             Err(AppError::BadRequest("Name is required"))
         ));
     }
+
+    #[test]
+    fn parse_chat_ids_deduplicates_and_rejects_empty() {
+        let ids = parse_chat_ids(&["7".to_string(), " 2 ".to_string(), "7".to_string()]).unwrap();
+        assert_eq!(ids, vec![2, 7]);
+        assert!(matches!(
+            parse_chat_ids(&[]),
+            Err(AppError::BadRequest("At least one chat id is required"))
+        ));
+        assert!(matches!(
+            parse_chat_ids(&["x".to_string()]),
+            Err(AppError::BadRequest("Chat id is invalid"))
+        ));
+    }
+
+    #[test]
+    fn expiry_and_rate_limit_bounds() {
+        let now = Utc::now();
+        assert!(validate_expires_at(None, now).is_ok());
+        assert!(validate_expires_at(Some(now + Duration::minutes(1)), now).is_ok());
+        assert!(validate_expires_at(Some(now), now).is_err());
+        assert!(validate_rate_limit(None).is_ok());
+        assert!(validate_rate_limit(Some(1)).is_ok());
+        assert!(validate_rate_limit(Some(6000)).is_ok());
+        assert!(validate_rate_limit(Some(0)).is_err());
+        assert!(validate_rate_limit(Some(6001)).is_err());
+    }
 }
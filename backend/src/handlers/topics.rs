@@ -0,0 +1,303 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::dto::groups::MuteResponse;
+use crate::dto::topics::{
+    CreateTopicRequest, ListTopicsResponse, MarkTopicReadRequest, MarkTopicReadResponse,
+    TopicResponse, UpdateTopicRequest,
+};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::handlers::groups::{resolve_mute_until, MuteBody};
+use crate::handlers::members::{check_membership, require_admin_role};
+use crate::models::{NewChatTopic, UpdateChatTopic};
+use crate::schema::chat_topics;
+use crate::services::topics::{
+    self as topic_service, normalize_topic_icon, normalize_topic_title, MAX_TOPICS_PER_CHAT,
+};
+use crate::utils::{auth::CurrentUid, ids};
+use crate::AppState;
+
+#[derive(serde::Deserialize)]
+pub struct ChatIdPath {
+    pub chat_id: i64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct TopicPath {
+    chat_id: i64,
+    topic_id: i64,
+}
+
+fn load_single_topic_response(
+    conn: &mut diesel::PgConnection,
+    chat_id: i64,
+    topic_id: i64,
+    uid: i32,
+) -> Result<TopicResponse, AppError> {
+    let topic = topic_service::load_chat_topic(conn, chat_id, topic_id)?;
+    topic_service::load_topic_responses(conn, chat_id, uid, vec![topic])?
+        .into_iter()
+        .next()
+        .ok_or(AppError::Internal("Failed to build topic response"))
+}
+
+/// GET /group/:chat_id/topics — List forum topics with the caller's unread counts.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "topics",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    responses(
+        (status = OK, body = ListTopicsResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn get_topics(
+    CurrentUid(uid): CurrentUid,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+) -> Result<Json<ListTopicsResponse>, AppError> {
+    let conn = &mut *conn;
+
+    check_membership(conn, chat_id, uid)?;
+
+    let topics = topic_service::load_chat_topics(conn, chat_id)?;
+    let topics = topic_service::load_topic_responses(conn, chat_id, uid, topics)?;
+
+    Ok(Json(ListTopicsResponse { topics }))
+}
+
+/// POST /group/:chat_id/topics — Create a topic (caller must be admin).
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "topics",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    request_body = CreateTopicRequest,
+    responses(
+        (status = CREATED, body = TopicResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn post_topic(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+    Json(body): Json<CreateTopicRequest>,
+) -> Result<(StatusCode, Json<TopicResponse>), AppError> {
+    let conn = &mut *conn;
+
+    require_admin_role(conn, chat_id, uid)?;
+
+    let title = normalize_topic_title(&body.title)?;
+    let icon_emoji = normalize_topic_icon(body.icon_emoji.as_deref())?;
+
+    let existing: i64 = chat_topics::table
+        .filter(chat_topics::chat_id.eq(chat_id))
+        .count()
+        .get_result(conn)?;
+    if existing >= MAX_TOPICS_PER_CHAT {
+        return Err(AppError::BadRequest("Too many topics"));
+    }
+    let sort_order = match body.sort_order {
+        Some(sort_order) => sort_order,
+        None => chat_topics::table
+            .filter(chat_topics::chat_id.eq(chat_id))
+            .select(diesel::dsl::max(chat_topics::sort_order))
+            .first::<Option<i32>>(conn)?
+            .map_or(0, |max| max + 1),
+    };
+
+    let topic_id = ids::next_id(state.id_gen.as_ref()).await.map_err(|e| {
+        tracing::error!("next_id for chat topic: {:?}", e);
+        AppError::Internal("ID generation failed")
+    })?;
+    let now = Utc::now();
+    diesel::insert_into(chat_topics::table)
+        .values(&NewChatTopic {
+            id: topic_id,
+            chat_id,
+            title,
+            icon_emoji,
+            closed: false,
+            sort_order,
+            created_by_uid: uid,
+            created_at: now,
+            updated_at: now,
+        })
+        .execute(conn)?;
+
+    let topic = load_single_topic_response(conn, chat_id, topic_id, uid)?;
+    Ok((StatusCode::CREATED, Json(topic)))
+}
+
+/// PATCH /group/:chat_id/topics/:topic_id — Rename, reorder, or open/close a topic (caller must be admin).
+#[utoipa::path(
+    patch,
+    path = "/{topic_id}",
+    tag = "topics",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("topic_id" = i64, Path, description = "Topic ID"),
+    ),
+    request_body = UpdateTopicRequest,
+    responses(
+        (status = OK, body = TopicResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn patch_topic(
+    CurrentUid(uid): CurrentUid,
+    Path(TopicPath { chat_id, topic_id }): Path<TopicPath>,
+    mut conn: DbConn,
+    Json(body): Json<UpdateTopicRequest>,
+) -> Result<Json<TopicResponse>, AppError> {
+    let conn = &mut *conn;
+
+    require_admin_role(conn, chat_id, uid)?;
+    topic_service::load_chat_topic(conn, chat_id, topic_id)?;
+
+    let changes = UpdateChatTopic {
+        title: body
+            .title
+            .as_deref()
+            .map(normalize_topic_title)
+            .transpose()?,
+        icon_emoji: body
+            .icon_emoji
+            .as_deref()
+            .map(|icon| normalize_topic_icon(Some(icon)))
+            .transpose()?,
+        closed: body.closed,
+        sort_order: body.sort_order,
+        updated_at: Utc::now(),
+    };
+    diesel::update(chat_topics::table.filter(chat_topics::id.eq(topic_id)))
+        .set(&changes)
+        .execute(conn)?;
+
+    Ok(Json(load_single_topic_response(
+        conn, chat_id, topic_id, uid,
+    )?))
+}
+
+/// POST /group/:chat_id/topics/:topic_id/read — Advance the caller's read position in a topic.
+#[utoipa::path(
+    post,
+    path = "/{topic_id}/read",
+    tag = "topics",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("topic_id" = i64, Path, description = "Topic ID"),
+    ),
+    request_body = MarkTopicReadRequest,
+    responses(
+        (status = OK, body = MarkTopicReadResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn post_topic_read(
+    CurrentUid(uid): CurrentUid,
+    Path(TopicPath { chat_id, topic_id }): Path<TopicPath>,
+    mut conn: DbConn,
+    Json(body): Json<MarkTopicReadRequest>,
+) -> Result<Json<MarkTopicReadResponse>, AppError> {
+    let conn = &mut *conn;
+
+    check_membership(conn, chat_id, uid)?;
+    topic_service::load_chat_topic(conn, chat_id, topic_id)?;
+
+    let last_read_message_id =
+        topic_service::mark_topic_read(conn, chat_id, topic_id, uid, body.message_id)?;
+    let unread_count = topic_service::count_topic_unreads(conn, chat_id, uid, &[topic_id])?
+        .get(&topic_id)
+        .copied()
+        .unwrap_or(0);
+
+    Ok(Json(MarkTopicReadResponse {
+        last_read_message_id,
+        unread_count,
+    }))
+}
+
+/// PUT /group/:chat_id/topics/:topic_id/mute — Mute push notifications for a topic.
+#[utoipa::path(
+    put,
+    path = "/{topic_id}/mute",
+    tag = "topics",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("topic_id" = i64, Path, description = "Topic ID"),
+    ),
+    request_body = MuteBody,
+    responses(
+        (status = OK, body = MuteResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn put_topic_mute(
+    CurrentUid(uid): CurrentUid,
+    Path(TopicPath { chat_id, topic_id }): Path<TopicPath>,
+    mut conn: DbConn,
+    Json(body): Json<MuteBody>,
+) -> Result<Json<MuteResponse>, AppError> {
+    let conn = &mut *conn;
+
+    check_membership(conn, chat_id, uid)?;
+    topic_service::load_chat_topic(conn, chat_id, topic_id)?;
+
+    let muted_until = resolve_mute_until(body.duration_seconds)?;
+    topic_service::set_topic_muted_until(conn, chat_id, topic_id, uid, Some(muted_until))?;
+
+    Ok(Json(MuteResponse { muted_until }))
+}
+
+/// DELETE /group/:chat_id/topics/:topic_id/mute — Unmute push notifications for a topic.
+#[utoipa::path(
+    delete,
+    path = "/{topic_id}/mute",
+    tag = "topics",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("topic_id" = i64, Path, description = "Topic ID"),
+    ),
+    responses(
+        (status = NO_CONTENT),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn delete_topic_mute(
+    CurrentUid(uid): CurrentUid,
+    Path(TopicPath { chat_id, topic_id }): Path<TopicPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+
+    check_membership(conn, chat_id, uid)?;
+    topic_service::load_chat_topic(conn, chat_id, topic_id)?;
+
+    topic_service::set_topic_muted_until(conn, chat_id, topic_id, uid, None::<DateTime<Utc>>)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_topics, post_topic))
+        .routes(routes!(patch_topic))
+        .routes(routes!(post_topic_read))
+        .routes(routes!(put_topic_mute, delete_topic_mute))
+}
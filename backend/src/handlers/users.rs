@@ -1,6 +1,6 @@
 use axum::{
     extract::{Query, State},
-    http::HeaderMap,
+    http::{HeaderMap, StatusCode},
     Json,
 };
 use serde::Deserialize;
@@ -9,19 +9,21 @@ use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 
 use crate::dto::users::{
-    AuthTokenResponse, MeResponse, MemberSummary, SearchUsersResponse, StickerPackOrderItem,
+    AuthTokenResponse, MeResponse, MemberSummary, RefreshAuthTokenRequest, SearchUsersResponse,
+    StickerPackOrderItem,
 };
 use crate::dto::ws::{ServerWsMessage, StickerPackOrderUpdatePayload};
 use crate::errors::AppError;
 use crate::extractors::DbConn;
 use crate::models::{NewUserExtra, UserExtra};
 use crate::schema::{group_membership, sticker_packs, user_extra, user_sticker_pack_subscriptions};
+use crate::services::auth_sessions::IssuedTokens;
 use crate::services::authz::{Action as AuthzAction, Resource as AuthzResource};
 use crate::services::user::{
     lookup_user_avatars, lookup_user_profiles, search_user_uids_by_prefix,
 };
 use crate::utils::auth::{
-    encode_auth_token, extract_auth_context, required_client_id, AuthClaims, AuthSource, CurrentUid,
+    extract_auth_context, required_client_id, AuthContext, AuthSource, CurrentUid,
 };
 use crate::AppState;
 use diesel::prelude::*;
@@ -395,6 +397,9 @@ async fn get_user_search(
     Ok(Json(SearchUsersResponse { members, excluded }))
 }
 
+/// GET /users/auth-token — Issue an access token and a refresh token for the calling client.
+/// Requires the primary (Discuz) credential; access tokens are renewed only
+/// through `/auth-token/refresh`.
 #[utoipa::path(
     get,
     path = "/auth-token",
@@ -406,24 +411,128 @@ async fn get_user_search(
 async fn get_auth_token(
     State(state): State<AppState>,
     headers: HeaderMap,
+    mut conn: DbConn,
 ) -> Result<Json<AuthTokenResponse>, AppError> {
+    let conn = &mut *conn;
     let auth = extract_auth_context(&headers, &state)?;
-    let client_id = match auth.client_id {
-        Some(client_id) => client_id,
-        None if auth.source == AuthSource::Legacy => required_client_id(&headers)?,
-        None => return Err(AppError::BadRequest("Missing X-Client-Id header")),
-    };
+    require_primary_credential(&auth)?;
+    let client_id = session_client_id(&auth, &headers)?;
 
-    let token = encode_auth_token(
-        &AuthClaims {
-            uid: auth.uid,
-            cid: client_id,
-            gen: 0,
-        },
-        &state.jwt_signing_key,
-    )?;
+    let tokens = state
+        .auth_sessions
+        .issue(conn, &state.jwt_signing_key, auth.uid, &client_id)?;
+
+    Ok(Json(auth_token_response(tokens)))
+}
+
+/// POST /users/auth-token/refresh — Exchange a refresh token for a new token pair.
+#[utoipa::path(
+    post,
+    path = "/auth-token/refresh",
+    tag = "users",
+    request_body = RefreshAuthTokenRequest,
+    responses(
+        (status = 200, description = "Auth token", body = AuthTokenResponse)
+    )
+)]
+async fn post_refresh_auth_token(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Json(body): Json<RefreshAuthTokenRequest>,
+) -> Result<Json<AuthTokenResponse>, AppError> {
+    let conn = &mut *conn;
+
+    let tokens =
+        state
+            .auth_sessions
+            .refresh(conn, &state.jwt_signing_key, body.refresh_token.trim())?;
+
+    Ok(Json(auth_token_response(tokens)))
+}
+
+/// POST /users/logout — Revoke the tokens issued to the calling client.
+#[utoipa::path(
+    post,
+    path = "/logout",
+    tag = "users",
+    responses(
+        (status = 204, description = "Client logged out")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn post_logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+    let auth = extract_auth_context(&headers, &state)?;
+    let client_id = session_client_id(&auth, &headers)?;
+
+    state.auth_sessions.logout(conn, auth.uid, &client_id)?;
+    state
+        .ws_registry
+        .close_client_connections(auth.uid, &client_id);
+    tracing::info!(uid = auth.uid, client_id, "client logged out");
+
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    Ok(Json(AuthTokenResponse { token }))
+/// POST /users/logout-all — Revoke the tokens issued to every client of the caller.
+#[utoipa::path(
+    post,
+    path = "/logout-all",
+    tag = "users",
+    responses(
+        (status = 204, description = "All clients logged out")
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = []))
+)]
+async fn post_logout_all(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+
+    let client_ids = state.auth_sessions.logout_all(conn, uid)?;
+    for client_id in state.ws_registry.connected_client_ids(uid) {
+        state.ws_registry.close_client_connections(uid, &client_id);
+    }
+    tracing::info!(uid, sessions = client_ids.len(), "all clients logged out");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// An access token must not mint a fresh token pair, or a leaked access token
+/// could be kept alive past its expiry and past session revocation.
+fn require_primary_credential(auth: &AuthContext) -> Result<(), AppError> {
+    match auth.source {
+        AuthSource::Legacy | AuthSource::PrimaryJwt => Ok(()),
+        AuthSource::Jwt => Err(AppError::Unauthorized(
+            "Access tokens cannot issue new tokens; use the refresh endpoint",
+        )),
+    }
+}
+
+pub(crate) fn session_client_id(
+    auth: &AuthContext,
+    headers: &HeaderMap,
+) -> Result<String, AppError> {
+    match &auth.client_id {
+        Some(client_id) => Ok(client_id.clone()),
+        None if auth.source == AuthSource::Legacy => Ok(required_client_id(headers)?),
+        None => Err(AppError::BadRequest("Missing X-Client-Id header")),
+    }
+}
+
+pub(crate) fn auth_token_response(tokens: IssuedTokens) -> AuthTokenResponse {
+    AuthTokenResponse {
+        token: tokens.access_token,
+        expires_at: tokens.access_expires_at,
+        refresh_token: tokens.refresh_token,
+        refresh_expires_at: tokens.refresh_expires_at,
+    }
 }
 
 pub fn router() -> OpenApiRouter<crate::AppState> {
@@ -431,6 +540,10 @@ pub fn router() -> OpenApiRouter<crate::AppState> {
         .routes(routes!(get_me))
         .routes(routes!(get_user_search))
         .routes(routes!(get_auth_token))
+        .routes(routes!(post_refresh_auth_token))
+        .routes(routes!(post_logout))
+        .routes(routes!(post_logout_all))
+        .nest("/devices", crate::handlers::devices::router())
         .routes(routes!(put_stickerpack_order))
 }
 
@@ -463,7 +576,15 @@ fn load_accessible_sticker_pack_ids(
 
 #[cfg(test)]
 mod tests {
-    use super::{normalize_user_search_limit, split_excluded_member_summaries, MemberSummary};
+    use super::{
+        normalize_user_search_limit, require_primary_credential, split_excluded_member_summaries,
+        MemberSummary,
+    };
+    use crate::errors::AppError;
+    use crate::utils::auth::{
+        decode_auth_token, encode_auth_token, AuthClaims, AuthContext, AuthSource,
+        ACCESS_TOKEN_TYPE,
+    };
     use std::collections::HashSet;
 
     fn make_summary(uid: i32) -> MemberSummary {
@@ -507,4 +628,49 @@ mod tests {
             vec![2, 3]
         );
     }
+
+    #[test]
+    fn auth_token_requires_the_primary_credential() {
+        let auth = |source| AuthContext {
+            uid: 1,
+            client_id: Some("client".to_string()),
+            source,
+        };
+        assert!(require_primary_credential(&auth(AuthSource::Legacy)).is_ok());
+        assert!(matches!(
+            require_primary_credential(&auth(AuthSource::Jwt)),
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn landing_page_jwt_issues_tokens_and_access_tokens_do_not() {
+        let key = b"01234567890123456789012345678901";
+        let landing = AuthClaims {
+            uid: 1,
+            cid: "client".to_string(),
+            gen: 0,
+            iat: 0,
+            exp: None,
+            typ: None,
+        };
+        let access = AuthClaims {
+            exp: Some(chrono::Utc::now().timestamp() + 900),
+            typ: Some(ACCESS_TOKEN_TYPE.to_string()),
+            ..landing.clone()
+        };
+
+        let auth = |claims: &AuthClaims| {
+            let token = encode_auth_token(claims, key).unwrap();
+            AuthContext::from(decode_auth_token(&token, key).unwrap())
+        };
+
+        let landing_auth = auth(&landing);
+        assert_eq!(landing_auth.source, AuthSource::PrimaryJwt);
+        assert!(require_primary_credential(&landing_auth).is_ok());
+        assert!(matches!(
+            require_primary_credential(&auth(&access)),
+            Err(AppError::Unauthorized(_))
+        ));
+    }
 }
@@ -0,0 +1,263 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+};
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::dto::webhooks::{
+    CreateWebhookRequest, CreateWebhookResponse, ListWebhookDeliveriesQuery,
+    ListWebhookDeliveriesResponse, ListWebhooksResponse, UpdateWebhookRequest,
+    WebhookDeliveryResponse, WebhookResponse,
+};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::handlers::members::require_admin_role;
+use crate::services::webhooks::{self as webhook_service, WebhookOwner};
+use crate::utils::{auth::CurrentUid, ids, pagination::validate_limit};
+use crate::AppState;
+
+pub(crate) const MAX_DELIVERIES_LIMIT: i64 = 100;
+
+#[derive(serde::Deserialize)]
+pub struct ChatIdPath {
+    pub chat_id: i64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct WebhookPath {
+    chat_id: i64,
+    webhook_id: i64,
+}
+
+/// GET /group/:chat_id/webhooks — List the chat's outgoing webhooks (admins only).
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    responses(
+        (status = OK, body = ListWebhooksResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn get_webhooks(
+    CurrentUid(uid): CurrentUid,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+) -> Result<Json<ListWebhooksResponse>, AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    let webhooks = webhook_service::list_webhooks(conn, WebhookOwner::Chat(chat_id))?
+        .into_iter()
+        .map(webhook_service::webhook_to_response)
+        .collect();
+    Ok(Json(ListWebhooksResponse { webhooks }))
+}
+
+/// POST /group/:chat_id/webhooks — Register an outgoing webhook for the chat (admins only).
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+    ),
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = CREATED, description = "Webhook created; the secret is only shown once", body = CreateWebhookResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn post_webhook(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(ChatIdPath { chat_id }): Path<ChatIdPath>,
+    mut conn: DbConn,
+    Json(body): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<CreateWebhookResponse>), AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    let url = state.webhook_service.validate_url(&body.url)?;
+    let events = webhook_service::parse_event_types(&body.events)?;
+    let id = ids::next_id(state.id_gen.as_ref()).await.map_err(|e| {
+        tracing::error!("next_id for webhook: {:?}", e);
+        AppError::Internal("ID generation failed")
+    })?;
+    let webhook = webhook_service::create_webhook(
+        conn,
+        WebhookOwner::Chat(chat_id),
+        id,
+        url,
+        events,
+        Some(uid),
+    )?;
+
+    let secret = webhook.secret.clone();
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateWebhookResponse {
+            webhook: webhook_service::webhook_to_response(webhook),
+            secret,
+        }),
+    ))
+}
+
+/// PATCH /group/:chat_id/webhooks/:webhook_id — Change a webhook's URL, events or enabled state.
+#[utoipa::path(
+    patch,
+    path = "/{webhook_id}",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("webhook_id" = i64, Path, description = "Webhook ID"),
+    ),
+    request_body = UpdateWebhookRequest,
+    responses(
+        (status = OK, body = WebhookResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn patch_webhook(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(WebhookPath {
+        chat_id,
+        webhook_id,
+    }): Path<WebhookPath>,
+    mut conn: DbConn,
+    Json(body): Json<UpdateWebhookRequest>,
+) -> Result<Json<WebhookResponse>, AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    let url = body
+        .url
+        .as_deref()
+        .map(|url| state.webhook_service.validate_url(url))
+        .transpose()?;
+    let events = body
+        .events
+        .as_deref()
+        .map(webhook_service::parse_event_types)
+        .transpose()?;
+    let webhook = webhook_service::load_webhook(conn, WebhookOwner::Chat(chat_id), webhook_id)?;
+    let webhook = webhook_service::update_webhook(conn, webhook, url, events, body.enabled)?;
+
+    Ok(Json(webhook_service::webhook_to_response(webhook)))
+}
+
+/// DELETE /group/:chat_id/webhooks/:webhook_id — Remove a webhook and its delivery log.
+#[utoipa::path(
+    delete,
+    path = "/{webhook_id}",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("webhook_id" = i64, Path, description = "Webhook ID"),
+    ),
+    responses(
+        (status = NO_CONTENT, description = "Webhook deleted"),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn delete_webhook(
+    CurrentUid(uid): CurrentUid,
+    Path(WebhookPath {
+        chat_id,
+        webhook_id,
+    }): Path<WebhookPath>,
+    mut conn: DbConn,
+) -> Result<StatusCode, AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    webhook_service::delete_webhook(conn, WebhookOwner::Chat(chat_id), webhook_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /group/:chat_id/webhooks/:webhook_id/ping — Queue a test delivery.
+#[utoipa::path(
+    post,
+    path = "/{webhook_id}/ping",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("webhook_id" = i64, Path, description = "Webhook ID"),
+    ),
+    responses(
+        (status = ACCEPTED, description = "Ping queued", body = WebhookDeliveryResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn post_webhook_ping(
+    CurrentUid(uid): CurrentUid,
+    State(state): State<AppState>,
+    Path(WebhookPath {
+        chat_id,
+        webhook_id,
+    }): Path<WebhookPath>,
+    mut conn: DbConn,
+) -> Result<(StatusCode, Json<WebhookDeliveryResponse>), AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    let webhook = webhook_service::load_webhook(conn, WebhookOwner::Chat(chat_id), webhook_id)?;
+    let delivery = state.webhook_service.enqueue_ping(conn, &webhook)?;
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(webhook_service::delivery_to_response(delivery)),
+    ))
+}
+
+/// GET /group/:chat_id/webhooks/:webhook_id/deliveries — Recent delivery attempts, newest first.
+#[utoipa::path(
+    get,
+    path = "/{webhook_id}/deliveries",
+    tag = "groups",
+    params(
+        ("chat_id" = i64, Path, description = "Chat ID"),
+        ("webhook_id" = i64, Path, description = "Webhook ID"),
+        ListWebhookDeliveriesQuery,
+    ),
+    responses(
+        (status = OK, body = ListWebhookDeliveriesResponse),
+    ),
+    security(("uid_header" = []), ("bearer_jwt" = [])),
+)]
+async fn get_webhook_deliveries(
+    CurrentUid(uid): CurrentUid,
+    Path(WebhookPath {
+        chat_id,
+        webhook_id,
+    }): Path<WebhookPath>,
+    Query(q): Query<ListWebhookDeliveriesQuery>,
+    mut conn: DbConn,
+) -> Result<Json<ListWebhookDeliveriesResponse>, AppError> {
+    let conn = &mut *conn;
+    require_admin_role(conn, chat_id, uid)?;
+
+    let webhook = webhook_service::load_webhook(conn, WebhookOwner::Chat(chat_id), webhook_id)?;
+    let limit = validate_limit(q.limit, MAX_DELIVERIES_LIMIT);
+    let (deliveries, next_cursor) =
+        webhook_service::list_deliveries(conn, webhook.id, q.before, limit)?;
+    Ok(Json(ListWebhookDeliveriesResponse {
+        deliveries: deliveries
+            .into_iter()
+            .map(webhook_service::delivery_to_response)
+            .collect(),
+        next_cursor,
+    }))
+}
+
+pub fn router() -> OpenApiRouter<crate::AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_webhooks, post_webhook))
+        .routes(routes!(patch_webhook, delete_webhook))
+        .routes(routes!(post_webhook_ping))
+        .routes(routes!(get_webhook_deliveries))
+}
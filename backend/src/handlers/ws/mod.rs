@@ -5,6 +5,7 @@ use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
 use axum::extract::State;
 use axum::response::Response;
 use axum::Json;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use std::sync::Arc;
 use std::time::Instant;
@@ -13,8 +14,10 @@ use tracing::{debug, trace};
 use utoipa_axum::router::OpenApiRouter;
 
 use crate::dto::ws::{ServerWsMessage, TicketResponse};
-use crate::services::ws_registry;
-use crate::utils::auth::{decode_auth_token, encode_auth_token, AuthClaims, ClientId, CurrentUid};
+use crate::errors::AppError;
+use crate::extractors::DbConn;
+use crate::services::{bots, service_tokens, ws_registry};
+use crate::utils::auth::{decode_auth_token, ClientId, CurrentUid};
 use crate::AppState;
 use ws_registry::AppPresenceState;
 
@@ -31,13 +34,13 @@ async fn get_ws_ticket(
     CurrentUid(uid): CurrentUid,
     ClientId(client_id): ClientId,
     State(state): State<AppState>,
-) -> Result<Json<TicketResponse>, (axum::http::StatusCode, &'static str)> {
-    let claims = AuthClaims {
-        uid,
-        cid: client_id,
-        gen: 0,
-    };
-    let ticket = encode_auth_token(&claims, &state.jwt_signing_key)?;
+    mut conn: DbConn,
+) -> Result<Json<TicketResponse>, AppError> {
+    let conn = &mut *conn;
+    let ticket =
+        state
+            .auth_sessions
+            .issue_ws_ticket(conn, &state.jwt_signing_key, uid, &client_id)?;
 
     Ok(Json(TicketResponse { ticket }))
 }
@@ -47,7 +50,11 @@ async fn get_ws_ticket(
 struct WsAuthMessage {
     #[serde(rename = "type")]
     type_: String,
-    ticket: String,
+    #[serde(default)]
+    ticket: Option<String>,
+    /// Bots authenticate with their service token instead of a ticket.
+    #[serde(default)]
+    service_token: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -94,16 +101,31 @@ async fn handle_auth_and_socket(mut socket: WebSocket, state: AppState) {
     // Wait for auth message, timeout after 5 seconds
     let auth_result = timeout(std::time::Duration::from_secs(5), socket.recv()).await;
 
-    let uid = match auth_result {
+    let (uid, client_id, expires_at) = match auth_result {
         Ok(Some(Ok(Message::Text(text)))) => {
             if let Ok(parsed) = serde_json::from_str::<WsAuthMessage>(&text) {
                 if parsed.type_ == "auth" {
-                    match decode_auth_token(&parsed.ticket, &state.jwt_signing_key) {
-                        Ok(claims) => claims.uid,
-                        Err(e) => {
-                            debug!("ws auth rejected (invalid ticket): {:?}", e);
-                            return;
-                        } // Invalid ticket
+                    if let Some(credential) = parsed.service_token.as_deref() {
+                        match authenticate_bot_session(&state, credential) {
+                            Ok(session) => session,
+                            Err(e) => {
+                                debug!("ws auth rejected (invalid service token): {:?}", e);
+                                return;
+                            }
+                        }
+                    } else {
+                        match decode_auth_token(
+                            parsed.ticket.as_deref().unwrap_or_default(),
+                            &state.jwt_signing_key,
+                        )
+                        .and_then(|claims| state.auth_sessions.verify(&claims).map(|()| claims))
+                        {
+                            Ok(claims) => (claims.uid, claims.cid, None),
+                            Err(e) => {
+                                debug!("ws auth rejected (invalid ticket): {:?}", e);
+                                return;
+                            } // Invalid ticket
+                        }
                     }
                 } else {
                     return; // First message not auth
@@ -116,24 +138,75 @@ async fn handle_auth_and_socket(mut socket: WebSocket, state: AppState) {
     };
 
     let registry = state.ws_registry.clone();
-    let (entry, rx) = registry.register(uid);
-    let conn_id = entry.conn_id;
+    let (entry, rx) = registry.register(uid, Some(client_id));
+
+    handle_socket(socket, state, uid, registry, entry, rx, expires_at).await;
+}
 
-    handle_socket(socket, state, uid, conn_id, registry, entry, rx).await;
+/// Resolve a service token to its bot's uid, so the bot receives command invocations and
+/// its chats' events like any member. Each token gets its own client id, and the session
+/// ends when the token expires.
+///
+/// Chat-scoped tokens are refused: the socket carries events for every chat the bot is
+/// in, which would leak chats outside the token's allowlist. They use webhooks instead.
+fn authenticate_bot_session(
+    state: &AppState,
+    credential: &str,
+) -> Result<(i32, String, Option<DateTime<Utc>>), AppError> {
+    let conn = &mut state.db.get()?;
+    let service_token =
+        service_tokens::authenticate(conn, &state.service_token_hash_key, credential)?;
+    if service_token.allowed_chat_ids.is_some() {
+        return Err(AppError::Forbidden(
+            "Chat-scoped service tokens cannot open a WebSocket session",
+        ));
+    }
+    service_tokens::consume_rate_limit(conn, &service_token)?;
+    let bot = bots::find_bot_for_service_token(conn, service_token.id)?
+        .ok_or(AppError::Forbidden("Service token has no bot"))?;
+    Ok((
+        bot.uid,
+        bots::ws_client_id(service_token.id),
+        service_token.expires_at,
+    ))
+}
+
+/// Resolves once `expires_at` passes; never for sessions without an expiry.
+async fn session_expiry(expires_at: Option<DateTime<Utc>>) {
+    match expires_at {
+        Some(expires_at) => {
+            let remaining = (expires_at - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(remaining).await;
+        }
+        None => std::future::pending().await,
+    }
 }
 
 async fn handle_socket(
     mut socket: WebSocket,
     state: AppState,
     uid: i32,
-    conn_id: u64,
     registry: Arc<ws_registry::ConnectionRegistry>,
     entry: Arc<ws_registry::ConnectionEntry>,
     mut rx: tokio::sync::mpsc::Receiver<Arc<ServerWsMessage>>,
+    expires_at: Option<DateTime<Utc>>,
 ) {
     let started_at = Instant::now();
+    let conn_id = entry.conn_id;
+    let expiry = session_expiry(expires_at);
+    tokio::pin!(expiry);
     loop {
         tokio::select! {
+            _ = &mut expiry => {
+                debug!("ws session expired uid={} conn_id={}", uid, conn_id);
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
+            _ = entry.close.notified() => {
+                debug!("ws closed by server uid={} conn_id={}", uid, conn_id);
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
             msg = rx.recv() => {
                 match msg {
                     Some(ws_msg) => {
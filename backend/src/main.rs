@@ -86,11 +86,15 @@ pub(crate) struct AppState {
     id_gen: Arc<utils::ids::IdGen>,
     metrics: Arc<metrics::Metrics>,
     authz_service: Arc<services::authz::AuthorizationService>,
+    auth_sessions: Arc<services::auth_sessions::AuthSessionService>,
     ws_registry: Arc<services::ws_registry::ConnectionRegistry>,
     push_service: Arc<services::push::PushService>,
     unread_service: Arc<services::unread::UnreadService>,
     client_tracking: Arc<services::client_tracking::ClientTrackingService>,
     background_service: Arc<services::background::BackgroundService>,
+    webhook_service: Arc<services::webhooks::WebhookService>,
+    rate_limiter: Arc<services::rate_limit::RateLimiter>,
+    oidc: Option<Arc<services::oidc::OidcClient>>,
     message_search: Option<Arc<services::message_search::MessageSearchService>>,
     s3_client: aws_sdk_s3::Client,
     s3_bucket_name: String,
@@ -129,6 +133,11 @@ async fn main() {
         let mut conn = pool.get().expect("Failed to get connection for migrations");
         conn.run_pending_migrations(MIGRATIONS)
             .expect("Failed to run database migrations");
+        match services::chat_export::reap_stale_exports(&mut conn) {
+            Ok(0) => {}
+            Ok(reaped) => info!(reaped, "failed stale chat exports"),
+            Err(err) => tracing::warn!(?err, "failed to reap stale chat exports"),
+        }
     }
 
     let metrics = Arc::new(metrics::Metrics::new());
@@ -144,7 +153,8 @@ async fn main() {
         .await
         .expect("Failed to initialize message search service");
 
-    let authz_service = services::authz::AuthorizationService::start();
+    let authz_service =
+        services::authz::AuthorizationService::start(database_url.clone(), metrics.clone());
     let ws_registry = Arc::new(services::ws_registry::ConnectionRegistry::new(
         metrics.clone(),
     ));
@@ -201,11 +211,19 @@ async fn main() {
         }
     };
 
+    let oidc = services::oidc::OidcConfig::from_env().map(|config| {
+        info!(issuer = %config.issuer, "OIDC login enabled");
+        Arc::new(services::oidc::OidcClient::new(config))
+    });
+
     let state = AppState {
         db: pool.clone(),
         id_gen: Arc::new(utils::ids::new_generator()),
         metrics: metrics.clone(),
         authz_service,
+        auth_sessions: Arc::new(services::auth_sessions::AuthSessionService::new(
+            pool.clone(),
+        )),
         ws_registry: ws_registry.clone(),
         push_service: services::push::PushService::start(
             pool.clone(),
@@ -224,7 +242,15 @@ async fn main() {
             metrics.clone(),
             message_search.clone(),
             unread_service.clone(),
+            services::chat_export::ExportStorage {
+                s3_client: s3_client.clone(),
+                bucket: s3_bucket_name.clone(),
+                public_base_url: s3_base_url.clone(),
+            },
         ),
+        webhook_service: services::webhooks::WebhookService::start(pool.clone(), metrics.clone()),
+        rate_limiter: services::rate_limit::RateLimiter::start(pool.clone(), metrics.clone()),
+        oidc,
         message_search,
         s3_client,
         s3_bucket_name,
@@ -274,6 +300,8 @@ async fn main() {
 
     let metrics_registry = state.metrics.clone();
     let client_tracking_state = state.clone();
+    let service_token_usage_state = state.clone();
+    let rate_limit_state = state.clone();
 
     let (api_router, api_openapi) = handlers::api_router().split_for_parts();
     let mut openapi_doc = openapi::ApiDoc::openapi();
@@ -290,6 +318,14 @@ async fn main() {
                 .propagate_x_request_id()
                 .layer(trace_layer),
         )
+        .layer(middleware::from_fn_with_state(
+            rate_limit_state,
+            services::rate_limit::enforce_rate_limits,
+        ))
+        .layer(middleware::from_fn_with_state(
+            service_token_usage_state,
+            services::service_tokens::track_service_token_usage,
+        ))
         .layer(middleware::from_fn_with_state(
             client_tracking_state,
             services::client_tracking::track_client_activity,
@@ -344,7 +380,10 @@ async fn main() {
     info!("Starting metrics server listening on {:?}", metrics_addr);
     let metrics_listener = tokio::net::TcpListener::bind(metrics_addr).await.unwrap();
 
-    let api_server = axum::serve(app_listener, app);
+    let api_server = axum::serve(
+        app_listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    );
     let metrics_server = axum::serve(metrics_listener, metrics_app);
 
     tokio::select! {
@@ -62,6 +62,7 @@ pub(crate) struct Metrics {
     client_activity_writes_skipped_total: IntCounterVec,
     client_rebinds_total: IntCounter,
     client_tracking_purge_total: IntCounterVec,
+    webhook_deliveries_total: IntCounterVec,
     activity_daily_rollup_updates_total: IntCounterVec,
     activity_today_active_users: IntGauge,
     activity_today_new_users: IntGauge,
@@ -89,6 +90,12 @@ pub(crate) struct Metrics {
     audio_transcode_source_total: IntCounterVec,
     audio_transcode_jobs_total: IntCounterVec,
     audio_transcode_job_duration_seconds: HistogramVec,
+    authz_cache_lookups_total: IntCounterVec,
+    authz_cache_invalidations_total: IntCounterVec,
+    rate_limit_throttled_total: IntCounterVec,
+    rate_limit_store_errors_total: IntCounterVec,
+    push_queue_jobs: IntGaugeVec,
+    push_queue_oldest_pending_age_seconds: IntGauge,
 }
 
 impl Metrics {
@@ -254,6 +261,14 @@ impl Metrics {
             "Total number of times a client was rebound to a different user"
         ))
         .expect("client_rebinds_total metric should be valid");
+        let webhook_deliveries_total = IntCounterVec::new(
+            opts!(
+                "webhook_deliveries_total",
+                "Total number of outgoing webhook delivery attempts"
+            ),
+            &["result"],
+        )
+        .expect("webhook_deliveries_total metric should be valid");
         let client_tracking_purge_total = IntCounterVec::new(
             opts!(
                 "client_tracking_purge_total",
@@ -499,6 +514,56 @@ impl Metrics {
         audio_transcode_jobs_total.with_label_values(&["failure"]);
         audio_transcode_job_duration_seconds.with_label_values(&["success"]);
         audio_transcode_job_duration_seconds.with_label_values(&["failure"]);
+        let authz_cache_lookups_total = IntCounterVec::new(
+            opts!(
+                "authz_cache_lookups_total",
+                "Total number of authorization cache lookups"
+            ),
+            &["cache", "result"],
+        )
+        .expect("authz_cache_lookups_total metric should be valid");
+        let authz_cache_invalidations_total = IntCounterVec::new(
+            opts!(
+                "authz_cache_invalidations_total",
+                "Total number of authorization cache invalidations applied"
+            ),
+            &["scope", "source"],
+        )
+        .expect("authz_cache_invalidations_total metric should be valid");
+        let rate_limit_throttled_total = IntCounterVec::new(
+            opts!(
+                "rate_limit_throttled_total",
+                "Total number of API requests rejected by a rate limit"
+            ),
+            &["group", "key"],
+        )
+        .expect("rate_limit_throttled_total metric should be valid");
+        let rate_limit_store_errors_total = IntCounterVec::new(
+            opts!(
+                "rate_limit_store_errors_total",
+                "Total number of rate limit checks let through because the store failed"
+            ),
+            &["store"],
+        )
+        .expect("rate_limit_store_errors_total metric should be valid");
+        let push_queue_jobs = IntGaugeVec::new(
+            opts!(
+                "push_queue_jobs",
+                "Number of push notification jobs in the durable queue"
+            ),
+            &["state"],
+        )
+        .expect("push_queue_jobs metric should be valid");
+        let push_queue_oldest_pending_age_seconds = IntGauge::with_opts(opts!(
+            "push_queue_oldest_pending_age_seconds",
+            "Age of the oldest pending push notification job"
+        ))
+        .expect("push_queue_oldest_pending_age_seconds metric should be valid");
+        for cache in ["permissions", "chat_roles"] {
+            for result in ["hit", "miss"] {
+                authz_cache_lookups_total.with_label_values(&[cache, result]);
+            }
+        }
 
         registry
             .register(Box::new(http_requests_total.clone()))
@@ -572,6 +637,9 @@ impl Metrics {
         registry
             .register(Box::new(client_tracking_purge_total.clone()))
             .expect("client_tracking_purge_total registration should succeed");
+        registry
+            .register(Box::new(webhook_deliveries_total.clone()))
+            .expect("webhook_deliveries_total registration should succeed");
         registry
             .register(Box::new(activity_daily_rollup_updates_total.clone()))
             .expect("activity_daily_rollup_updates_total registration should succeed");
@@ -656,6 +724,24 @@ impl Metrics {
         registry
             .register(Box::new(audio_transcode_job_duration_seconds.clone()))
             .expect("audio_transcode_job_duration_seconds registration should succeed");
+        registry
+            .register(Box::new(authz_cache_lookups_total.clone()))
+            .expect("authz_cache_lookups_total registration should succeed");
+        registry
+            .register(Box::new(authz_cache_invalidations_total.clone()))
+            .expect("authz_cache_invalidations_total registration should succeed");
+        registry
+            .register(Box::new(rate_limit_throttled_total.clone()))
+            .expect("rate_limit_throttled_total registration should succeed");
+        registry
+            .register(Box::new(rate_limit_store_errors_total.clone()))
+            .expect("rate_limit_store_errors_total registration should succeed");
+        registry
+            .register(Box::new(push_queue_jobs.clone()))
+            .expect("push_queue_jobs registration should succeed");
+        registry
+            .register(Box::new(push_queue_oldest_pending_age_seconds.clone()))
+            .expect("push_queue_oldest_pending_age_seconds registration should succeed");
 
         Self {
             registry,
@@ -683,6 +769,7 @@ impl Metrics {
             client_activity_writes_skipped_total,
             client_rebinds_total,
             client_tracking_purge_total,
+            webhook_deliveries_total,
             activity_daily_rollup_updates_total,
             activity_today_active_users,
             activity_today_new_users,
@@ -710,6 +797,12 @@ impl Metrics {
             audio_transcode_source_total,
             audio_transcode_jobs_total,
             audio_transcode_job_duration_seconds,
+            authz_cache_lookups_total,
+            authz_cache_invalidations_total,
+            rate_limit_throttled_total,
+            rate_limit_store_errors_total,
+            push_queue_jobs,
+            push_queue_oldest_pending_age_seconds,
         }
     }
 
@@ -801,6 +894,39 @@ impl Metrics {
             .observe(duration_seconds);
     }
 
+    pub(crate) fn record_authz_cache_lookup(&self, cache: &str, hit: bool) {
+        self.authz_cache_lookups_total
+            .with_label_values(&[cache, if hit { "hit" } else { "miss" }])
+            .inc();
+    }
+
+    pub(crate) fn record_authz_cache_invalidation(&self, scope: &str, source: &str) {
+        self.authz_cache_invalidations_total
+            .with_label_values(&[scope, source])
+            .inc();
+    }
+
+    pub(crate) fn record_rate_limit_throttled(&self, group: &str, key: &str) {
+        self.rate_limit_throttled_total
+            .with_label_values(&[group, key])
+            .inc();
+    }
+
+    pub(crate) fn record_rate_limit_store_error(&self, store: &str) {
+        self.rate_limit_store_errors_total
+            .with_label_values(&[store])
+            .inc();
+    }
+
+    pub(crate) fn set_push_queue_stats(&self, pending: i64, dead: i64, oldest_pending_age: i64) {
+        self.push_queue_jobs
+            .with_label_values(&["pending"])
+            .set(pending);
+        self.push_queue_jobs.with_label_values(&["dead"]).set(dead);
+        self.push_queue_oldest_pending_age_seconds
+            .set(oldest_pending_age);
+    }
+
     pub(crate) fn set_ws_connected_users(&self, connected_users: usize) {
         self.ws_connected_users.set(connected_users as i64);
     }
@@ -882,6 +1008,12 @@ impl Metrics {
         self.client_rebinds_total.inc();
     }
 
+    pub(crate) fn record_webhook_delivery(&self, result: &str) {
+        self.webhook_deliveries_total
+            .with_label_values(&[result])
+            .inc();
+    }
+
     pub(crate) fn record_client_tracking_purge(&self, kind: &str, count: u64) {
         self.client_tracking_purge_total
             .with_label_values(&[kind])
@@ -1145,6 +1277,9 @@ mod tests {
             legacy_subscriptions_purged: 0,
         });
         metrics.record_app_version_request("abc1234", Some("client-a"));
+        metrics.record_rate_limit_throttled("messages", "uid");
+        metrics.record_rate_limit_store_error("postgres");
+        metrics.set_push_queue_stats(3, 1, 12);
         let app = Router::new()
             .route("/metrics", get(metrics_handler))
             .with_state(metrics);
@@ -1212,6 +1347,11 @@ mod tests {
         assert!(body.contains("audio_transcode_source_total"));
         assert!(body.contains("audio_transcode_jobs_total"));
         assert!(body.contains("audio_transcode_job_duration_seconds"));
+        assert!(body.contains("authz_cache_lookups_total"));
+        assert!(body.contains("rate_limit_throttled_total"));
+        assert!(body.contains("rate_limit_store_errors_total"));
+        assert!(body.contains("push_queue_jobs"));
+        assert!(body.contains("push_queue_oldest_pending_age_seconds"));
     }
 
     #[tokio::test]
@@ -69,6 +69,7 @@ pub enum GroupJoinReason {
     Deserialize,
     PartialEq,
     Eq,
+    Hash,
     utoipa::ToSchema,
 )]
 #[ExistingTypePath = "crate::schema::sql_types::GroupRole"]
@@ -78,6 +79,70 @@ pub enum GroupRole {
     Admin,
 }
 
+#[derive(
+    diesel_derive_enum::DbEnum,
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    utoipa::ToSchema,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::ChatExportStatus"]
+#[serde(rename_all = "snake_case")]
+pub enum ChatExportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(
+    diesel_derive_enum::DbEnum,
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    utoipa::ToSchema,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::WebhookDeliveryStatus"]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+#[derive(
+    diesel_derive_enum::DbEnum,
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    utoipa::ToSchema,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::GroupAuditAction"]
+#[serde(rename_all = "snake_case")]
+pub enum GroupAuditAction {
+    MemberAdd,
+    MemberRemove,
+    MemberRoleChange,
+    GroupUpdate,
+    MessageDelete,
+    MessagePin,
+    MessageUnpin,
+    InviteUpdate,
+    InviteRevoke,
+}
+
 #[derive(
     diesel_derive_enum::DbEnum,
     Debug,
@@ -153,6 +218,7 @@ pub enum TranscodeStatus {
 pub enum PushProvider {
     WebPush,
     Apns,
+    Fcm,
 }
 
 impl PushProvider {
@@ -160,10 +226,50 @@ impl PushProvider {
         match self {
             Self::WebPush => "web_push",
             Self::Apns => "apns",
+            Self::Fcm => "fcm",
         }
     }
 }
 
+#[derive(
+    diesel_derive_enum::DbEnum,
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    utoipa::ToSchema,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::NotificationLevel"]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationLevel {
+    All,
+    MentionsAndReplies,
+    Nothing,
+}
+
+#[derive(
+    diesel_derive_enum::DbEnum,
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    utoipa::ToSchema,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::QuietHoursMode"]
+#[serde(rename_all = "snake_case")]
+pub enum QuietHoursMode {
+    /// Deliver once quiet hours end.
+    Hold,
+    /// Deliver immediately without sound or vibration.
+    Silent,
+}
+
 #[derive(
     diesel_derive_enum::DbEnum,
     Debug,
@@ -183,7 +289,16 @@ pub enum PushEnvironment {
 }
 
 #[derive(
-    diesel_derive_enum::DbEnum, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash,
+    diesel_derive_enum::DbEnum,
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    utoipa::ToSchema,
 )]
 #[ExistingTypePath = "crate::schema::sql_types::PolicySubjectType"]
 #[serde(rename_all = "snake_case")]
@@ -194,7 +309,16 @@ pub enum PolicySubjectType {
 }
 
 #[derive(
-    diesel_derive_enum::DbEnum, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash,
+    diesel_derive_enum::DbEnum,
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    utoipa::ToSchema,
 )]
 #[ExistingTypePath = "crate::schema::sql_types::PermissionResourceType"]
 #[serde(rename_all = "snake_case")]
@@ -225,6 +349,11 @@ pub struct Group {
     pub visibility: GroupVisibility,
     pub last_message_id: Option<i64>,
     pub last_message_at: Option<DateTime<Utc>>,
+    pub slow_mode_secs: i32,
+    pub thread_slow_mode_secs: i32,
+    pub announcement_only: bool,
+    pub member_export_enabled: bool,
+    pub sticker_pack_id: Option<i64>,
 }
 
 /// For inserting a group. Set `id` and `created_at` (e.g. `Utc::now()`) when not relying on DB defaults.
@@ -316,6 +445,9 @@ pub struct ServiceToken {
     pub metadata: serde_json::Value,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub allowed_chat_ids: Option<Vec<i64>>,
+    pub rate_limit_per_minute: Option<i32>,
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -331,6 +463,158 @@ pub struct NewServiceToken {
     pub metadata: serde_json::Value,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub allowed_chat_ids: Option<Vec<i64>>,
+    pub rate_limit_per_minute: Option<i32>,
+}
+
+#[derive(Debug, Clone, QueryableByName)]
+#[diesel(table_name = schema::service_token_usage)]
+pub struct ServiceTokenUsage {
+    pub service_token_id: i64,
+    pub method: String,
+    pub endpoint: String,
+    pub status: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::service_token_usage)]
+pub struct NewServiceTokenUsage {
+    pub service_token_id: i64,
+    pub method: String,
+    pub endpoint: String,
+    pub status: i32,
+}
+
+/// Bot identity bound to a service token; `uid` is negative.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = schema::bots)]
+pub struct Bot {
+    pub uid: i32,
+    pub service_token_id: i64,
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub created_by_uid: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::bots)]
+pub struct NewBot {
+    pub service_token_id: i64,
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub created_by_uid: i32,
+}
+
+/// Secret-URL webhook that posts into one chat as its own negative `uid`.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = schema::incoming_webhooks)]
+pub struct IncomingWebhook {
+    pub id: i64,
+    pub chat_id: i64,
+    pub uid: i32,
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub secret_hash: String,
+    pub rate_limit_per_minute: i32,
+    pub created_by_uid: i32,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::incoming_webhooks)]
+pub struct NewIncomingWebhook {
+    pub id: i64,
+    pub chat_id: i64,
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub secret_hash: String,
+    pub rate_limit_per_minute: i32,
+    pub created_by_uid: i32,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::message_sender_overrides)]
+pub struct MessageSenderOverride {
+    pub message_id: i64,
+    pub name: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+/// Slash command a bot declared for one chat; `args` is a JSON array of `BotCommandArg`.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = schema::bot_commands)]
+pub struct BotCommand {
+    pub chat_id: i64,
+    pub name: String,
+    pub bot_uid: i32,
+    pub description: String,
+    pub args: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::bot_commands)]
+pub struct NewBotCommand {
+    pub chat_id: i64,
+    pub name: String,
+    pub bot_uid: i32,
+    pub description: String,
+    pub args: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::bot_command_invocations)]
+pub struct BotCommandInvocation {
+    pub id: i64,
+    pub chat_id: i64,
+    pub bot_uid: i32,
+    pub command: String,
+    pub invoker_uid: i32,
+    pub topic_id: Option<i64>,
+    pub reply_to_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// External OpenID Connect subject linked to a local uid.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = schema::oidc_identities)]
+pub struct OidcIdentity {
+    pub id: i64,
+    pub issuer: String,
+    pub subject: String,
+    pub uid: i32,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::oidc_identities)]
+pub struct NewOidcIdentity {
+    pub id: i64,
+    pub issuer: String,
+    pub subject: String,
+    pub uid: i32,
+    pub email: Option<String>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::oidc_login_states)]
+pub struct OidcLoginState {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub client_id: String,
+    pub link_uid: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Queryable, Selectable, Serialize, Insertable)]
@@ -519,6 +803,8 @@ pub struct Message {
     pub sticker_id: Option<i64>,
     pub is_published: bool,
     pub transcode_status: TranscodeStatus,
+    pub topic_id: Option<i64>,
+    pub is_important: bool,
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -541,6 +827,8 @@ pub struct NewMessage {
     pub sticker_id: Option<i64>,
     pub is_published: bool,
     pub transcode_status: TranscodeStatus,
+    pub topic_id: Option<i64>,
+    pub is_important: bool,
 }
 
 #[derive(Debug, Clone, Queryable, Selectable, Insertable)]
@@ -550,6 +838,7 @@ pub struct MessageReaction {
     pub user_uid: i32,
     pub emoji: String,
     pub created_at: DateTime<Utc>,
+    pub sticker_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, Queryable, Selectable, Serialize)]
@@ -693,6 +982,171 @@ pub struct UpdateGroup {
     pub name: Option<String>,
     pub description: Option<String>,
     pub visibility: Option<GroupVisibility>,
+    pub slow_mode_secs: Option<i32>,
+    pub thread_slow_mode_secs: Option<i32>,
+    pub announcement_only: Option<bool>,
+    pub member_export_enabled: Option<bool>,
+    pub sticker_pack_id: Option<Option<i64>>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = schema::chat_folders)]
+pub struct ChatFolder {
+    pub id: i64,
+    pub name: String,
+    pub sort_order: i32,
+    pub include_unread: bool,
+    pub include_muted: bool,
+    pub include_mentions: bool,
+    pub include_admin_of: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::chat_folders)]
+pub struct NewChatFolder {
+    pub id: i64,
+    pub uid: i32,
+    pub name: String,
+    pub sort_order: i32,
+    pub include_unread: bool,
+    pub include_muted: bool,
+    pub include_mentions: bool,
+    pub include_admin_of: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, AsChangeset)]
+#[diesel(table_name = schema::chat_folders)]
+pub struct UpdateChatFolder {
+    pub name: Option<String>,
+    pub include_unread: Option<bool>,
+    pub include_muted: Option<bool>,
+    pub include_mentions: Option<bool>,
+    pub include_admin_of: Option<bool>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::chat_folder_chats)]
+pub struct NewChatFolderChat {
+    pub folder_id: i64,
+    pub chat_id: i64,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = schema::chat_topics)]
+pub struct ChatTopic {
+    pub id: i64,
+    pub chat_id: i64,
+    pub title: String,
+    pub icon_emoji: Option<String>,
+    pub closed: bool,
+    pub sort_order: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::chat_topics)]
+pub struct NewChatTopic {
+    pub id: i64,
+    pub chat_id: i64,
+    pub title: String,
+    pub icon_emoji: Option<String>,
+    pub closed: bool,
+    pub sort_order: i32,
+    pub created_by_uid: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, AsChangeset)]
+#[diesel(table_name = schema::chat_topics)]
+pub struct UpdateChatTopic {
+    pub title: Option<String>,
+    /// `Some(None)` clears the icon.
+    pub icon_emoji: Option<Option<String>>,
+    pub closed: Option<bool>,
+    pub sort_order: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::topic_user_states)]
+pub struct TopicUserState {
+    pub topic_id: i64,
+    pub uid: i32,
+    pub chat_id: i64,
+    pub last_read_message_id: Option<i64>,
+    pub muted_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = schema::chat_exports)]
+pub struct ChatExport {
+    pub id: i64,
+    pub chat_id: i64,
+    pub requester_uid: i32,
+    pub include_deleted: bool,
+    pub status: ChatExportStatus,
+    pub json_key: Option<String>,
+    pub html_key: Option<String>,
+    pub message_count: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::chat_exports)]
+pub struct NewChatExport {
+    pub id: i64,
+    pub chat_id: i64,
+    pub requester_uid: i32,
+    pub include_deleted: bool,
+    pub status: ChatExportStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = schema::group_audit_log)]
+pub struct GroupAuditLogEntry {
+    pub id: i64,
+    pub actor_uid: i32,
+    pub action: GroupAuditAction,
+    pub target_uid: Option<i32>,
+    pub target_id: Option<i64>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::chat_role_permissions)]
+pub struct ChatRolePermission {
+    pub chat_id: i64,
+    pub role: GroupRole,
+    pub action: String,
+    pub allowed: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// `id` and `created_at` come from the database defaults.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::group_audit_log)]
+pub struct NewGroupAuditLogEntry {
+    pub id: i64,
+    pub chat_id: i64,
+    pub actor_uid: i32,
+    pub action: GroupAuditAction,
+    pub target_uid: Option<i32>,
+    pub target_id: Option<i64>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Queryable, Selectable, Serialize, Insertable)]
@@ -730,6 +1184,8 @@ pub struct ClientRecord {
     pub created_at: chrono::NaiveDateTime,
     pub last_active: chrono::NaiveDateTime,
     pub last_active_uid: i32,
+    pub device_name: Option<String>,
+    pub app_version: Option<String>,
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -739,6 +1195,7 @@ pub struct NewClientRecord {
     pub created_at: chrono::NaiveDateTime,
     pub last_active: chrono::NaiveDateTime,
     pub last_active_uid: i32,
+    pub app_version: Option<String>,
 }
 
 #[derive(Debug, Clone, Queryable, Selectable, Serialize, Insertable)]
@@ -748,6 +1205,7 @@ pub struct UserExtra {
     pub first_seen_at: chrono::NaiveDateTime,
     pub last_seen_at: chrono::NaiveDateTime,
     pub sticker_pack_order: serde_json::Value,
+    pub tokens_valid_after: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -842,3 +1300,79 @@ pub struct NewPushSubscription {
     pub last_delivery_error: Option<String>,
     pub last_delivery_error_at: Option<chrono::DateTime<chrono::Utc>>,
 }
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::push_jobs)]
+pub struct NewPushJob {
+    pub chat_id: i64,
+    pub message_id: i64,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = schema::notification_preferences)]
+#[diesel(treat_none_as_null = true)]
+pub struct NotificationPreferences {
+    pub uid: i32,
+    pub level: NotificationLevel,
+    pub keywords: Vec<String>,
+    pub quiet_hours_start: Option<i16>,
+    pub quiet_hours_end: Option<i16>,
+    pub quiet_hours_timezone: Option<String>,
+    pub quiet_hours_mode: QuietHoursMode,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = schema::webhooks)]
+pub struct Webhook {
+    pub id: i64,
+    pub chat_id: Option<i64>,
+    pub service_token_id: Option<i64>,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    pub enabled: bool,
+    pub consecutive_failures: i32,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub created_by_uid: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::webhooks)]
+pub struct NewWebhook {
+    pub id: i64,
+    pub chat_id: Option<i64>,
+    pub service_token_id: Option<i64>,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    pub created_by_uid: Option<i32>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = schema::webhook_deliveries)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::webhook_deliveries)]
+pub struct NewWebhookDelivery {
+    pub webhook_id: i64,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+}
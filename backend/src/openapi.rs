@@ -1,6 +1,7 @@
 use crate::dto::ws::{
-    ChatArchiveStateChangedPayload, PinUpdatePayload, PresenceUpdatePayload, ReactionUpdatePayload,
-    ServerWsMessage, ThreadMembershipChangedPayload, ThreadUpdatePayload,
+    ChatArchiveStateChangedPayload, ChatFoldersUpdatedPayload, ChatPinsChangedPayload,
+    PinUpdatePayload, PresenceUpdatePayload, ReactionUpdatePayload, ServerWsMessage,
+    ThreadMembershipChangedPayload, ThreadUpdatePayload,
 };
 use utoipa::openapi::security::{ApiKey, ApiKeyValue, Http, HttpAuthScheme, SecurityScheme};
 use utoipa::OpenApi;
@@ -21,6 +22,8 @@ use utoipa::OpenApi;
             ThreadUpdatePayload,
             ThreadMembershipChangedPayload,
             ChatArchiveStateChangedPayload,
+            ChatFoldersUpdatedPayload,
+            ChatPinsChangedPayload,
             PinUpdatePayload,
         )
     ),
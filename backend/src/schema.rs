@@ -6,11 +6,15 @@ pub mod primary;
 use discuz::discuz::{common_member, common_usergroup};
 use discuz_manual::discuz::common_member_profile;
 pub use primary::{
-    activity_daily_metrics, attachments, clients, group_membership, groups, invites, media,
-    message_reactions, messages, pinned_messages, policies, policy_assignments, policy_permissions,
-    push_subscriptions, saved_messages, service_tokens, sql_types, sticker_pack_stickers,
-    sticker_packs, stickers, thread_meta, thread_user_states, user_extra, user_favorite_stickers,
-    user_sticker_pack_subscriptions, usergroup_extra,
+    activity_daily_metrics, attachments, auth_sessions, authz_generation, bot_command_invocations,
+    bot_commands, bots, chat_exports, chat_folder_chats, chat_folders, chat_role_permissions,
+    chat_topics, clients, group_audit_log, group_membership, groups, incoming_webhooks, invites,
+    media, message_reactions, message_sender_overrides, messages, notification_preferences,
+    oidc_identities, oidc_login_states, pinned_messages, policies, policy_assignments,
+    policy_permissions, push_jobs, push_subscriptions, rate_limit_buckets, saved_messages,
+    service_token_usage, service_tokens, sql_types, sticker_pack_stickers, sticker_packs, stickers,
+    thread_meta, thread_user_states, topic_user_states, user_extra, user_favorite_stickers,
+    user_sticker_pack_subscriptions, usergroup_extra, webhook_deliveries, webhooks,
 };
 
 diesel::allow_tables_to_appear_in_same_query!(group_membership, common_member);
@@ -1,6 +1,14 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "chat_export_status"))]
+    pub struct ChatExportStatus;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "group_audit_action"))]
+    pub struct GroupAuditAction;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "group_join_reason"))]
     pub struct GroupJoinReason;
@@ -21,10 +29,18 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "media_purpose"))]
     pub struct MediaPurpose;
 
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "webhook_delivery_status"))]
+    pub struct WebhookDeliveryStatus;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "message_type"))]
     pub struct MessageType;
 
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "notification_level"))]
+    pub struct NotificationLevel;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "permission_resource_type"))]
     pub struct PermissionResourceType;
@@ -41,6 +57,10 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "push_provider"))]
     pub struct PushProvider;
 
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "quiet_hours_mode"))]
+    pub struct QuietHoursMode;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "transcode_status"))]
     pub struct TranscodeStatus;
@@ -78,6 +98,137 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    auth_sessions (uid, client_id) {
+        uid -> Int4,
+        #[max_length = 64]
+        client_id -> Varchar,
+        generation -> Int4,
+        refresh_token_hash -> Nullable<Text>,
+        refresh_expires_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    authz_generation (id) {
+        id -> Int2,
+        generation -> Int8,
+    }
+}
+
+diesel::table! {
+    bot_command_invocations (id) {
+        id -> Int8,
+        chat_id -> Int8,
+        bot_uid -> Int4,
+        #[max_length = 32]
+        command -> Varchar,
+        invoker_uid -> Int4,
+        topic_id -> Nullable<Int8>,
+        reply_to_id -> Nullable<Int8>,
+        created_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    bot_commands (chat_id, name) {
+        chat_id -> Int8,
+        #[max_length = 32]
+        name -> Varchar,
+        bot_uid -> Int4,
+        #[max_length = 256]
+        description -> Varchar,
+        args -> Jsonb,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    bots (uid) {
+        uid -> Int4,
+        service_token_id -> Int8,
+        #[max_length = 64]
+        name -> Varchar,
+        avatar_url -> Nullable<Text>,
+        created_by_uid -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ChatExportStatus;
+
+    chat_exports (id) {
+        id -> Int8,
+        chat_id -> Int8,
+        requester_uid -> Int4,
+        include_deleted -> Bool,
+        status -> ChatExportStatus,
+        json_key -> Nullable<Text>,
+        html_key -> Nullable<Text>,
+        message_count -> Nullable<Int4>,
+        error -> Nullable<Text>,
+        created_at -> Timestamptz,
+        completed_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::GroupRole;
+
+    chat_role_permissions (chat_id, role, action) {
+        chat_id -> Int8,
+        role -> GroupRole,
+        action -> Text,
+        allowed -> Bool,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    chat_folder_chats (folder_id, chat_id) {
+        folder_id -> Int8,
+        chat_id -> Int8,
+        added_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    chat_folders (id) {
+        id -> Int8,
+        uid -> Int4,
+        name -> Text,
+        sort_order -> Int4,
+        include_unread -> Bool,
+        include_muted -> Bool,
+        include_mentions -> Bool,
+        include_admin_of -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    chat_topics (id) {
+        id -> Int8,
+        chat_id -> Int8,
+        title -> Text,
+        icon_emoji -> Nullable<Text>,
+        closed -> Bool,
+        sort_order -> Int4,
+        created_by_uid -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     clients (client_id) {
         #[max_length = 64]
@@ -85,6 +236,27 @@ diesel::table! {
         created_at -> Timestamp,
         last_active -> Timestamp,
         last_active_uid -> Int4,
+        #[max_length = 64]
+        device_name -> Nullable<Varchar>,
+        #[max_length = 128]
+        app_version -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::GroupAuditAction;
+
+    group_audit_log (id) {
+        id -> Int8,
+        chat_id -> Int8,
+        actor_uid -> Int4,
+        action -> GroupAuditAction,
+        target_uid -> Nullable<Int4>,
+        target_id -> Nullable<Int8>,
+        before -> Nullable<Jsonb>,
+        after -> Nullable<Jsonb>,
+        created_at -> Timestamptz,
     }
 }
 
@@ -92,6 +264,7 @@ diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::GroupRole;
     use super::sql_types::GroupJoinReason;
+    use super::sql_types::NotificationLevel;
 
     group_membership (chat_id, uid) {
         chat_id -> Int8,
@@ -103,6 +276,8 @@ diesel::table! {
         join_reason -> GroupJoinReason,
         join_reason_extra -> Nullable<Jsonb>,
         archived -> Bool,
+        pin_order -> Nullable<Int4>,
+        notification_level -> Nullable<NotificationLevel>,
     }
 }
 
@@ -120,6 +295,30 @@ diesel::table! {
         last_message_id -> Nullable<Int8>,
         last_message_at -> Nullable<Timestamptz>,
         avatar_image_id -> Nullable<Int8>,
+        slow_mode_secs -> Int4,
+        thread_slow_mode_secs -> Int4,
+        announcement_only -> Bool,
+        member_export_enabled -> Bool,
+        sticker_pack_id -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    incoming_webhooks (id) {
+        id -> Int8,
+        chat_id -> Int8,
+        uid -> Int4,
+        #[max_length = 64]
+        name -> Varchar,
+        avatar_url -> Nullable<Text>,
+        secret_hash -> Text,
+        rate_limit_per_minute -> Int4,
+        window_started_at -> Timestamptz,
+        window_count -> Int4,
+        created_by_uid -> Int4,
+        last_used_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
@@ -172,6 +371,16 @@ diesel::table! {
         #[max_length = 32]
         emoji -> Varchar,
         created_at -> Timestamptz,
+        sticker_id -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    message_sender_overrides (message_id) {
+        message_id -> Int8,
+        #[max_length = 64]
+        name -> Nullable<Varchar>,
+        avatar_url -> Nullable<Text>,
     }
 }
 
@@ -198,6 +407,50 @@ diesel::table! {
         sticker_id -> Nullable<Int8>,
         is_published -> Bool,
         transcode_status -> TranscodeStatus,
+        topic_id -> Nullable<Int8>,
+        is_important -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::NotificationLevel;
+    use super::sql_types::QuietHoursMode;
+
+    notification_preferences (uid) {
+        uid -> Int4,
+        level -> NotificationLevel,
+        keywords -> Array<Text>,
+        quiet_hours_start -> Nullable<Int2>,
+        quiet_hours_end -> Nullable<Int2>,
+        quiet_hours_timezone -> Nullable<Text>,
+        quiet_hours_mode -> QuietHoursMode,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    oidc_identities (id) {
+        id -> Int8,
+        issuer -> Text,
+        subject -> Text,
+        uid -> Int4,
+        email -> Nullable<Text>,
+        created_at -> Timestamptz,
+        last_login_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    oidc_login_states (state) {
+        state -> Text,
+        nonce -> Text,
+        code_verifier -> Text,
+        #[max_length = 64]
+        client_id -> Varchar,
+        link_uid -> Nullable<Int4>,
+        created_at -> Timestamptz,
+        expires_at -> Timestamptz,
     }
 }
 
@@ -250,6 +503,22 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    push_jobs (id) {
+        id -> Int8,
+        chat_id -> Int8,
+        message_id -> Int8,
+        payload -> Jsonb,
+        retry_subscription_ids -> Nullable<Array<Int8>>,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamptz,
+        dead_at -> Nullable<Timestamptz>,
+        silent_uids -> Array<Int4>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::PushProvider;
@@ -272,6 +541,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    rate_limit_buckets (key) {
+        key -> Text,
+        tokens -> Float8,
+        allowed -> Bool,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::MessageType;
@@ -308,6 +586,23 @@ diesel::table! {
         metadata -> Jsonb,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        expires_at -> Nullable<Timestamptz>,
+        allowed_chat_ids -> Nullable<Array<Int8>>,
+        rate_limit_per_minute -> Nullable<Int4>,
+        window_started_at -> Timestamptz,
+        window_count -> Int4,
+    }
+}
+
+diesel::table! {
+    service_token_usage (id) {
+        id -> Int8,
+        service_token_id -> Int8,
+        #[max_length = 16]
+        method -> Varchar,
+        endpoint -> Text,
+        status -> Int4,
+        created_at -> Timestamptz,
     }
 }
 
@@ -365,12 +660,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    topic_user_states (topic_id, uid) {
+        topic_id -> Int8,
+        uid -> Int4,
+        chat_id -> Int8,
+        last_read_message_id -> Nullable<Int8>,
+        muted_until -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     user_extra (uid) {
         uid -> Int4,
         first_seen_at -> Timestamp,
         last_seen_at -> Timestamp,
         sticker_pack_order -> Jsonb,
+        tokens_valid_after -> Nullable<Timestamp>,
     }
 }
 
@@ -400,16 +706,70 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::WebhookDeliveryStatus;
+
+    webhook_deliveries (id) {
+        id -> Int8,
+        webhook_id -> Int8,
+        event_type -> Text,
+        payload -> Jsonb,
+        status -> WebhookDeliveryStatus,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_attempt_at -> Nullable<Timestamptz>,
+        response_status -> Nullable<Int4>,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamptz,
+        completed_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    webhooks (id) {
+        id -> Int8,
+        chat_id -> Nullable<Int8>,
+        service_token_id -> Nullable<Int8>,
+        url -> Text,
+        secret -> Text,
+        events -> Array<Text>,
+        enabled -> Bool,
+        consecutive_failures -> Int4,
+        disabled_at -> Nullable<Timestamptz>,
+        created_by_uid -> Nullable<Int4>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::joinable!(attachments -> messages (message_id));
+diesel::joinable!(auth_sessions -> clients (client_id));
+diesel::joinable!(bot_command_invocations -> bots (bot_uid));
+diesel::joinable!(bot_command_invocations -> groups (chat_id));
+diesel::joinable!(bot_commands -> bots (bot_uid));
+diesel::joinable!(bot_commands -> groups (chat_id));
+diesel::joinable!(bots -> service_tokens (service_token_id));
+diesel::joinable!(chat_exports -> groups (chat_id));
+diesel::joinable!(chat_folder_chats -> chat_folders (folder_id));
+diesel::joinable!(chat_folder_chats -> groups (chat_id));
+diesel::joinable!(chat_role_permissions -> groups (chat_id));
+diesel::joinable!(chat_topics -> groups (chat_id));
+diesel::joinable!(group_audit_log -> groups (chat_id));
 diesel::joinable!(group_membership -> groups (chat_id));
 diesel::joinable!(groups -> media (avatar_image_id));
+diesel::joinable!(groups -> sticker_packs (sticker_pack_id));
+diesel::joinable!(incoming_webhooks -> groups (chat_id));
 diesel::joinable!(message_reactions -> messages (message_id));
+diesel::joinable!(message_reactions -> stickers (sticker_id));
+diesel::joinable!(message_sender_overrides -> messages (message_id));
 diesel::joinable!(messages -> stickers (sticker_id));
 diesel::joinable!(pinned_messages -> groups (chat_id));
 diesel::joinable!(pinned_messages -> messages (message_id));
 diesel::joinable!(policy_assignments -> policies (policy_id));
 diesel::joinable!(policy_permissions -> policies (policy_id));
 diesel::joinable!(saved_messages -> groups (original_chat_id));
+diesel::joinable!(service_token_usage -> service_tokens (service_token_id));
 diesel::joinable!(sticker_pack_stickers -> sticker_packs (pack_id));
 diesel::joinable!(sticker_pack_stickers -> stickers (sticker_id));
 diesel::joinable!(stickers -> media (media_id));
@@ -417,33 +777,59 @@ diesel::joinable!(thread_meta -> groups (chat_id));
 diesel::joinable!(thread_meta -> messages (thread_root_id));
 diesel::joinable!(thread_user_states -> groups (chat_id));
 diesel::joinable!(thread_user_states -> messages (thread_root_id));
+diesel::joinable!(topic_user_states -> chat_topics (topic_id));
 diesel::joinable!(user_favorite_stickers -> stickers (sticker_id));
 diesel::joinable!(user_sticker_pack_subscriptions -> sticker_packs (pack_id));
+diesel::joinable!(webhook_deliveries -> webhooks (webhook_id));
+diesel::joinable!(webhooks -> groups (chat_id));
+diesel::joinable!(webhooks -> service_tokens (service_token_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     activity_daily_metrics,
     attachments,
+    auth_sessions,
+    authz_generation,
+    bot_command_invocations,
+    bot_commands,
+    bots,
+    chat_exports,
+    chat_folder_chats,
+    chat_folders,
+    chat_role_permissions,
+    chat_topics,
     clients,
+    group_audit_log,
     group_membership,
     groups,
+    incoming_webhooks,
     invites,
     media,
     message_reactions,
+    message_sender_overrides,
     messages,
+    notification_preferences,
+    oidc_identities,
+    oidc_login_states,
     pinned_messages,
     policies,
     policy_assignments,
     policy_permissions,
+    push_jobs,
     push_subscriptions,
+    rate_limit_buckets,
     saved_messages,
+    service_token_usage,
     service_tokens,
     sticker_pack_stickers,
     sticker_packs,
     stickers,
     thread_meta,
     thread_user_states,
+    topic_user_states,
     user_extra,
     user_favorite_stickers,
     user_sticker_pack_subscriptions,
     usergroup_extra,
+    webhook_deliveries,
+    webhooks,
 );
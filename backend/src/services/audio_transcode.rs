@@ -427,6 +427,7 @@ async fn process_message(state: AppState, message_id: i64) -> Result<(), AppErro
         message.sender_uid,
         message.chat_id,
         true,
+        message.is_important,
     )?;
     let member_uids = side_effects.broadcast_uids.clone();
     side_effects.fire(&state);
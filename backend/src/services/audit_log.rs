@@ -0,0 +1,249 @@
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde_json::{json, Map, Value};
+
+use crate::errors::AppError;
+use crate::models::{
+    Group, GroupAuditAction, GroupAuditLogEntry, NewGroupAuditLogEntry, UpdateGroup,
+};
+use crate::schema::group_audit_log;
+use crate::utils::ids::{self, IdGen};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuditLogFilter {
+    pub action: Option<GroupAuditAction>,
+    pub actor_uid: Option<i32>,
+    pub target_uid: Option<i32>,
+}
+
+impl NewGroupAuditLogEntry {
+    pub fn new(id: i64, chat_id: i64, actor_uid: i32, action: GroupAuditAction) -> Self {
+        Self {
+            id,
+            chat_id,
+            actor_uid,
+            action,
+            target_uid: None,
+            target_id: None,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn target_uid(mut self, uid: i32) -> Self {
+        self.target_uid = Some(uid);
+        self
+    }
+
+    pub fn target_id(mut self, id: i64) -> Self {
+        self.target_id = Some(id);
+        self
+    }
+
+    pub fn before(mut self, before: Value) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    pub fn after(mut self, after: Value) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    pub fn change(mut self, before: Value, after: Value) -> Self {
+        self.before = Some(before);
+        self.after = Some(after);
+        self
+    }
+}
+
+/// Allocate the id of an audit entry; entries are recorded inside sync transactions, so
+/// callers take the id before opening one.
+pub async fn next_audit_entry_id(id_gen: &IdGen) -> Result<i64, AppError> {
+    ids::next_id(id_gen).await.map_err(|e| {
+        tracing::error!("next_id for audit entry: {:?}", e);
+        AppError::Internal("ID generation failed")
+    })
+}
+
+pub fn record_audit_entry(
+    conn: &mut PgConnection,
+    entry: &NewGroupAuditLogEntry,
+) -> Result<(), diesel::result::Error> {
+    diesel::insert_into(group_audit_log::table)
+        .values(entry)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Before/after snapshots of the group fields an update actually changes, or
+/// `None` when the update is a no-op.
+pub fn group_update_diff(
+    current: &Group,
+    changes: &UpdateGroup,
+    avatar_image_id: Option<Option<i64>>,
+) -> Option<(Value, Value)> {
+    let mut before = Map::new();
+    let mut after = Map::new();
+    let mut push = |key: &str, old: Value, new: Value| {
+        if old != new {
+            before.insert(key.to_string(), old);
+            after.insert(key.to_string(), new);
+        }
+    };
+
+    if let Some(name) = &changes.name {
+        push("name", json!(current.name), json!(name));
+    }
+    if let Some(description) = &changes.description {
+        push(
+            "description",
+            json!(current.description),
+            json!(description),
+        );
+    }
+    if let Some(visibility) = &changes.visibility {
+        push("visibility", json!(current.visibility), json!(visibility));
+    }
+    if let Some(secs) = changes.slow_mode_secs {
+        push("slowModeSecs", json!(current.slow_mode_secs), json!(secs));
+    }
+    if let Some(secs) = changes.thread_slow_mode_secs {
+        push(
+            "threadSlowModeSecs",
+            json!(current.thread_slow_mode_secs),
+            json!(secs),
+        );
+    }
+    if let Some(announcement_only) = changes.announcement_only {
+        push(
+            "announcementOnly",
+            json!(current.announcement_only),
+            json!(announcement_only),
+        );
+    }
+    if let Some(member_export_enabled) = changes.member_export_enabled {
+        push(
+            "memberExportEnabled",
+            json!(current.member_export_enabled),
+            json!(member_export_enabled),
+        );
+    }
+    if let Some(sticker_pack_id) = changes.sticker_pack_id {
+        push(
+            "stickerPackId",
+            json!(current.sticker_pack_id.map(|id| id.to_string())),
+            json!(sticker_pack_id.map(|id| id.to_string())),
+        );
+    }
+    if let Some(avatar_image_id) = avatar_image_id {
+        push(
+            "avatarImageId",
+            json!(current.avatar_image_id.map(|id| id.to_string())),
+            json!(avatar_image_id.map(|id| id.to_string())),
+        );
+    }
+
+    (!after.is_empty()).then_some((Value::Object(before), Value::Object(after)))
+}
+
+/// Newest-first page of a group's audit entries, strictly older than `before`.
+pub fn load_audit_log_page(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    filter: &AuditLogFilter,
+    before: Option<i64>,
+    limit: i64,
+) -> Result<Vec<GroupAuditLogEntry>, diesel::result::Error> {
+    let mut query = group_audit_log::table
+        .filter(group_audit_log::chat_id.eq(chat_id))
+        .into_boxed();
+    if let Some(action) = filter.action {
+        query = query.filter(group_audit_log::action.eq(action));
+    }
+    if let Some(actor_uid) = filter.actor_uid {
+        query = query.filter(group_audit_log::actor_uid.eq(actor_uid));
+    }
+    if let Some(target_uid) = filter.target_uid {
+        query = query.filter(group_audit_log::target_uid.eq(target_uid));
+    }
+    if let Some(before) = before {
+        query = query.filter(group_audit_log::id.lt(before));
+    }
+
+    query
+        .order(group_audit_log::id.desc())
+        .limit(limit)
+        .select(GroupAuditLogEntry::as_select())
+        .load(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GroupVisibility;
+    use chrono::Utc;
+
+    fn group() -> Group {
+        Group {
+            id: 1,
+            name: "General".to_string(),
+            description: None,
+            avatar_image_id: Some(5),
+            created_at: Utc::now(),
+            visibility: GroupVisibility::Public,
+            last_message_id: None,
+            last_message_at: None,
+            slow_mode_secs: 0,
+            thread_slow_mode_secs: 0,
+            announcement_only: false,
+            member_export_enabled: false,
+            sticker_pack_id: None,
+        }
+    }
+
+    fn no_changes() -> UpdateGroup {
+        UpdateGroup {
+            name: None,
+            description: None,
+            visibility: None,
+            slow_mode_secs: None,
+            thread_slow_mode_secs: None,
+            announcement_only: None,
+            member_export_enabled: None,
+            sticker_pack_id: None,
+        }
+    }
+
+    #[test]
+    fn group_update_diff_lists_only_changed_fields() {
+        let changes = UpdateGroup {
+            name: Some("Renamed".to_string()),
+            slow_mode_secs: Some(0),
+            announcement_only: Some(true),
+            ..no_changes()
+        };
+
+        let (before, after) = group_update_diff(&group(), &changes, Some(None)).unwrap();
+
+        assert_eq!(
+            before,
+            json!({"name": "General", "announcementOnly": false, "avatarImageId": "5"})
+        );
+        assert_eq!(
+            after,
+            json!({"name": "Renamed", "announcementOnly": true, "avatarImageId": null})
+        );
+    }
+
+    #[test]
+    fn group_update_diff_ignores_no_op_updates() {
+        let changes = UpdateGroup {
+            name: Some("General".to_string()),
+            ..no_changes()
+        };
+
+        assert!(group_update_diff(&group(), &changes, Some(Some(5))).is_none());
+        assert!(group_update_diff(&group(), &no_changes(), None).is_none());
+    }
+}
@@ -0,0 +1,407 @@
+//! Per-user, per-client auth sessions: short-lived access tokens, rotating refresh tokens and
+//! the `gen` counter that revokes a client's outstanding tokens.
+
+use std::time::{Duration, Instant};
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::PgConnection;
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use tracing::error;
+
+use crate::errors::AppError;
+use crate::schema::{auth_sessions, user_extra};
+use crate::utils::auth::{encode_auth_token, AuthClaims, ACCESS_TOKEN_TYPE};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const ACCESS_TOKEN_TTL_SECS: i64 = 15 * 60;
+const REFRESH_TOKEN_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+const WS_TICKET_TTL_SECS: i64 = 60;
+const REFRESH_TOKEN_BYTES: usize = 32;
+/// Bounds how long another node may keep accepting a revoked token.
+const GENERATION_CACHE_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy)]
+struct CachedGeneration {
+    generation: i32,
+    valid_after: Option<i64>,
+    cached_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct IssuedTokens {
+    pub access_token: String,
+    pub access_expires_at: DateTime<Utc>,
+    pub refresh_token: String,
+    pub refresh_expires_at: DateTime<Utc>,
+}
+
+pub struct AuthSessionService {
+    db: Pool<ConnectionManager<PgConnection>>,
+    generations: DashMap<(i32, String), CachedGeneration>,
+}
+
+impl AuthSessionService {
+    pub fn new(db: Pool<ConnectionManager<PgConnection>>) -> Self {
+        Self {
+            db,
+            generations: DashMap::new(),
+        }
+    }
+
+    /// Reject access tokens whose `gen` no longer matches the client's session, or that were
+    /// issued before the user logged out of all devices.
+    pub fn verify(&self, claims: &AuthClaims) -> Result<(), (StatusCode, &'static str)> {
+        let key = (claims.uid, claims.cid.clone());
+        let cached = self
+            .generations
+            .get(&key)
+            .map(|entry| *entry)
+            .filter(|entry| entry.cached_at.elapsed() <= GENERATION_CACHE_TTL);
+        let current = match cached {
+            Some(current) => current,
+            None => {
+                let conn = &mut self.db.get().map_err(|e| {
+                    error!("auth sessions: failed to get DB connection: {:?}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Database connection failed",
+                    )
+                })?;
+                let current = load_generation(conn, claims.uid, &claims.cid).map_err(|e| {
+                    error!("auth sessions: failed to load generation: {:?}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Failed to verify auth token",
+                    )
+                })?;
+                self.generations.insert(key, current);
+                self.prune_stale_entries();
+                current
+            }
+        };
+
+        if is_revoked(claims, current.generation, current.valid_after) {
+            return Err((StatusCode::UNAUTHORIZED, "Auth token revoked"));
+        }
+        Ok(())
+    }
+
+    /// Start or resume the session for `uid` on `client_id`, rotating its refresh token. The
+    /// generation is kept, so tokens already issued to the client stay valid.
+    pub fn issue(
+        &self,
+        conn: &mut PgConnection,
+        jwt_signing_key: &[u8],
+        uid: i32,
+        client_id: &str,
+    ) -> Result<IssuedTokens, AppError> {
+        let now = Utc::now();
+        let refresh_token = generate_refresh_token();
+        let refresh_token_hash = hash_refresh_token(jwt_signing_key, &refresh_token)?;
+        let refresh_expires_at = now + chrono::Duration::seconds(REFRESH_TOKEN_TTL_SECS);
+
+        let generation = diesel::insert_into(auth_sessions::table)
+            .values((
+                auth_sessions::uid.eq(uid),
+                auth_sessions::client_id.eq(client_id),
+                auth_sessions::generation.eq(0),
+                auth_sessions::refresh_token_hash.eq(Some(&refresh_token_hash)),
+                auth_sessions::refresh_expires_at.eq(Some(refresh_expires_at)),
+                auth_sessions::created_at.eq(now),
+                auth_sessions::updated_at.eq(now),
+            ))
+            .on_conflict((auth_sessions::uid, auth_sessions::client_id))
+            .do_update()
+            .set((
+                auth_sessions::refresh_token_hash.eq(Some(&refresh_token_hash)),
+                auth_sessions::refresh_expires_at.eq(Some(refresh_expires_at)),
+                auth_sessions::updated_at.eq(now),
+            ))
+            .returning(auth_sessions::generation)
+            .get_result::<i32>(conn)?;
+
+        let (access_token, access_expires_at) = encode_access_token(
+            jwt_signing_key,
+            uid,
+            client_id,
+            generation,
+            ACCESS_TOKEN_TTL_SECS,
+        )?;
+
+        Ok(IssuedTokens {
+            access_token,
+            access_expires_at,
+            refresh_token,
+            refresh_expires_at,
+        })
+    }
+
+    /// Exchange a refresh token for a new access token and a new refresh token.
+    pub fn refresh(
+        &self,
+        conn: &mut PgConnection,
+        jwt_signing_key: &[u8],
+        refresh_token: &str,
+    ) -> Result<IssuedTokens, AppError> {
+        if !is_well_formed_refresh_token(refresh_token) {
+            return Err(AppError::Unauthorized("Invalid refresh token"));
+        }
+        let presented_hash = hash_refresh_token(jwt_signing_key, refresh_token)?;
+        let now = Utc::now();
+
+        let (uid, client_id, generation) = auth_sessions::table
+            .filter(auth_sessions::refresh_token_hash.eq(&presented_hash))
+            .filter(auth_sessions::refresh_expires_at.gt(now))
+            .select((
+                auth_sessions::uid,
+                auth_sessions::client_id,
+                auth_sessions::generation,
+            ))
+            .first::<(i32, String, i32)>(conn)
+            .optional()?
+            .ok_or(AppError::Unauthorized("Invalid refresh token"))?;
+
+        let next_refresh_token = generate_refresh_token();
+        let next_refresh_token_hash = hash_refresh_token(jwt_signing_key, &next_refresh_token)?;
+        let refresh_expires_at = now + chrono::Duration::seconds(REFRESH_TOKEN_TTL_SECS);
+        // Matching on the old hash makes concurrent use of one refresh token succeed once.
+        let rotated = diesel::update(
+            auth_sessions::table
+                .filter(auth_sessions::uid.eq(uid))
+                .filter(auth_sessions::client_id.eq(&client_id))
+                .filter(auth_sessions::refresh_token_hash.eq(&presented_hash)),
+        )
+        .set((
+            auth_sessions::refresh_token_hash.eq(Some(&next_refresh_token_hash)),
+            auth_sessions::refresh_expires_at.eq(Some(refresh_expires_at)),
+            auth_sessions::updated_at.eq(now),
+        ))
+        .execute(conn)?;
+        if rotated == 0 {
+            return Err(AppError::Unauthorized("Invalid refresh token"));
+        }
+
+        let (access_token, access_expires_at) = encode_access_token(
+            jwt_signing_key,
+            uid,
+            &client_id,
+            generation,
+            ACCESS_TOKEN_TTL_SECS,
+        )?;
+
+        Ok(IssuedTokens {
+            access_token,
+            access_expires_at,
+            refresh_token: next_refresh_token,
+            refresh_expires_at,
+        })
+    }
+
+    /// Short-lived token for the WebSocket auth handshake, bound to the client's generation.
+    pub fn issue_ws_ticket(
+        &self,
+        conn: &mut PgConnection,
+        jwt_signing_key: &[u8],
+        uid: i32,
+        client_id: &str,
+    ) -> Result<String, AppError> {
+        let generation = auth_sessions::table
+            .find((uid, client_id))
+            .select(auth_sessions::generation)
+            .first::<i32>(conn)
+            .optional()?
+            .unwrap_or(0);
+        let (ticket, _) = encode_access_token(
+            jwt_signing_key,
+            uid,
+            client_id,
+            generation,
+            WS_TICKET_TTL_SECS,
+        )?;
+        Ok(ticket)
+    }
+
+    /// Revoke every token issued to `uid` on `client_id` and drop its refresh token.
+    pub fn logout(
+        &self,
+        conn: &mut PgConnection,
+        uid: i32,
+        client_id: &str,
+    ) -> Result<(), AppError> {
+        let now = Utc::now();
+        // A missing row still has tokens at generation 0 (e.g. issued by the landing page).
+        diesel::insert_into(auth_sessions::table)
+            .values((
+                auth_sessions::uid.eq(uid),
+                auth_sessions::client_id.eq(client_id),
+                auth_sessions::generation.eq(1),
+                auth_sessions::created_at.eq(now),
+                auth_sessions::updated_at.eq(now),
+            ))
+            .on_conflict((auth_sessions::uid, auth_sessions::client_id))
+            .do_update()
+            .set((
+                auth_sessions::generation.eq(auth_sessions::generation + 1),
+                auth_sessions::refresh_token_hash.eq(None::<String>),
+                auth_sessions::refresh_expires_at.eq(None::<DateTime<Utc>>),
+                auth_sessions::updated_at.eq(now),
+            ))
+            .execute(conn)?;
+
+        self.generations.remove(&(uid, client_id.to_string()));
+        Ok(())
+    }
+
+    /// Revoke every token issued to `uid` on any client. Returns the affected client IDs.
+    pub fn logout_all(&self, conn: &mut PgConnection, uid: i32) -> Result<Vec<String>, AppError> {
+        let now = Utc::now();
+        let client_ids = conn.transaction::<_, AppError, _>(|conn| {
+            let client_ids =
+                diesel::update(auth_sessions::table.filter(auth_sessions::uid.eq(uid)))
+                    .set((
+                        auth_sessions::generation.eq(auth_sessions::generation + 1),
+                        auth_sessions::refresh_token_hash.eq(None::<String>),
+                        auth_sessions::refresh_expires_at.eq(None::<DateTime<Utc>>),
+                        auth_sessions::updated_at.eq(now),
+                    ))
+                    .returning(auth_sessions::client_id)
+                    .get_results::<String>(conn)?;
+            diesel::update(user_extra::table.find(uid))
+                .set(user_extra::tokens_valid_after.eq(Some(now.naive_utc())))
+                .execute(conn)?;
+            Ok(client_ids)
+        })?;
+
+        self.generations
+            .retain(|(cached_uid, _), _| *cached_uid != uid);
+        Ok(client_ids)
+    }
+
+    fn prune_stale_entries(&self) {
+        if self.generations.len() <= 4096 {
+            return;
+        }
+        self.generations
+            .retain(|_, entry| entry.cached_at.elapsed() <= GENERATION_CACHE_TTL);
+    }
+}
+
+fn load_generation(
+    conn: &mut PgConnection,
+    uid: i32,
+    client_id: &str,
+) -> Result<CachedGeneration, diesel::result::Error> {
+    let generation = auth_sessions::table
+        .find((uid, client_id))
+        .select(auth_sessions::generation)
+        .first::<i32>(conn)
+        .optional()?
+        .unwrap_or(0);
+    let valid_after = user_extra::table
+        .find(uid)
+        .select(user_extra::tokens_valid_after)
+        .first::<Option<chrono::NaiveDateTime>>(conn)
+        .optional()?
+        .flatten()
+        .map(|at| at.and_utc().timestamp());
+
+    Ok(CachedGeneration {
+        generation,
+        valid_after,
+        cached_at: Instant::now(),
+    })
+}
+
+fn is_revoked(claims: &AuthClaims, generation: i32, valid_after: Option<i64>) -> bool {
+    claims.gen != generation || valid_after.is_some_and(|valid_after| claims.iat < valid_after)
+}
+
+fn encode_access_token(
+    jwt_signing_key: &[u8],
+    uid: i32,
+    client_id: &str,
+    generation: i32,
+    ttl_secs: i64,
+) -> Result<(String, DateTime<Utc>), AppError> {
+    let now = Utc::now();
+    let expires_at = now + chrono::Duration::seconds(ttl_secs);
+    let token = encode_auth_token(
+        &AuthClaims {
+            uid,
+            cid: client_id.to_string(),
+            gen: generation,
+            iat: now.timestamp(),
+            exp: Some(expires_at.timestamp()),
+            typ: Some(ACCESS_TOKEN_TYPE.to_string()),
+        },
+        jwt_signing_key,
+    )?;
+    Ok((token, expires_at))
+}
+
+fn generate_refresh_token() -> String {
+    let mut bytes = [0u8; REFRESH_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn is_well_formed_refresh_token(token: &str) -> bool {
+    token.len() == REFRESH_TOKEN_BYTES * 2 && token.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn hash_refresh_token(jwt_signing_key: &[u8], token: &str) -> Result<String, AppError> {
+    let mut mac = HmacSha256::new_from_slice(jwt_signing_key)
+        .map_err(|_| AppError::Internal("Failed to hash refresh token"))?;
+    mac.update(b"wetty-chat-refresh-token:v1:");
+    mac.update(token.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(gen: i32, iat: i64) -> AuthClaims {
+        AuthClaims {
+            uid: 42,
+            cid: "client_123".to_string(),
+            gen,
+            iat,
+            exp: Some(iat + ACCESS_TOKEN_TTL_SECS),
+            typ: Some(ACCESS_TOKEN_TYPE.to_string()),
+        }
+    }
+
+    #[test]
+    fn tokens_are_revoked_by_generation_bump_or_logout_all() {
+        assert!(!is_revoked(&claims(0, 1_000), 0, None));
+        assert!(is_revoked(&claims(0, 1_000), 1, None));
+        assert!(is_revoked(&claims(2, 1_000), 1, None));
+        assert!(is_revoked(&claims(1, 1_000), 1, Some(1_001)));
+        assert!(!is_revoked(&claims(1, 1_001), 1, Some(1_001)));
+    }
+
+    #[test]
+    fn refresh_tokens_are_hex_and_hash_deterministically() {
+        let token = generate_refresh_token();
+        assert!(is_well_formed_refresh_token(&token));
+        assert!(!is_well_formed_refresh_token("not-a-token"));
+
+        let key = b"01234567890123456789012345678901";
+        assert_eq!(
+            hash_refresh_token(key, &token).unwrap(),
+            hash_refresh_token(key, &token).unwrap()
+        );
+        assert_ne!(
+            hash_refresh_token(key, &token).unwrap(),
+            hash_refresh_token(b"abcdefabcdefabcdefabcdefabcdefab", &token).unwrap()
+        );
+    }
+}
@@ -1,16 +1,29 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use diesel::prelude::*;
 use diesel::PgConnection;
+use serde::{Deserialize, Serialize};
 
 use crate::errors::AppError;
-use crate::models::{PermissionResourceType, PolicySubjectType};
+use crate::metrics::Metrics;
+use crate::models::{GroupRole, PermissionResourceType, PolicySubjectType};
 use crate::schema::discuz::discuz::common_member;
-use crate::schema::{policy_assignments, policy_permissions};
+use crate::schema::{
+    authz_generation, chat_role_permissions, group_membership, policies, policy_assignments,
+    policy_permissions, service_tokens,
+};
+/// Fallback expiry; changes made through the API are invalidated immediately.
 const CACHE_TTL: Duration = Duration::from_secs(60);
+const INVALIDATION_CHANNEL: &str = "authz_invalidation";
+const LISTENER_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How often the listener re-reads the generation to catch notifications it missed.
+const GENERATION_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,8 +33,52 @@ pub enum Action {
     InviteCreate,
     PermissionAll,
     ServiceTokenManage,
+    ChatAnnounce,
+    ChatImport,
+    ChatSendMedia,
+    ChatPin,
+    ChatInvite,
+    ChatChangeInfo,
+    ChatDeleteOthers,
+    ChatMentionEveryone,
+    /// Post, edit and delete messages as the service token's bot.
+    BotPost,
+    /// React to messages as the service token's bot.
+    BotReact,
+    /// Manage the service token's global webhooks.
+    WebhookManage,
 }
 
+/// Every action policy rules may grant.
+pub const ALL_ACTIONS: [Action; 16] = [
+    Action::ChatCreate,
+    Action::MemberViewAll,
+    Action::InviteCreate,
+    Action::PermissionAll,
+    Action::ServiceTokenManage,
+    Action::ChatAnnounce,
+    Action::ChatImport,
+    Action::ChatSendMedia,
+    Action::ChatPin,
+    Action::ChatInvite,
+    Action::ChatChangeInfo,
+    Action::ChatDeleteOthers,
+    Action::ChatMentionEveryone,
+    Action::BotPost,
+    Action::BotReact,
+    Action::WebhookManage,
+];
+
+/// Chat-scoped actions that a chat's role settings can grant to its members.
+pub const CHAT_ROLE_ACTIONS: [Action; 6] = [
+    Action::ChatSendMedia,
+    Action::ChatPin,
+    Action::ChatInvite,
+    Action::ChatChangeInfo,
+    Action::ChatDeleteOthers,
+    Action::ChatMentionEveryone,
+];
+
 impl Action {
     pub const fn as_str(self) -> &'static str {
         match self {
@@ -30,6 +87,38 @@ impl Action {
             Self::InviteCreate => "invite.create",
             Self::PermissionAll => "permission.all",
             Self::ServiceTokenManage => "serviceToken.manage",
+            Self::ChatAnnounce => "chat.announce",
+            Self::ChatImport => "chat.import",
+            Self::ChatSendMedia => "chat.sendMedia",
+            Self::ChatPin => "chat.pin",
+            Self::ChatInvite => "chat.invite",
+            Self::ChatChangeInfo => "chat.changeInfo",
+            Self::ChatDeleteOthers => "chat.deleteOthers",
+            Self::ChatMentionEveryone => "chat.mentionEveryone",
+            Self::BotPost => "bot.post",
+            Self::BotReact => "bot.react",
+            Self::WebhookManage => "webhook.manage",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        ALL_ACTIONS
+            .into_iter()
+            .find(|action| action.as_str() == value)
+    }
+
+    pub fn from_chat_role_action(value: &str) -> Option<Self> {
+        CHAT_ROLE_ACTIONS
+            .into_iter()
+            .find(|action| action.as_str() == value)
+    }
+
+    /// Whether `role` may perform this chat action when the chat has no override for it.
+    /// Admins get every chat action; members may only attach media by default.
+    pub fn default_allowed_for_role(self, role: &GroupRole) -> bool {
+        match role {
+            GroupRole::Admin => CHAT_ROLE_ACTIONS.contains(&self),
+            GroupRole::Member => matches!(self, Self::ChatSendMedia),
         }
     }
 }
@@ -66,17 +155,207 @@ struct CachedPermissionSet {
     cached_at: Instant,
 }
 
+type ChatRoleOverrides = HashMap<(GroupRole, String), bool>;
+
+#[derive(Debug, Clone)]
+struct CachedChatRoleOverrides {
+    overrides: Arc<ChatRoleOverrides>,
+    cached_at: Instant,
+}
+
+/// A policy rule reaching a subject through one of its assignments.
+#[derive(Debug, Clone)]
+pub struct PermissionGrant {
+    /// Whom the assignment names: the user directly, their Discuz group, or a service token.
+    pub subject_type: PolicySubjectType,
+    pub subject_id: i64,
+    pub assignment_id: i64,
+    pub policy_id: i64,
+    pub policy_name: String,
+    pub permission_id: i64,
+    pub action: String,
+    pub resource: Resource,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ExplainSubject {
+    User(i32),
+    ServiceToken(i64),
+}
+
+#[derive(Debug, Clone)]
+pub struct RoleDecision {
+    pub role: GroupRole,
+    pub allowed: bool,
+    /// Whether the chat overrides the built-in default for this role and action.
+    pub overridden: bool,
+}
+
+/// Checks outside the policy rules that refuse a request regardless of grants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DenyReason {
+    ServiceTokenRevoked,
+    ServiceTokenExpired,
+    /// The chat is outside the service token's `allowed_chat_ids`.
+    ChatNotAllowed,
+}
+
+#[derive(Debug, Clone)]
+pub struct PermissionExplanation {
+    pub allowed: bool,
+    pub discuz_group_id: Option<i32>,
+    /// Rules granting the action (directly or via `permission.all`).
+    pub matching_grants: Vec<PermissionGrant>,
+    /// The chat's role settings, for chat role actions checked for a chat member.
+    pub role_decision: Option<RoleDecision>,
+    pub deny_reasons: Vec<DenyReason>,
+}
+
+/// The token-level checks service token authentication and handlers apply on
+/// top of the token's policies.
+fn service_token_deny_reasons(
+    revoked_at: Option<DateTime<Utc>>,
+    expires_at: Option<DateTime<Utc>>,
+    allowed_chat_ids: Option<&[i64]>,
+    resource: Resource,
+    now: DateTime<Utc>,
+) -> Vec<DenyReason> {
+    let mut reasons = Vec::new();
+    if revoked_at.is_some() {
+        reasons.push(DenyReason::ServiceTokenRevoked);
+    }
+    if expires_at.is_some_and(|expires_at| expires_at <= now) {
+        reasons.push(DenyReason::ServiceTokenExpired);
+    }
+    if let (Resource::Chat(chat_id), Some(allowed)) = (resource, allowed_chat_ids) {
+        if !allowed.contains(&chat_id) {
+            reasons.push(DenyReason::ChatNotAllowed);
+        }
+    }
+    reasons
+}
+
+/// Cache entries affected by a policy, assignment or chat role settings change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scope", rename_all = "camelCase")]
+pub enum AuthzInvalidation {
+    All,
+    #[serde(rename_all = "camelCase")]
+    User {
+        uid: i32,
+    },
+    #[serde(rename_all = "camelCase")]
+    DiscuzGroup {
+        group_id: i32,
+    },
+    #[serde(rename_all = "camelCase")]
+    ServiceToken {
+        token_id: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    ChatRoles {
+        chat_id: i64,
+    },
+}
+
+impl AuthzInvalidation {
+    /// Invalidation for a change to the policies assigned to one subject.
+    pub fn for_subject(subject_type: PolicySubjectType, subject_id: i64) -> Self {
+        let narrow = |id: i64| i32::try_from(id).ok();
+        match subject_type {
+            PolicySubjectType::User => narrow(subject_id)
+                .map(|uid| Self::User { uid })
+                .unwrap_or(Self::All),
+            PolicySubjectType::DiscuzGroup => narrow(subject_id)
+                .map(|group_id| Self::DiscuzGroup { group_id })
+                .unwrap_or(Self::All),
+            PolicySubjectType::ServiceToken => Self::ServiceToken {
+                token_id: subject_id,
+            },
+        }
+    }
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::User { .. } => "user",
+            Self::DiscuzGroup { .. } => "discuz_group",
+            Self::ServiceToken { .. } => "service_token",
+            Self::ChatRoles { .. } => "chat_roles",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InvalidationNotice {
+    generation: i64,
+    #[serde(flatten)]
+    invalidation: AuthzInvalidation,
+}
+
 pub struct AuthorizationService {
     cache: DashMap<CacheKey, CachedPermissionSet>,
+    chat_role_cache: DashMap<i64, CachedChatRoleOverrides>,
+    /// Latest invalidation generation applied on this node. Cache fills that raced
+    /// with a newer generation are discarded instead of stored.
+    generation: AtomicI64,
+    metrics: Arc<Metrics>,
 }
 
 impl AuthorizationService {
-    pub fn start() -> Arc<Self> {
-        Arc::new(Self {
+    pub fn start(database_url: String, metrics: Arc<Metrics>) -> Arc<Self> {
+        let service = Arc::new(Self {
             cache: DashMap::new(),
-        })
+            chat_role_cache: DashMap::new(),
+            generation: AtomicI64::new(0),
+            metrics,
+        });
+
+        let listener = service.clone();
+        std::thread::Builder::new()
+            .name("authz-invalidation".to_string())
+            .spawn(move || listener.run_invalidation_listener(&database_url))
+            .expect("Failed to spawn authz invalidation listener");
+
+        service
+    }
+
+    /// Record a permission-affecting change: bump the shared generation, notify every
+    /// node and drop the affected entries locally. Call after the change is committed.
+    pub fn publish_invalidation(
+        &self,
+        conn: &mut PgConnection,
+        invalidation: AuthzInvalidation,
+    ) -> Result<(), AppError> {
+        let generation = conn.transaction::<i64, diesel::result::Error, _>(|conn| {
+            let generation = diesel::update(authz_generation::table)
+                .set(authz_generation::generation.eq(authz_generation::generation + 1))
+                .returning(authz_generation::generation)
+                .get_result::<i64>(conn)?;
+            let payload = serde_json::to_string(&InvalidationNotice {
+                generation,
+                invalidation,
+            })
+            .expect("invalidation notice should serialize");
+            diesel::sql_query("SELECT pg_notify($1, $2)")
+                .bind::<diesel::sql_types::Text, _>(INVALIDATION_CHANNEL)
+                .bind::<diesel::sql_types::Text, _>(payload)
+                .execute(conn)?;
+            Ok(generation)
+        })?;
+
+        let previous = self.generation.fetch_max(generation, Ordering::SeqCst);
+        if generation > previous + 1 {
+            // Other nodes' notices have not reached us yet; they would now be skipped.
+            self.apply_invalidation(AuthzInvalidation::All, "local");
+        } else {
+            self.apply_invalidation(invalidation, "local");
+        }
+        Ok(())
     }
 
+    /// Chat role actions are also granted by the chat's role settings for the caller's
+    /// membership role, and by policy grants on the global resource.
     pub fn has_permission(
         &self,
         conn: &mut PgConnection,
@@ -85,7 +364,214 @@ impl AuthorizationService {
         resource: Resource,
     ) -> Result<bool, AppError> {
         let actions = self.load_cached_user_actions(conn, uid, resource)?;
-        Ok(actions.contains(action.as_str()) || actions.contains(Action::PermissionAll.as_str()))
+        if actions.contains(action.as_str()) || actions.contains(Action::PermissionAll.as_str()) {
+            return Ok(true);
+        }
+
+        let Resource::Chat(chat_id) = resource else {
+            return Ok(false);
+        };
+        if !CHAT_ROLE_ACTIONS.contains(&action) {
+            return Ok(false);
+        }
+
+        let global_actions = self.load_cached_user_actions(conn, uid, Resource::Global)?;
+        if global_actions.contains(action.as_str())
+            || global_actions.contains(Action::PermissionAll.as_str())
+        {
+            return Ok(true);
+        }
+
+        let Some(role) = self.lookup_chat_role(conn, chat_id, uid)? else {
+            return Ok(false);
+        };
+        self.role_allows(conn, chat_id, &role, action)
+    }
+
+    /// Effective role settings of a chat: every chat role action with whether `role` may
+    /// perform it after applying the chat's overrides.
+    pub fn chat_role_actions(
+        &self,
+        conn: &mut PgConnection,
+        chat_id: i64,
+        role: &GroupRole,
+    ) -> Result<Vec<(Action, bool)>, AppError> {
+        let overrides = self.load_cached_chat_role_overrides(conn, chat_id)?;
+        Ok(CHAT_ROLE_ACTIONS
+            .into_iter()
+            .map(|action| (action, resolve_role_action(&overrides, role, action)))
+            .collect())
+    }
+
+    fn apply_invalidation(&self, invalidation: AuthzInvalidation, source: &str) {
+        match invalidation {
+            AuthzInvalidation::All => {
+                self.cache.clear();
+                self.chat_role_cache.clear();
+            }
+            AuthzInvalidation::User { uid } => self.cache.retain(|key, _| {
+                !matches!(key.subject, CacheSubject::User { uid: cached, .. } if cached == uid)
+            }),
+            AuthzInvalidation::DiscuzGroup { group_id } => self.cache.retain(|key, _| {
+                !matches!(
+                    key.subject,
+                    CacheSubject::User { discuz_group_id: Some(cached), .. } if cached == group_id
+                )
+            }),
+            AuthzInvalidation::ServiceToken { token_id } => self.cache.retain(|key, _| {
+                key.subject != CacheSubject::ServiceToken { token_id }
+            }),
+            AuthzInvalidation::ChatRoles { chat_id } => {
+                self.chat_role_cache.remove(&chat_id);
+            }
+        }
+        self.metrics
+            .record_authz_cache_invalidation(invalidation.label(), source);
+    }
+
+    fn handle_notice(&self, payload: &str) {
+        let notice = match serde_json::from_str::<InvalidationNotice>(payload) {
+            Ok(notice) => notice,
+            Err(e) => {
+                tracing::warn!("malformed authz invalidation notice: {}", e);
+                self.apply_invalidation(AuthzInvalidation::All, "remote");
+                return;
+            }
+        };
+
+        let previous = self
+            .generation
+            .fetch_max(notice.generation, Ordering::SeqCst);
+        if notice.generation <= previous {
+            // Already applied, e.g. our own notice echoed back.
+            return;
+        }
+        if notice.generation > previous + 1 {
+            // Notices in between were missed; scoped invalidation is not enough.
+            self.apply_invalidation(AuthzInvalidation::All, "remote");
+        } else {
+            self.apply_invalidation(notice.invalidation, "remote");
+        }
+    }
+
+    /// Adopt the stored generation, dropping everything if it moved while we were not listening.
+    fn sync_generation(&self, conn: &mut PgConnection) -> Result<(), diesel::result::Error> {
+        let stored = authz_generation::table
+            .select(authz_generation::generation)
+            .first::<i64>(conn)?;
+        let previous = self.generation.fetch_max(stored, Ordering::SeqCst);
+        if stored != previous {
+            self.apply_invalidation(AuthzInvalidation::All, "resync");
+        }
+        Ok(())
+    }
+
+    fn run_invalidation_listener(&self, database_url: &str) {
+        loop {
+            match PgConnection::establish(database_url) {
+                Ok(mut conn) => {
+                    if let Err(e) = self.listen(&mut conn) {
+                        tracing::warn!("authz invalidation listener failed: {}", e);
+                    }
+                }
+                Err(e) => tracing::warn!("authz invalidation listener connect failed: {}", e),
+            }
+            std::thread::sleep(LISTENER_RETRY_DELAY);
+        }
+    }
+
+    fn listen(&self, conn: &mut PgConnection) -> Result<(), diesel::result::Error> {
+        diesel::sql_query(format!("LISTEN {INVALIDATION_CHANNEL}")).execute(conn)?;
+        self.sync_generation(conn)?;
+
+        let mut last_check = Instant::now();
+        loop {
+            std::thread::sleep(LISTENER_POLL_INTERVAL);
+            for notification in conn.notifications_iter() {
+                let notification = notification?;
+                self.handle_notice(&notification.payload);
+            }
+            if last_check.elapsed() >= GENERATION_CHECK_INTERVAL {
+                self.sync_generation(conn)?;
+                last_check = Instant::now();
+            }
+        }
+    }
+
+    fn role_allows(
+        &self,
+        conn: &mut PgConnection,
+        chat_id: i64,
+        role: &GroupRole,
+        action: Action,
+    ) -> Result<bool, AppError> {
+        let overrides = self.load_cached_chat_role_overrides(conn, chat_id)?;
+        Ok(resolve_role_action(&overrides, role, action))
+    }
+
+    fn load_cached_chat_role_overrides(
+        &self,
+        conn: &mut PgConnection,
+        chat_id: i64,
+    ) -> Result<Arc<ChatRoleOverrides>, AppError> {
+        if let Some(entry) = self.chat_role_cache.get(&chat_id) {
+            if entry.cached_at.elapsed() <= CACHE_TTL {
+                self.metrics.record_authz_cache_lookup("chat_roles", true);
+                return Ok(entry.overrides.clone());
+            }
+        }
+        self.metrics.record_authz_cache_lookup("chat_roles", false);
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let overrides = Arc::new(self.load_chat_role_overrides(conn, chat_id)?);
+
+        if self.generation.load(Ordering::SeqCst) == generation {
+            self.chat_role_cache.insert(
+                chat_id,
+                CachedChatRoleOverrides {
+                    overrides: overrides.clone(),
+                    cached_at: Instant::now(),
+                },
+            );
+        }
+        if self.chat_role_cache.len() > 1024 {
+            self.chat_role_cache
+                .retain(|_, entry| entry.cached_at.elapsed() <= CACHE_TTL);
+        }
+
+        Ok(overrides)
+    }
+
+    fn load_chat_role_overrides(
+        &self,
+        conn: &mut PgConnection,
+        chat_id: i64,
+    ) -> Result<ChatRoleOverrides, AppError> {
+        use crate::schema::chat_role_permissions::dsl as crp_dsl;
+
+        let rows: Vec<(GroupRole, String, bool)> = chat_role_permissions::table
+            .filter(crp_dsl::chat_id.eq(chat_id))
+            .select((crp_dsl::role, crp_dsl::action, crp_dsl::allowed))
+            .load(conn)?;
+        Ok(rows
+            .into_iter()
+            .map(|(role, action, allowed)| ((role, action), allowed))
+            .collect())
+    }
+
+    fn lookup_chat_role(
+        &self,
+        conn: &mut PgConnection,
+        chat_id: i64,
+        uid: i32,
+    ) -> Result<Option<GroupRole>, AppError> {
+        use crate::schema::group_membership::dsl as gm_dsl;
+
+        Ok(group_membership::table
+            .filter(gm_dsl::chat_id.eq(chat_id).and(gm_dsl::uid.eq(uid)))
+            .select(gm_dsl::role)
+            .first(conn)
+            .optional()?)
     }
 
     #[allow(dead_code)]
@@ -156,19 +642,24 @@ impl AuthorizationService {
 
         if let Some(entry) = self.cache.get(&cache_key) {
             if entry.cached_at.elapsed() <= CACHE_TTL {
+                self.metrics.record_authz_cache_lookup("permissions", true);
                 return Ok(entry.actions.clone());
             }
         }
+        self.metrics.record_authz_cache_lookup("permissions", false);
 
+        let generation = self.generation.load(Ordering::SeqCst);
         let actions = Arc::new(self.load_actions_for_resource(conn, subject, resource)?);
 
-        self.cache.insert(
-            cache_key,
-            CachedPermissionSet {
-                actions: actions.clone(),
-                cached_at: Instant::now(),
-            },
-        );
+        if self.generation.load(Ordering::SeqCst) == generation {
+            self.cache.insert(
+                cache_key,
+                CachedPermissionSet {
+                    actions: actions.clone(),
+                    cached_at: Instant::now(),
+                },
+            );
+        }
 
         self.prune_stale_entries();
 
@@ -189,7 +680,6 @@ impl AuthorizationService {
         Err(AppError::Forbidden("Permission required"))
     }
 
-    #[allow(dead_code)]
     pub fn require_service_token_permission(
         &self,
         conn: &mut PgConnection,
@@ -224,52 +714,65 @@ impl AuthorizationService {
         subject: CacheSubject,
         resource: Resource,
     ) -> Result<HashSet<String>, AppError> {
+        Ok(self
+            .load_grants_for_resource(conn, subject, resource)?
+            .into_iter()
+            .map(|grant| grant.action)
+            .collect())
+    }
+
+    /// Every rule scoped exactly to `resource` reaching `subject`, once per assignment
+    /// it is reachable through.
+    fn load_grants_for_resource(
+        &self,
+        conn: &mut PgConnection,
+        subject: CacheSubject,
+        resource: Resource,
+    ) -> Result<Vec<PermissionGrant>, AppError> {
         use crate::schema::policy_assignments::dsl as pa_dsl;
         use crate::schema::policy_permissions::dsl as pp_dsl;
 
-        let policy_ids = match subject {
+        let subjects: Vec<(PolicySubjectType, i64)> = match subject {
             CacheSubject::User {
                 uid,
                 discuz_group_id,
-            } => {
-                let mut policy_ids = policy_assignments::table
+            } => std::iter::once((PolicySubjectType::User, i64::from(uid)))
+                .chain(
+                    discuz_group_id
+                        .map(|group_id| (PolicySubjectType::DiscuzGroup, i64::from(group_id))),
+                )
+                .collect(),
+            CacheSubject::ServiceToken { token_id } => {
+                vec![(PolicySubjectType::ServiceToken, token_id)]
+            }
+        };
+
+        let mut assignments: Vec<(i64, PolicySubjectType, i64, i64)> = Vec::new();
+        for (subject_type, subject_id) in subjects {
+            assignments.extend(
+                policy_assignments::table
                     .filter(
                         pa_dsl::subject_type
-                            .eq(PolicySubjectType::User)
-                            .and(pa_dsl::subject_id.eq(i64::from(uid))),
+                            .eq(subject_type)
+                            .and(pa_dsl::subject_id.eq(subject_id)),
                     )
-                    .select(pa_dsl::policy_id)
-                    .load::<i64>(conn)?;
-
-                if let Some(group_id) = discuz_group_id {
-                    let group_policy_ids = policy_assignments::table
-                        .filter(
-                            pa_dsl::subject_type
-                                .eq(PolicySubjectType::DiscuzGroup)
-                                .and(pa_dsl::subject_id.eq(i64::from(group_id))),
-                        )
-                        .select(pa_dsl::policy_id)
-                        .load::<i64>(conn)?;
-                    policy_ids.extend(group_policy_ids);
-                }
-
-                policy_ids
-            }
-            CacheSubject::ServiceToken { token_id } => policy_assignments::table
-                .filter(
-                    pa_dsl::subject_type
-                        .eq(PolicySubjectType::ServiceToken)
-                        .and(pa_dsl::subject_id.eq(token_id)),
-                )
-                .select(pa_dsl::policy_id)
-                .load::<i64>(conn)?,
-        };
+                    .select((
+                        pa_dsl::id,
+                        pa_dsl::subject_type,
+                        pa_dsl::subject_id,
+                        pa_dsl::policy_id,
+                    ))
+                    .load::<(i64, PolicySubjectType, i64, i64)>(conn)?,
+            );
+        }
 
-        if policy_ids.is_empty() {
-            return Ok(HashSet::new());
+        if assignments.is_empty() {
+            return Ok(Vec::new());
         }
 
+        let policy_ids: Vec<i64> = assignments.iter().map(|row| row.3).collect();
         let mut query = policy_permissions::table
+            .inner_join(policies::table)
             .filter(pp_dsl::policy_id.eq_any(policy_ids))
             .filter(pp_dsl::resource_type.eq(resource.resource_type()))
             .into_boxed();
@@ -279,12 +782,127 @@ impl AuthorizationService {
             Resource::Chat(chat_id) => query.filter(pp_dsl::resource_id.eq(Some(chat_id))),
         };
 
-        let actions = query
-            .select(pp_dsl::action)
-            .distinct()
-            .load::<String>(conn)?;
+        let rules = query
+            .select((
+                pp_dsl::id,
+                pp_dsl::policy_id,
+                policies::name,
+                pp_dsl::action,
+            ))
+            .load::<(i64, i64, String, String)>(conn)?;
+
+        let mut grants = Vec::new();
+        for (permission_id, policy_id, policy_name, action) in rules {
+            for (assignment_id, subject_type, subject_id, _) in assignments
+                .iter()
+                .filter(|assignment| assignment.3 == policy_id)
+            {
+                grants.push(PermissionGrant {
+                    subject_type: *subject_type,
+                    subject_id: *subject_id,
+                    assignment_id: *assignment_id,
+                    policy_id,
+                    policy_name: policy_name.clone(),
+                    permission_id,
+                    action: action.clone(),
+                    resource,
+                });
+            }
+        }
+
+        Ok(grants)
+    }
+
+    /// Explain a decision with uncached reads, following the same rules as
+    /// `has_permission` and `has_service_token_permission`.
+    pub fn explain(
+        &self,
+        conn: &mut PgConnection,
+        subject: ExplainSubject,
+        action: Action,
+        resource: Resource,
+    ) -> Result<PermissionExplanation, AppError> {
+        let (cache_subject, discuz_group_id) = match subject {
+            ExplainSubject::User(uid) => {
+                let discuz_group_id = self.lookup_discuz_group_id(conn, uid)?;
+                (
+                    CacheSubject::User {
+                        uid,
+                        discuz_group_id,
+                    },
+                    discuz_group_id,
+                )
+            }
+            ExplainSubject::ServiceToken(token_id) => {
+                (CacheSubject::ServiceToken { token_id }, None)
+            }
+        };
+        let grants_action = |grant: &PermissionGrant| {
+            grant.action == action.as_str() || grant.action == Action::PermissionAll.as_str()
+        };
+
+        let mut matching_grants: Vec<PermissionGrant> = self
+            .load_grants_for_resource(conn, cache_subject, resource)?
+            .into_iter()
+            .filter(grants_action)
+            .collect();
+        let mut role_decision = None;
+
+        if let (ExplainSubject::User(uid), Resource::Chat(chat_id)) = (subject, resource) {
+            if CHAT_ROLE_ACTIONS.contains(&action) {
+                matching_grants.extend(
+                    self.load_grants_for_resource(conn, cache_subject, Resource::Global)?
+                        .into_iter()
+                        .filter(grants_action),
+                );
+                if let Some(role) = self.lookup_chat_role(conn, chat_id, uid)? {
+                    let overrides = self.load_chat_role_overrides(conn, chat_id)?;
+                    role_decision = Some(RoleDecision {
+                        allowed: resolve_role_action(&overrides, &role, action),
+                        overridden: overrides
+                            .contains_key(&(role.clone(), action.as_str().to_string())),
+                        role,
+                    });
+                }
+            }
+        }
+
+        let deny_reasons = match subject {
+            ExplainSubject::User(_) => Vec::new(),
+            ExplainSubject::ServiceToken(token_id) => {
+                let (revoked_at, expires_at, allowed_chat_ids) = service_tokens::table
+                    .filter(service_tokens::id.eq(token_id))
+                    .select((
+                        service_tokens::revoked_at,
+                        service_tokens::expires_at,
+                        service_tokens::allowed_chat_ids,
+                    ))
+                    .first::<(
+                        Option<DateTime<Utc>>,
+                        Option<DateTime<Utc>>,
+                        Option<Vec<i64>>,
+                    )>(conn)?;
+                service_token_deny_reasons(
+                    revoked_at,
+                    expires_at,
+                    allowed_chat_ids.as_deref(),
+                    resource,
+                    Utc::now(),
+                )
+            }
+        };
 
-        Ok(actions.into_iter().collect())
+        Ok(PermissionExplanation {
+            allowed: deny_reasons.is_empty()
+                && (!matching_grants.is_empty()
+                    || role_decision
+                        .as_ref()
+                        .is_some_and(|decision| decision.allowed)),
+            discuz_group_id,
+            matching_grants,
+            role_decision,
+            deny_reasons,
+        })
     }
 
     fn prune_stale_entries(&self) {
@@ -329,6 +947,13 @@ impl AuthorizationService {
     }
 }
 
+fn resolve_role_action(overrides: &ChatRoleOverrides, role: &GroupRole, action: Action) -> bool {
+    overrides
+        .get(&(role.clone(), action.as_str().to_string()))
+        .copied()
+        .unwrap_or_else(|| action.default_allowed_for_role(role))
+}
+
 impl Resource {
     pub const fn resource_type(self) -> PermissionResourceType {
         match self {
@@ -347,12 +972,104 @@ impl Resource {
 
 #[cfg(test)]
 mod tests {
-    use super::{AuthorizationService, Resource};
+    use super::{
+        resolve_role_action, service_token_deny_reasons, Action, AuthorizationService,
+        ChatRoleOverrides, DenyReason, Resource, ALL_ACTIONS,
+    };
+    use crate::metrics::Metrics;
+    use crate::models::GroupRole;
+    use chrono::{Duration, Utc};
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
 
     fn empty_service() -> AuthorizationService {
         AuthorizationService {
             cache: Default::default(),
+            chat_role_cache: Default::default(),
+            generation: AtomicI64::new(0),
+            metrics: Arc::new(Metrics::new()),
+        }
+    }
+
+    #[test]
+    fn notices_apply_scoped_invalidation_in_sequence() {
+        let service = empty_service();
+        service
+            .insert_cached_permissions_for_test((1, Some(7), Resource::Global), &["chat.create"]);
+        service
+            .insert_cached_permissions_for_test((2, Some(8), Resource::Global), &["chat.create"]);
+
+        service.handle_notice(r#"{"generation":1,"scope":"user","uid":1}"#);
+        assert_eq!(service.cache.len(), 1);
+
+        // Replayed notices are ignored.
+        service
+            .insert_cached_permissions_for_test((1, Some(7), Resource::Global), &["chat.create"]);
+        service.handle_notice(r#"{"generation":1,"scope":"user","uid":1}"#);
+        assert_eq!(service.cache.len(), 2);
+
+        service.handle_notice(r#"{"generation":2,"scope":"discuzGroup","groupId":8}"#);
+        assert_eq!(service.cache.len(), 1);
+    }
+
+    #[test]
+    fn skipped_generation_drops_every_entry() {
+        let service = empty_service();
+        service
+            .insert_cached_permissions_for_test((1, Some(7), Resource::Global), &["chat.create"]);
+        service
+            .insert_cached_permissions_for_test((2, Some(8), Resource::Global), &["chat.create"]);
+
+        service.handle_notice(r#"{"generation":3,"scope":"serviceToken","tokenId":9}"#);
+
+        assert!(service.cache.is_empty());
+        assert_eq!(service.generation.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn chat_role_overrides_take_precedence_over_defaults() {
+        let mut overrides = ChatRoleOverrides::new();
+        assert!(resolve_role_action(
+            &overrides,
+            &GroupRole::Member,
+            Action::ChatSendMedia
+        ));
+        assert!(!resolve_role_action(
+            &overrides,
+            &GroupRole::Member,
+            Action::ChatPin
+        ));
+        assert!(resolve_role_action(
+            &overrides,
+            &GroupRole::Admin,
+            Action::ChatPin
+        ));
+
+        overrides.insert((GroupRole::Member, "chat.pin".to_string()), true);
+        overrides.insert((GroupRole::Member, "chat.sendMedia".to_string()), false);
+        assert!(resolve_role_action(
+            &overrides,
+            &GroupRole::Member,
+            Action::ChatPin
+        ));
+        assert!(!resolve_role_action(
+            &overrides,
+            &GroupRole::Member,
+            Action::ChatSendMedia
+        ));
+        assert!(!resolve_role_action(
+            &overrides,
+            &GroupRole::Member,
+            Action::ChatAnnounce
+        ));
+    }
+
+    #[test]
+    fn parse_accepts_every_action_string() {
+        for action in ALL_ACTIONS {
+            assert_eq!(Action::parse(action.as_str()), Some(action));
         }
+        assert_eq!(Action::parse("chat.unknown"), None);
     }
 
     #[test]
@@ -370,4 +1087,30 @@ mod tests {
 
         assert_eq!(service.cache.len(), 1);
     }
+
+    #[test]
+    fn service_token_deny_reasons_cover_revocation_expiry_and_allowlist() {
+        let now = Utc::now();
+        assert!(service_token_deny_reasons(None, None, None, Resource::Chat(1), now).is_empty());
+        assert!(service_token_deny_reasons(
+            None,
+            Some(now + Duration::hours(1)),
+            Some(&[1, 2]),
+            Resource::Chat(2),
+            now
+        )
+        .is_empty());
+        assert_eq!(
+            service_token_deny_reasons(Some(now), Some(now), Some(&[1]), Resource::Chat(3), now),
+            vec![
+                DenyReason::ServiceTokenRevoked,
+                DenyReason::ServiceTokenExpired,
+                DenyReason::ChatNotAllowed
+            ]
+        );
+        // The allowlist only restricts chat resources.
+        assert!(
+            service_token_deny_reasons(None, None, Some(&[1]), Resource::Global, now).is_empty()
+        );
+    }
 }
@@ -13,6 +13,7 @@ use tracing::{error, info, warn};
 use crate::dto::ws::{BulkDeletedPayload, ServerWsMessage};
 use crate::metrics::Metrics;
 use crate::schema::{attachments, group_membership, messages};
+use crate::services::chat_export::{process_chat_export, ExportStorage};
 use crate::services::message_search::MessageSearchService;
 use crate::services::unread::UnreadService;
 use crate::services::ws_registry::ConnectionRegistry;
@@ -39,6 +40,8 @@ pub enum BackgroundJob {
         target_uid: i32,
         scope: DeleteScope,
     },
+    /// Render a chat's history to JSON and HTML and upload it for download.
+    ExportChat { export_id: i64 },
     // Future variants: CleanupStaleUploads, CompressMedia, etc.
 }
 
@@ -47,6 +50,7 @@ impl BackgroundJob {
     fn kind(&self) -> &'static str {
         match self {
             BackgroundJob::BulkDeleteMessages { .. } => "bulk_delete_messages",
+            BackgroundJob::ExportChat { .. } => "chat_export",
         }
     }
 }
@@ -66,23 +70,36 @@ impl BackgroundService {
         metrics: Arc<Metrics>,
         message_search: Option<Arc<MessageSearchService>>,
         unread_service: Arc<UnreadService>,
+        export_storage: ExportStorage,
     ) -> Arc<Self> {
         let (tx, rx) = mpsc::channel(CHANNEL_BUFFER);
 
         let service = Arc::new(Self { job_tx: tx });
 
         tokio::spawn(async move {
-            supervise_worker(rx, db, ws_registry, metrics, message_search, unread_service).await;
+            supervise_worker(
+                rx,
+                db,
+                ws_registry,
+                metrics,
+                message_search,
+                unread_service,
+                export_storage,
+            )
+            .await;
         });
 
         service
     }
 
-    /// Enqueue a background job. Non-blocking; logs a warning if the channel is full.
-    pub fn enqueue(&self, job: BackgroundJob) {
+    /// Enqueue a background job. Non-blocking; logs a warning and returns
+    /// `false` if the channel is full.
+    pub fn enqueue(&self, job: BackgroundJob) -> bool {
         if let Err(e) = self.job_tx.try_send(job) {
             warn!("Background job channel full, dropping job: {}", e);
+            return false;
         }
+        true
     }
 }
 
@@ -98,6 +115,7 @@ async fn supervise_worker(
     metrics: Arc<Metrics>,
     message_search: Option<Arc<MessageSearchService>>,
     unread_service: Arc<UnreadService>,
+    export_storage: ExportStorage,
 ) {
     loop {
         let worker_result = std::panic::AssertUnwindSafe(run_worker(
@@ -107,6 +125,7 @@ async fn supervise_worker(
             &metrics,
             &message_search,
             &unread_service,
+            &export_storage,
         ))
         .catch_unwind()
         .await;
@@ -137,6 +156,7 @@ async fn run_worker(
     metrics: &Arc<Metrics>,
     message_search: &Option<Arc<MessageSearchService>>,
     unread_service: &Arc<UnreadService>,
+    export_storage: &ExportStorage,
 ) {
     while let Some(job) = rx.recv().await {
         let job_kind = job.kind();
@@ -156,6 +176,9 @@ async fn run_worker(
                 message_search,
                 unread_service,
             ),
+            BackgroundJob::ExportChat { export_id } => {
+                process_chat_export(*export_id, db, export_storage, ws_registry).await
+            }
         };
 
         let duration = started_at.elapsed().as_secs_f64();
@@ -0,0 +1,437 @@
+//! Bot slash commands: per-chat registry, `/name args` parsing and invocation records.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use crate::dto::bot_commands::{BotCommandArg, BotCommandDefinition, BotCommandResponse};
+use crate::errors::AppError;
+use crate::models::{Bot, BotCommand, BotCommandInvocation, NewBotCommand};
+use crate::schema::{bot_command_invocations, bot_commands, bots, group_membership};
+
+const MAX_COMMAND_NAME_CHARS: usize = 32;
+const MAX_DESCRIPTION_CHARS: usize = 256;
+const MAX_COMMANDS_PER_BOT: usize = 50;
+const MAX_ARGS_PER_COMMAND: usize = 10;
+/// How long a bot may reply to an invocation.
+const INVOCATION_TTL_MINUTES: i64 = 15;
+/// Commands one user may invoke per minute, across all chats.
+const MAX_INVOCATIONS_PER_MINUTE: i64 = 10;
+const INVOCATION_RATE_WINDOW_SECS: i64 = 60;
+const INVOCATIONS_RATE_LIMITED: &str = "Too many commands, slow down";
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_COMMAND_NAME_CHARS
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+}
+
+/// Split `/name rest` into the lowercased command name and the text after it.
+/// Returns `None` for anything that is not a well-formed command, e.g. `//` or `/ x`.
+pub fn parse_command_text(text: &str) -> Option<(String, &str)> {
+    let rest = text.trim_start().strip_prefix('/')?;
+    let (name, args) = match rest.find(char::is_whitespace) {
+        Some(idx) => (&rest[..idx], rest[idx..].trim()),
+        None => (rest, ""),
+    };
+    let name = name.to_ascii_lowercase();
+    is_valid_name(&name).then_some((name, args))
+}
+
+/// Bind whitespace-separated words to the declared arguments in order; the last argument
+/// takes the remaining text.
+pub fn bind_args(spec: &[BotCommandArg], text: &str) -> Result<BTreeMap<String, String>, AppError> {
+    let mut values = BTreeMap::new();
+    let mut remaining = text.trim();
+    for (idx, arg) in spec.iter().enumerate() {
+        if remaining.is_empty() {
+            if arg.required {
+                return Err(AppError::BadRequest("Missing required command argument"));
+            }
+            break;
+        }
+        let value = if idx + 1 == spec.len() {
+            std::mem::take(&mut remaining)
+        } else {
+            match remaining.split_once(char::is_whitespace) {
+                Some((word, rest)) => {
+                    remaining = rest.trim_start();
+                    word
+                }
+                None => std::mem::take(&mut remaining),
+            }
+        };
+        values.insert(arg.name.clone(), value.to_string());
+    }
+    Ok(values)
+}
+
+/// Validate a bot's full command list for one chat.
+pub fn normalize_definitions(
+    chat_id: i64,
+    bot_uid: i32,
+    definitions: Vec<BotCommandDefinition>,
+) -> Result<Vec<NewBotCommand>, AppError> {
+    if definitions.len() > MAX_COMMANDS_PER_BOT {
+        return Err(AppError::BadRequest("Too many commands"));
+    }
+    let mut names = HashSet::new();
+    definitions
+        .into_iter()
+        .map(|definition| {
+            let name = definition.name.trim().to_ascii_lowercase();
+            if !is_valid_name(&name) {
+                return Err(AppError::BadRequest(
+                    "Command names must be 1-32 lowercase letters, digits or underscores",
+                ));
+            }
+            if !names.insert(name.clone()) {
+                return Err(AppError::BadRequest("Duplicate command name"));
+            }
+            let description = definition.description.trim().to_string();
+            if description.is_empty() || description.chars().count() > MAX_DESCRIPTION_CHARS {
+                return Err(AppError::BadRequest(
+                    "Command description must be 1-256 characters",
+                ));
+            }
+            let args = normalize_args(definition.args)?;
+            Ok(NewBotCommand {
+                chat_id,
+                name,
+                bot_uid,
+                description,
+                args: serde_json::to_value(args)
+                    .map_err(|_| AppError::Internal("Failed to encode command arguments"))?,
+            })
+        })
+        .collect()
+}
+
+fn normalize_args(args: Vec<BotCommandArg>) -> Result<Vec<BotCommandArg>, AppError> {
+    if args.len() > MAX_ARGS_PER_COMMAND {
+        return Err(AppError::BadRequest("Too many command arguments"));
+    }
+    let mut names = HashSet::new();
+    let mut seen_optional = false;
+    args.into_iter()
+        .map(|arg| {
+            let name = arg.name.trim().to_ascii_lowercase();
+            if !is_valid_name(&name) || !names.insert(name.clone()) {
+                return Err(AppError::BadRequest("Invalid or duplicate argument name"));
+            }
+            if arg.required && seen_optional {
+                return Err(AppError::BadRequest(
+                    "Required arguments must come before optional ones",
+                ));
+            }
+            seen_optional |= !arg.required;
+            let description = arg.description.trim().to_string();
+            if description.chars().count() > MAX_DESCRIPTION_CHARS {
+                return Err(AppError::BadRequest("Argument description is too long"));
+            }
+            Ok(BotCommandArg {
+                name,
+                description,
+                required: arg.required,
+            })
+        })
+        .collect()
+}
+
+pub fn command_args(command: &BotCommand) -> Vec<BotCommandArg> {
+    serde_json::from_value(command.args.clone()).unwrap_or_default()
+}
+
+/// Commands of bots that are still members of the chat, sorted by name.
+pub fn list_commands(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    prefix: Option<&str>,
+) -> QueryResult<Vec<(BotCommand, Bot)>> {
+    let prefix = prefix
+        .map(|prefix| prefix.trim().trim_start_matches('/').to_ascii_lowercase())
+        .unwrap_or_default();
+    let commands = bot_commands::table
+        .inner_join(bots::table)
+        .filter(bot_commands::chat_id.eq(chat_id))
+        .filter(diesel::dsl::exists(
+            group_membership::table
+                .filter(group_membership::chat_id.eq(bot_commands::chat_id))
+                .filter(group_membership::uid.eq(bot_commands::bot_uid)),
+        ))
+        .order(bot_commands::name.asc())
+        .select((BotCommand::as_select(), Bot::as_select()))
+        .load::<(BotCommand, Bot)>(conn)?;
+    Ok(commands
+        .into_iter()
+        .filter(|(command, _)| command.name.starts_with(&prefix))
+        .collect())
+}
+
+/// The command `name` in the chat, if its bot is still a member.
+pub fn find_command(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    name: &str,
+) -> QueryResult<Option<(BotCommand, Bot)>> {
+    bot_commands::table
+        .inner_join(bots::table)
+        .filter(bot_commands::chat_id.eq(chat_id))
+        .filter(bot_commands::name.eq(name))
+        .filter(diesel::dsl::exists(
+            group_membership::table
+                .filter(group_membership::chat_id.eq(bot_commands::chat_id))
+                .filter(group_membership::uid.eq(bot_commands::bot_uid)),
+        ))
+        .select((BotCommand::as_select(), Bot::as_select()))
+        .first(conn)
+        .optional()
+}
+
+/// Replace every command `bot_uid` declared in the chat. Names held by bots that have
+/// since left the chat are released; names held by current members are a conflict.
+pub fn replace_commands(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    bot_uid: i32,
+    commands: Vec<NewBotCommand>,
+) -> Result<(), AppError> {
+    conn.transaction::<_, AppError, _>(|conn| {
+        diesel::delete(
+            bot_commands::table
+                .filter(bot_commands::chat_id.eq(chat_id))
+                .filter(bot_commands::bot_uid.eq(bot_uid)),
+        )
+        .execute(conn)?;
+        diesel::delete(
+            bot_commands::table
+                .filter(bot_commands::chat_id.eq(chat_id))
+                .filter(diesel::dsl::not(diesel::dsl::exists(
+                    group_membership::table
+                        .filter(group_membership::chat_id.eq(bot_commands::chat_id))
+                        .filter(group_membership::uid.eq(bot_commands::bot_uid)),
+                ))),
+        )
+        .execute(conn)?;
+
+        let names: Vec<&str> = commands
+            .iter()
+            .map(|command| command.name.as_str())
+            .collect();
+        let taken: i64 = bot_commands::table
+            .filter(bot_commands::chat_id.eq(chat_id))
+            .filter(bot_commands::name.eq_any(&names))
+            .count()
+            .get_result(conn)?;
+        if taken > 0 {
+            return Err(AppError::Conflict(
+                "Command name is already used by another bot in this chat",
+            ));
+        }
+
+        diesel::insert_into(bot_commands::table)
+            .values(&commands)
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+/// Record an invocation the bot may reply to until it expires, pruning expired ones.
+/// Reject with 429 once `uid` has invoked [`MAX_INVOCATIONS_PER_MINUTE`] commands
+/// within the last minute. Invocation records outlive the window, so they double as
+/// the counter.
+pub fn check_invocation_rate(conn: &mut PgConnection, uid: i32) -> Result<(), AppError> {
+    let now = Utc::now();
+    let window_start = now - Duration::seconds(INVOCATION_RATE_WINDOW_SECS);
+    let (recent, oldest): (i64, Option<DateTime<Utc>>) = bot_command_invocations::table
+        .filter(bot_command_invocations::invoker_uid.eq(uid))
+        .filter(bot_command_invocations::created_at.gt(window_start))
+        .select((
+            diesel::dsl::count_star(),
+            diesel::dsl::min(bot_command_invocations::created_at),
+        ))
+        .first(conn)?;
+    match invocation_retry_after(recent, oldest, now) {
+        Some(retry_after_secs) => Err(AppError::TooManyRequests {
+            message: INVOCATIONS_RATE_LIMITED,
+            retry_after_secs,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Seconds until the oldest invocation in a full window ages out of it.
+fn invocation_retry_after(
+    recent: i64,
+    oldest: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<u64> {
+    if recent < MAX_INVOCATIONS_PER_MINUTE {
+        return None;
+    }
+    let elapsed = oldest.map_or(0, |oldest| (now - oldest).num_seconds().max(0));
+    Some((INVOCATION_RATE_WINDOW_SECS - elapsed).max(1) as u64)
+}
+
+pub fn create_invocation(
+    conn: &mut PgConnection,
+    id: i64,
+    command: &BotCommand,
+    invoker_uid: i32,
+    topic_id: Option<i64>,
+    reply_to_id: Option<i64>,
+) -> QueryResult<BotCommandInvocation> {
+    let now = Utc::now();
+    diesel::delete(
+        bot_command_invocations::table.filter(bot_command_invocations::expires_at.le(now)),
+    )
+    .execute(conn)?;
+    diesel::insert_into(bot_command_invocations::table)
+        .values(&BotCommandInvocation {
+            id,
+            chat_id: command.chat_id,
+            bot_uid: command.bot_uid,
+            command: command.name.clone(),
+            invoker_uid,
+            topic_id,
+            reply_to_id,
+            created_at: now,
+            expires_at: invocation_expiry(now),
+        })
+        .returning(BotCommandInvocation::as_returning())
+        .get_result(conn)
+}
+
+fn invocation_expiry(created_at: DateTime<Utc>) -> DateTime<Utc> {
+    created_at + Duration::minutes(INVOCATION_TTL_MINUTES)
+}
+
+/// Load an invocation addressed to `bot_uid`, rejecting expired ones.
+pub fn load_invocation_for_bot(
+    conn: &mut PgConnection,
+    id: i64,
+    bot_uid: i32,
+) -> Result<BotCommandInvocation, AppError> {
+    let invocation = bot_command_invocations::table
+        .filter(bot_command_invocations::id.eq(id))
+        .filter(bot_command_invocations::bot_uid.eq(bot_uid))
+        .select(BotCommandInvocation::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Command invocation not found"))?;
+    if invocation.expires_at <= Utc::now() {
+        return Err(AppError::Gone("Command invocation has expired"));
+    }
+    Ok(invocation)
+}
+
+pub fn command_to_response(command: BotCommand, bot: &Bot) -> BotCommandResponse {
+    BotCommandResponse {
+        args: command_args(&command),
+        name: command.name,
+        description: command.description,
+        bot_uid: bot.uid,
+        bot_name: bot.name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        bind_args, invocation_retry_after, normalize_definitions, parse_command_text,
+        MAX_INVOCATIONS_PER_MINUTE,
+    };
+    use crate::dto::bot_commands::{BotCommandArg, BotCommandDefinition};
+    use crate::errors::AppError;
+    use chrono::{Duration, Utc};
+
+    fn arg(name: &str, required: bool) -> BotCommandArg {
+        BotCommandArg {
+            name: name.to_string(),
+            description: String::new(),
+            required,
+        }
+    }
+
+    #[test]
+    fn parses_command_name_and_rest() {
+        assert_eq!(
+            parse_command_text("/Deploy  prod now "),
+            Some(("deploy".to_string(), "prod now"))
+        );
+        assert_eq!(
+            parse_command_text("  /ping"),
+            Some(("ping".to_string(), ""))
+        );
+        assert_eq!(parse_command_text("//ping"), None);
+        assert_eq!(parse_command_text("/ ping"), None);
+        assert_eq!(parse_command_text("ping /now"), None);
+        assert_eq!(parse_command_text("/déploy"), None);
+    }
+
+    #[test]
+    fn binds_positional_args_and_rest() {
+        let spec = [arg("env", true), arg("note", false)];
+        let bound = bind_args(&spec, "prod  ship it  ").unwrap();
+        assert_eq!(bound["env"], "prod");
+        assert_eq!(bound["note"], "ship it");
+
+        let bound = bind_args(&spec, "staging").unwrap();
+        assert_eq!(bound.len(), 1);
+        assert!(matches!(
+            bind_args(&spec, "   "),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(bind_args(&[], "anything goes").unwrap().is_empty());
+    }
+
+    #[test]
+    fn normalize_definitions_rejects_bad_commands() {
+        let definition = |name: &str, args: Vec<BotCommandArg>| BotCommandDefinition {
+            name: name.to_string(),
+            description: "Does a thing".to_string(),
+            args,
+        };
+
+        let commands =
+            normalize_definitions(1, -1, vec![definition(" Deploy ", vec![arg("env", true)])])
+                .unwrap();
+        assert_eq!(commands[0].name, "deploy");
+
+        assert!(normalize_definitions(1, -1, vec![definition("bad name", vec![])]).is_err());
+        assert!(normalize_definitions(
+            1,
+            -1,
+            vec![definition("a", vec![]), definition("A", vec![])]
+        )
+        .is_err());
+        assert!(normalize_definitions(
+            1,
+            -1,
+            vec![definition("a", vec![arg("x", false), arg("y", true)])]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn invocation_rate_waits_for_the_oldest_invocation_to_age_out() {
+        let now = Utc::now();
+        let oldest = Some(now - Duration::seconds(45));
+        assert_eq!(
+            invocation_retry_after(MAX_INVOCATIONS_PER_MINUTE - 1, oldest, now),
+            None
+        );
+        assert_eq!(
+            invocation_retry_after(MAX_INVOCATIONS_PER_MINUTE, oldest, now),
+            Some(15)
+        );
+        assert_eq!(
+            invocation_retry_after(MAX_INVOCATIONS_PER_MINUTE, Some(now), now),
+            Some(60)
+        );
+    }
+}
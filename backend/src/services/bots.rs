@@ -0,0 +1,98 @@
+use diesel::prelude::*;
+use diesel::PgConnection;
+use std::collections::HashMap;
+
+use crate::errors::AppError;
+use crate::models::Bot;
+use crate::schema::bots;
+
+const MAX_BOT_NAME_CHARS: usize = 64;
+const MAX_BOT_AVATAR_URL_LEN: usize = 2048;
+
+/// Bot uids are allocated from a negative sequence, so they never overlap Discuz uids.
+pub fn is_bot_uid(uid: i32) -> bool {
+    uid < 0
+}
+
+pub fn lookup_bots(conn: &mut PgConnection, uids: &[i32]) -> QueryResult<HashMap<i32, Bot>> {
+    let bot_uids: Vec<i32> = uids
+        .iter()
+        .copied()
+        .filter(|uid| is_bot_uid(*uid))
+        .collect();
+    if bot_uids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(bots::table
+        .filter(bots::uid.eq_any(&bot_uids))
+        .select(Bot::as_select())
+        .load::<Bot>(conn)?
+        .into_iter()
+        .map(|bot| (bot.uid, bot))
+        .collect())
+}
+
+/// Client id of the WebSocket sessions a service token opens for its bot.
+pub fn ws_client_id(service_token_id: i64) -> String {
+    format!("service-token:{service_token_id}")
+}
+
+pub fn find_bot_for_service_token(
+    conn: &mut PgConnection,
+    service_token_id: i64,
+) -> QueryResult<Option<Bot>> {
+    bots::table
+        .filter(bots::service_token_id.eq(service_token_id))
+        .select(Bot::as_select())
+        .first::<Bot>(conn)
+        .optional()
+}
+
+pub fn normalize_bot_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("Bot name is required"));
+    }
+    if name.chars().count() > MAX_BOT_NAME_CHARS {
+        return Err(AppError::BadRequest("Bot name is too long"));
+    }
+    Ok(name.to_string())
+}
+
+pub fn normalize_bot_avatar_url(avatar_url: Option<&str>) -> Result<Option<String>, AppError> {
+    let Some(avatar_url) = avatar_url.map(str::trim).filter(|url| !url.is_empty()) else {
+        return Ok(None);
+    };
+    if avatar_url.len() > MAX_BOT_AVATAR_URL_LEN {
+        return Err(AppError::BadRequest("Avatar URL is too long"));
+    }
+    if !avatar_url.starts_with("https://") && !avatar_url.starts_with("http://") {
+        return Err(AppError::BadRequest("Avatar URL must be http(s)"));
+    }
+    Ok(Some(avatar_url.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_bot_avatar_url, normalize_bot_name};
+    use crate::errors::AppError;
+
+    #[test]
+    fn normalize_bot_fields() {
+        assert_eq!(normalize_bot_name("  Deploy bot ").unwrap(), "Deploy bot");
+        assert!(matches!(
+            normalize_bot_name(&"x".repeat(65)),
+            Err(AppError::BadRequest(_))
+        ));
+        assert_eq!(normalize_bot_avatar_url(Some("  ")).unwrap(), None);
+        assert_eq!(
+            normalize_bot_avatar_url(Some("https://cdn.example/bot.png")).unwrap(),
+            Some("https://cdn.example/bot.png".to_string())
+        );
+        assert!(matches!(
+            normalize_bot_avatar_url(Some("javascript:alert(1)")),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
@@ -2,9 +2,16 @@ use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel::PgConnection;
 
-use crate::schema::group_membership;
+use crate::models::{GroupRole, MessageType};
+use crate::schema::{group_membership, groups, messages};
 use crate::services::unread::UnreadService;
 
+/// Upper bound for a group's slow-mode interval: 1 hour.
+pub const MAX_SLOW_MODE_SECS: i32 = 3600;
+
+/// How many chats a user may pin to the top of their chat list.
+pub const MAX_PINNED_CHATS: usize = 10;
+
 pub fn indefinite_mute_until() -> DateTime<Utc> {
     DateTime::parse_from_rfc3339("9999-12-31T23:59:59Z")
         .expect("valid indefinite mute timestamp")
@@ -71,12 +78,197 @@ pub fn mark_chat_as_read_state(
     })
 }
 
+/// The user's pinned chat ids in display order. Locks all of the user's membership rows
+/// until the transaction ends, so concurrent pin changes see each other's result.
+pub fn lock_pinned_chat_ids(
+    conn: &mut PgConnection,
+    uid: i32,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    use crate::schema::group_membership::dsl as gm_dsl;
+
+    let rows: Vec<(i64, Option<i32>)> = group_membership::table
+        .filter(gm_dsl::uid.eq(uid))
+        .order((gm_dsl::pin_order.asc(), gm_dsl::chat_id.desc()))
+        .select((gm_dsl::chat_id, gm_dsl::pin_order))
+        .for_update()
+        .load(conn)?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(chat_id, pin_order)| pin_order.map(|_| chat_id))
+        .collect())
+}
+
+/// Rewrite `pin_order` so the given chats are pinned in exactly this order and every
+/// other chat of the user is unpinned.
+pub fn store_pinned_chat_order(
+    conn: &mut PgConnection,
+    uid: i32,
+    chat_ids: &[i64],
+) -> Result<(), diesel::result::Error> {
+    use crate::schema::group_membership::dsl as gm_dsl;
+
+    conn.transaction(|conn| {
+        diesel::update(
+            group_membership::table
+                .filter(gm_dsl::uid.eq(uid))
+                .filter(gm_dsl::pin_order.is_not_null())
+                .filter(gm_dsl::chat_id.ne_all(chat_ids)),
+        )
+        .set(gm_dsl::pin_order.eq(None::<i32>))
+        .execute(conn)?;
+
+        for (index, chat_id) in chat_ids.iter().enumerate() {
+            diesel::update(
+                group_membership::table
+                    .filter(gm_dsl::chat_id.eq(*chat_id).and(gm_dsl::uid.eq(uid))),
+            )
+            .set(gm_dsl::pin_order.eq(Some(index as i32)))
+            .execute(conn)?;
+        }
+        Ok(())
+    })
+}
+
+/// True when `requested` is a reordering of `current`: same chats, no duplicates.
+pub fn is_pin_reorder(current: &[i64], requested: &[i64]) -> bool {
+    let mut current = current.to_vec();
+    let mut requested = requested.to_vec();
+    current.sort_unstable();
+    requested.sort_unstable();
+    let before = requested.len();
+    requested.dedup();
+    before == requested.len() && current == requested
+}
+
+/// Returns how many seconds the sender must still wait before posting again, or `None`
+/// when slow mode is off or the interval has already elapsed.
+pub fn slow_mode_retry_after(
+    interval_secs: i32,
+    last_sent_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<u64> {
+    if interval_secs <= 0 {
+        return None;
+    }
+    let last_sent_at = last_sent_at?;
+    let ready_at = last_sent_at + chrono::Duration::seconds(i64::from(interval_secs));
+    if now >= ready_at {
+        return None;
+    }
+
+    // Round up so clients never retry a fraction of a second too early.
+    let remaining_ms = (ready_at - now).num_milliseconds();
+    Some(((remaining_ms + 999) / 1000).max(1) as u64)
+}
+
+/// Enforce the group's slow-mode interval for a non-admin sender.
+///
+/// Top-level messages and thread replies are throttled independently, each against the
+/// sender's most recent message of the same kind. System messages never count.
+pub fn check_slow_mode(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    uid: i32,
+    is_thread_reply: bool,
+) -> Result<Option<u64>, diesel::result::Error> {
+    use crate::schema::group_membership::dsl as gm_dsl;
+    use crate::schema::groups::dsl as g_dsl;
+    use crate::schema::messages::dsl as m_dsl;
+
+    let Some((slow_mode_secs, thread_slow_mode_secs)) = groups::table
+        .filter(g_dsl::id.eq(chat_id))
+        .select((g_dsl::slow_mode_secs, g_dsl::thread_slow_mode_secs))
+        .first::<(i32, i32)>(conn)
+        .optional()?
+    else {
+        return Ok(None);
+    };
+    let interval_secs = if is_thread_reply {
+        thread_slow_mode_secs
+    } else {
+        slow_mode_secs
+    };
+    if interval_secs <= 0 {
+        return Ok(None);
+    }
+
+    let role: Option<GroupRole> = group_membership::table
+        .filter(gm_dsl::chat_id.eq(chat_id).and(gm_dsl::uid.eq(uid)))
+        .select(gm_dsl::role)
+        .first(conn)
+        .optional()?;
+    if role == Some(GroupRole::Admin) {
+        return Ok(None);
+    }
+
+    // Served by `idx_messages_chat_sender_created`. Deleted messages still count, so
+    // deleting a message does not reset the wait.
+    let mut last_sent_query = messages::table
+        .filter(m_dsl::chat_id.eq(chat_id))
+        .filter(m_dsl::sender_uid.eq(uid))
+        .filter(m_dsl::message_type.ne(MessageType::System))
+        .into_boxed();
+    last_sent_query = if is_thread_reply {
+        last_sent_query.filter(m_dsl::reply_root_id.is_not_null())
+    } else {
+        last_sent_query.filter(m_dsl::reply_root_id.is_null())
+    };
+    let last_sent_at: Option<DateTime<Utc>> = last_sent_query
+        .order(m_dsl::created_at.desc())
+        .select(m_dsl::created_at)
+        .first(conn)
+        .optional()?;
+
+    Ok(slow_mode_retry_after(
+        interval_secs,
+        last_sent_at,
+        Utc::now(),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{is_pin_reorder, slow_mode_retry_after};
     use crate::constants::MAX_UNREAD_COUNT;
+    use chrono::{Duration, TimeZone, Utc};
 
     #[test]
     fn unread_count_cap_matches_display_overflow_boundary() {
         assert_eq!(MAX_UNREAD_COUNT, 1000);
     }
+
+    #[test]
+    fn pin_reorder_requires_same_chats_without_duplicates() {
+        assert!(is_pin_reorder(&[1, 2, 3], &[3, 1, 2]));
+        assert!(is_pin_reorder(&[], &[]));
+        assert!(!is_pin_reorder(&[1, 2], &[1]));
+        assert!(!is_pin_reorder(&[1, 2], &[1, 2, 4]));
+        assert!(!is_pin_reorder(&[1, 2], &[1, 1]));
+    }
+
+    #[test]
+    fn slow_mode_is_inactive_when_interval_is_zero() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        assert_eq!(slow_mode_retry_after(0, Some(now), now), None);
+    }
+
+    #[test]
+    fn slow_mode_allows_first_message() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        assert_eq!(slow_mode_retry_after(30, None, now), None);
+    }
+
+    #[test]
+    fn slow_mode_reports_remaining_seconds_rounded_up() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let last_sent_at = now - Duration::milliseconds(10_500);
+        assert_eq!(slow_mode_retry_after(30, Some(last_sent_at), now), Some(20));
+    }
+
+    #[test]
+    fn slow_mode_allows_message_once_interval_elapsed() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let last_sent_at = now - Duration::seconds(30);
+        assert_eq!(slow_mode_retry_after(30, Some(last_sent_at), now), None);
+    }
 }
@@ -0,0 +1,722 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use aws_sdk_s3::primitives::ByteStream;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::PgConnection;
+use serde::Serialize;
+
+use crate::dto::chat_exports::{ChatExportDownload, ChatExportResponse};
+use crate::dto::ws::ServerWsMessage;
+use crate::models::{
+    Attachment, ChatExport, ChatExportStatus, Group, Message, MessageReaction, MessageType,
+    PinnedMessage,
+};
+use crate::schema::{
+    attachments, chat_exports, groups, message_reactions, messages, pinned_messages,
+};
+use crate::services::media::{presign_download, public_object_url, upload_private_object};
+use crate::services::user::lookup_user_profiles;
+use crate::services::ws_registry::ConnectionRegistry;
+
+/// Storage key prefix for generated archives; objects are private and only
+/// reachable through presigned links.
+const EXPORT_KEY_PREFIX: &str = "chat-exports";
+const EXPORT_BATCH_SIZE: i64 = 1000;
+pub const MAX_EXPORT_MESSAGES: usize = 100_000;
+pub const EXPORT_LINK_TTL_HOURS: i64 = 24;
+/// Exports still pending or running this long after creation are assumed lost
+/// (the in-memory job queue does not survive restarts) and are failed.
+pub const EXPORT_STALE_AFTER_MINS: i64 = 60;
+
+/// S3 settings the background worker needs; it runs without an `AppState`.
+#[derive(Clone)]
+pub struct ExportStorage {
+    pub s3_client: aws_sdk_s3::Client,
+    pub bucket: String,
+    pub public_base_url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatExportDocument {
+    pub chat: ExportedChat,
+    pub exported_at: DateTime<Utc>,
+    pub exported_by_uid: i32,
+    /// True when the message cap was reached before the end of the history.
+    pub truncated: bool,
+    pub messages: Vec<ExportedMessage>,
+    pub pins: Vec<ExportedPin>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedChat {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedMessage {
+    pub id: String,
+    pub sender_uid: i32,
+    pub sender_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub is_deleted: bool,
+    pub message_type: MessageType,
+    pub text: Option<String>,
+    pub reply_to_id: Option<String>,
+    pub thread_root_id: Option<String>,
+    pub topic_id: Option<String>,
+    pub attachments: Vec<ExportedAttachment>,
+    pub reactions: Vec<ExportedReaction>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedAttachment {
+    pub file_name: String,
+    pub kind: String,
+    pub size: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedReaction {
+    pub emoji: String,
+    pub user_uids: Vec<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedPin {
+    pub message_id: String,
+    pub pinned_by_uid: i32,
+    pub pinned_at: DateTime<Utc>,
+}
+
+pub fn load_chat_export(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    export_id: i64,
+) -> Result<Option<ChatExport>, diesel::result::Error> {
+    chat_exports::table
+        .filter(chat_exports::id.eq(export_id))
+        .filter(chat_exports::chat_id.eq(chat_id))
+        .select(ChatExport::as_select())
+        .first(conn)
+        .optional()
+}
+
+pub fn has_export_in_progress(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    requester_uid: i32,
+) -> Result<bool, diesel::result::Error> {
+    let in_progress: i64 = chat_exports::table
+        .filter(chat_exports::chat_id.eq(chat_id))
+        .filter(chat_exports::requester_uid.eq(requester_uid))
+        .filter(chat_exports::status.eq_any([ChatExportStatus::Pending, ChatExportStatus::Running]))
+        .count()
+        .get_result(conn)?;
+    Ok(in_progress > 0)
+}
+
+/// Fail pending or running exports older than [`EXPORT_STALE_AFTER_MINS`],
+/// so a job lost to a restart does not block new requests forever.
+pub fn reap_stale_exports(conn: &mut PgConnection) -> Result<usize, diesel::result::Error> {
+    let cutoff = Utc::now() - Duration::minutes(EXPORT_STALE_AFTER_MINS);
+    diesel::update(
+        chat_exports::table
+            .filter(
+                chat_exports::status.eq_any([ChatExportStatus::Pending, ChatExportStatus::Running]),
+            )
+            .filter(chat_exports::created_at.lt(cutoff)),
+    )
+    .set((
+        chat_exports::status.eq(ChatExportStatus::Failed),
+        chat_exports::error.eq(Some("Export timed out")),
+        chat_exports::completed_at.eq(Some(Utc::now())),
+    ))
+    .execute(conn)
+}
+
+pub fn mark_export_failed(
+    conn: &mut PgConnection,
+    export_id: i64,
+    error: &str,
+) -> Result<(), diesel::result::Error> {
+    // Finished exports keep their outcome, including a reaper's earlier failure reason.
+    diesel::update(
+        chat_exports::table
+            .filter(chat_exports::id.eq(export_id))
+            .filter(
+                chat_exports::status.eq_any([ChatExportStatus::Pending, ChatExportStatus::Running]),
+            ),
+    )
+    .set((
+        chat_exports::status.eq(ChatExportStatus::Failed),
+        chat_exports::error.eq(Some(error)),
+        chat_exports::completed_at.eq(Some(Utc::now())),
+    ))
+    .execute(conn)?;
+    Ok(())
+}
+
+/// Build the API view of an export, presigning fresh download links when it is done.
+pub async fn build_export_response(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    export: ChatExport,
+) -> Result<ChatExportResponse, String> {
+    let download = match (&export.status, &export.json_key, &export.html_key) {
+        (ChatExportStatus::Completed, Some(json_key), Some(html_key)) => {
+            let ttl = Duration::hours(EXPORT_LINK_TTL_HOURS);
+            let json_url = presign_download(s3_client, bucket, json_key, ttl).await?;
+            let html_url = presign_download(s3_client, bucket, html_key, ttl).await?;
+            Some(ChatExportDownload {
+                json_url,
+                html_url,
+                expires_at: Utc::now() + ttl,
+            })
+        }
+        _ => None,
+    };
+
+    Ok(ChatExportResponse {
+        id: export.id,
+        chat_id: export.chat_id,
+        status: export.status,
+        include_deleted: export.include_deleted,
+        message_count: export.message_count,
+        error: export.error,
+        created_at: export.created_at,
+        completed_at: export.completed_at,
+        download,
+    })
+}
+
+/// Background job entry point: build the archive, upload it, and notify the requester.
+pub async fn process_chat_export(
+    export_id: i64,
+    db: &Pool<ConnectionManager<PgConnection>>,
+    storage: &ExportStorage,
+    ws_registry: &Arc<ConnectionRegistry>,
+) -> Result<(), String> {
+    let result = run_chat_export(export_id, db, storage).await;
+    if let Err(e) = &result {
+        let mut conn = db.get().map_err(|e| format!("pool error: {e}"))?;
+        mark_export_failed(&mut conn, export_id, "Export failed")
+            .map_err(|db_err| format!("{e}; additionally failed to mark export: {db_err}"))?;
+    }
+
+    let export = {
+        let mut conn = db.get().map_err(|e| format!("pool error: {e}"))?;
+        chat_exports::table
+            .filter(chat_exports::id.eq(export_id))
+            .select(ChatExport::as_select())
+            .first(&mut conn)
+            .map_err(|e| format!("db error: {e}"))?
+    };
+    let requester_uid = export.requester_uid;
+    match build_export_response(&storage.s3_client, &storage.bucket, export).await {
+        Ok(response) => {
+            ws_registry.broadcast_to_uids(
+                &[requester_uid],
+                Arc::new(ServerWsMessage::ChatExportUpdated(response)),
+            );
+        }
+        Err(e) => tracing::warn!(export_id, "failed to build export notification: {}", e),
+    }
+
+    result
+}
+
+async fn run_chat_export(
+    export_id: i64,
+    db: &Pool<ConnectionManager<PgConnection>>,
+    storage: &ExportStorage,
+) -> Result<(), String> {
+    let map_db = |e: diesel::result::Error| format!("db error: {e}");
+
+    let (export, document) = {
+        let mut conn = db.get().map_err(|e| format!("pool error: {e}"))?;
+        // Only a pending export may start; a reaped one stays failed.
+        let export: ChatExport = diesel::update(
+            chat_exports::table
+                .filter(chat_exports::id.eq(export_id))
+                .filter(chat_exports::status.eq(ChatExportStatus::Pending)),
+        )
+        .set(chat_exports::status.eq(ChatExportStatus::Running))
+        .returning(ChatExport::as_returning())
+        .get_result(&mut conn)
+        .optional()
+        .map_err(map_db)?
+        .ok_or_else(|| "export is no longer pending".to_string())?;
+        let document = load_export_document(&mut conn, &export, storage).map_err(map_db)?;
+        (export, document)
+    };
+
+    let json = serde_json::to_vec_pretty(&document).map_err(|e| format!("serialize: {e}"))?;
+    let html = render_export_html(&document);
+
+    let key_base = format!("{}/{}/{}", EXPORT_KEY_PREFIX, export.chat_id, export.id);
+    let json_key = format!("{key_base}.json");
+    let html_key = format!("{key_base}.html");
+    upload_private_object(
+        &storage.s3_client,
+        &storage.bucket,
+        &json_key,
+        "application/json",
+        ByteStream::from(json),
+    )
+    .await?;
+    upload_private_object(
+        &storage.s3_client,
+        &storage.bucket,
+        &html_key,
+        "text/html; charset=utf-8",
+        ByteStream::from(html.into_bytes()),
+    )
+    .await?;
+
+    let mut conn = db.get().map_err(|e| format!("pool error: {e}"))?;
+    // A reaper may have failed the export while it was uploading; leave that outcome alone.
+    let updated = diesel::update(
+        chat_exports::table
+            .filter(chat_exports::id.eq(export_id))
+            .filter(chat_exports::status.eq(ChatExportStatus::Running)),
+    )
+    .set((
+        chat_exports::status.eq(ChatExportStatus::Completed),
+        chat_exports::json_key.eq(Some(json_key)),
+        chat_exports::html_key.eq(Some(html_key)),
+        chat_exports::message_count.eq(Some(document.messages.len() as i32)),
+        chat_exports::completed_at.eq(Some(Utc::now())),
+    ))
+    .execute(&mut conn)
+    .map_err(map_db)?;
+    if updated == 0 {
+        return Err("export was abandoned before it completed".to_string());
+    }
+
+    tracing::info!(
+        export_id,
+        chat_id = export.chat_id,
+        message_count = document.messages.len(),
+        "Chat export completed"
+    );
+    Ok(())
+}
+
+/// Batch size for the next page, capped so the page after the export limit is a single
+/// probe row that only tells whether the history was truncated.
+fn export_batch_limit(loaded: usize) -> i64 {
+    let remaining = MAX_EXPORT_MESSAGES.saturating_sub(loaded) as i64 + 1;
+    remaining.min(EXPORT_BATCH_SIZE)
+}
+
+fn load_export_document(
+    conn: &mut PgConnection,
+    export: &ChatExport,
+    storage: &ExportStorage,
+) -> Result<ChatExportDocument, diesel::result::Error> {
+    let group: Group = groups::table
+        .filter(groups::id.eq(export.chat_id))
+        .select(Group::as_select())
+        .first(conn)?;
+
+    let mut rows: Vec<Message> = Vec::new();
+    let mut cursor = 0_i64;
+    let mut truncated = false;
+    loop {
+        let limit = export_batch_limit(rows.len());
+        let mut query = messages::table
+            .filter(messages::chat_id.eq(export.chat_id))
+            .filter(messages::is_published.eq(true))
+            .filter(messages::id.gt(cursor))
+            .into_boxed();
+        if !export.include_deleted {
+            query = query.filter(messages::deleted_at.is_null());
+        }
+        let batch: Vec<Message> = query
+            .order(messages::id.asc())
+            .limit(limit)
+            .select(Message::as_select())
+            .load(conn)?;
+        let batch_len = batch.len() as i64;
+        if let Some(last) = batch.last() {
+            cursor = last.id;
+        }
+        rows.extend(batch);
+        // The cap is only hit when a row exists past it; an exact fit is not truncated.
+        if rows.len() > MAX_EXPORT_MESSAGES {
+            truncated = true;
+            rows.truncate(MAX_EXPORT_MESSAGES);
+            break;
+        }
+        if batch_len < limit {
+            break;
+        }
+    }
+
+    let mut attachments_by_message: HashMap<i64, Vec<ExportedAttachment>> = HashMap::new();
+    let mut reactions_by_message: HashMap<i64, BTreeMap<String, Vec<i32>>> = HashMap::new();
+    for chunk in rows.chunks(EXPORT_BATCH_SIZE as usize) {
+        let ids: Vec<i64> = chunk
+            .iter()
+            .filter(|m| m.deleted_at.is_none())
+            .map(|m| m.id)
+            .collect();
+
+        let chunk_attachments: Vec<Attachment> = attachments::table
+            .filter(attachments::message_id.eq_any(&ids))
+            .filter(attachments::deleted_at.is_null())
+            .order((attachments::message_id.asc(), attachments::order.asc()))
+            .select(Attachment::as_select())
+            .load(conn)?;
+        for attachment in chunk_attachments {
+            let Some(message_id) = attachment.message_id else {
+                continue;
+            };
+            attachments_by_message
+                .entry(message_id)
+                .or_default()
+                .push(ExportedAttachment {
+                    url: public_object_url(
+                        storage.public_base_url.as_deref(),
+                        &storage.bucket,
+                        &attachment.external_reference,
+                    ),
+                    file_name: attachment.file_name,
+                    kind: attachment.kind,
+                    size: attachment.size,
+                    width: attachment.width,
+                    height: attachment.height,
+                });
+        }
+
+        let chunk_reactions: Vec<MessageReaction> = message_reactions::table
+            .filter(message_reactions::message_id.eq_any(&ids))
+            .order(message_reactions::created_at.asc())
+            .select(MessageReaction::as_select())
+            .load(conn)?;
+        for reaction in chunk_reactions {
+            reactions_by_message
+                .entry(reaction.message_id)
+                .or_default()
+                .entry(reaction.emoji)
+                .or_default()
+                .push(reaction.user_uid);
+        }
+    }
+
+    let mut sender_uids: Vec<i32> = rows.iter().map(|m| m.sender_uid).collect();
+    sender_uids.sort_unstable();
+    sender_uids.dedup();
+    let profiles = lookup_user_profiles(conn, &sender_uids)?;
+
+    let now = Utc::now();
+    let pins: Vec<PinnedMessage> = pinned_messages::table
+        .filter(pinned_messages::chat_id.eq(export.chat_id))
+        .filter(
+            pinned_messages::expires_at
+                .is_null()
+                .or(pinned_messages::expires_at.gt(now)),
+        )
+        .order(pinned_messages::pinned_at.asc())
+        .select(PinnedMessage::as_select())
+        .load(conn)?;
+
+    let messages = rows
+        .into_iter()
+        .map(|m| {
+            let is_deleted = m.deleted_at.is_some();
+            ExportedMessage {
+                id: m.id.to_string(),
+                sender_uid: m.sender_uid,
+                sender_name: profiles
+                    .get(&m.sender_uid)
+                    .and_then(|profile| profile.username.clone()),
+                created_at: m.created_at,
+                edited_at: m.updated_at,
+                is_deleted,
+                message_type: m.message_type,
+                text: if is_deleted { None } else { m.message },
+                reply_to_id: m.reply_to_id.map(|id| id.to_string()),
+                thread_root_id: m.reply_root_id.map(|id| id.to_string()),
+                topic_id: m.topic_id.map(|id| id.to_string()),
+                attachments: attachments_by_message.remove(&m.id).unwrap_or_default(),
+                reactions: reactions_by_message
+                    .remove(&m.id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(emoji, user_uids)| ExportedReaction { emoji, user_uids })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(ChatExportDocument {
+        chat: ExportedChat {
+            id: group.id.to_string(),
+            name: group.name,
+            description: group.description,
+        },
+        exported_at: now,
+        exported_by_uid: export.requester_uid,
+        truncated,
+        messages,
+        pins: pins
+            .into_iter()
+            .map(|pin| ExportedPin {
+                message_id: pin.message_id.to_string(),
+                pinned_by_uid: pin.pinned_by,
+                pinned_at: pin.pinned_at,
+            })
+            .collect(),
+    })
+}
+
+fn escape_html(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+const EXPORT_HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:860px;margin:2rem auto;padding:0 1rem;color:#222}\
+.msg{border-bottom:1px solid #eee;padding:.5rem 0}\
+.meta{color:#777;font-size:.85em}\
+.deleted{color:#999;font-style:italic}\
+.thread{margin-left:1.5rem;border-left:3px solid #ddd;padding-left:.75rem}\
+.reactions span{background:#f2f2f2;border-radius:1em;padding:0 .5em;margin-right:.25em}\
+.pinned{color:#b8860b}\
+pre{white-space:pre-wrap;margin:.25rem 0;font-family:inherit}";
+
+fn render_message_html(out: &mut String, message: &ExportedMessage, pinned: bool) {
+    let sender = message
+        .sender_name
+        .clone()
+        .unwrap_or_else(|| format!("User {}", message.sender_uid));
+    let _ = write!(
+        out,
+        "<div class=\"msg\" id=\"m{}\"><div class=\"meta\"><strong>{}</strong> · {}{}{}</div>",
+        escape_html(&message.id),
+        escape_html(&sender),
+        message.created_at.format("%Y-%m-%d %H:%M UTC"),
+        if message.edited_at.is_some() {
+            " · edited"
+        } else {
+            ""
+        },
+        if pinned {
+            " · <span class=\"pinned\">pinned</span>"
+        } else {
+            ""
+        },
+    );
+
+    if message.is_deleted {
+        out.push_str("<div class=\"deleted\">Message deleted</div>");
+    } else {
+        if let Some(reply_to_id) = &message.reply_to_id {
+            let _ = write!(
+                out,
+                "<div class=\"meta\">↪ <a href=\"#m{0}\">reply to {0}</a></div>",
+                escape_html(reply_to_id)
+            );
+        }
+        if let Some(text) = message.text.as_deref().filter(|text| !text.is_empty()) {
+            let _ = write!(out, "<pre>{}</pre>", escape_html(text));
+        }
+        for attachment in &message.attachments {
+            let _ = write!(
+                out,
+                "<div>📎 <a href=\"{}\">{}</a> <span class=\"meta\">({}, {} bytes)</span></div>",
+                escape_html(&attachment.url),
+                escape_html(&attachment.file_name),
+                escape_html(&attachment.kind),
+                attachment.size
+            );
+        }
+        if !message.reactions.is_empty() {
+            out.push_str("<div class=\"reactions\">");
+            for reaction in &message.reactions {
+                let _ = write!(
+                    out,
+                    "<span>{} {}</span>",
+                    escape_html(&reaction.emoji),
+                    reaction.user_uids.len()
+                );
+            }
+            out.push_str("</div>");
+        }
+    }
+    out.push_str("</div>");
+}
+
+/// Self-contained HTML rendering: inline styles, thread replies nested under their root.
+pub fn render_export_html(document: &ChatExportDocument) -> String {
+    let pinned: std::collections::HashSet<&str> = document
+        .pins
+        .iter()
+        .map(|pin| pin.message_id.as_str())
+        .collect();
+    let mut replies: HashMap<&str, Vec<&ExportedMessage>> = HashMap::new();
+    for message in &document.messages {
+        if let Some(root_id) = &message.thread_root_id {
+            replies.entry(root_id.as_str()).or_default().push(message);
+        }
+    }
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{0}</title><style>{1}</style></head><body><h1>{0}</h1>",
+        escape_html(&document.chat.name),
+        EXPORT_HTML_STYLE
+    );
+    if let Some(description) = &document.chat.description {
+        let _ = write!(out, "<p>{}</p>", escape_html(description));
+    }
+    let _ = write!(
+        out,
+        "<p class=\"meta\">Exported {} · {} messages{}</p>",
+        document.exported_at.format("%Y-%m-%d %H:%M UTC"),
+        document.messages.len(),
+        if document.truncated {
+            " · truncated"
+        } else {
+            ""
+        }
+    );
+
+    for message in document
+        .messages
+        .iter()
+        .filter(|message| message.thread_root_id.is_none())
+    {
+        render_message_html(&mut out, message, pinned.contains(message.id.as_str()));
+        if let Some(thread) = replies.get(message.id.as_str()) {
+            let _ = write!(
+                out,
+                "<details class=\"thread\" open><summary>{} replies</summary>",
+                thread.len()
+            );
+            for reply in thread {
+                render_message_html(&mut out, reply, pinned.contains(reply.id.as_str()));
+            }
+            out.push_str("</details>");
+        }
+    }
+
+    out.push_str("</body></html>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_limit_probes_one_row_past_the_cap() {
+        assert_eq!(export_batch_limit(0), EXPORT_BATCH_SIZE);
+        assert_eq!(
+            export_batch_limit(MAX_EXPORT_MESSAGES - 10),
+            11,
+            "the last page stops one row past the cap"
+        );
+        assert_eq!(export_batch_limit(MAX_EXPORT_MESSAGES), 1);
+    }
+
+    fn message(id: i64, thread_root_id: Option<i64>, text: &str) -> ExportedMessage {
+        ExportedMessage {
+            id: id.to_string(),
+            sender_uid: 7,
+            sender_name: Some("alice".to_string()),
+            created_at: Utc::now(),
+            edited_at: None,
+            is_deleted: false,
+            message_type: MessageType::Text,
+            text: Some(text.to_string()),
+            reply_to_id: None,
+            thread_root_id: thread_root_id.map(|id| id.to_string()),
+            topic_id: None,
+            attachments: Vec::new(),
+            reactions: Vec::new(),
+        }
+    }
+
+    fn document(messages: Vec<ExportedMessage>) -> ChatExportDocument {
+        ChatExportDocument {
+            chat: ExportedChat {
+                id: "1".to_string(),
+                name: "General <team>".to_string(),
+                description: None,
+            },
+            exported_at: Utc::now(),
+            exported_by_uid: 7,
+            truncated: false,
+            messages,
+            pins: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn export_html_escapes_user_content() {
+        let html = render_export_html(&document(vec![message(
+            10,
+            None,
+            "<script>alert('x')</script>",
+        )]));
+
+        assert!(html.contains("General &lt;team&gt;"));
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn export_html_nests_thread_replies_under_root() {
+        let html = render_export_html(&document(vec![
+            message(10, None, "root"),
+            message(11, Some(10), "reply"),
+            message(12, None, "later"),
+        ]));
+
+        let root = html.find("id=\"m10\"").unwrap();
+        let thread = html.find("<details class=\"thread\"").unwrap();
+        let reply = html.find("id=\"m11\"").unwrap();
+        let later = html.find("id=\"m12\"").unwrap();
+        assert!(root < thread && thread < reply && reply < later);
+        assert_eq!(html.matches("<details").count(), 1);
+    }
+
+    #[test]
+    fn deleted_messages_render_as_placeholders() {
+        let mut deleted = message(10, None, "");
+        deleted.is_deleted = true;
+        deleted.text = None;
+
+        let html = render_export_html(&document(vec![deleted]));
+
+        assert!(html.contains("Message deleted"));
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use crate::constants::MAX_UNREAD_COUNT;
+use crate::dto::chat_folders::{ChatFolderResponse, ChatFolderRules};
+use crate::errors::AppError;
+use crate::models::{ChatFolder, GroupRole};
+use crate::schema::{chat_folder_chats, chat_folders, group_membership};
+use crate::services::unread::{ChatUnreadMembership, UnreadService};
+
+pub const MAX_FOLDERS_PER_USER: i64 = 20;
+pub const MAX_FOLDER_NAME_LEN: usize = 64;
+pub const MAX_FOLDER_CHATS: usize = 500;
+
+/// Per-membership facts needed to evaluate folder rules for one user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FolderChatFacts {
+    pub chat_id: i64,
+    pub archived: bool,
+    pub muted_until: Option<DateTime<Utc>>,
+    pub is_admin: bool,
+    pub unread_count: i64,
+    pub has_unread_mention: bool,
+}
+
+#[derive(diesel::QueryableByName)]
+struct MentionChatRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    chat_id: i64,
+}
+
+pub fn folder_rules(folder: &ChatFolder) -> ChatFolderRules {
+    ChatFolderRules {
+        include_unread: folder.include_unread,
+        include_muted: folder.include_muted,
+        include_mentions: folder.include_mentions,
+        include_admin_of: folder.include_admin_of,
+    }
+}
+
+/// Rules never pull in archived chats; those only appear when added explicitly.
+pub fn rules_match(rules: &ChatFolderRules, facts: &FolderChatFacts, now: DateTime<Utc>) -> bool {
+    if facts.archived {
+        return false;
+    }
+
+    (rules.include_unread && facts.unread_count > 0)
+        || (rules.include_muted && facts.muted_until.is_some_and(|t| t > now))
+        || (rules.include_mentions && facts.has_unread_mention)
+        || (rules.include_admin_of && facts.is_admin)
+}
+
+/// Resolve the chats in a folder, preserving the order of `facts`.
+pub fn resolve_folder_chats<'a>(
+    rules: &ChatFolderRules,
+    explicit_chat_ids: &HashSet<i64>,
+    facts: &'a [FolderChatFacts],
+    now: DateTime<Utc>,
+) -> Vec<&'a FolderChatFacts> {
+    facts
+        .iter()
+        .filter(|f| explicit_chat_ids.contains(&f.chat_id) || rules_match(rules, f, now))
+        .collect()
+}
+
+/// Folder badge totals. Muted chats (including archived ones) do not count, matching
+/// the global unread summary.
+pub fn folder_unread_totals(chats: &[&FolderChatFacts], now: DateTime<Utc>) -> (i64, i64) {
+    let mut unread_count: i64 = 0;
+    let mut unread_chat_count: i64 = 0;
+    for facts in chats {
+        if facts.muted_until.is_some_and(|t| t > now) {
+            continue;
+        }
+        unread_count = unread_count
+            .saturating_add(facts.unread_count)
+            .min(MAX_UNREAD_COUNT);
+        if facts.unread_count > 0 {
+            unread_chat_count = unread_chat_count.saturating_add(1).min(MAX_UNREAD_COUNT);
+        }
+    }
+    (unread_count, unread_chat_count)
+}
+
+pub fn load_folder_chat_facts(
+    conn: &mut PgConnection,
+    unread_service: &UnreadService,
+    uid: i32,
+    include_mentions: bool,
+) -> Result<Vec<FolderChatFacts>, AppError> {
+    use crate::schema::group_membership::dsl as gm_dsl;
+
+    let rows = group_membership::table
+        .filter(gm_dsl::uid.eq(uid))
+        .select((
+            gm_dsl::chat_id,
+            gm_dsl::last_read_message_id,
+            gm_dsl::archived,
+            gm_dsl::muted_until,
+            gm_dsl::role,
+        ))
+        .order(gm_dsl::chat_id.asc())
+        .load::<(i64, Option<i64>, bool, Option<DateTime<Utc>>, GroupRole)>(conn)?;
+
+    let memberships: Vec<ChatUnreadMembership> = rows
+        .iter()
+        .map(
+            |(chat_id, last_read_message_id, archived, muted_until, _)| ChatUnreadMembership {
+                chat_id: *chat_id,
+                last_read_message_id: *last_read_message_id,
+                archived: *archived,
+                muted_until: *muted_until,
+            },
+        )
+        .collect();
+    let unread_counts = unread_service.count_membership_unreads(conn, &memberships)?;
+
+    let mention_chat_ids: HashSet<i64> = if include_mentions {
+        load_unread_mention_chat_ids(conn, uid)?
+    } else {
+        HashSet::new()
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(chat_id, _, archived, muted_until, role)| FolderChatFacts {
+                chat_id,
+                archived,
+                muted_until,
+                is_admin: role == GroupRole::Admin,
+                unread_count: unread_counts.get(&chat_id).copied().unwrap_or(0),
+                has_unread_mention: mention_chat_ids.contains(&chat_id),
+            },
+        )
+        .collect())
+}
+
+/// Chats with an unread top-level message that mentions `uid` or `@[all]`.
+fn load_unread_mention_chat_ids(
+    conn: &mut PgConnection,
+    uid: i32,
+) -> Result<HashSet<i64>, diesel::result::Error> {
+    let rows = diesel::sql_query(
+        "SELECT DISTINCT mm.chat_id \
+         FROM group_membership gm \
+         JOIN message_mentions mm ON mm.chat_id = gm.chat_id \
+              AND (mm.uid = gm.uid OR mm.mentions_all) \
+              AND (gm.last_read_message_id IS NULL OR mm.message_id > gm.last_read_message_id) \
+         JOIN messages m ON m.id = mm.message_id \
+         WHERE gm.uid = $1 \
+           AND m.reply_root_id IS NULL \
+           AND m.deleted_at IS NULL \
+           AND m.is_published = TRUE \
+           AND m.sender_uid <> $1",
+    )
+    .bind::<diesel::sql_types::Integer, _>(uid)
+    .load::<MentionChatRow>(conn)?;
+
+    Ok(rows.into_iter().map(|row| row.chat_id).collect())
+}
+
+pub fn load_user_folders(
+    conn: &mut PgConnection,
+    uid: i32,
+) -> Result<Vec<ChatFolder>, diesel::result::Error> {
+    chat_folders::table
+        .filter(chat_folders::uid.eq(uid))
+        .order((chat_folders::sort_order.asc(), chat_folders::id.asc()))
+        .select(ChatFolder::as_select())
+        .load(conn)
+}
+
+pub fn load_owned_folder(
+    conn: &mut PgConnection,
+    uid: i32,
+    folder_id: i64,
+) -> Result<ChatFolder, AppError> {
+    chat_folders::table
+        .filter(chat_folders::id.eq(folder_id))
+        .filter(chat_folders::uid.eq(uid))
+        .select(ChatFolder::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Folder not found"))
+}
+
+fn load_explicit_chat_ids(
+    conn: &mut PgConnection,
+    folder_ids: &[i64],
+) -> Result<HashMap<i64, Vec<i64>>, diesel::result::Error> {
+    let rows = chat_folder_chats::table
+        .filter(chat_folder_chats::folder_id.eq_any(folder_ids))
+        .order((
+            chat_folder_chats::folder_id.asc(),
+            chat_folder_chats::added_at.asc(),
+        ))
+        .select((chat_folder_chats::folder_id, chat_folder_chats::chat_id))
+        .load::<(i64, i64)>(conn)?;
+
+    let mut by_folder: HashMap<i64, Vec<i64>> = HashMap::new();
+    for (folder_id, chat_id) in rows {
+        by_folder.entry(folder_id).or_default().push(chat_id);
+    }
+    Ok(by_folder)
+}
+
+/// Drop chats from the user's folders once the user is no longer a member of them.
+pub fn remove_chats_from_user_folders(
+    conn: &mut PgConnection,
+    uid: i32,
+    chat_ids: &[i64],
+) -> Result<usize, diesel::result::Error> {
+    diesel::delete(
+        chat_folder_chats::table
+            .filter(chat_folder_chats::chat_id.eq_any(chat_ids))
+            .filter(
+                chat_folder_chats::folder_id.eq_any(
+                    chat_folders::table
+                        .filter(chat_folders::uid.eq(uid))
+                        .select(chat_folders::id),
+                ),
+            ),
+    )
+    .execute(conn)
+}
+
+/// Resolve the chat ids for `get_chats?folder=<id>`.
+pub fn load_folder_chat_ids(
+    conn: &mut PgConnection,
+    unread_service: &UnreadService,
+    uid: i32,
+    folder_id: i64,
+) -> Result<Vec<i64>, AppError> {
+    let folder = load_owned_folder(conn, uid, folder_id)?;
+    let rules = folder_rules(&folder);
+    let explicit: HashSet<i64> = load_explicit_chat_ids(conn, &[folder.id])?
+        .remove(&folder.id)
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    let facts = load_folder_chat_facts(conn, unread_service, uid, rules.include_mentions)?;
+
+    Ok(resolve_folder_chats(&rules, &explicit, &facts, Utc::now())
+        .into_iter()
+        .map(|f| f.chat_id)
+        .collect())
+}
+
+/// List a user's folders in display order with per-folder unread totals.
+pub fn list_folder_responses(
+    conn: &mut PgConnection,
+    unread_service: &UnreadService,
+    uid: i32,
+) -> Result<Vec<ChatFolderResponse>, AppError> {
+    let folders = load_user_folders(conn, uid)?;
+    if folders.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let folder_ids: Vec<i64> = folders.iter().map(|f| f.id).collect();
+    let mut explicit_by_folder = load_explicit_chat_ids(conn, &folder_ids)?;
+    let include_mentions = folders.iter().any(|f| f.include_mentions);
+    let facts = load_folder_chat_facts(conn, unread_service, uid, include_mentions)?;
+    let now = Utc::now();
+
+    Ok(folders
+        .into_iter()
+        .map(|folder| {
+            let rules = folder_rules(&folder);
+            let explicit_chat_ids = explicit_by_folder.remove(&folder.id).unwrap_or_default();
+            let explicit: HashSet<i64> = explicit_chat_ids.iter().copied().collect();
+            let chats = resolve_folder_chats(&rules, &explicit, &facts, now);
+            let (unread_count, unread_chat_count) = folder_unread_totals(&chats, now);
+
+            ChatFolderResponse {
+                id: folder.id,
+                name: folder.name,
+                sort_order: folder.sort_order,
+                rules,
+                chat_ids: explicit_chat_ids
+                    .into_iter()
+                    .map(|id| id.to_string())
+                    .collect(),
+                unread_count,
+                unread_chat_count,
+                created_at: folder.created_at,
+                updated_at: folder.updated_at,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn facts(chat_id: i64) -> FolderChatFacts {
+        FolderChatFacts {
+            chat_id,
+            archived: false,
+            muted_until: None,
+            is_admin: false,
+            unread_count: 0,
+            has_unread_mention: false,
+        }
+    }
+
+    #[test]
+    fn empty_rules_match_nothing() {
+        let now = Utc::now();
+        let mut chat = facts(1);
+        chat.unread_count = 3;
+        chat.is_admin = true;
+        assert!(!rules_match(&ChatFolderRules::default(), &chat, now));
+    }
+
+    #[test]
+    fn each_rule_matches_its_fact() {
+        let now = Utc::now();
+
+        let mut unread = facts(1);
+        unread.unread_count = 1;
+        let mut muted = facts(2);
+        muted.muted_until = Some(now + Duration::minutes(5));
+        let mut mentioned = facts(3);
+        mentioned.has_unread_mention = true;
+        let mut admin = facts(4);
+        admin.is_admin = true;
+
+        let only = |f: fn(&mut ChatFolderRules)| {
+            let mut rules = ChatFolderRules::default();
+            f(&mut rules);
+            rules
+        };
+
+        assert!(rules_match(
+            &only(|r| r.include_unread = true),
+            &unread,
+            now
+        ));
+        assert!(rules_match(&only(|r| r.include_muted = true), &muted, now));
+        assert!(rules_match(
+            &only(|r| r.include_mentions = true),
+            &mentioned,
+            now
+        ));
+        assert!(rules_match(
+            &only(|r| r.include_admin_of = true),
+            &admin,
+            now
+        ));
+        assert!(!rules_match(
+            &only(|r| r.include_unread = true),
+            &admin,
+            now
+        ));
+    }
+
+    #[test]
+    fn expired_mute_does_not_match_muted_rule() {
+        let now = Utc::now();
+        let mut chat = facts(1);
+        chat.muted_until = Some(now - Duration::minutes(1));
+        let rules = ChatFolderRules {
+            include_muted: true,
+            ..ChatFolderRules::default()
+        };
+        assert!(!rules_match(&rules, &chat, now));
+    }
+
+    #[test]
+    fn archived_chats_only_included_explicitly() {
+        let now = Utc::now();
+        let mut archived = facts(1);
+        archived.archived = true;
+        archived.unread_count = 4;
+        let all = [archived, facts(2)];
+        let rules = ChatFolderRules {
+            include_unread: true,
+            ..ChatFolderRules::default()
+        };
+
+        assert!(resolve_folder_chats(&rules, &HashSet::new(), &all, now).is_empty());
+
+        let explicit: HashSet<i64> = [1].into_iter().collect();
+        let resolved = resolve_folder_chats(&rules, &explicit, &all, now);
+        assert_eq!(
+            resolved.iter().map(|f| f.chat_id).collect::<Vec<_>>(),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn unread_totals_skip_muted_chats_and_cap() {
+        let now = Utc::now();
+        let mut a = facts(1);
+        a.unread_count = MAX_UNREAD_COUNT;
+        let mut b = facts(2);
+        b.unread_count = 5;
+        let mut muted = facts(3);
+        muted.unread_count = 7;
+        muted.muted_until = Some(now + Duration::days(1));
+
+        let (count, chats) = folder_unread_totals(&[&a, &b, &muted], now);
+        assert_eq!(count, MAX_UNREAD_COUNT);
+        assert_eq!(chats, 2);
+    }
+}
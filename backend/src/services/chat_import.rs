@@ -0,0 +1,587 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, TimeZone, Utc};
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use crate::dto::external::imports::{ExternalImportMedia, TelegramExport};
+use crate::errors::AppError;
+use crate::models::{
+    GroupJoinReason, GroupRole, MessageType, NewAttachment, NewGroupMembership, NewMessage,
+    TranscodeStatus,
+};
+use crate::schema::{attachments, group_membership, messages};
+use crate::utils::ids;
+
+pub const MAX_IMPORT_MESSAGES: usize = 50_000;
+pub const MAX_IMPORT_BODY_BYTES: usize = 64 * 1024 * 1024;
+const INSERT_BATCH_SIZE: usize = 1000;
+
+/// One export message after sender mapping and reply resolution, in import order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedMessage {
+    pub source_id: i64,
+    pub sender_uid: i32,
+    pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub text: Option<String>,
+    pub reply_to_source: Option<i64>,
+    pub thread_root_source: Option<i64>,
+    pub media_path: Option<String>,
+    pub file_name: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportPlan {
+    pub messages: Vec<PlannedMessage>,
+    pub skipped_service: usize,
+    /// Entries with neither text nor media.
+    pub skipped_empty: usize,
+}
+
+/// Rows ready to insert, with snowflake ids already allocated.
+pub struct PreparedImport {
+    pub messages: Vec<NewMessage>,
+    pub attachments: Vec<NewAttachment>,
+    pub thread_root_ids: Vec<i64>,
+    pub sender_uids: Vec<i32>,
+    /// Media-only entries whose media was not supplied.
+    pub skipped_empty: usize,
+}
+
+/// Key that makes re-running the same import a no-op for messages already imported.
+pub fn import_client_id(chat_id: i64, source_id: i64) -> String {
+    format!("import:{chat_id}:{source_id}")
+}
+
+/// Flatten Telegram's rich text (a string, or an array of strings and `{type, text}` entities).
+pub fn flatten_telegram_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(parts) => parts
+            .iter()
+            .map(|part| match part {
+                serde_json::Value::String(text) => text.as_str(),
+                serde_json::Value::Object(entity) => entity
+                    .get("text")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or(""),
+                _ => "",
+            })
+            .collect(),
+        _ => String::new(),
+    }
+}
+
+fn parse_unixtime(raw: &str) -> Option<DateTime<Utc>> {
+    let secs: i64 = raw.trim().parse().ok()?;
+    Utc.timestamp_opt(secs, 0).single()
+}
+
+/// Validate and order an export: map senders, skip service entries, sort by original
+/// timestamp, and resolve replies (and, optionally, thread roots) within the export.
+pub fn plan_telegram_import(
+    export: &TelegramExport,
+    sender_map: &HashMap<String, i32>,
+    fallback_sender_uid: Option<i32>,
+    replies_as_threads: bool,
+) -> Result<ImportPlan, AppError> {
+    let mut plan = ImportPlan::default();
+    for message in &export.messages {
+        if message.kind != "message" {
+            plan.skipped_service += 1;
+            continue;
+        }
+
+        let sender_uid = message
+            .from_id
+            .as_deref()
+            .and_then(|from_id| sender_map.get(from_id).copied())
+            .or(fallback_sender_uid)
+            .ok_or(AppError::BadRequest(
+                "Export contains senders missing from senderMap",
+            ))?;
+        if sender_uid <= 0 {
+            return Err(AppError::BadRequest("senderMap contains an invalid uid"));
+        }
+        let created_at = parse_unixtime(&message.date_unixtime)
+            .ok_or(AppError::BadRequest("Export message has an invalid date"))?;
+        // Ids derive from the date; a future one could collide with ids issued later.
+        if created_at > Utc::now() {
+            return Err(AppError::BadRequest(
+                "Export message is dated in the future",
+            ));
+        }
+        let text = flatten_telegram_text(&message.text);
+        let media_path = message.photo.clone().or_else(|| message.file.clone());
+        if text.trim().is_empty() && media_path.is_none() {
+            plan.skipped_empty += 1;
+            continue;
+        }
+
+        plan.messages.push(PlannedMessage {
+            source_id: message.id,
+            sender_uid,
+            created_at,
+            edited_at: message.edited_unixtime.as_deref().and_then(parse_unixtime),
+            text: (!text.trim().is_empty()).then_some(text),
+            reply_to_source: message.reply_to_message_id,
+            thread_root_source: None,
+            file_name: message.file_name.clone().or_else(|| {
+                media_path
+                    .as_deref()
+                    .and_then(|path| path.rsplit('/').next())
+                    .map(str::to_string)
+            }),
+            media_path,
+        });
+    }
+
+    if plan.messages.len() > MAX_IMPORT_MESSAGES {
+        return Err(AppError::BadRequest("Too many messages in one import"));
+    }
+    let mut seen = HashSet::new();
+    if !plan.messages.iter().all(|m| seen.insert(m.source_id)) {
+        return Err(AppError::BadRequest(
+            "Export contains duplicate message ids",
+        ));
+    }
+
+    plan.messages
+        .sort_by_key(|message| (message.created_at, message.source_id));
+
+    if replies_as_threads {
+        let parents: HashMap<i64, Option<i64>> = plan
+            .messages
+            .iter()
+            .map(|m| (m.source_id, m.reply_to_source))
+            .collect();
+        for message in &mut plan.messages {
+            // Walk up the reply chain to the top-level message it hangs off.
+            let mut root = None;
+            let mut cursor = message.reply_to_source;
+            let mut steps = 0;
+            while let Some(parent) = cursor.filter(|id| parents.contains_key(id)) {
+                root = Some(parent);
+                cursor = parents[&parent];
+                steps += 1;
+                if steps > parents.len() {
+                    root = None;
+                    break;
+                }
+            }
+            message.thread_root_source = root;
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Source id → message id for messages a previous run already imported into this chat.
+pub fn load_imported_message_ids(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    source_ids: &[i64],
+) -> Result<HashMap<i64, i64>, diesel::result::Error> {
+    let mut imported = HashMap::new();
+    for chunk in source_ids.chunks(INSERT_BATCH_SIZE) {
+        let client_ids: Vec<String> = chunk
+            .iter()
+            .map(|source_id| import_client_id(chat_id, *source_id))
+            .collect();
+        let rows: Vec<(String, i64)> = messages::table
+            .filter(messages::chat_id.eq(chat_id))
+            .filter(messages::client_generated_id.eq_any(&client_ids))
+            .select((messages::client_generated_id, messages::id))
+            .load(conn)?;
+        for (client_id, id) in rows {
+            if let Some(source_id) = client_id
+                .rsplit(':')
+                .next()
+                .and_then(|source_id| source_id.parse().ok())
+            {
+                imported.insert(source_id, id);
+            }
+        }
+    }
+    Ok(imported)
+}
+
+/// Message ids at the original timestamps (in import order), so imported history sorts
+/// among the chat's live messages by time. Ids already taken are skipped.
+pub fn allocate_historical_ids(
+    conn: &mut PgConnection,
+    timestamps: &[DateTime<Utc>],
+) -> Result<Vec<i64>, diesel::result::Error> {
+    let mut taken = HashSet::new();
+    loop {
+        let ids = historical_ids(timestamps, &taken);
+        let mut collided = false;
+        for chunk in ids.chunks(INSERT_BATCH_SIZE) {
+            let existing: Vec<i64> = messages::table
+                .filter(messages::id.eq_any(chunk))
+                .select(messages::id)
+                .load(conn)?;
+            collided |= !existing.is_empty();
+            taken.extend(existing);
+        }
+        if !collided {
+            return Ok(ids);
+        }
+    }
+}
+
+/// Increasing ids starting at each timestamp's first id, stepping over `taken`.
+fn historical_ids(timestamps: &[DateTime<Utc>], taken: &HashSet<i64>) -> Vec<i64> {
+    let mut next = 0;
+    timestamps
+        .iter()
+        .map(|at| {
+            let mut id = next.max(ids::first_id_at(*at));
+            while taken.contains(&id) {
+                id += 1;
+            }
+            next = id + 1;
+            id
+        })
+        .collect()
+}
+
+/// Build insertable rows for the messages not imported yet. `message_ids` and
+/// `attachment_ids` are pre-allocated snowflake ids, consumed in import order.
+pub fn prepare_import(
+    chat_id: i64,
+    plan: &ImportPlan,
+    already_imported: &HashMap<i64, i64>,
+    media: &HashMap<String, ExternalImportMedia>,
+    message_ids: &mut impl Iterator<Item = i64>,
+    attachment_ids: &mut impl Iterator<Item = i64>,
+    missing_media: &mut Vec<String>,
+) -> PreparedImport {
+    let mut id_by_source = already_imported.clone();
+    let mut prepared = PreparedImport {
+        messages: Vec::new(),
+        attachments: Vec::new(),
+        thread_root_ids: Vec::new(),
+        sender_uids: Vec::new(),
+        skipped_empty: 0,
+    };
+    let mut thread_roots = HashSet::new();
+    let mut senders = HashSet::new();
+
+    for planned in &plan.messages {
+        if already_imported.contains_key(&planned.source_id) {
+            continue;
+        }
+        let Some(id) = message_ids.next() else {
+            break;
+        };
+        let attachment = planned
+            .media_path
+            .as_ref()
+            .and_then(|path| match media.get(path) {
+                Some(media) => Some(media),
+                None => {
+                    missing_media.push(path.clone());
+                    None
+                }
+            });
+        if planned.text.is_none() && attachment.is_none() {
+            prepared.skipped_empty += 1;
+            continue;
+        }
+        id_by_source.insert(planned.source_id, id);
+
+        let reply_root_id = planned
+            .thread_root_source
+            .and_then(|source| id_by_source.get(&source).copied());
+        if let Some(root_id) = reply_root_id {
+            thread_roots.insert(root_id);
+        }
+        senders.insert(planned.sender_uid);
+
+        let attachment_id = attachment.and_then(|_| attachment_ids.next());
+        if let (Some(media), Some(attachment_id)) = (attachment, attachment_id) {
+            prepared.attachments.push(NewAttachment {
+                id: attachment_id,
+                message_id: Some(id),
+                file_name: planned.file_name.clone().unwrap_or_default(),
+                kind: media.content_type.clone(),
+                external_reference: media.storage_key.clone(),
+                size: media.size,
+                created_at: planned.created_at,
+                deleted_at: None,
+                width: media.width,
+                height: media.height,
+                order: 0,
+            });
+        }
+        // Imported voice notes are served as uploaded rather than transcoded.
+        let message_type = match attachment {
+            Some(media) if media.content_type.starts_with("audio/") => MessageType::Audio,
+            Some(_) => MessageType::File,
+            None => MessageType::Text,
+        };
+        let transcode_status = if matches!(message_type, MessageType::Audio) {
+            TranscodeStatus::Done
+        } else {
+            TranscodeStatus::None
+        };
+
+        prepared.messages.push(NewMessage {
+            id,
+            message: planned.text.clone(),
+            message_type,
+            reply_to_id: planned
+                .reply_to_source
+                .and_then(|source| id_by_source.get(&source).copied()),
+            reply_root_id,
+            client_generated_id: import_client_id(chat_id, planned.source_id),
+            sender_uid: planned.sender_uid,
+            chat_id,
+            created_at: planned.created_at,
+            updated_at: planned.edited_at,
+            deleted_at: None,
+            has_attachments: attachment_id.is_some(),
+            has_thread: false,
+            has_reactions: false,
+            sticker_id: None,
+            is_published: true,
+            transcode_status,
+            topic_id: None,
+            is_important: false,
+        });
+    }
+
+    prepared.thread_root_ids = thread_roots.into_iter().collect();
+    prepared.sender_uids = senders.into_iter().collect();
+    prepared
+}
+
+/// Insert prepared rows and rebuild derived state (thread roots, thread_meta,
+/// the chat's last message). Runs inside the caller's transaction.
+pub fn apply_import(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    prepared: &PreparedImport,
+) -> Result<(), AppError> {
+    if prepared.messages.is_empty() {
+        return Ok(());
+    }
+    for chunk in prepared.messages.chunks(INSERT_BATCH_SIZE) {
+        diesel::insert_into(messages::table)
+            .values(chunk)
+            .execute(conn)?;
+    }
+    for chunk in prepared.attachments.chunks(INSERT_BATCH_SIZE) {
+        diesel::insert_into(attachments::table)
+            .values(chunk)
+            .execute(conn)?;
+    }
+
+    // Imported senders become members. They and existing members are caught up to
+    // the end of the imported history rather than seeing it all as unread.
+    let last_imported_id = prepared.messages.iter().map(|m| m.id).max();
+    let now = Utc::now();
+    let memberships: Vec<NewGroupMembership> = prepared
+        .sender_uids
+        .iter()
+        .map(|&uid| NewGroupMembership {
+            chat_id,
+            uid,
+            role: GroupRole::Member,
+            joined_at: now,
+            join_reason: GroupJoinReason::Other,
+            join_reason_extra: None,
+            last_read_message_id: last_imported_id,
+        })
+        .collect();
+    diesel::insert_into(group_membership::table)
+        .values(&memberships)
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+    if let Some(last_imported_id) = last_imported_id {
+        diesel::update(
+            group_membership::table
+                .filter(group_membership::chat_id.eq(chat_id))
+                .filter(
+                    group_membership::last_read_message_id
+                        .is_null()
+                        .or(group_membership::last_read_message_id.lt(last_imported_id)),
+                ),
+        )
+        .set(group_membership::last_read_message_id.eq(last_imported_id))
+        .execute(conn)?;
+    }
+
+    if !prepared.thread_root_ids.is_empty() {
+        diesel::update(messages::table.filter(messages::id.eq_any(&prepared.thread_root_ids)))
+            .set(messages::has_thread.eq(true))
+            .execute(conn)?;
+        for &thread_root_id in &prepared.thread_root_ids {
+            crate::services::threads::recalculate_thread_meta(conn, chat_id, thread_root_id)?;
+        }
+    }
+
+    crate::handlers::chats::recalculate_group_last_message(conn, chat_id)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::external::imports::TelegramExportMessage;
+    use serde_json::json;
+
+    fn export(messages: serde_json::Value) -> TelegramExport {
+        serde_json::from_value(json!({ "name": "Old group", "messages": messages })).unwrap()
+    }
+
+    fn senders() -> HashMap<String, i32> {
+        HashMap::from([("user1".to_string(), 10), ("user2".to_string(), 20)])
+    }
+
+    #[test]
+    fn flattens_rich_text_entities() {
+        let text = json!(["Hello ", {"type": "bold", "text": "world"}, "!"]);
+        assert_eq!(flatten_telegram_text(&text), "Hello world!");
+        assert_eq!(flatten_telegram_text(&json!("plain")), "plain");
+        assert_eq!(flatten_telegram_text(&serde_json::Value::Null), "");
+    }
+
+    #[test]
+    fn plan_sorts_by_date_and_skips_service_messages() {
+        let export = export(json!([
+            {"id": 2, "type": "message", "date_unixtime": "200", "from_id": "user2", "text": "second"},
+            {"id": 3, "type": "service", "date_unixtime": "150", "action": "pin_message"},
+            {"id": 1, "type": "message", "date_unixtime": "100", "from_id": "user1", "text": "first"},
+        ]));
+
+        let plan = plan_telegram_import(&export, &senders(), None, false).unwrap();
+
+        assert_eq!(plan.skipped_service, 1);
+        let ids: Vec<i64> = plan.messages.iter().map(|m| m.source_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(plan.messages[0].sender_uid, 10);
+    }
+
+    #[test]
+    fn unmapped_senders_need_a_fallback() {
+        let export = export(json!([
+            {"id": 1, "type": "message", "date_unixtime": "100", "from_id": "user9", "text": "hi"},
+        ]));
+
+        assert!(matches!(
+            plan_telegram_import(&export, &senders(), None, false),
+            Err(AppError::BadRequest(_))
+        ));
+        let plan = plan_telegram_import(&export, &senders(), Some(99), false).unwrap();
+        assert_eq!(plan.messages[0].sender_uid, 99);
+    }
+
+    #[test]
+    fn replies_as_threads_resolve_to_chain_root() {
+        let export = export(json!([
+            {"id": 1, "type": "message", "date_unixtime": "100", "from_id": "user1", "text": "root"},
+            {"id": 2, "type": "message", "date_unixtime": "110", "from_id": "user2", "text": "a", "reply_to_message_id": 1},
+            {"id": 3, "type": "message", "date_unixtime": "120", "from_id": "user1", "text": "b", "reply_to_message_id": 2},
+            {"id": 4, "type": "message", "date_unixtime": "130", "from_id": "user1", "text": "c", "reply_to_message_id": 77},
+        ]));
+
+        let plan = plan_telegram_import(&export, &senders(), None, true).unwrap();
+
+        let roots: Vec<Option<i64>> = plan.messages.iter().map(|m| m.thread_root_source).collect();
+        assert_eq!(roots, vec![None, Some(1), Some(1), None]);
+    }
+
+    #[test]
+    fn prepare_skips_already_imported_and_links_replies() {
+        let export = export(json!([
+            {"id": 1, "type": "message", "date_unixtime": "100", "from_id": "user1", "text": "old"},
+            {"id": 2, "type": "message", "date_unixtime": "110", "from_id": "user2", "text": "new", "reply_to_message_id": 1, "photo": "photos/p.jpg"},
+        ]));
+        let plan = plan_telegram_import(&export, &senders(), None, false).unwrap();
+        let already_imported = HashMap::from([(1, 500)]);
+        let mut missing = Vec::new();
+
+        let prepared = prepare_import(
+            7,
+            &plan,
+            &already_imported,
+            &HashMap::new(),
+            &mut [900_i64].into_iter(),
+            &mut std::iter::empty(),
+            &mut missing,
+        );
+
+        assert_eq!(prepared.messages.len(), 1);
+        assert_eq!(prepared.messages[0].id, 900);
+        assert_eq!(prepared.messages[0].reply_to_id, Some(500));
+        assert_eq!(prepared.messages[0].client_generated_id, "import:7:2");
+        assert!(!prepared.messages[0].has_attachments);
+        assert_eq!(missing, vec!["photos/p.jpg".to_string()]);
+    }
+
+    #[test]
+    fn historical_ids_follow_timestamps_and_skip_taken_ids() {
+        let t = |secs| Utc.timestamp_opt(secs, 0).unwrap();
+        let base = ids::first_id_at(t(100));
+        let taken = HashSet::from([base + 1]);
+
+        let allocated = historical_ids(&[t(100), t(100), t(100), t(200)], &taken);
+
+        assert_eq!(allocated[..3], [base, base + 2, base + 3]);
+        assert_eq!(allocated[3], ids::first_id_at(t(200)));
+        assert!(ids::first_id_at(t(100)) < ids::first_id_at(t(101)));
+    }
+
+    #[test]
+    fn media_entries_get_their_type_and_empty_entries_are_skipped() {
+        let export = export(json!([
+            {"id": 1, "type": "message", "date_unixtime": "100", "from_id": "user1", "text": "", "photo": "photos/p.jpg"},
+            {"id": 2, "type": "message", "date_unixtime": "110", "from_id": "user1", "text": "", "file": "voice/v.ogg"},
+            {"id": 3, "type": "message", "date_unixtime": "120", "from_id": "user1", "text": "", "file": "files/gone.pdf"},
+            {"id": 4, "type": "message", "date_unixtime": "130", "from_id": "user1", "text": ""},
+        ]));
+        let plan = plan_telegram_import(&export, &senders(), None, false).unwrap();
+        assert_eq!(plan.skipped_empty, 1);
+        let media_entry = |content_type: &str| ExternalImportMedia {
+            storage_key: "attachments/x".to_string(),
+            content_type: content_type.to_string(),
+            size: 1,
+            width: None,
+            height: None,
+        };
+        let media = HashMap::from([
+            ("photos/p.jpg".to_string(), media_entry("image/jpeg")),
+            ("voice/v.ogg".to_string(), media_entry("audio/ogg")),
+        ]);
+        let mut missing = Vec::new();
+
+        let prepared = prepare_import(
+            7,
+            &plan,
+            &HashMap::new(),
+            &media,
+            &mut [901_i64, 902, 903].into_iter(),
+            &mut [1_i64, 2].into_iter(),
+            &mut missing,
+        );
+
+        let types: Vec<&MessageType> = prepared.messages.iter().map(|m| &m.message_type).collect();
+        assert_eq!(types, vec![&MessageType::File, &MessageType::Audio]);
+        assert_eq!(prepared.skipped_empty, 1);
+        assert_eq!(missing, vec!["files/gone.pdf".to_string()]);
+    }
+
+    #[test]
+    fn export_message_ignores_unknown_fields() {
+        let message: TelegramExportMessage = serde_json::from_value(json!({
+            "id": 1, "type": "message", "date": "2023-01-01T00:00:00",
+            "date_unixtime": "1672531200", "from": "Alice", "from_id": "user1",
+            "text_entities": [], "text": "hi"
+        }))
+        .unwrap();
+        assert_eq!(message.from_id.as_deref(), Some("user1"));
+    }
+}
@@ -27,6 +27,7 @@ const PURGE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
 const PURGE_RESTART_DELAY: Duration = Duration::from_secs(1);
 const STALE_CLIENT_RETENTION_DAYS: u64 = 45;
 const WS_UPGRADE_PATHS: [&str; 2] = ["/ws", "/ws/"];
+const MAX_APP_VERSION_CHARS: usize = 128;
 
 #[derive(Clone, Copy)]
 struct CachedActivity {
@@ -113,6 +114,7 @@ impl ClientTrackingService {
         &self,
         uid: i32,
         client_id: &str,
+        app_version: Option<&str>,
     ) -> Result<(), (StatusCode, &'static str)> {
         if let Some(entry) = self.recent_writes.get(client_id) {
             if entry.uid == uid && entry.last_written_at.elapsed() < ACTIVITY_WRITE_THROTTLE {
@@ -170,6 +172,10 @@ impl ClientTrackingService {
                 )
                 .set(push_subscriptions::user_id.eq(uid))
                 .execute(conn)?;
+                // The device label belongs to the previous user.
+                diesel::update(clients::table.find(client_id))
+                    .set(clients::device_name.eq(None::<String>))
+                    .execute(conn)?;
             }
 
             let new_client = NewClientRecord {
@@ -179,6 +185,9 @@ impl ClientTrackingService {
                     .map_or(now, |client| client.created_at),
                 last_active: now,
                 last_active_uid: uid,
+                app_version: app_version
+                    .map(truncate_app_version)
+                    .or_else(|| existing_client.as_ref()?.app_version.clone()),
             };
 
             diesel::insert_into(clients::table)
@@ -188,6 +197,7 @@ impl ClientTrackingService {
                 .set((
                     clients::last_active.eq(now),
                     clients::last_active_uid.eq(uid),
+                    clients::app_version.eq(&new_client.app_version),
                 ))
                 .execute(conn)?;
 
@@ -453,7 +463,9 @@ pub async fn track_client_activity(
         if let Some(client_id) = client_id {
             resolved_client_id = Some(client_id.clone());
             if let Err((status, message)) =
-                state.client_tracking.record_activity(auth.uid, &client_id)
+                state
+                    .client_tracking
+                    .record_activity(auth.uid, &client_id, app_version.as_deref())
             {
                 return (status, message).into_response();
             }
@@ -470,6 +482,10 @@ pub async fn track_client_activity(
     next.run(request).await
 }
 
+fn truncate_app_version(version: &str) -> String {
+    version.trim().chars().take(MAX_APP_VERSION_CHARS).collect()
+}
+
 fn should_record_app_version_request(path: &str) -> bool {
     !WS_UPGRADE_PATHS.contains(&path)
 }
@@ -0,0 +1,276 @@
+//! Incoming webhooks: secret-URL tokens that let external systems post into one chat.
+
+use std::collections::HashMap;
+
+use constant_time_eq::constant_time_eq;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::dto::incoming_webhooks::IncomingWebhookResponse;
+use crate::errors::AppError;
+use crate::models::{IncomingWebhook, MessageSenderOverride};
+use crate::schema::{incoming_webhooks, message_sender_overrides};
+use crate::services::bots::is_bot_uid;
+use crate::services::rate_windows::{self, RateWindow};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const DEFAULT_RATE_LIMIT_PER_MINUTE: i32 = 30;
+const MAX_RATE_LIMIT_PER_MINUTE: i32 = 600;
+pub const MAX_WEBHOOKS_PER_CHAT: i64 = 10;
+pub const MAX_ATTACHMENTS_PER_POST: usize = 10;
+const MAX_ATTACHMENT_BYTES: usize = 25 * 1024 * 1024;
+const MAX_FILE_NAME_CHARS: usize = 255;
+const RATE_LIMITED: &str = "Incoming webhook rate limit exceeded";
+
+pub struct FetchedAttachment {
+    pub data: Vec<u8>,
+    pub content_type: String,
+    pub file_name: String,
+}
+
+/// Fresh URL secret and its hash for the webhook `id`.
+pub fn generate_token(hash_key: &[u8], id: i64) -> Result<(String, String), AppError> {
+    let token = crate::services::service_tokens::generate_secret();
+    let token_hash = hash_token(hash_key, id, &token)?;
+    Ok((token, token_hash))
+}
+
+fn hash_token(hash_key: &[u8], id: i64, token: &str) -> Result<String, AppError> {
+    let mut mac = HmacSha256::new_from_slice(hash_key)
+        .map_err(|_| AppError::Internal("Failed to hash webhook token"))?;
+    mac.update(b"wetty-chat-incoming-webhook:v1:");
+    mac.update(id.to_string().as_bytes());
+    mac.update(b":");
+    mac.update(token.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Resolve the webhook addressed by a secret URL. Unknown ids and wrong tokens look the same.
+pub fn authenticate(
+    conn: &mut PgConnection,
+    hash_key: &[u8],
+    id: i64,
+    token: &str,
+) -> Result<IncomingWebhook, AppError> {
+    let webhook = incoming_webhooks::table
+        .filter(incoming_webhooks::id.eq(id))
+        .select(IncomingWebhook::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Webhook not found"))?;
+    let actual = hash_token(hash_key, id, token)?;
+    if !constant_time_eq(actual.as_bytes(), webhook.secret_hash.as_bytes()) {
+        return Err(AppError::NotFound("Webhook not found"));
+    }
+    Ok(webhook)
+}
+
+/// URL path an incoming webhook is posted to, relative to the API root.
+pub fn webhook_path(id: i64, token: &str) -> String {
+    format!("/incoming-webhooks/{id}/{token}")
+}
+
+pub fn normalize_rate_limit(rate_limit_per_minute: Option<i32>) -> Result<i32, AppError> {
+    let limit = rate_limit_per_minute.unwrap_or(DEFAULT_RATE_LIMIT_PER_MINUTE);
+    if !(1..=MAX_RATE_LIMIT_PER_MINUTE).contains(&limit) {
+        return Err(AppError::BadRequest(
+            "Rate limit must be between 1 and 600 per minute",
+        ));
+    }
+    Ok(limit)
+}
+
+/// Count one post against the webhook's one-minute window, rejecting it with 429 once the
+/// window is full. The window lives in the row so every node shares it.
+pub fn consume_rate_limit(
+    conn: &mut PgConnection,
+    webhook: &IncomingWebhook,
+) -> Result<(), AppError> {
+    let window: RateWindow = diesel::sql_query(
+        "UPDATE incoming_webhooks
+         SET window_started_at = CASE
+               WHEN window_started_at <= NOW() - make_interval(secs => $2) THEN NOW()
+               ELSE window_started_at
+             END,
+             window_count = CASE
+               WHEN window_started_at <= NOW() - make_interval(secs => $2) THEN 1
+               ELSE window_count + 1
+             END,
+             last_used_at = NOW()
+         WHERE id = $1
+         RETURNING window_started_at, window_count",
+    )
+    .bind::<diesel::sql_types::BigInt, _>(webhook.id)
+    .bind::<diesel::sql_types::Double, _>(rate_windows::WINDOW_SECS as f64)
+    .get_result(conn)?;
+
+    window.check(webhook.rate_limit_per_minute, RATE_LIMITED)
+}
+
+/// Download one attachment, capped at 25 MiB. `client` must be the webhook service's
+/// outbound client, which refuses private addresses at connect time and on every
+/// redirect, and `url` must already have passed the webhook target policy.
+pub async fn fetch_attachment(
+    client: &reqwest::Client,
+    url: &str,
+    file_name: Option<&str>,
+) -> Result<FetchedAttachment, AppError> {
+    let mut response = client.get(url).send().await.map_err(|err| {
+        tracing::warn!(url, %err, "incoming webhook attachment fetch failed");
+        AppError::BadRequest("Failed to fetch attachment")
+    })?;
+    if !response.status().is_success() {
+        return Err(AppError::BadRequest("Failed to fetch attachment"));
+    }
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_ATTACHMENT_BYTES as u64)
+    {
+        return Err(AppError::BadRequest("Attachment is too large"));
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+
+    let mut data = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|_| AppError::BadRequest("Failed to fetch attachment"))?
+    {
+        if data.len() + chunk.len() > MAX_ATTACHMENT_BYTES {
+            return Err(AppError::BadRequest("Attachment is too large"));
+        }
+        data.extend_from_slice(&chunk);
+    }
+
+    Ok(FetchedAttachment {
+        data,
+        content_type,
+        file_name: attachment_file_name(url, file_name),
+    })
+}
+
+fn attachment_file_name(url: &str, file_name: Option<&str>) -> String {
+    let from_url = || {
+        url.split(['?', '#'])
+            .next()
+            .and_then(|path| path.rsplit('/').next())
+            .unwrap_or_default()
+    };
+    let name = file_name
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(from_url);
+    let name: String = name
+        .chars()
+        .filter(|c| !c.is_control() && *c != '/' && *c != '\\')
+        .take(MAX_FILE_NAME_CHARS)
+        .collect();
+    if name.is_empty() {
+        "attachment".to_string()
+    } else {
+        name
+    }
+}
+
+pub fn lookup_webhook_senders(
+    conn: &mut PgConnection,
+    uids: &[i32],
+) -> QueryResult<HashMap<i32, IncomingWebhook>> {
+    let bot_uids: Vec<i32> = uids
+        .iter()
+        .copied()
+        .filter(|uid| is_bot_uid(*uid))
+        .collect();
+    if bot_uids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(incoming_webhooks::table
+        .filter(incoming_webhooks::uid.eq_any(&bot_uids))
+        .select(IncomingWebhook::as_select())
+        .load::<IncomingWebhook>(conn)?
+        .into_iter()
+        .map(|webhook| (webhook.uid, webhook))
+        .collect())
+}
+
+pub fn load_sender_overrides(
+    conn: &mut PgConnection,
+    message_ids: &[i64],
+) -> QueryResult<HashMap<i64, MessageSenderOverride>> {
+    if message_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(message_sender_overrides::table
+        .filter(message_sender_overrides::message_id.eq_any(message_ids))
+        .select(MessageSenderOverride::as_select())
+        .load::<MessageSenderOverride>(conn)?
+        .into_iter()
+        .map(|row| (row.message_id, row))
+        .collect())
+}
+
+pub fn incoming_webhook_to_response(webhook: IncomingWebhook) -> IncomingWebhookResponse {
+    IncomingWebhookResponse {
+        id: webhook.id,
+        chat_id: webhook.chat_id,
+        uid: webhook.uid,
+        name: webhook.name,
+        avatar_url: webhook.avatar_url,
+        rate_limit_per_minute: webhook.rate_limit_per_minute,
+        created_by_uid: webhook.created_by_uid,
+        last_used_at: webhook.last_used_at,
+        created_at: webhook.created_at,
+        updated_at: webhook.updated_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{attachment_file_name, hash_token, normalize_rate_limit};
+
+    #[test]
+    fn token_hash_is_bound_to_webhook_id() {
+        let key = b"test-key";
+        let hash = hash_token(key, 1, "secret").unwrap();
+        assert_eq!(hash, hash_token(key, 1, "secret").unwrap());
+        assert_ne!(hash, hash_token(key, 2, "secret").unwrap());
+        assert_ne!(hash, hash_token(key, 1, "other").unwrap());
+    }
+
+    #[test]
+    fn rate_limit_defaults_and_bounds() {
+        assert_eq!(normalize_rate_limit(None).unwrap(), 30);
+        assert_eq!(normalize_rate_limit(Some(120)).unwrap(), 120);
+        assert!(normalize_rate_limit(Some(0)).is_err());
+        assert!(normalize_rate_limit(Some(601)).is_err());
+    }
+
+    #[test]
+    fn attachment_file_name_falls_back_to_url_path() {
+        assert_eq!(
+            attachment_file_name("https://ci.example/artifacts/report.html?x=1", None),
+            "report.html"
+        );
+        assert_eq!(
+            attachment_file_name("https://ci.example/a.png", Some("  build/log.txt ")),
+            "buildlog.txt"
+        );
+        assert_eq!(
+            attachment_file_name("https://ci.example/", None),
+            "attachment"
+        );
+    }
+}
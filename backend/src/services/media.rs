@@ -23,10 +23,18 @@ fn collect_presigned_headers(
 }
 
 pub fn build_public_object_url(state: &AppState, storage_key: &str) -> String {
-    let base_url = state
-        .s3_base_url
-        .clone()
-        .unwrap_or_else(|| format!("https://{}.s3.amazonaws.com", state.s3_bucket_name));
+    public_object_url(
+        state.s3_base_url.as_deref(),
+        &state.s3_bucket_name,
+        storage_key,
+    )
+}
+
+/// Same as [`build_public_object_url`] for callers that run outside a request.
+pub fn public_object_url(base_url: Option<&str>, bucket: &str, storage_key: &str) -> String {
+    let base_url = base_url
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("https://{}.s3.amazonaws.com", bucket));
     format!("{}/{}", base_url, storage_key)
 }
 
@@ -101,3 +109,48 @@ pub async fn upload_public_object(
 
     Ok(())
 }
+
+/// Upload an object without a public ACL; it is only reachable through presigned URLs.
+pub async fn upload_private_object(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    storage_key: &str,
+    content_type: &str,
+    body: ByteStream,
+) -> Result<(), String> {
+    s3_client
+        .put_object()
+        .bucket(bucket)
+        .key(storage_key)
+        .content_type(content_type)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to upload object {storage_key}: {e:?}"))?;
+
+    Ok(())
+}
+
+pub async fn presign_download(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    storage_key: &str,
+    expires_in: Duration,
+) -> Result<String, String> {
+    let presigning_config = PresigningConfig::expires_in(
+        expires_in
+            .to_std()
+            .map_err(|e| format!("invalid presign duration: {e}"))?,
+    )
+    .map_err(|e| format!("presigning config error: {e:?}"))?;
+
+    let presigned_request = s3_client
+        .get_object()
+        .bucket(bucket)
+        .key(storage_key)
+        .presigned(presigning_config)
+        .await
+        .map_err(|e| format!("Failed to presign download for {storage_key}: {e:?}"))?;
+
+    Ok(presigned_request.uri().to_string())
+}
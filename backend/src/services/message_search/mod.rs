@@ -357,6 +357,29 @@ impl MessageSearchService {
         }
     }
 
+    /// Index a batch of messages in one task, e.g. after a history import.
+    pub fn upsert_messages_best_effort(self: &Arc<Self>, messages: Vec<Message>) {
+        let documents = messages
+            .iter()
+            .filter_map(project_message_document)
+            .collect::<Vec<_>>();
+        if documents.is_empty() {
+            return;
+        }
+
+        let service = self.clone();
+        let document_count = documents.len();
+        let started_at = Instant::now();
+        tokio::spawn(async move {
+            service.record_index_result(
+                "upsert_batch",
+                document_count,
+                started_at,
+                service.upsert_documents(documents).await,
+            );
+        });
+    }
+
     pub fn upsert_response_best_effort(self: &Arc<Self>, response: MessageResponse) {
         if let Some(document) = project_message_response_document(&response) {
             self.upsert_document_best_effort(document);
@@ -511,6 +534,14 @@ impl MessageSearchService {
             .await
     }
 
+    async fn upsert_documents(
+        &self,
+        documents: Vec<MessageSearchDocument>,
+    ) -> Result<(), MessageSearchError> {
+        self.wait_for_task(self.index().add_documents(&documents, Some("id")).await?)
+            .await
+    }
+
     async fn delete_message(&self, message_id: i64) -> Result<(), MessageSearchError> {
         self.wait_for_task(self.index().delete_document(message_id.to_string()).await?)
             .await
@@ -853,6 +884,8 @@ mod tests {
             sticker_id: None,
             is_published: true,
             transcode_status: TranscodeStatus::None,
+            topic_id: None,
+            is_important: false,
         }
     }
 
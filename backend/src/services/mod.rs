@@ -1,16 +1,31 @@
 pub mod audio_transcode;
+pub mod audit_log;
+pub mod auth_sessions;
 pub mod authz;
 pub mod background;
+pub mod bot_commands;
+pub mod bots;
 pub mod chat;
+pub mod chat_export;
+pub mod chat_folders;
+pub mod chat_import;
 pub mod client_tracking;
 pub mod image_processing;
+pub mod incoming_webhooks;
 pub mod invites;
 pub mod media;
 pub mod message_search;
+pub mod notification_preferences;
+pub mod oidc;
+pub mod outbound_http;
 pub mod push;
+pub mod rate_limit;
+pub mod rate_windows;
 pub mod saved_messages;
 pub mod service_tokens;
 pub mod threads;
+pub mod topics;
 pub mod unread;
 pub mod user;
+pub mod webhooks;
 pub mod ws_registry;
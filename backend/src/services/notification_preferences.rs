@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use crate::dto::push::{NotificationPreferencesResponse, QuietHoursBody};
+use crate::errors::AppError;
+use crate::models::{NotificationLevel, NotificationPreferences, QuietHoursMode};
+use crate::schema::notification_preferences;
+
+pub const MAX_KEYWORDS: usize = 20;
+pub const MAX_KEYWORD_LEN: usize = 64;
+
+/// Preferences for users who never saved any: every push, no keywords, no quiet hours.
+pub fn default_preferences(uid: i32) -> NotificationPreferences {
+    NotificationPreferences {
+        uid,
+        level: NotificationLevel::All,
+        keywords: Vec::new(),
+        quiet_hours_start: None,
+        quiet_hours_end: None,
+        quiet_hours_timezone: None,
+        quiet_hours_mode: QuietHoursMode::Hold,
+        updated_at: DateTime::<Utc>::UNIX_EPOCH,
+    }
+}
+
+pub fn load_preferences(
+    conn: &mut PgConnection,
+    uid: i32,
+) -> Result<NotificationPreferences, AppError> {
+    Ok(notification_preferences::table
+        .find(uid)
+        .select(NotificationPreferences::as_select())
+        .first(conn)
+        .optional()?
+        .unwrap_or_else(|| default_preferences(uid)))
+}
+
+/// Saved preferences of `uids`; users without a row are absent.
+pub fn load_preferences_for(
+    conn: &mut PgConnection,
+    uids: &[i32],
+) -> Result<HashMap<i32, NotificationPreferences>, diesel::result::Error> {
+    let rows: Vec<NotificationPreferences> = notification_preferences::table
+        .filter(notification_preferences::uid.eq_any(uids))
+        .select(NotificationPreferences::as_select())
+        .load(conn)?;
+    Ok(rows.into_iter().map(|row| (row.uid, row)).collect())
+}
+
+pub fn save_preferences(
+    conn: &mut PgConnection,
+    preferences: &NotificationPreferences,
+) -> Result<(), AppError> {
+    diesel::insert_into(notification_preferences::table)
+        .values(preferences)
+        .on_conflict(notification_preferences::uid)
+        .do_update()
+        .set(preferences)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Trim, lowercase and de-duplicate keywords, dropping blanks.
+pub fn normalize_keywords(raw: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut keywords: Vec<String> = Vec::new();
+    for keyword in raw {
+        let keyword = keyword.trim().to_lowercase();
+        if keyword.is_empty() || keywords.contains(&keyword) {
+            continue;
+        }
+        if keyword.chars().count() > MAX_KEYWORD_LEN {
+            return Err(AppError::BadRequest("Keyword is too long"));
+        }
+        keywords.push(keyword);
+    }
+    if keywords.len() > MAX_KEYWORDS {
+        return Err(AppError::BadRequest("Too many keywords"));
+    }
+    Ok(keywords)
+}
+
+/// Validate a quiet-hours window into `(start, end, timezone)` minutes after local midnight.
+pub fn resolve_quiet_hours(body: &QuietHoursBody) -> Result<(i16, i16, String), AppError> {
+    let start = parse_clock(&body.start)?;
+    let end = parse_clock(&body.end)?;
+    if start == end {
+        return Err(AppError::BadRequest(
+            "Quiet hours must start and end at different times",
+        ));
+    }
+    let timezone =
+        Tz::from_str(body.timezone.trim()).map_err(|_| AppError::BadRequest("Unknown timezone"))?;
+    Ok((start, end, timezone.name().to_string()))
+}
+
+/// Parse `HH:MM` (24-hour) into minutes after midnight.
+fn parse_clock(raw: &str) -> Result<i16, AppError> {
+    let invalid = || AppError::BadRequest("Quiet hours times must be HH:MM");
+    let (hours, minutes) = raw.trim().split_once(':').ok_or_else(invalid)?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return Err(invalid());
+    }
+    let hours: i16 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i16 = minutes.parse().map_err(|_| invalid())?;
+    if !(0..24).contains(&hours) || !(0..60).contains(&minutes) {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+fn format_clock(minutes: i16) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+pub fn preferences_to_response(
+    preferences: NotificationPreferences,
+) -> NotificationPreferencesResponse {
+    let quiet_hours = match (
+        preferences.quiet_hours_start,
+        preferences.quiet_hours_end,
+        preferences.quiet_hours_timezone,
+    ) {
+        (Some(start), Some(end), Some(timezone)) => Some(QuietHoursBody {
+            start: format_clock(start),
+            end: format_clock(end),
+            timezone,
+            mode: preferences.quiet_hours_mode,
+        }),
+        _ => None,
+    };
+    NotificationPreferencesResponse {
+        level: preferences.level,
+        keywords: preferences.keywords,
+        quiet_hours,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet_hours(start: &str, end: &str, timezone: &str) -> QuietHoursBody {
+        QuietHoursBody {
+            start: start.to_string(),
+            end: end.to_string(),
+            timezone: timezone.to_string(),
+            mode: QuietHoursMode::Hold,
+        }
+    }
+
+    #[test]
+    fn keywords_are_trimmed_lowercased_and_deduplicated() {
+        let keywords = normalize_keywords(vec![
+            " Deploy ".to_string(),
+            "deploy".to_string(),
+            "  ".to_string(),
+            "On Call".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(keywords, vec!["deploy", "on call"]);
+    }
+
+    #[test]
+    fn keyword_limits_are_enforced() {
+        assert!(normalize_keywords(vec!["x".repeat(MAX_KEYWORD_LEN + 1)]).is_err());
+        let too_many = (0..=MAX_KEYWORDS).map(|i| format!("word{i}")).collect();
+        assert!(normalize_keywords(too_many).is_err());
+    }
+
+    #[test]
+    fn quiet_hours_parse_clock_times_and_timezone() {
+        assert_eq!(
+            resolve_quiet_hours(&quiet_hours("22:00", "07:30", "Europe/Berlin")).unwrap(),
+            (22 * 60, 7 * 60 + 30, "Europe/Berlin".to_string())
+        );
+        assert!(resolve_quiet_hours(&quiet_hours("24:00", "07:00", "UTC")).is_err());
+        assert!(resolve_quiet_hours(&quiet_hours("7:00", "08:00", "UTC")).is_err());
+        assert!(resolve_quiet_hours(&quiet_hours("07:00", "07:00", "UTC")).is_err());
+        assert!(resolve_quiet_hours(&quiet_hours("22:00", "07:00", "Mars/Olympus")).is_err());
+    }
+
+    #[test]
+    fn stored_quiet_hours_round_trip_to_clock_times() {
+        let mut preferences = default_preferences(7);
+        preferences.quiet_hours_start = Some(22 * 60);
+        preferences.quiet_hours_end = Some(7 * 60 + 5);
+        preferences.quiet_hours_timezone = Some("UTC".to_string());
+
+        let response = preferences_to_response(preferences);
+        let quiet_hours = response.quiet_hours.unwrap();
+        assert_eq!(quiet_hours.start, "22:00");
+        assert_eq!(quiet_hours.end, "07:05");
+    }
+}
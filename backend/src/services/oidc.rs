@@ -0,0 +1,694 @@
+//! OpenID Connect authorization-code login (with PKCE). The provider's ID token is
+//! verified here, its subject is mapped to a local uid through `oidc_identities`, and
+//! the caller then issues the usual `AuthClaims` session tokens for that uid.
+//!
+//! Endpoints come from `{OIDC_ISSUER}/.well-known/openid-configuration` unless set
+//! explicitly, and `OIDC_JWKS_JSON` pins the signing keys instead of fetching them, so
+//! a local mock provider only needs an authorization and a token endpoint.
+
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use rand::{rngs::OsRng, RngCore};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::sync::{OnceCell, RwLock};
+
+use crate::errors::AppError;
+use crate::models::{NewOidcIdentity, OidcIdentity, OidcLoginState};
+use crate::schema::discuz::discuz::common_member;
+use crate::schema::{oidc_identities, oidc_login_states};
+use crate::utils::auth::ensure_jwt_crypto_provider;
+
+const LOGIN_STATE_TTL_SECS: i64 = 10 * 60;
+const RANDOM_BYTES: usize = 32;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const ID_TOKEN_LEEWAY_SECS: u64 = 60;
+const DEFAULT_SCOPES: &str = "openid email profile";
+const DEFAULT_ID_TOKEN_ALGORITHMS: &str = "RS256";
+
+diesel::define_sql_function!(fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text);
+
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    pub scopes: String,
+    pub authorization_endpoint: Option<String>,
+    pub token_endpoint: Option<String>,
+    pub jwks_uri: Option<String>,
+    pub static_jwks: Option<JwkSet>,
+    /// Signature algorithms accepted for ID tokens; the token header's `alg` is only
+    /// trusted if it is one of these.
+    pub id_token_algorithms: Vec<Algorithm>,
+    /// Link first-time subjects to the Discuz account with the same verified email.
+    pub link_by_email: bool,
+}
+
+impl OidcConfig {
+    /// `None` unless `OIDC_ISSUER` is set.
+    pub fn from_env() -> Option<Self> {
+        let issuer = read_env("OIDC_ISSUER")?;
+        let static_jwks = read_env("OIDC_JWKS_JSON").map(|raw| {
+            serde_json::from_str::<JwkSet>(&raw).expect("OIDC_JWKS_JSON must be a JWK set")
+        });
+        Some(Self {
+            issuer: issuer.trim_end_matches('/').to_string(),
+            client_id: read_env("OIDC_CLIENT_ID")
+                .expect("OIDC_CLIENT_ID must be set when OIDC_ISSUER is set"),
+            client_secret: read_env("OIDC_CLIENT_SECRET"),
+            redirect_uri: read_env("OIDC_REDIRECT_URI")
+                .expect("OIDC_REDIRECT_URI must be set when OIDC_ISSUER is set"),
+            scopes: read_env("OIDC_SCOPES").unwrap_or_else(|| DEFAULT_SCOPES.to_string()),
+            authorization_endpoint: read_env("OIDC_AUTHORIZATION_ENDPOINT"),
+            token_endpoint: read_env("OIDC_TOKEN_ENDPOINT"),
+            jwks_uri: read_env("OIDC_JWKS_URI"),
+            static_jwks,
+            id_token_algorithms: parse_algorithms(
+                read_env("OIDC_ID_TOKEN_ALGORITHMS")
+                    .as_deref()
+                    .unwrap_or(DEFAULT_ID_TOKEN_ALGORITHMS),
+            )
+            .unwrap_or_else(|err| panic!("OIDC_ID_TOKEN_ALGORITHMS is invalid: {err}")),
+            link_by_email: read_env("OIDC_LINK_BY_EMAIL")
+                .is_some_and(|value| matches!(value.as_str(), "1" | "true")),
+        })
+    }
+}
+
+/// Parse a comma-separated list of JWS algorithm names, e.g. `RS256,ES256`.
+fn parse_algorithms(raw: &str) -> Result<Vec<Algorithm>, String> {
+    let algorithms = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| Algorithm::from_str(name).map_err(|_| format!("unknown algorithm `{name}`")))
+        .collect::<Result<Vec<_>, _>>()?;
+    if algorithms.is_empty() {
+        return Err("at least one algorithm is required".to_string());
+    }
+    Ok(algorithms)
+}
+
+fn read_env(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderEndpoints {
+    issuer: Option<String>,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+/// The ID token claims used for linking; `iss`, `aud` and `exp` are checked while decoding.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: Option<bool>,
+    #[serde(default)]
+    pub nonce: Option<String>,
+}
+
+impl IdTokenClaims {
+    fn verified_email(&self) -> Option<&str> {
+        self.email
+            .as_deref()
+            .filter(|_| self.email_verified == Some(true))
+    }
+}
+
+struct CachedJwks {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+pub struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    endpoints: OnceCell<ProviderEndpoints>,
+    jwks: RwLock<Option<CachedJwks>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("OIDC HTTP client should build");
+        Self {
+            config,
+            http,
+            endpoints: OnceCell::new(),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.config.issuer
+    }
+
+    pub fn link_by_email(&self) -> bool {
+        self.config.link_by_email
+    }
+
+    async fn endpoints(&self) -> Result<&ProviderEndpoints, AppError> {
+        self.endpoints
+            .get_or_try_init(|| async {
+                if let (Some(authorization_endpoint), Some(token_endpoint)) = (
+                    &self.config.authorization_endpoint,
+                    &self.config.token_endpoint,
+                ) {
+                    return Ok(ProviderEndpoints {
+                        issuer: None,
+                        authorization_endpoint: authorization_endpoint.clone(),
+                        token_endpoint: token_endpoint.clone(),
+                        jwks_uri: self.config.jwks_uri.clone(),
+                    });
+                }
+
+                let url = format!("{}/.well-known/openid-configuration", self.config.issuer);
+                let discovered: ProviderEndpoints = self.get_json(&url).await?;
+                if discovered
+                    .issuer
+                    .as_deref()
+                    .is_some_and(|issuer| issuer.trim_end_matches('/') != self.config.issuer)
+                {
+                    tracing::error!(?discovered.issuer, "OIDC discovery issuer mismatch");
+                    return Err(AppError::ServiceUnavailable(
+                        "Identity provider unavailable",
+                    ));
+                }
+                Ok(ProviderEndpoints {
+                    authorization_endpoint: self
+                        .config
+                        .authorization_endpoint
+                        .clone()
+                        .unwrap_or(discovered.authorization_endpoint),
+                    token_endpoint: self
+                        .config
+                        .token_endpoint
+                        .clone()
+                        .unwrap_or(discovered.token_endpoint),
+                    jwks_uri: self.config.jwks_uri.clone().or(discovered.jwks_uri),
+                    issuer: discovered.issuer,
+                })
+            })
+            .await
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, AppError> {
+        let unavailable = |err: &dyn std::fmt::Debug| {
+            tracing::warn!(url, ?err, "OIDC provider request failed");
+            AppError::ServiceUnavailable("Identity provider unavailable")
+        };
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| unavailable(&e))?;
+        let response = response.error_for_status().map_err(|e| unavailable(&e))?;
+        let body = response.bytes().await.map_err(|e| unavailable(&e))?;
+        serde_json::from_slice(&body).map_err(|e| unavailable(&e))
+    }
+
+    /// Where to send the browser to start a login.
+    pub async fn authorization_url(&self, login: &OidcLoginState) -> Result<String, AppError> {
+        let endpoints = self.endpoints().await?;
+        let mut url = reqwest::Url::parse(&endpoints.authorization_endpoint).map_err(|e| {
+            tracing::error!(?e, "invalid OIDC authorization endpoint");
+            AppError::Internal("Invalid identity provider configuration")
+        })?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_uri)
+            .append_pair("scope", &self.config.scopes)
+            .append_pair("state", &login.state)
+            .append_pair("nonce", &login.nonce)
+            .append_pair("code_challenge", &pkce_challenge(&login.code_verifier))
+            .append_pair("code_challenge_method", "S256");
+        Ok(url.into())
+    }
+
+    /// Redeem an authorization code and return the verified ID token claims.
+    pub async fn complete_login(
+        &self,
+        code: &str,
+        login: &OidcLoginState,
+    ) -> Result<IdTokenClaims, AppError> {
+        let endpoints = self.endpoints().await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", login.code_verifier.as_str()),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let response = self
+            .http
+            .post(&endpoints.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|err| {
+                tracing::warn!(?err, "OIDC token request failed");
+                AppError::ServiceUnavailable("Identity provider unavailable")
+            })?;
+        let status = response.status();
+        let body = response.bytes().await.map_err(|err| {
+            tracing::warn!(?err, "OIDC token response unreadable");
+            AppError::ServiceUnavailable("Identity provider unavailable")
+        })?;
+        if status.is_client_error() {
+            tracing::info!(%status, body = %String::from_utf8_lossy(&body), "OIDC code rejected");
+            return Err(AppError::Unauthorized("Authorization code was rejected"));
+        }
+        if !status.is_success() {
+            tracing::warn!(%status, "OIDC token endpoint failed");
+            return Err(AppError::ServiceUnavailable(
+                "Identity provider unavailable",
+            ));
+        }
+        let id_token = serde_json::from_slice::<TokenResponse>(&body)
+            .ok()
+            .and_then(|tokens| tokens.id_token)
+            .ok_or(AppError::Unauthorized(
+                "Identity provider returned no ID token",
+            ))?;
+
+        self.verify_id_token(&id_token, &login.nonce).await
+    }
+
+    async fn verify_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, AppError> {
+        let header =
+            decode_header(id_token).map_err(|_| AppError::Unauthorized("Invalid ID token"))?;
+        if !self.config.id_token_algorithms.contains(&header.alg) {
+            tracing::info!(alg = ?header.alg, "OIDC ID token uses a disallowed algorithm");
+            return Err(AppError::Unauthorized("Invalid ID token"));
+        }
+        let key = self.decoding_key(header.kid.as_deref()).await?;
+        let claims = decode_id_token(id_token, &key, header.alg, &self.config)?;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(AppError::Unauthorized("Invalid ID token"));
+        }
+        Ok(claims)
+    }
+
+    async fn decoding_key(&self, kid: Option<&str>) -> Result<DecodingKey, AppError> {
+        if let Some(keys) = &self.config.static_jwks {
+            return select_key(keys, kid).ok_or(AppError::Unauthorized("Invalid ID token"));
+        }
+
+        if let Some(cached) = self.jwks.read().await.as_ref() {
+            if let Some(key) = select_key(&cached.keys, kid) {
+                return Ok(key);
+            }
+            // Unknown key id: the provider may have rotated, but don't refetch on every token.
+            if cached.fetched_at.elapsed() < JWKS_REFRESH_INTERVAL {
+                return Err(AppError::Unauthorized("Invalid ID token"));
+            }
+        }
+
+        let jwks_uri = self.endpoints().await?.jwks_uri.clone().ok_or_else(|| {
+            tracing::error!("OIDC provider has no jwks_uri and OIDC_JWKS_JSON is unset");
+            AppError::Internal("Invalid identity provider configuration")
+        })?;
+        let keys: JwkSet = self.get_json(&jwks_uri).await?;
+        let key = select_key(&keys, kid);
+        *self.jwks.write().await = Some(CachedJwks {
+            keys,
+            fetched_at: Instant::now(),
+        });
+        key.ok_or(AppError::Unauthorized("Invalid ID token"))
+    }
+}
+
+/// The key named by `kid`, or the only key when the token names none.
+fn select_key(keys: &JwkSet, kid: Option<&str>) -> Option<DecodingKey> {
+    let jwk: &Jwk = match kid {
+        Some(kid) => keys.find(kid)?,
+        None if keys.keys.len() == 1 => &keys.keys[0],
+        None => return None,
+    };
+    DecodingKey::from_jwk(jwk).ok()
+}
+
+/// `alg` must already have been checked against `config.id_token_algorithms`.
+fn decode_id_token(
+    id_token: &str,
+    key: &DecodingKey,
+    alg: Algorithm,
+    config: &OidcConfig,
+) -> Result<IdTokenClaims, AppError> {
+    ensure_jwt_crypto_provider();
+    let mut validation = Validation::new(alg);
+    validation.set_issuer(&[config.issuer.as_str(), &format!("{}/", config.issuer)]);
+    validation.set_audience(&[config.client_id.as_str()]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+    validation.leeway = ID_TOKEN_LEEWAY_SECS;
+    decode::<IdTokenClaims>(id_token, key, &validation)
+        .map(|data| data.claims)
+        .map_err(|err| {
+            tracing::info!(?err, "OIDC ID token rejected");
+            AppError::Unauthorized("Invalid ID token")
+        })
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; RANDOM_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// RFC 7636 `S256` code challenge.
+fn pkce_challenge(code_verifier: &str) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier))
+}
+
+/// Start a login for `client_id`; with `link_uid` the identity is linked to that user.
+pub fn create_login_state(
+    conn: &mut PgConnection,
+    client_id: &str,
+    link_uid: Option<i32>,
+) -> Result<OidcLoginState, AppError> {
+    let now = Utc::now();
+    diesel::delete(oidc_login_states::table.filter(oidc_login_states::expires_at.lt(now)))
+        .execute(conn)?;
+
+    let login = OidcLoginState {
+        state: random_token(),
+        nonce: random_token(),
+        code_verifier: random_token(),
+        client_id: client_id.to_string(),
+        link_uid,
+        created_at: now,
+        expires_at: now + chrono::Duration::seconds(LOGIN_STATE_TTL_SECS),
+    };
+    diesel::insert_into(oidc_login_states::table)
+        .values(&login)
+        .execute(conn)?;
+    Ok(login)
+}
+
+/// Consume a pending login; each state can be redeemed once.
+pub fn take_login_state(conn: &mut PgConnection, state: &str) -> Result<OidcLoginState, AppError> {
+    diesel::delete(
+        oidc_login_states::table
+            .filter(oidc_login_states::state.eq(state))
+            .filter(oidc_login_states::expires_at.gt(Utc::now())),
+    )
+    .returning(OidcLoginState::as_returning())
+    .get_result(conn)
+    .optional()?
+    .ok_or(AppError::BadRequest("Login state is invalid or expired"))
+}
+
+/// The local uid for a verified subject, linking it first when the login was started by
+/// a signed-in user or, if enabled, when its verified email matches one account.
+pub fn resolve_uid(
+    conn: &mut PgConnection,
+    client: &OidcClient,
+    claims: &IdTokenClaims,
+    link_uid: Option<i32>,
+    new_identity_id: i64,
+) -> Result<i32, AppError> {
+    let now = Utc::now();
+    let existing = oidc_identities::table
+        .filter(oidc_identities::issuer.eq(client.issuer()))
+        .filter(oidc_identities::subject.eq(&claims.sub))
+        .select(OidcIdentity::as_select())
+        .first::<OidcIdentity>(conn)
+        .optional()?;
+
+    if let Some(identity) = existing {
+        if link_uid.is_some_and(|uid| uid != identity.uid) {
+            return Err(AppError::Conflict(
+                "Identity is already linked to another account",
+            ));
+        }
+        diesel::update(oidc_identities::table.filter(oidc_identities::id.eq(identity.id)))
+            .set((
+                oidc_identities::email.eq(&claims.email),
+                oidc_identities::last_login_at.eq(now),
+            ))
+            .execute(conn)?;
+        return Ok(identity.uid);
+    }
+
+    let uid = match link_uid {
+        Some(uid) => uid,
+        None => find_uid_by_email(conn, client, claims)?
+            .ok_or(AppError::Forbidden("No account is linked to this identity"))?,
+    };
+    diesel::insert_into(oidc_identities::table)
+        .values(&NewOidcIdentity {
+            id: new_identity_id,
+            issuer: client.issuer().to_string(),
+            subject: claims.sub.clone(),
+            uid,
+            email: claims.email.clone(),
+            last_login_at: Some(now),
+        })
+        .execute(conn)?;
+    tracing::info!(uid, issuer = client.issuer(), "OIDC identity linked");
+    Ok(uid)
+}
+
+fn find_uid_by_email(
+    conn: &mut PgConnection,
+    client: &OidcClient,
+    claims: &IdTokenClaims,
+) -> Result<Option<i32>, AppError> {
+    let Some(email) = claims.verified_email().filter(|_| client.link_by_email()) else {
+        return Ok(None);
+    };
+    let uids: Vec<i32> = common_member::table
+        .filter(lower(common_member::email).eq(email.trim().to_lowercase()))
+        .select(common_member::uid)
+        .limit(2)
+        .load(conn)?;
+    // An email shared by several accounts can't identify one of them.
+    Ok(match uids.as_slice() {
+        [uid] => Some(*uid),
+        _ => None,
+    })
+}
+
+pub fn list_identities(conn: &mut PgConnection, uid: i32) -> QueryResult<Vec<OidcIdentity>> {
+    oidc_identities::table
+        .filter(oidc_identities::uid.eq(uid))
+        .order(oidc_identities::created_at.asc())
+        .select(OidcIdentity::as_select())
+        .load(conn)
+}
+
+pub fn unlink_identity(conn: &mut PgConnection, uid: i32, id: i64) -> Result<(), AppError> {
+    let deleted = diesel::delete(
+        oidc_identities::table
+            .filter(oidc_identities::id.eq(id))
+            .filter(oidc_identities::uid.eq(uid)),
+    )
+    .execute(conn)?;
+    if deleted == 0 {
+        return Err(AppError::NotFound("Identity not found"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::post, Form, Json, Router};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    use super::*;
+
+    const SECRET: &[u8] = b"mock-idp-signing-key-0123456789abcdef";
+
+    fn config(token_endpoint: String) -> OidcConfig {
+        let k = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(SECRET);
+        OidcConfig {
+            issuer: "https://idp.test".to_string(),
+            client_id: "wetty".to_string(),
+            client_secret: None,
+            redirect_uri: "https://chat.test/oidc/callback".to_string(),
+            scopes: DEFAULT_SCOPES.to_string(),
+            authorization_endpoint: Some("https://idp.test/authorize".to_string()),
+            token_endpoint: Some(token_endpoint),
+            jwks_uri: None,
+            static_jwks: Some(
+                serde_json::from_value(json!({
+                    "keys": [{"kty": "oct", "kid": "mock", "alg": "HS256", "k": k}]
+                }))
+                .unwrap(),
+            ),
+            id_token_algorithms: vec![Algorithm::HS256],
+            link_by_email: false,
+        }
+    }
+
+    fn login() -> OidcLoginState {
+        let now = Utc::now();
+        OidcLoginState {
+            state: "state".to_string(),
+            nonce: "nonce-1".to_string(),
+            code_verifier: "verifier".to_string(),
+            client_id: "client_1".to_string(),
+            link_uid: None,
+            created_at: now,
+            expires_at: now,
+        }
+    }
+
+    fn id_token(claims: serde_json::Value) -> String {
+        ensure_jwt_crypto_provider();
+        let mut header = Header::new(jsonwebtoken::Algorithm::HS256);
+        header.kid = Some("mock".to_string());
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn valid_claims() -> serde_json::Value {
+        json!({
+            "iss": "https://idp.test",
+            "aud": "wetty",
+            "sub": "user-7",
+            "exp": Utc::now().timestamp() + 300,
+            "nonce": "nonce-1",
+            "email": "a@example.com",
+            "email_verified": true,
+        })
+    }
+
+    /// Token endpoint of a mock provider that answers `good-code` for the expected verifier.
+    async fn spawn_mock_idp(claims: serde_json::Value) -> String {
+        let app = Router::new().route(
+            "/token",
+            post(
+                move |Form(form): Form<std::collections::HashMap<String, String>>| {
+                    let claims = claims.clone();
+                    async move {
+                        let ok = form.get("code").map(String::as_str) == Some("good-code")
+                            && form.get("code_verifier").map(String::as_str) == Some("verifier");
+                        if ok {
+                            Ok(Json(json!({"id_token": id_token(claims)})))
+                        } else {
+                            Err(axum::http::StatusCode::BAD_REQUEST)
+                        }
+                    }
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}/token")
+    }
+
+    #[test]
+    fn pkce_challenge_matches_rfc_example() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[tokio::test]
+    async fn authorization_url_carries_state_nonce_and_challenge() {
+        let client = OidcClient::new(config("https://idp.test/token".to_string()));
+        let url = client.authorization_url(&login()).await.unwrap();
+        let url = reqwest::Url::parse(&url).unwrap();
+        let params: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(params["client_id"], "wetty");
+        assert_eq!(params["state"], "state");
+        assert_eq!(params["nonce"], "nonce-1");
+        assert_eq!(params["code_challenge"], pkce_challenge("verifier"));
+        assert_eq!(params["code_challenge_method"], "S256");
+    }
+
+    #[tokio::test]
+    async fn complete_login_against_mock_idp() {
+        let client = OidcClient::new(config(spawn_mock_idp(valid_claims()).await));
+        let claims = client.complete_login("good-code", &login()).await.unwrap();
+        assert_eq!(claims.sub, "user-7");
+        assert_eq!(claims.verified_email(), Some("a@example.com"));
+
+        assert!(matches!(
+            client.complete_login("bad-code", &login()).await,
+            Err(AppError::Unauthorized("Authorization code was rejected"))
+        ));
+    }
+
+    #[tokio::test]
+    async fn complete_login_rejects_wrong_nonce_or_audience() {
+        let mut claims = valid_claims();
+        claims["nonce"] = json!("other");
+        let client = OidcClient::new(config(spawn_mock_idp(claims).await));
+        assert!(matches!(
+            client.complete_login("good-code", &login()).await,
+            Err(AppError::Unauthorized("Invalid ID token"))
+        ));
+
+        let mut claims = valid_claims();
+        claims["aud"] = json!("someone-else");
+        let client = OidcClient::new(config(spawn_mock_idp(claims).await));
+        assert!(matches!(
+            client.complete_login("good-code", &login()).await,
+            Err(AppError::Unauthorized("Invalid ID token"))
+        ));
+    }
+
+    #[tokio::test]
+    async fn complete_login_rejects_algorithms_outside_the_configured_set() {
+        let mut config = config(spawn_mock_idp(valid_claims()).await);
+        config.id_token_algorithms = vec![Algorithm::RS256];
+        let client = OidcClient::new(config);
+        assert!(matches!(
+            client.complete_login("good-code", &login()).await,
+            Err(AppError::Unauthorized("Invalid ID token"))
+        ));
+    }
+
+    #[test]
+    fn parse_algorithms_requires_known_names() {
+        assert_eq!(
+            parse_algorithms("RS256, ES256").unwrap(),
+            vec![Algorithm::RS256, Algorithm::ES256]
+        );
+        assert!(parse_algorithms("none").is_err());
+        assert!(parse_algorithms(" , ").is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! HTTP client for requests to user-supplied URLs (webhook deliveries and
+//! incoming webhook attachment fetches).
+//!
+//! Checking the URL when it is registered is not enough: a public hostname can
+//! resolve to an internal address later, and a public target can redirect to
+//! an internal one. The client therefore filters the addresses it connects to
+//! at resolve time and re-checks every redirect hop.
+
+use std::error::Error as StdError;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect::{Attempt, Policy};
+
+const MAX_REDIRECTS: usize = 5;
+
+/// Build a client for user-supplied targets. Unless `allow_private_targets` is
+/// set, it refuses to connect to private, loopback, link-local and metadata
+/// addresses, and bypasses any configured proxy so the check sees the real
+/// destination.
+pub fn build_client(
+    timeout: Duration,
+    user_agent: &str,
+    allow_private_targets: bool,
+) -> reqwest::Client {
+    let builder = reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent(user_agent)
+        .redirect(Policy::custom(move |attempt| {
+            check_redirect(attempt, allow_private_targets)
+        }));
+    let builder = if allow_private_targets {
+        builder
+    } else {
+        builder
+            .no_proxy()
+            .dns_resolver(Arc::new(PublicOnlyResolver))
+    };
+    builder.build().expect("outbound HTTP client should build")
+}
+
+fn check_redirect(attempt: Attempt<'_>, allow_private_targets: bool) -> reqwest::redirect::Action {
+    if attempt.previous().len() > MAX_REDIRECTS {
+        return attempt.error("too many redirects");
+    }
+    let url = attempt.url();
+    if !matches!(url.scheme(), "http" | "https") {
+        return attempt.error("redirect to a non-http(s) URL");
+    }
+    // Hostnames are checked by the resolver; IP literals never reach it.
+    if !allow_private_targets && url.host_str().is_some_and(is_private_host) {
+        return attempt.error("redirect to a private address");
+    }
+    attempt.follow()
+}
+
+/// Resolves through the system resolver and drops every non-public address.
+struct PublicOnlyResolver;
+
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| !is_private_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                let err: Box<dyn StdError + Send + Sync> =
+                    format!("{host} does not resolve to a public address").into();
+                return Err(err);
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Whether a URL host (an IP literal, possibly bracketed, or a domain) is
+/// obviously private without resolving it.
+pub fn is_private_host(host: &str) -> bool {
+    match host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        Ok(ip) => is_private_ip(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+    }
+}
+
+/// Addresses a user-supplied URL must never reach: loopback, private and
+/// shared networks, link-local (which includes cloud metadata endpoints),
+/// unspecified, broadcast, multicast and reserved ranges, including IPv4
+/// addresses embedded in IPv6.
+pub fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_private_ipv4(ip),
+        IpAddr::V6(ip) => is_private_ipv6(ip),
+    }
+}
+
+fn is_private_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || a == 0
+        // Shared address space (100.64.0.0/10), used by some metadata services.
+        || (a == 100 && (b & 0xc0) == 64)
+        // IETF protocol assignments (192.0.0.0/24).
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking (198.18.0.0/15).
+        || (a == 198 && (b & 0xfe) == 18)
+        // Reserved (240.0.0.0/4).
+        || a >= 240
+}
+
+fn is_private_ipv6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_private_ipv4(v4);
+    }
+    // NAT64 (64:ff9b::/96) reaches the embedded IPv4 address.
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let v4 = Ipv4Addr::from((u32::from(segments[6]) << 16) | u32::from(segments[7]));
+        return is_private_ipv4(v4);
+    }
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local (fc00::/7), which includes cloud IPv6 metadata endpoints.
+        || (segments[0] & 0xfe00) == 0xfc00
+        // Link-local (fe80::/10).
+        || (segments[0] & 0xffc0) == 0xfe80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_client, is_private_host, is_private_ip};
+    use std::net::IpAddr;
+    use std::time::Duration;
+
+    fn ip(raw: &str) -> IpAddr {
+        raw.parse().unwrap()
+    }
+
+    #[test]
+    fn private_ranges_are_refused() {
+        for raw in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.100.100.200",
+            "0.0.0.0",
+            "224.0.0.1",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00:ec2::254",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(is_private_ip(ip(raw)), "{raw} should be private");
+        }
+        for raw in [
+            "93.184.216.34",
+            "100.128.0.1",
+            "2606:4700::1111",
+            "64:ff9b::808:808",
+        ] {
+            assert!(!is_private_ip(ip(raw)), "{raw} should be public");
+        }
+    }
+
+    #[test]
+    fn private_hosts_are_recognized_without_resolving() {
+        assert!(is_private_host("[::1]"));
+        assert!(is_private_host("127.0.0.1"));
+        assert!(is_private_host("LOCALHOST"));
+        assert!(is_private_host("api.localhost."));
+        assert!(!is_private_host("hooks.example.com"));
+        assert!(!is_private_host("8.8.8.8"));
+    }
+
+    #[tokio::test]
+    async fn client_refuses_hostnames_resolving_to_private_addresses() {
+        let app = axum::Router::new().route("/", axum::routing::get(|| async { "ok" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let url = format!("http://localhost:{port}/");
+
+        let filtered = build_client(Duration::from_secs(5), "test", false);
+        assert!(filtered.get(&url).send().await.is_err());
+
+        let permissive = build_client(Duration::from_secs(5), "test", true);
+        let response = permissive.get(&url).send().await.unwrap();
+        assert!(response.status().is_success());
+    }
+}
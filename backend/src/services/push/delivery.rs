@@ -3,17 +3,29 @@ use a2::{
     DefaultNotificationBuilder, Endpoint as ApnsEndpoint, ErrorReason as ApnsErrorReason,
     NotificationBuilder, NotificationOptions, Priority as ApnsPriority, PushType as ApnsPushType,
 };
+use serde::Deserialize;
 use std::fs::File;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
 use tracing::{error, warn};
 use web_push::WebPushClient;
 
 use crate::models::{PushEnvironment, PushProvider, PushSubscription};
+use crate::utils::auth::ensure_jwt_crypto_provider;
 
-use super::payload::ApnsNotification;
+use super::payload::{ApnsNotification, FcmNotification};
 use super::PushService;
 
 const APNS_CUSTOM_DATA_ROOT: &str = "wettyChat";
 
+const FCM_DEFAULT_ENDPOINT: &str = "https://fcm.googleapis.com";
+const FCM_DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const FCM_OAUTH_SCOPE: &str = "https://www.googleapis.com/auth/firebase.messaging";
+const FCM_ASSERTION_LIFETIME_SECS: i64 = 3600;
+const FCM_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Access tokens are refreshed this long before Google says they expire.
+const FCM_TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
 #[derive(Debug)]
 struct PayloadWithThreadId<'a> {
     inner: a2::request::payload::Payload<'a>,
@@ -54,6 +66,35 @@ pub(super) struct ApnsSender {
     topic: String,
 }
 
+/// The fields of a Google service-account key file that FCM auth needs.
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct FcmServiceAccount {
+    pub(super) project_id: String,
+    pub(super) client_email: String,
+    pub(super) private_key: String,
+    #[serde(default = "default_fcm_token_uri")]
+    pub(super) token_uri: String,
+}
+
+fn default_fcm_token_uri() -> String {
+    FCM_DEFAULT_TOKEN_URI.to_string()
+}
+
+struct CachedAccessToken {
+    token: String,
+    expires_at: Instant,
+}
+
+/// Sends through the FCM HTTP v1 API, authenticating with a service-account JWT that is
+/// exchanged for a short-lived OAuth access token.
+pub(super) struct FcmSender {
+    http: reqwest::Client,
+    endpoint: String,
+    account: FcmServiceAccount,
+    signing_key: jsonwebtoken::EncodingKey,
+    access_token: Mutex<Option<CachedAccessToken>>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum DeliveryFailureAction {
     None,
@@ -118,10 +159,12 @@ impl PushService {
         sub: &PushSubscription,
         web_payload: &[u8],
         apns_notification: &ApnsNotification,
+        fcm_notification: &FcmNotification,
     ) -> Result<(), DeliveryFailure> {
         match sub.provider {
             PushProvider::WebPush => self.send_web_push(sub, web_payload).await,
             PushProvider::Apns => self.send_apns_push(sub, apns_notification).await,
+            PushProvider::Fcm => self.send_fcm_push(sub, fcm_notification).await,
         }
     }
 
@@ -370,6 +413,83 @@ impl PushService {
             }
         }
     }
+
+    async fn send_fcm_push(
+        &self,
+        sub: &PushSubscription,
+        notification: &FcmNotification,
+    ) -> Result<(), DeliveryFailure> {
+        let sender = match &self.fcm_sender {
+            Some(sender) => sender,
+            None => {
+                warn!(
+                    "received FCM subscription {} without FCM sender configured",
+                    sub.id
+                );
+                self.metrics
+                    .record_push_notification(PushProvider::Fcm.as_metrics_label(), false);
+                return Err(DeliveryFailure::from_details(
+                    sub,
+                    DeliveryFailureDetails {
+                        class: DeliveryFailureClass::BackendConfig,
+                        reason: "fcm_not_configured".to_string(),
+                        action: DeliveryFailureAction::None,
+                    },
+                ));
+            }
+        };
+        let device_token = match &sub.device_token {
+            Some(token) => token.as_str(),
+            None => {
+                warn!("FCM subscription {} missing device token", sub.id);
+                self.metrics
+                    .record_push_notification(PushProvider::Fcm.as_metrics_label(), false);
+                return Err(DeliveryFailure::from_details(
+                    sub,
+                    DeliveryFailureDetails {
+                        class: DeliveryFailureClass::InvalidSubscription,
+                        reason: "missing_device_token".to_string(),
+                        action: DeliveryFailureAction::PruneImmediate,
+                    },
+                ));
+            }
+        };
+
+        match sender.send(device_token, notification).await {
+            Ok(()) => {
+                self.metrics
+                    .record_push_notification(PushProvider::Fcm.as_metrics_label(), true);
+                Ok(())
+            }
+            Err(details) if details.action == DeliveryFailureAction::PruneImmediate => {
+                warn!(
+                    provider = PushProvider::Fcm.as_metrics_label(),
+                    subscription_id = sub.id,
+                    user_id = sub.user_id,
+                    reason = %details.reason,
+                    action = "prune",
+                    "stale FCM subscription"
+                );
+                self.metrics
+                    .record_push_notification(PushProvider::Fcm.as_metrics_label(), false);
+                Err(DeliveryFailure::from_details(sub, details))
+            }
+            Err(details) => {
+                error!(
+                    provider = PushProvider::Fcm.as_metrics_label(),
+                    subscription_id = sub.id,
+                    user_id = sub.user_id,
+                    failure_class = details.class.as_metrics_label(),
+                    reason = %details.reason,
+                    action = "retry",
+                    "failed to send FCM notification"
+                );
+                self.metrics
+                    .record_push_notification(PushProvider::Fcm.as_metrics_label(), false);
+                Err(DeliveryFailure::from_details(sub, details))
+            }
+        }
+    }
 }
 
 impl ApnsSender {
@@ -433,13 +553,15 @@ impl ApnsSender {
             .iter()
             .map(String::as_str)
             .collect();
-        let builder = DefaultNotificationBuilder::new()
+        let mut builder = DefaultNotificationBuilder::new()
             .set_title_loc_key(notification.title_loc_key)
             .set_title_loc_args(&title_loc_args)
             .set_loc_key(notification.body_loc_key)
             .set_loc_args(&body_loc_args)
-            .set_badge(notification.badge)
-            .set_sound("default");
+            .set_badge(notification.badge);
+        if !notification.silent {
+            builder = builder.set_sound("default");
+        }
         let options = NotificationOptions {
             apns_push_type: Some(ApnsPushType::Alert),
             apns_priority: Some(ApnsPriority::High),
@@ -491,6 +613,279 @@ impl ApnsSender {
     }
 }
 
+impl FcmSender {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        let account_path = std::env::var("FCM_SERVICE_ACCOUNT_PATH").ok();
+        let project_id = std::env::var("FCM_PROJECT_ID").ok();
+        let endpoint = std::env::var("FCM_ENDPOINT").ok();
+
+        let Some(account_path) = account_path else {
+            if project_id.is_some() || endpoint.is_some() {
+                return Err("FCM_SERVICE_ACCOUNT_PATH must be set".to_string());
+            }
+            return Ok(None);
+        };
+
+        let file = File::open(&account_path)
+            .map_err(|e| format!("failed to open FCM service account: {:?}", e))?;
+        let mut account: FcmServiceAccount = serde_json::from_reader(file)
+            .map_err(|e| format!("failed to parse FCM service account: {:?}", e))?;
+        if let Some(project_id) = project_id {
+            account.project_id = project_id;
+        }
+
+        Self::new(
+            account,
+            endpoint.unwrap_or_else(|| FCM_DEFAULT_ENDPOINT.to_string()),
+        )
+        .map(Some)
+    }
+
+    pub(super) fn new(account: FcmServiceAccount, endpoint: String) -> Result<Self, String> {
+        let signing_key =
+            jsonwebtoken::EncodingKey::from_rsa_pem(account.private_key.as_bytes())
+                .map_err(|e| format!("invalid FCM service account private key: {:?}", e))?;
+        let http = reqwest::Client::builder()
+            .timeout(FCM_REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("failed to initialize FCM client: {:?}", e))?;
+
+        Ok(Self {
+            http,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            account,
+            signing_key,
+            access_token: Mutex::new(None),
+        })
+    }
+
+    /// A cached OAuth access token, fetched again shortly before it expires. The lock is held
+    /// across the exchange so concurrent sends share one refresh.
+    async fn access_token(&self) -> Result<String, DeliveryFailureDetails> {
+        let mut cached = self.access_token.lock().await;
+        if let Some(token) = cached.as_ref() {
+            if token.expires_at > Instant::now() + FCM_TOKEN_REFRESH_MARGIN {
+                return Ok(token.token.clone());
+            }
+        }
+
+        #[derive(serde::Serialize)]
+        struct AssertionClaims<'a> {
+            iss: &'a str,
+            scope: &'a str,
+            aud: &'a str,
+            iat: i64,
+            exp: i64,
+        }
+
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            access_token: String,
+            expires_in: u64,
+        }
+
+        ensure_jwt_crypto_provider();
+        let now = chrono::Utc::now().timestamp();
+        let assertion = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
+            &AssertionClaims {
+                iss: &self.account.client_email,
+                scope: FCM_OAUTH_SCOPE,
+                aud: &self.account.token_uri,
+                iat: now,
+                exp: now + FCM_ASSERTION_LIFETIME_SECS,
+            },
+            &self.signing_key,
+        )
+        .map_err(|e| {
+            error!("failed to sign FCM service account assertion: {:?}", e);
+            fcm_auth_failure("fcm_auth_signing", DeliveryFailureClass::BackendConfig)
+        })?;
+
+        let response = self
+            .http
+            .post(&self.account.token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| {
+                warn!("FCM access token request failed: {:?}", e);
+                fcm_auth_failure(
+                    "fcm_auth_unavailable",
+                    DeliveryFailureClass::ProviderTransient,
+                )
+            })?;
+        let status = response.status();
+        let body = response.bytes().await.map_err(|e| {
+            warn!("FCM access token response unreadable: {:?}", e);
+            fcm_auth_failure(
+                "fcm_auth_unavailable",
+                DeliveryFailureClass::ProviderTransient,
+            )
+        })?;
+        if !status.is_success() {
+            error!(
+                "FCM access token request rejected with {}: {}",
+                status,
+                String::from_utf8_lossy(&body)
+            );
+            return Err(if status.is_server_error() {
+                fcm_auth_failure(
+                    "fcm_auth_unavailable",
+                    DeliveryFailureClass::ProviderTransient,
+                )
+            } else {
+                fcm_auth_failure("fcm_auth", DeliveryFailureClass::BackendConfig)
+            });
+        }
+        let token: TokenResponse = serde_json::from_slice(&body).map_err(|e| {
+            error!("FCM access token response malformed: {:?}", e);
+            fcm_auth_failure("fcm_auth", DeliveryFailureClass::BackendConfig)
+        })?;
+
+        *cached = Some(CachedAccessToken {
+            token: token.access_token.clone(),
+            expires_at: Instant::now() + Duration::from_secs(token.expires_in),
+        });
+        Ok(token.access_token)
+    }
+
+    pub(super) async fn send(
+        &self,
+        device_token: &str,
+        notification: &FcmNotification,
+    ) -> Result<(), DeliveryFailureDetails> {
+        let access_token = self.access_token().await?;
+        let mut android_notification = serde_json::json!({
+            "tag": notification.tag,
+            "notification_count": notification.notification_count,
+        });
+        if notification.silent {
+            android_notification["notification_priority"] = "PRIORITY_LOW".into();
+            android_notification["default_sound"] = false.into();
+            android_notification["default_vibrate_timings"] = false.into();
+            android_notification["vibrate_timings"] = serde_json::json!([]);
+        }
+        let message = serde_json::json!({
+            "message": {
+                "token": device_token,
+                "notification": {
+                    "title": notification.title,
+                    "body": notification.body,
+                },
+                "data": notification.data,
+                "android": {
+                    "priority": if notification.silent { "normal" } else { "high" },
+                    "notification": android_notification,
+                },
+            },
+        });
+        let url = format!(
+            "{}/v1/projects/{}/messages:send",
+            self.endpoint, self.account.project_id
+        );
+
+        let response = self
+            .http
+            .post(&url)
+            .bearer_auth(&access_token)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(message.to_string())
+            .send()
+            .await
+            .map_err(|e| DeliveryFailureDetails {
+                class: DeliveryFailureClass::ProviderTransient,
+                reason: if e.is_timeout() {
+                    "request_timeout".to_string()
+                } else {
+                    "connection".to_string()
+                },
+                action: DeliveryFailureAction::None,
+            })?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            // Revoked or rotated credentials; exchange a fresh token on the next send.
+            self.access_token.lock().await.take();
+        }
+        let body = response.bytes().await.unwrap_or_default();
+        Err(classify_fcm_error(status.as_u16(), &body))
+    }
+}
+
+fn fcm_auth_failure(reason: &str, class: DeliveryFailureClass) -> DeliveryFailureDetails {
+    DeliveryFailureDetails {
+        class,
+        reason: reason.to_string(),
+        action: DeliveryFailureAction::None,
+    }
+}
+
+/// Classify an FCM v1 error response by its `FcmError` code, falling back to the RPC status.
+pub(super) fn classify_fcm_error(status: u16, body: &[u8]) -> DeliveryFailureDetails {
+    #[derive(Deserialize)]
+    struct ErrorResponse {
+        error: ErrorBody,
+    }
+
+    #[derive(Deserialize)]
+    struct ErrorBody {
+        status: Option<String>,
+        #[serde(default)]
+        details: Vec<ErrorDetail>,
+    }
+
+    #[derive(Deserialize)]
+    struct ErrorDetail {
+        #[serde(rename = "errorCode")]
+        error_code: Option<String>,
+    }
+
+    let code = serde_json::from_slice::<ErrorResponse>(body)
+        .ok()
+        .and_then(|response| {
+            response
+                .error
+                .details
+                .into_iter()
+                .find_map(|detail| detail.error_code)
+                .or(response.error.status)
+        })
+        .unwrap_or_else(|| format!("status_{}", status));
+
+    let (class, action) = match code.as_str() {
+        "UNREGISTERED" | "SENDER_ID_MISMATCH" => (
+            DeliveryFailureClass::ProviderRejected,
+            DeliveryFailureAction::PruneImmediate,
+        ),
+        // Returned for malformed tokens and malformed messages alike, so only prune once
+        // the same subscription keeps failing.
+        "INVALID_ARGUMENT" => (
+            DeliveryFailureClass::ProviderRejected,
+            DeliveryFailureAction::Counted,
+        ),
+        "THIRD_PARTY_AUTH_ERROR" | "UNAUTHENTICATED" | "PERMISSION_DENIED" => (
+            DeliveryFailureClass::BackendConfig,
+            DeliveryFailureAction::None,
+        ),
+        _ => (
+            DeliveryFailureClass::ProviderTransient,
+            DeliveryFailureAction::None,
+        ),
+    };
+
+    DeliveryFailureDetails {
+        class,
+        reason: code,
+        action,
+    }
+}
+
 pub(super) fn classify_web_push_error(error: &web_push::WebPushError) -> DeliveryFailureDetails {
     match error {
         web_push::WebPushError::EndpointNotValid(_) => DeliveryFailureDetails {
@@ -1,10 +1,12 @@
+use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool};
 use diesel::PgConnection;
 use futures::future::FutureExt;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::mpsc;
-use tracing::{error, warn};
+use tokio::sync::Notify;
+use tracing::error;
 use web_push::HyperWebPushClient;
 
 mod delivery;
@@ -14,15 +16,16 @@ mod worker;
 
 pub use payload::{PushMessagePreview, PushMessagePreviewSticker};
 
+use crate::errors::AppError;
 use crate::metrics::Metrics;
-use crate::models::PushProvider;
+use crate::models::{NewPushJob, PushProvider};
+use crate::schema::push_jobs;
 use crate::services::unread::UnreadService;
 use crate::services::ws_registry::ConnectionRegistry;
-use delivery::ApnsSender;
+use delivery::{ApnsSender, FcmSender};
 use worker::supervise_push_worker;
 
-const CHANNEL_BUFFER: usize = 1024;
-
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PushJob {
     pub chat_id: i64,
     pub sender_uid: i32,
@@ -34,6 +37,10 @@ pub struct PushJob {
     pub thread_root_id: Option<i64>,
     pub mentioned_uids: Vec<i32>,
     pub reply_target_uid: Option<i32>,
+    /// Announcement flagged as important by its poster; bypasses group mutes.
+    pub important: bool,
+    /// Forum topic of the message, used to honour per-topic mutes.
+    pub topic_id: Option<i64>,
 }
 
 pub struct PushService {
@@ -42,16 +49,18 @@ pub struct PushService {
     pub vapid_private_key: String,
     pub vapid_subject: String,
     apns_sender: Option<ApnsSender>,
+    fcm_sender: Option<FcmSender>,
     metrics: Arc<Metrics>,
     unread_service: Arc<UnreadService>,
-    job_tx: mpsc::Sender<PushJob>,
+    db: Pool<ConnectionManager<PgConnection>>,
+    wake: Notify,
 }
 
 impl PushService {
     /// Create the push service and spawn the background worker.
     ///
-    /// The worker pulls `PushJob`s from the channel and delivers push notifications
-    /// to all subscribed, offline members of the relevant chat.
+    /// The worker claims `PushJob`s from the `push_jobs` queue table and delivers push
+    /// notifications to all subscribed, offline members of the relevant chat.
     pub fn start(
         db: Pool<ConnectionManager<PgConnection>>,
         ws_registry: Arc<ConnectionRegistry>,
@@ -71,8 +80,7 @@ impl PushService {
 
         let apns_sender =
             ApnsSender::from_env().expect("invalid APNS configuration; set all vars or none");
-
-        let (tx, rx) = mpsc::channel(CHANNEL_BUFFER);
+        let fcm_sender = FcmSender::from_env().expect("invalid FCM configuration");
 
         let service = Arc::new(Self {
             client: HyperWebPushClient::new(),
@@ -80,15 +88,17 @@ impl PushService {
             vapid_private_key: private_key,
             vapid_subject: subject,
             apns_sender,
+            fcm_sender,
             metrics,
             unread_service,
-            job_tx: tx,
+            db,
+            wake: Notify::new(),
         });
 
         // Spawn the background worker supervisor.
         let worker_service = service.clone();
         tokio::spawn(async move {
-            supervise_push_worker(rx, worker_service, db, ws_registry).await;
+            supervise_push_worker(worker_service, ws_registry).await;
         });
 
         service
@@ -98,17 +108,53 @@ impl PushService {
         match provider {
             PushProvider::WebPush => true,
             PushProvider::Apns => self.apns_sender.is_some(),
+            PushProvider::Fcm => self.fcm_sender.is_some(),
         }
     }
 
-    /// Enqueue a push job. Non-blocking; logs a warning if the channel is full.
-    pub fn enqueue(&self, job: PushJob) {
-        if let Err(e) = self.job_tx.try_send(job) {
-            warn!("Push job channel full, dropping notification: {}", e);
-        }
+    /// Wake the worker after a transaction that queued a job has committed.
+    pub fn wake(&self) {
+        self.wake.notify_one();
     }
 }
 
+/// Queue a push job on `conn`. Call inside the transaction that creates the message so
+/// the job commits or rolls back with it, then [`PushService::wake`] after commit.
+pub fn queue_job(conn: &mut PgConnection, job: &PushJob) -> Result<(), AppError> {
+    let payload = serde_json::to_value(job).map_err(|e| {
+        error!(
+            "Failed to serialize push job for message_id={}: {:?}",
+            job.message_id, e
+        );
+        AppError::Internal("Failed to queue push notification")
+    })?;
+    diesel::insert_into(push_jobs::table)
+        .values(&NewPushJob {
+            chat_id: job.chat_id,
+            message_id: job.message_id,
+            payload,
+        })
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Replace the sender name shown by a message's queued push job, for senders whose
+/// display name is overridden per message.
+pub fn rename_queued_sender(
+    conn: &mut PgConnection,
+    message_id: i64,
+    sender_username: &str,
+) -> QueryResult<usize> {
+    diesel::sql_query(
+        "UPDATE push_jobs
+         SET payload = jsonb_set(payload, '{sender_username}', to_jsonb($1::text))
+         WHERE message_id = $2",
+    )
+    .bind::<diesel::sql_types::Text, _>(sender_username)
+    .bind::<diesel::sql_types::BigInt, _>(message_id)
+    .execute(conn)
+}
+
 pub(crate) fn panic_payload_message(payload: &(dyn std::any::Any + Send)) -> String {
     if let Some(message) = payload.downcast_ref::<&'static str>() {
         (*message).to_string()
@@ -149,21 +195,22 @@ pub(crate) async fn supervise_worker<F, Fut>(
 #[cfg(test)]
 mod tests {
     use super::delivery::{
-        classify_apns_send_error, classify_web_push_error, is_stale_apns_error_reason,
-        DeliveryFailureAction,
+        classify_apns_send_error, classify_fcm_error, classify_web_push_error,
+        is_stale_apns_error_reason, DeliveryFailureAction, DeliveryFailureClass, FcmServiceAccount,
     };
     use super::payload::{
-        build_apns_notification, build_push_payload, format_push_body, truncate_preview,
-        APNS_BODY_LOC_KEY_AUDIO, APNS_BODY_LOC_KEY_IMAGE, APNS_BODY_LOC_KEY_IMAGE_WITH_PREVIEW,
-        APNS_BODY_LOC_KEY_INVITE, APNS_BODY_LOC_KEY_NO_PREVIEW, APNS_BODY_LOC_KEY_STICKER_EMOJI,
-        APNS_BODY_LOC_KEY_VIDEO, APNS_BODY_LOC_KEY_WITH_PREVIEW, APNS_TITLE_LOC_KEY,
-        MESSAGE_PREVIEW_MAX,
+        build_apns_notification, build_fcm_notification, build_push_payload, format_push_body,
+        truncate_preview, APNS_BODY_LOC_KEY_AUDIO, APNS_BODY_LOC_KEY_IMAGE,
+        APNS_BODY_LOC_KEY_IMAGE_WITH_PREVIEW, APNS_BODY_LOC_KEY_INVITE,
+        APNS_BODY_LOC_KEY_NO_PREVIEW, APNS_BODY_LOC_KEY_STICKER_EMOJI, APNS_BODY_LOC_KEY_VIDEO,
+        APNS_BODY_LOC_KEY_WITH_PREVIEW, APNS_TITLE_LOC_KEY, MESSAGE_PREVIEW_MAX,
     };
     use super::*;
     use crate::dto::messages::MessagePreviewAttachment;
     use crate::models::MessageType;
     use a2::ErrorReason as ApnsErrorReason;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::mpsc;
 
     #[test]
     fn truncate_preview_keeps_short_ascii() {
@@ -215,6 +262,8 @@ mod tests {
             thread_root_id: None,
             mentioned_uids: Vec::new(),
             reply_target_uid: None,
+            important: false,
+            topic_id: None,
         };
 
         let payload = build_push_payload(&job, 3, "alice: [Sticker] 🙂");
@@ -241,6 +290,43 @@ mod tests {
         assert!(serialized["data"].get("chat_id").is_none());
     }
 
+    #[test]
+    fn push_job_round_trips_through_queue_payload() {
+        let job = PushJob {
+            chat_id: 10,
+            sender_uid: 42,
+            sender_username: "alice".to_string(),
+            chat_name: "General".to_string(),
+            message_preview: PushMessagePreview {
+                message: None,
+                message_type: MessageType::File,
+                sticker: None,
+                attachments: vec![MessagePreviewAttachment {
+                    kind: "image/png".to_string(),
+                }],
+                is_deleted: false,
+            },
+            body_preview: None,
+            message_id: 99,
+            thread_root_id: Some(77),
+            mentioned_uids: vec![3, 4],
+            reply_target_uid: Some(3),
+            important: true,
+            topic_id: Some(5),
+        };
+
+        let payload = serde_json::to_value(&job).expect("serialize push job");
+        let restored: PushJob = serde_json::from_value(payload).expect("deserialize push job");
+
+        assert_eq!(restored.message_id, 99);
+        assert_eq!(restored.thread_root_id, Some(77));
+        assert_eq!(restored.mentioned_uids, vec![3, 4]);
+        assert_eq!(restored.reply_target_uid, Some(3));
+        assert!(restored.important);
+        assert_eq!(restored.topic_id, Some(5));
+        assert_eq!(restored.message_preview, job.message_preview);
+    }
+
     #[test]
     fn build_apns_notification_uses_localized_keys_and_custom_data() {
         let job = PushJob {
@@ -260,6 +346,8 @@ mod tests {
             thread_root_id: Some(77),
             mentioned_uids: Vec::new(),
             reply_target_uid: None,
+            important: false,
+            topic_id: None,
         };
 
         let n = build_apns_notification(&job, 7);
@@ -296,6 +384,8 @@ mod tests {
             thread_root_id: None,
             mentioned_uids: Vec::new(),
             reply_target_uid: None,
+            important: false,
+            topic_id: None,
         };
 
         let payload = build_apns_notification(&job, 0);
@@ -324,6 +414,8 @@ mod tests {
             thread_root_id: None,
             mentioned_uids: Vec::new(),
             reply_target_uid: None,
+            important: false,
+            topic_id: None,
         };
 
         let n = build_apns_notification(&job, 2);
@@ -352,6 +444,8 @@ mod tests {
             thread_root_id: None,
             mentioned_uids: Vec::new(),
             reply_target_uid: None,
+            important: false,
+            topic_id: None,
         };
 
         let n = build_apns_notification(&job, 0);
@@ -380,6 +474,8 @@ mod tests {
             thread_root_id: None,
             mentioned_uids: Vec::new(),
             reply_target_uid: None,
+            important: false,
+            topic_id: None,
         };
 
         let n = build_apns_notification(&job, 1);
@@ -408,6 +504,8 @@ mod tests {
             thread_root_id: None,
             mentioned_uids: Vec::new(),
             reply_target_uid: None,
+            important: false,
+            topic_id: None,
         };
 
         let n = build_apns_notification(&job, 0);
@@ -439,6 +537,8 @@ mod tests {
             thread_root_id: None,
             mentioned_uids: Vec::new(),
             reply_target_uid: None,
+            important: false,
+            topic_id: None,
         };
 
         let n = build_apns_notification(&job, 0);
@@ -464,6 +564,8 @@ mod tests {
             thread_root_id: None,
             mentioned_uids: Vec::new(),
             reply_target_uid: None,
+            important: false,
+            topic_id: None,
         };
 
         let n = build_apns_notification(&job, 0);
@@ -603,13 +705,268 @@ mod tests {
             vapid_private_key: "private".to_string(),
             vapid_subject: "mailto:test@example.com".to_string(),
             apns_sender: None,
+            fcm_sender: None,
             metrics: Arc::new(Metrics::new()),
             unread_service: Arc::new(UnreadService::new()),
-            job_tx: mpsc::channel(1).0,
+            db: Pool::builder()
+                .build_unchecked(ConnectionManager::new("postgres://localhost/unused")),
+            wake: Notify::new(),
         };
 
         assert!(service.supports_provider(&PushProvider::WebPush));
         assert!(!service.supports_provider(&PushProvider::Apns));
+        assert!(!service.supports_provider(&PushProvider::Fcm));
+    }
+
+    const FCM_TEST_KEY: &str = include_str!("testdata/fcm_test_key.pem");
+
+    fn fcm_test_job() -> PushJob {
+        PushJob {
+            chat_id: 10,
+            sender_uid: 42,
+            sender_username: "alice".to_string(),
+            chat_name: "General".to_string(),
+            message_preview: PushMessagePreview {
+                message: Some("Hello".to_string()),
+                message_type: MessageType::Text,
+                sticker: None,
+                attachments: Vec::new(),
+                is_deleted: false,
+            },
+            body_preview: Some("Hello".to_string()),
+            message_id: 99,
+            thread_root_id: Some(77),
+            mentioned_uids: Vec::new(),
+            reply_target_uid: None,
+            important: false,
+            topic_id: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct FcmStubState {
+        token_requests: AtomicUsize,
+        messages: std::sync::Mutex<Vec<serde_json::Value>>,
+    }
+
+    /// Serves an OAuth token endpoint and an FCM send endpoint that rejects `stale-token`
+    /// as unregistered and `busy-token` as over quota.
+    async fn spawn_fcm_stub() -> (String, Arc<FcmStubState>) {
+        use axum::{
+            extract::State,
+            http::{HeaderMap, StatusCode},
+            routing::post,
+            Form, Json, Router,
+        };
+        use serde_json::json;
+
+        let stub = Arc::new(FcmStubState::default());
+        let app = Router::new()
+            .route(
+                "/token",
+                post(
+                    |State(stub): State<Arc<FcmStubState>>,
+                     Form(form): Form<std::collections::HashMap<String, String>>| async move {
+                        stub.token_requests.fetch_add(1, Ordering::SeqCst);
+                        let ok = form.get("grant_type").map(String::as_str)
+                            == Some("urn:ietf:params:oauth:grant-type:jwt-bearer")
+                            && form.get("assertion").is_some_and(|a| a.split('.').count() == 3);
+                        if ok {
+                            Ok(Json(json!({"access_token": "stub-access", "expires_in": 3600})))
+                        } else {
+                            Err(StatusCode::BAD_REQUEST)
+                        }
+                    },
+                ),
+            )
+            .route(
+                "/v1/projects/wetty-test/messages:send",
+                post(
+                    |State(stub): State<Arc<FcmStubState>>,
+                     headers: HeaderMap,
+                     Json(body): Json<serde_json::Value>| async move {
+                        if headers.get("authorization").and_then(|v| v.to_str().ok())
+                            != Some("Bearer stub-access")
+                        {
+                            return (StatusCode::UNAUTHORIZED, Json(json!({})));
+                        }
+                        let token = body["message"]["token"].as_str().unwrap_or_default();
+                        let error_code = match token {
+                            "stale-token" => Some((StatusCode::NOT_FOUND, "NOT_FOUND", "UNREGISTERED")),
+                            "busy-token" => Some((
+                                StatusCode::TOO_MANY_REQUESTS,
+                                "RESOURCE_EXHAUSTED",
+                                "QUOTA_EXCEEDED",
+                            )),
+                            _ => None,
+                        };
+                        stub.messages.lock().unwrap().push(body);
+                        match error_code {
+                            Some((code, status, error_code)) => (
+                                code,
+                                Json(json!({"error": {
+                                    "code": code.as_u16(),
+                                    "status": status,
+                                    "details": [{
+                                        "@type": "type.googleapis.com/google.firebase.fcm.v1.FcmError",
+                                        "errorCode": error_code,
+                                    }],
+                                }})),
+                            ),
+                            None => (
+                                StatusCode::OK,
+                                Json(json!({"name": "projects/wetty-test/messages/1"})),
+                            ),
+                        }
+                    },
+                ),
+            )
+            .with_state(stub.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{addr}"), stub)
+    }
+
+    fn fcm_stub_sender(base_url: &str) -> FcmSender {
+        FcmSender::new(
+            FcmServiceAccount {
+                project_id: "wetty-test".to_string(),
+                client_email: "push@wetty-test.iam.gserviceaccount.com".to_string(),
+                private_key: FCM_TEST_KEY.to_string(),
+                token_uri: format!("{base_url}/token"),
+            },
+            base_url.to_string(),
+        )
+        .expect("build FCM sender")
+    }
+
+    #[test]
+    fn build_fcm_notification_carries_display_text_and_string_data() {
+        let n = build_fcm_notification(&fcm_test_job(), 4, "alice: Hello");
+
+        assert_eq!(n.title, "General");
+        assert_eq!(n.body, "alice: Hello");
+        assert_eq!(n.tag, "chat_10_thread_77");
+        assert_eq!(n.notification_count, 4);
+        assert_eq!(n.data["chatId"], "10");
+        assert_eq!(n.data["messageId"], "99");
+        assert_eq!(n.data["threadRootId"], "77");
+        assert_eq!(n.data["unreadCount"], "4");
+        let preview: serde_json::Value =
+            serde_json::from_str(&n.data["messagePreview"]).expect("preview is JSON");
+        assert_eq!(preview["messageType"], "text");
+    }
+
+    #[test]
+    fn fcm_unregistered_prunes_subscription() {
+        let body = br#"{"error":{"code":404,"status":"NOT_FOUND","details":[{"@type":"type.googleapis.com/google.firebase.fcm.v1.FcmError","errorCode":"UNREGISTERED"}]}}"#;
+
+        let failure = classify_fcm_error(404, body);
+
+        assert_eq!(failure.action, DeliveryFailureAction::PruneImmediate);
+        assert_eq!(failure.class, DeliveryFailureClass::ProviderRejected);
+        assert_eq!(failure.reason, "UNREGISTERED");
+    }
+
+    #[test]
+    fn fcm_quota_exceeded_is_retryable() {
+        let body = br#"{"error":{"code":429,"status":"RESOURCE_EXHAUSTED","details":[{"errorCode":"QUOTA_EXCEEDED"}]}}"#;
+
+        let failure = classify_fcm_error(429, body);
+
+        assert_eq!(failure.action, DeliveryFailureAction::None);
+        assert_eq!(failure.class, DeliveryFailureClass::ProviderTransient);
+        assert_eq!(failure.reason, "QUOTA_EXCEEDED");
+    }
+
+    #[test]
+    fn fcm_invalid_argument_counts_toward_pruning() {
+        let body = br#"{"error":{"code":400,"status":"INVALID_ARGUMENT"}}"#;
+
+        let failure = classify_fcm_error(400, body);
+
+        assert_eq!(failure.action, DeliveryFailureAction::Counted);
+        assert_eq!(failure.reason, "INVALID_ARGUMENT");
+    }
+
+    #[test]
+    fn fcm_unparseable_error_falls_back_to_status_code() {
+        let failure = classify_fcm_error(503, b"upstream connect error");
+
+        assert_eq!(failure.action, DeliveryFailureAction::None);
+        assert_eq!(failure.reason, "status_503");
+    }
+
+    #[tokio::test]
+    async fn fcm_sender_sends_with_cached_access_token() {
+        let (base_url, stub) = spawn_fcm_stub().await;
+        let sender = fcm_stub_sender(&base_url);
+        let notification = build_fcm_notification(&fcm_test_job(), 2, "alice: Hello");
+
+        sender
+            .send("device-1", &notification)
+            .await
+            .expect("first send succeeds");
+        sender
+            .send("device-2", &notification)
+            .await
+            .expect("second send succeeds");
+
+        assert_eq!(stub.token_requests.load(Ordering::SeqCst), 1);
+        let messages = stub.messages.lock().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["message"]["token"], "device-1");
+        assert_eq!(messages[0]["message"]["notification"]["title"], "General");
+        assert_eq!(messages[0]["message"]["data"]["chatId"], "10");
+        assert_eq!(
+            messages[0]["message"]["android"]["notification"]["tag"],
+            "chat_10_thread_77"
+        );
+    }
+
+    #[tokio::test]
+    async fn fcm_sender_sends_silent_notifications_at_low_priority() {
+        let (base_url, stub) = spawn_fcm_stub().await;
+        let sender = fcm_stub_sender(&base_url);
+        let mut notification = build_fcm_notification(&fcm_test_job(), 0, "alice: Hello");
+        notification.silent = true;
+
+        sender
+            .send("device-1", &notification)
+            .await
+            .expect("silent send succeeds");
+
+        let messages = stub.messages.lock().unwrap();
+        let android = &messages[0]["message"]["android"];
+        assert_eq!(android["priority"], "normal");
+        assert_eq!(
+            android["notification"]["notification_priority"],
+            "PRIORITY_LOW"
+        );
+        assert_eq!(android["notification"]["default_sound"], false);
+    }
+
+    #[tokio::test]
+    async fn fcm_sender_classifies_stub_errors() {
+        let (base_url, _stub) = spawn_fcm_stub().await;
+        let sender = fcm_stub_sender(&base_url);
+        let notification = build_fcm_notification(&fcm_test_job(), 0, "alice: Hello");
+
+        let stale = sender
+            .send("stale-token", &notification)
+            .await
+            .expect_err("unregistered token fails");
+        assert_eq!(stale.action, DeliveryFailureAction::PruneImmediate);
+
+        let busy = sender
+            .send("busy-token", &notification)
+            .await
+            .expect_err("quota error fails");
+        assert_eq!(busy.action, DeliveryFailureAction::None);
+        assert_eq!(busy.reason, "QUOTA_EXCEEDED");
     }
 
     #[tokio::test]
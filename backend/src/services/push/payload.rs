@@ -1,4 +1,5 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use crate::dto::messages::MessagePreviewAttachment;
 use crate::models::MessageType;
@@ -30,13 +31,13 @@ pub(super) enum PushPayloadType {
     NewMessage,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct PushMessagePreviewSticker {
     pub emoji: String,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct PushMessagePreview {
     pub message: Option<String>,
@@ -67,6 +68,8 @@ pub(super) struct PushPayload {
     pub(super) message_preview: PushMessagePreview,
     pub(super) unread_count: i64,
     pub(super) data: PushPayloadData,
+    /// Sent during the recipient's silent quiet hours; show without sound or vibration.
+    pub(super) silent: bool,
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
@@ -92,6 +95,22 @@ pub(super) struct ApnsNotification {
     pub(super) badge: u32,
     pub(super) thread_id: String,
     pub(super) custom_data: ApnsCustomData,
+    /// Omit the sound during the recipient's silent quiet hours.
+    pub(super) silent: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct FcmNotification {
+    pub(super) title: String,
+    pub(super) body: String,
+    /// Android replaces an earlier notification with the same tag, so one chat (or thread)
+    /// shows a single entry in the shade.
+    pub(super) tag: String,
+    pub(super) notification_count: u32,
+    /// FCM data values must be strings; the preview travels as embedded JSON.
+    pub(super) data: BTreeMap<String, String>,
+    /// Low priority without sound during the recipient's silent quiet hours.
+    pub(super) silent: bool,
 }
 
 pub(super) fn build_push_payload(job: &PushJob, unread_count: i64, body_text: &str) -> PushPayload {
@@ -107,6 +126,7 @@ pub(super) fn build_push_payload(job: &PushJob, unread_count: i64, body_text: &s
             message_id: job.message_id.to_string(),
             thread_root_id: job.thread_root_id.map(|id| id.to_string()),
         },
+        silent: false,
     }
 }
 
@@ -177,6 +197,41 @@ pub(super) fn build_apns_notification(job: &PushJob, unread_count: i64) -> ApnsN
             message_preview: job.message_preview.clone(),
             unread_count,
         },
+        silent: false,
+    }
+}
+
+pub(super) fn build_fcm_notification(
+    job: &PushJob,
+    unread_count: i64,
+    body_text: &str,
+) -> FcmNotification {
+    let tag = match job.thread_root_id {
+        Some(root_id) => format!("chat_{}_thread_{}", job.chat_id, root_id),
+        None => format!("chat_{}", job.chat_id),
+    };
+
+    let mut data = BTreeMap::new();
+    data.insert("type".to_string(), "newMessage".to_string());
+    data.insert("chatId".to_string(), job.chat_id.to_string());
+    data.insert("messageId".to_string(), job.message_id.to_string());
+    if let Some(root_id) = job.thread_root_id {
+        data.insert("threadRootId".to_string(), root_id.to_string());
+    }
+    data.insert("senderName".to_string(), job.sender_username.clone());
+    data.insert("unreadCount".to_string(), unread_count.to_string());
+    data.insert(
+        "messagePreview".to_string(),
+        serde_json::to_string(&job.message_preview).unwrap_or_default(),
+    );
+
+    FcmNotification {
+        title: job.chat_name.clone(),
+        body: body_text.to_string(),
+        tag,
+        notification_count: unread_count.clamp(0, u32::MAX as i64) as u32,
+        data,
+        silent: false,
     }
 }
 
@@ -1,4 +1,7 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+
+use crate::models::{NotificationLevel, QuietHoursMode};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum ThreadPushState {
@@ -16,14 +19,76 @@ pub(crate) struct PushRecipientContext {
     pub is_reply_target: bool,
     pub chat_archived: bool,
     pub group_muted_until: Option<DateTime<Utc>>,
+    /// Set for top-level announcements flagged as important; these bypass group mutes.
+    pub is_important_announcement: bool,
+    /// Per-topic mute for forum topics; applies only to top-level messages.
+    pub topic_muted_until: Option<DateTime<Utc>>,
     pub thread_state: ThreadPushState,
     pub has_active_presence: bool,
+    /// The chat's override if set, otherwise the account level.
+    pub notification_level: NotificationLevel,
+    /// The message contains one of the recipient's keywords; treated like a mention.
+    pub matches_keyword: bool,
+    pub quiet_hours: Option<QuietHours>,
+}
+
+/// A daily window in the recipient's timezone. An end before the start runs past midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct QuietHours {
+    /// Minutes after local midnight.
+    pub start_minute: u16,
+    pub end_minute: u16,
+    pub timezone: Tz,
+    pub mode: QuietHoursMode,
+}
+
+impl QuietHours {
+    /// When the window containing `now` ends, or `None` outside quiet hours.
+    pub(crate) fn ends_after(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = now.with_timezone(&self.timezone);
+        let minute = (local.hour() * 60 + local.minute()) as u16;
+        let inside = if self.start_minute < self.end_minute {
+            minute >= self.start_minute && minute < self.end_minute
+        } else {
+            minute >= self.start_minute || minute < self.end_minute
+        };
+        if !inside {
+            return None;
+        }
+
+        let mut date = local.date_naive();
+        if minute >= self.end_minute {
+            date = date.succ_opt()?;
+        }
+        let end = date.and_time(NaiveTime::from_hms_opt(
+            u32::from(self.end_minute / 60),
+            u32::from(self.end_minute % 60),
+            0,
+        )?);
+        // An end inside a DST gap resolves to the first valid local time after it.
+        let end = self
+            .timezone
+            .from_local_datetime(&end)
+            .earliest()
+            .or_else(|| {
+                self.timezone
+                    .from_local_datetime(&(end + Duration::hours(1)))
+                    .earliest()
+            })?;
+        Some(end.with_timezone(&Utc))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum PushDecision {
     Send,
     SendOneOffMention,
+    /// Quiet hours in silent mode: deliver without sound.
+    SendSilently,
+    /// Quiet hours in hold mode: deliver when they end.
+    Hold {
+        until: DateTime<Utc>,
+    },
     Skip(PushSkipReason),
 }
 
@@ -33,14 +98,36 @@ pub(crate) enum PushSkipReason {
     ChatArchived,
     ActivePresence,
     GroupMuted,
+    TopicMuted,
     ThreadArchived,
     NoThreadSubscription,
+    NotificationsOff,
+    MentionsAndRepliesOnly,
 }
 
 pub(crate) fn should_send_push(
     recipient: &PushRecipientContext,
     now: DateTime<Utc>,
 ) -> PushDecision {
+    let decision = delivery_decision(recipient, now);
+    if !matches!(
+        decision,
+        PushDecision::Send | PushDecision::SendOneOffMention
+    ) {
+        return decision;
+    }
+
+    match recipient
+        .quiet_hours
+        .and_then(|quiet_hours| Some((quiet_hours.mode, quiet_hours.ends_after(now)?)))
+    {
+        Some((QuietHoursMode::Hold, until)) => PushDecision::Hold { until },
+        Some((QuietHoursMode::Silent, _)) => PushDecision::SendSilently,
+        None => decision,
+    }
+}
+
+fn delivery_decision(recipient: &PushRecipientContext, now: DateTime<Utc>) -> PushDecision {
     if recipient.is_sender {
         return PushDecision::Skip(PushSkipReason::Sender);
     }
@@ -49,6 +136,21 @@ pub(crate) fn should_send_push(
         return PushDecision::Skip(PushSkipReason::ActivePresence);
     }
 
+    let is_mentioned = recipient.is_mentioned || recipient.matches_keyword;
+    match recipient.notification_level {
+        NotificationLevel::All => {}
+        NotificationLevel::Nothing => {
+            return PushDecision::Skip(PushSkipReason::NotificationsOff);
+        }
+        NotificationLevel::MentionsAndReplies => {
+            let is_reply = recipient.is_reply_target
+                || matches!(recipient.thread_state, ThreadPushState::ActiveSubscription);
+            if !is_mentioned && !is_reply {
+                return PushDecision::Skip(PushSkipReason::MentionsAndRepliesOnly);
+            }
+        }
+    }
+
     if recipient.is_reply_target
         && matches!(recipient.thread_state, ThreadPushState::NotThreadMessage)
     {
@@ -61,22 +163,26 @@ pub(crate) fn should_send_push(
 
     match recipient.thread_state {
         ThreadPushState::NotThreadMessage => {
-            if recipient.is_mentioned || !is_group_muted(recipient.group_muted_until, now) {
+            if is_mentioned || recipient.is_important_announcement {
                 PushDecision::Send
-            } else {
+            } else if is_group_muted(recipient.group_muted_until, now) {
                 PushDecision::Skip(PushSkipReason::GroupMuted)
+            } else if is_group_muted(recipient.topic_muted_until, now) {
+                PushDecision::Skip(PushSkipReason::TopicMuted)
+            } else {
+                PushDecision::Send
             }
         }
         ThreadPushState::ActiveSubscription => PushDecision::Send,
         ThreadPushState::ArchivedSubscription => {
-            if recipient.is_mentioned {
+            if is_mentioned {
                 PushDecision::SendOneOffMention
             } else {
                 PushDecision::Skip(PushSkipReason::ThreadArchived)
             }
         }
         ThreadPushState::NoSubscription => {
-            if recipient.is_mentioned {
+            if is_mentioned {
                 PushDecision::SendOneOffMention
             } else {
                 PushDecision::Skip(PushSkipReason::NoThreadSubscription)
@@ -89,6 +195,21 @@ fn is_group_muted(muted_until: Option<DateTime<Utc>>, now: DateTime<Utc>) -> boo
     muted_until.is_some_and(|t| t > now)
 }
 
+/// Whether `text` contains any of the lowercased `keywords` as a whole word.
+pub(crate) fn matches_any_keyword(text: &str, keywords: &[String]) -> bool {
+    if keywords.is_empty() {
+        return false;
+    }
+    let text = text.to_lowercase();
+    keywords.iter().any(|keyword| {
+        text.match_indices(keyword.as_str()).any(|(start, found)| {
+            let before = text[..start].chars().next_back();
+            let after = text[start + found.len()..].chars().next();
+            !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+        })
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,8 +226,13 @@ mod tests {
                 is_reply_target: false,
                 chat_archived: false,
                 group_muted_until: None,
+                is_important_announcement: false,
+                topic_muted_until: None,
                 thread_state,
                 has_active_presence: false,
+                notification_level: NotificationLevel::All,
+                matches_keyword: false,
+                quiet_hours: None,
             },
         )
     }
@@ -135,6 +261,58 @@ mod tests {
         assert_eq!(should_send_push(&recipient, now), PushDecision::Send);
     }
 
+    #[test]
+    fn important_announcement_bypasses_group_mute() {
+        let (now, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.group_muted_until = Some(now + Duration::minutes(5));
+        recipient.is_important_announcement = true;
+        assert_eq!(should_send_push(&recipient, now), PushDecision::Send);
+    }
+
+    #[test]
+    fn important_announcement_does_not_bypass_chat_archive() {
+        let (now, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.chat_archived = true;
+        recipient.is_important_announcement = true;
+        assert_eq!(
+            should_send_push(&recipient, now),
+            PushDecision::Skip(PushSkipReason::ChatArchived)
+        );
+    }
+
+    #[test]
+    fn topic_mute_suppresses_unmentioned_recipient() {
+        let (now, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.topic_muted_until = Some(now + Duration::minutes(5));
+        assert_eq!(
+            should_send_push(&recipient, now),
+            PushDecision::Skip(PushSkipReason::TopicMuted)
+        );
+
+        recipient.topic_muted_until = Some(now - Duration::minutes(5));
+        assert_eq!(should_send_push(&recipient, now), PushDecision::Send);
+    }
+
+    #[test]
+    fn topic_mute_is_bypassed_by_mentions_and_important_announcements() {
+        let (now, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.topic_muted_until = Some(now + Duration::minutes(5));
+        recipient.is_mentioned = true;
+        assert_eq!(should_send_push(&recipient, now), PushDecision::Send);
+
+        let (now, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.topic_muted_until = Some(now + Duration::minutes(5));
+        recipient.is_important_announcement = true;
+        assert_eq!(should_send_push(&recipient, now), PushDecision::Send);
+    }
+
+    #[test]
+    fn active_thread_subscription_ignores_topic_mute() {
+        let (now, mut recipient) = base(ThreadPushState::ActiveSubscription);
+        recipient.topic_muted_until = Some(now + Duration::minutes(5));
+        assert_eq!(should_send_push(&recipient, now), PushDecision::Send);
+    }
+
     #[test]
     fn normal_reply_target_bypasses_group_mute() {
         let (now, mut reply_target) = base(ThreadPushState::NotThreadMessage);
@@ -232,4 +410,167 @@ mod tests {
             PushDecision::Skip(PushSkipReason::ActivePresence)
         );
     }
+
+    fn quiet_hours(mode: QuietHoursMode) -> QuietHours {
+        QuietHours {
+            start_minute: 22 * 60,
+            end_minute: 7 * 60,
+            timezone: chrono_tz::Europe::Berlin,
+            mode,
+        }
+    }
+
+    fn utc(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn nothing_level_suppresses_mentions_and_replies() {
+        let (now, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.notification_level = NotificationLevel::Nothing;
+        recipient.is_mentioned = true;
+        recipient.is_reply_target = true;
+        assert_eq!(
+            should_send_push(&recipient, now),
+            PushDecision::Skip(PushSkipReason::NotificationsOff)
+        );
+    }
+
+    #[test]
+    fn mentions_and_replies_level_skips_plain_messages() {
+        let (now, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.notification_level = NotificationLevel::MentionsAndReplies;
+        assert_eq!(
+            should_send_push(&recipient, now),
+            PushDecision::Skip(PushSkipReason::MentionsAndRepliesOnly)
+        );
+
+        recipient.is_important_announcement = true;
+        assert_eq!(
+            should_send_push(&recipient, now),
+            PushDecision::Skip(PushSkipReason::MentionsAndRepliesOnly)
+        );
+    }
+
+    #[test]
+    fn mentions_and_replies_level_allows_mentions_replies_and_followed_threads() {
+        let (now, mut mentioned) = base(ThreadPushState::NotThreadMessage);
+        mentioned.notification_level = NotificationLevel::MentionsAndReplies;
+        mentioned.is_mentioned = true;
+        assert_eq!(should_send_push(&mentioned, now), PushDecision::Send);
+
+        let (now, mut reply_target) = base(ThreadPushState::NotThreadMessage);
+        reply_target.notification_level = NotificationLevel::MentionsAndReplies;
+        reply_target.is_reply_target = true;
+        assert_eq!(should_send_push(&reply_target, now), PushDecision::Send);
+
+        let (now, mut follower) = base(ThreadPushState::ActiveSubscription);
+        follower.notification_level = NotificationLevel::MentionsAndReplies;
+        assert_eq!(should_send_push(&follower, now), PushDecision::Send);
+    }
+
+    #[test]
+    fn keyword_match_bypasses_group_mute_and_mentions_only_level() {
+        let (now, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.group_muted_until = Some(now + Duration::minutes(5));
+        recipient.notification_level = NotificationLevel::MentionsAndReplies;
+        recipient.matches_keyword = true;
+        assert_eq!(should_send_push(&recipient, now), PushDecision::Send);
+    }
+
+    #[test]
+    fn keyword_match_in_unfollowed_thread_gets_one_off_push() {
+        let (now, mut recipient) = base(ThreadPushState::NoSubscription);
+        recipient.matches_keyword = true;
+        assert_eq!(
+            should_send_push(&recipient, now),
+            PushDecision::SendOneOffMention
+        );
+    }
+
+    #[test]
+    fn keywords_match_whole_words_case_insensitively() {
+        let keywords = vec!["deploy".to_string(), "on call".to_string()];
+        assert!(matches_any_keyword("Deploy is done", &keywords));
+        assert!(matches_any_keyword("who is ON CALL?", &keywords));
+        assert!(!matches_any_keyword("redeployed yesterday", &keywords));
+        assert!(!matches_any_keyword("deployment", &keywords));
+        assert!(!matches_any_keyword("deploy", &[]));
+    }
+
+    #[test]
+    fn quiet_hours_hold_defers_until_local_end() {
+        let (_, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.quiet_hours = Some(quiet_hours(QuietHoursMode::Hold));
+
+        // 23:30 in Berlin (UTC+1): held until 07:00 the next morning.
+        assert_eq!(
+            should_send_push(&recipient, utc("2026-01-10T22:30:00Z")),
+            PushDecision::Hold {
+                until: utc("2026-01-11T06:00:00Z")
+            }
+        );
+        // 03:00 in Berlin: held until 07:00 the same morning.
+        assert_eq!(
+            should_send_push(&recipient, utc("2026-01-11T02:00:00Z")),
+            PushDecision::Hold {
+                until: utc("2026-01-11T06:00:00Z")
+            }
+        );
+    }
+
+    #[test]
+    fn quiet_hours_silent_mode_sends_silently() {
+        let (_, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.quiet_hours = Some(quiet_hours(QuietHoursMode::Silent));
+        assert_eq!(
+            should_send_push(&recipient, utc("2026-01-10T22:30:00Z")),
+            PushDecision::SendSilently
+        );
+    }
+
+    #[test]
+    fn outside_quiet_hours_sends_normally() {
+        let (_, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.quiet_hours = Some(quiet_hours(QuietHoursMode::Hold));
+        // 12:00 in Berlin; the window ends at 07:00 exactly.
+        assert_eq!(
+            should_send_push(&recipient, utc("2026-01-11T11:00:00Z")),
+            PushDecision::Send
+        );
+        assert_eq!(
+            should_send_push(&recipient, utc("2026-01-11T06:00:00Z")),
+            PushDecision::Send
+        );
+    }
+
+    #[test]
+    fn quiet_hours_do_not_revive_skipped_pushes() {
+        let (_, mut recipient) = base(ThreadPushState::NoSubscription);
+        recipient.quiet_hours = Some(quiet_hours(QuietHoursMode::Hold));
+        assert_eq!(
+            should_send_push(&recipient, utc("2026-01-10T22:30:00Z")),
+            PushDecision::Skip(PushSkipReason::NoThreadSubscription)
+        );
+    }
+
+    #[test]
+    fn quiet_hours_ending_in_dst_gap_release_after_the_gap() {
+        let (_, mut recipient) = base(ThreadPushState::NotThreadMessage);
+        recipient.quiet_hours = Some(QuietHours {
+            start_minute: 23 * 60,
+            end_minute: 2 * 60 + 30,
+            timezone: chrono_tz::America::New_York,
+            mode: QuietHoursMode::Hold,
+        });
+        // 01:00 EST on the spring-forward night; 02:30 does not exist, 03:30 EDT does.
+        assert_eq!(
+            should_send_push(&recipient, utc("2026-03-08T06:00:00Z")),
+            PushDecision::Hold {
+                until: utc("2026-03-08T07:30:00Z")
+            }
+        );
+    }
 }
@@ -1,21 +1,26 @@
 use diesel::prelude::*;
-use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::r2d2::ConnectionManager;
 use diesel::PgConnection;
 use futures::future::FutureExt;
 use futures::stream::{self, StreamExt};
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
-use crate::models::PushSubscription;
-use crate::schema::push_subscriptions;
+use crate::models::{NotificationLevel, NotificationPreferences, PushSubscription};
+use crate::schema::{push_jobs, push_subscriptions};
+use crate::services::notification_preferences::load_preferences_for;
 use crate::services::ws_registry::ConnectionRegistry;
 
-use super::delivery::{DeliveryFailure, DeliveryFailureAction};
-use super::payload::{build_apns_notification, build_push_payload, format_push_body};
+use super::delivery::{DeliveryFailure, DeliveryFailureAction, DeliveryFailureClass};
+use super::payload::{
+    build_apns_notification, build_fcm_notification, build_push_payload, format_push_body,
+};
 use super::policy::{
-    should_send_push, PushDecision, PushRecipientContext, PushSkipReason, ThreadPushState,
+    matches_any_keyword, should_send_push, PushDecision, PushRecipientContext, PushSkipReason,
+    QuietHours, ThreadPushState,
 };
 use super::{panic_payload_message, PushJob, PushService};
 
@@ -23,22 +28,32 @@ const PUSH_CONCURRENCY: usize = 10;
 const PUSH_SUPPRESSION_FRESHNESS_SECS: u64 = 30;
 const PUSH_WORKER_RESTART_DELAY: Duration = Duration::from_secs(1);
 const PUSH_COUNTED_FAILURE_PRUNE_THRESHOLD: i32 = 3;
+/// How long a claimed job stays hidden from other workers before it is retried.
+const PUSH_CLAIM_LEASE_SECS: f64 = 60.0;
+/// Jobs are claimed one at a time so each gets the full lease; a claimed batch would
+/// leave later jobs waiting while their leases ran out.
+const PUSH_CLAIM_BATCH_SIZE: i64 = 1;
+const PUSH_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const PUSH_QUEUE_STATS_INTERVAL: Duration = Duration::from_secs(15);
+/// Attempts, including the first, before a job is dead-lettered.
+const PUSH_MAX_ATTEMPTS: i32 = 5;
+const PUSH_INITIAL_RETRY_DELAY_SECS: i64 = 15;
+const PUSH_MAX_RETRY_DELAY_SECS: i64 = 10 * 60;
+const PUSH_DEAD_LETTER_RETENTION_DAYS: i64 = 7;
+const PUSH_MAX_ERROR_CHARS: usize = 500;
 
 pub(super) async fn supervise_push_worker(
-    mut rx: mpsc::Receiver<PushJob>,
     service: Arc<PushService>,
-    db: Pool<ConnectionManager<PgConnection>>,
     ws_registry: Arc<ConnectionRegistry>,
 ) {
     loop {
-        let worker_result =
-            std::panic::AssertUnwindSafe(run_push_worker(&mut rx, &service, &db, &ws_registry))
-                .catch_unwind()
-                .await;
+        let worker_result = std::panic::AssertUnwindSafe(run_push_worker(&service, &ws_registry))
+            .catch_unwind()
+            .await;
 
         match worker_result {
             Ok(()) => {
-                info!("Push notification worker stopped (channel closed)");
+                info!("Push notification worker stopped");
                 return;
             }
             Err(payload) => {
@@ -54,59 +69,349 @@ pub(super) async fn supervise_push_worker(
     }
 }
 
-/// Background worker that processes push notification jobs.
-async fn run_push_worker(
-    rx: &mut mpsc::Receiver<PushJob>,
+/// A job leased from the queue. `attempts` already counts this attempt.
+#[derive(Debug, QueryableByName)]
+struct ClaimedPushJob {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    id: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    message_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Jsonb)]
+    payload: serde_json::Value,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Array<diesel::sql_types::BigInt>>)]
+    retry_subscription_ids: Option<Vec<i64>>,
+    #[diesel(sql_type = diesel::sql_types::Array<diesel::sql_types::Integer>)]
+    silent_uids: Vec<i32>,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    attempts: i32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PushJobOutcome {
+    Delivered,
+    /// Redeliver later, only to `subscription_ids` when set.
+    Retry {
+        subscription_ids: Option<Vec<i64>>,
+        silent_uids: Vec<i32>,
+        error: String,
+    },
+    DeadLetter {
+        error: String,
+    },
+}
+
+/// Subscriptions held for quiet hours, keyed by when the hours end.
+type HeldDeliveries = BTreeMap<chrono::DateTime<chrono::Utc>, Vec<i64>>;
+
+/// What one attempt at a job left to do.
+#[derive(Debug, Default)]
+struct PushAttempt {
+    /// Subscriptions whose delivery failed transiently and should be retried.
+    retry_ids: Vec<i64>,
+    /// Users whose deliveries go out silently; a retry keeps them silent.
+    silent_uids: Vec<i32>,
+    held: HeldDeliveries,
+}
+
+/// Background worker that claims and processes queued push notification jobs.
+async fn run_push_worker(service: &Arc<PushService>, ws_registry: &Arc<ConnectionRegistry>) {
+    info!("Push notification worker started");
+    let mut stats_refreshed_at: Option<Instant> = None;
+
+    loop {
+        if stats_refreshed_at.is_none_or(|at| at.elapsed() >= PUSH_QUEUE_STATS_INTERVAL) {
+            if let Err(e) = maintain_queue(service) {
+                warn!("Push worker: failed to maintain queue: {}", e);
+            }
+            stats_refreshed_at = Some(Instant::now());
+        }
+
+        let claimed = match service
+            .db
+            .get()
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|mut conn| claim_due_jobs(&mut conn).map_err(|e| format!("{:?}", e)))
+        {
+            Ok(claimed) => claimed,
+            Err(e) => {
+                error!("Push worker: failed to claim jobs: {}", e);
+                Vec::new()
+            }
+        };
+        let count = claimed.len();
+
+        for claimed_job in claimed {
+            run_claimed_job(service, ws_registry, claimed_job).await;
+        }
+
+        if count < PUSH_CLAIM_BATCH_SIZE as usize {
+            tokio::select! {
+                _ = service.wake.notified() => {}
+                _ = tokio::time::sleep(PUSH_POLL_INTERVAL) => {}
+            }
+        }
+    }
+}
+
+async fn run_claimed_job(
     service: &Arc<PushService>,
-    db: &Pool<ConnectionManager<PgConnection>>,
     ws_registry: &Arc<ConnectionRegistry>,
+    claimed: ClaimedPushJob,
 ) {
-    info!("Push notification worker started");
+    let started_at = Instant::now();
+    let mut held = HeldDeliveries::new();
+    let outcome = match serde_json::from_value::<PushJob>(claimed.payload) {
+        Ok(job) => {
+            debug!(
+                "Processing push job: chat_id={} sender_uid={} message_id={} attempt={}",
+                job.chat_id, job.sender_uid, job.message_id, claimed.attempts
+            );
 
-    while let Some(job) = rx.recv().await {
-        debug!(
-            "Processing push job: chat_id={} sender_uid={} message_id={}",
-            job.chat_id, job.sender_uid, job.message_id
+            #[cfg(test)]
+            maybe_panic_for_test(&job);
+
+            match service.db.get() {
+                Ok(conn) => match process_push_job(
+                    service,
+                    conn,
+                    ws_registry,
+                    &job,
+                    claimed.retry_subscription_ids.as_deref(),
+                    &claimed.silent_uids,
+                )
+                .await
+                {
+                    Ok(attempt) => {
+                        held = attempt.held;
+                        if attempt.retry_ids.is_empty() {
+                            PushJobOutcome::Delivered
+                        } else {
+                            PushJobOutcome::Retry {
+                                error: format!(
+                                    "{} transient delivery failures",
+                                    attempt.retry_ids.len()
+                                ),
+                                subscription_ids: Some(attempt.retry_ids),
+                                silent_uids: attempt.silent_uids,
+                            }
+                        }
+                    }
+                    Err(e) => PushJobOutcome::Retry {
+                        subscription_ids: claimed.retry_subscription_ids,
+                        silent_uids: claimed.silent_uids,
+                        error: e,
+                    },
+                },
+                Err(e) => PushJobOutcome::Retry {
+                    subscription_ids: claimed.retry_subscription_ids,
+                    silent_uids: claimed.silent_uids,
+                    error: format!("Failed to get DB connection: {:?}", e),
+                },
+            }
+        }
+        Err(e) => PushJobOutcome::DeadLetter {
+            error: format!("Invalid push job payload: {}", e),
+        },
+    };
+    let outcome = exhaust_retries(outcome, claimed.attempts);
+
+    let result = match &outcome {
+        PushJobOutcome::Delivered => "success",
+        PushJobOutcome::Retry { error, .. } => {
+            warn!(
+                "Push worker: retrying job {} for message_id={} (attempt {}): {}",
+                claimed.id, claimed.message_id, claimed.attempts, error
+            );
+            "failure"
+        }
+        PushJobOutcome::DeadLetter { error } => {
+            error!(
+                "Push worker: dead-lettering job {} for message_id={} after {} attempts: {}",
+                claimed.id, claimed.message_id, claimed.attempts, error
+            );
+            "dead_letter"
+        }
+    };
+    service
+        .metrics
+        .record_push_job(result, started_at.elapsed().as_secs_f64());
+
+    let recorded = service
+        .db
+        .get()
+        .map_err(|e| format!("{:?}", e))
+        .and_then(|mut conn| {
+            record_job_outcome(&mut conn, claimed.id, claimed.attempts, outcome, held)
+                .map_err(|e| format!("{:?}", e))
+        });
+    if let Err(e) = recorded {
+        // The lease expires and the job is claimed again.
+        error!(
+            "Push worker: failed to record outcome of job {}: {}",
+            claimed.id, e
         );
-        let started_at = Instant::now();
+    }
+}
 
-        #[cfg(test)]
-        maybe_panic_for_test(&job);
+/// Dead-letter a retry once the job has used up its attempts.
+fn exhaust_retries(outcome: PushJobOutcome, attempts: i32) -> PushJobOutcome {
+    match outcome {
+        PushJobOutcome::Retry { error, .. } if attempts >= PUSH_MAX_ATTEMPTS => {
+            PushJobOutcome::DeadLetter { error }
+        }
+        other => other,
+    }
+}
 
-        let conn = match db.get() {
-            Ok(c) => c,
-            Err(e) => {
-                error!("Push worker: failed to get DB connection: {:?}", e);
-                service
-                    .metrics
-                    .record_push_job("failure", started_at.elapsed().as_secs_f64());
-                continue;
-            }
-        };
+fn claim_due_jobs(conn: &mut PgConnection) -> QueryResult<Vec<ClaimedPushJob>> {
+    diesel::sql_query(
+        "UPDATE push_jobs
+         SET next_attempt_at = NOW() + make_interval(secs => $1),
+             attempts = attempts + 1
+         WHERE id IN (
+             SELECT id
+             FROM push_jobs
+             WHERE dead_at IS NULL
+               AND next_attempt_at <= NOW()
+               AND attempts < $3
+             ORDER BY next_attempt_at
+             LIMIT $2
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING id, message_id, payload, retry_subscription_ids, silent_uids, attempts",
+    )
+    .bind::<diesel::sql_types::Double, _>(PUSH_CLAIM_LEASE_SECS)
+    .bind::<diesel::sql_types::BigInt, _>(PUSH_CLAIM_BATCH_SIZE)
+    .bind::<diesel::sql_types::Integer, _>(PUSH_MAX_ATTEMPTS)
+    .load(conn)
+}
 
-        match process_push_job(service, conn, ws_registry, &job).await {
-            Ok(()) => service
-                .metrics
-                .record_push_job("success", started_at.elapsed().as_secs_f64()),
-            Err(e) => {
-                error!(
-                    "Push worker: job failed for message_id={}: {}",
-                    job.message_id, e
-                );
-                service
-                    .metrics
-                    .record_push_job("failure", started_at.elapsed().as_secs_f64());
+/// Record the outcome of an attempt together with the deliveries it held, so a job
+/// that is attempted again does not queue its held copies twice.
+fn record_job_outcome(
+    conn: &mut PgConnection,
+    job_id: i64,
+    attempts: i32,
+    outcome: PushJobOutcome,
+    held: HeldDeliveries,
+) -> QueryResult<()> {
+    let now = chrono::Utc::now();
+    conn.transaction(|conn| {
+        hold_for_quiet_hours(conn, job_id, held)?;
+
+        let row = push_jobs::table.filter(push_jobs::id.eq(job_id));
+        match outcome {
+            PushJobOutcome::Delivered => {
+                diesel::delete(row).execute(conn)?;
+            }
+            PushJobOutcome::Retry {
+                subscription_ids,
+                silent_uids,
+                error,
+            } => {
+                diesel::update(row)
+                    .set((
+                        push_jobs::next_attempt_at.eq(now + retry_delay(attempts)),
+                        push_jobs::retry_subscription_ids.eq(subscription_ids),
+                        push_jobs::silent_uids.eq(silent_uids),
+                        push_jobs::last_error.eq(Some(truncate_error(&error))),
+                    ))
+                    .execute(conn)?;
+            }
+            PushJobOutcome::DeadLetter { error } => {
+                diesel::update(row)
+                    .set((
+                        push_jobs::dead_at.eq(Some(now)),
+                        push_jobs::last_error.eq(Some(truncate_error(&error))),
+                    ))
+                    .execute(conn)?;
             }
         }
+        Ok(())
+    })
+}
+
+/// Dead-letter jobs whose final lease expired (their worker crashed), drop old dead
+/// letters, and publish queue depth and age.
+fn maintain_queue(service: &PushService) -> Result<(), String> {
+    let conn = &mut service.db.get().map_err(|e| format!("{:?}", e))?;
+    let now = chrono::Utc::now();
+
+    let abandoned = diesel::update(
+        push_jobs::table
+            .filter(push_jobs::dead_at.is_null())
+            .filter(push_jobs::attempts.ge(PUSH_MAX_ATTEMPTS))
+            .filter(push_jobs::next_attempt_at.le(now)),
+    )
+    .set((
+        push_jobs::dead_at.eq(Some(now)),
+        push_jobs::last_error.eq(Some("Worker did not finish the final attempt")),
+    ))
+    .execute(conn)
+    .map_err(|e| format!("Failed to dead-letter abandoned push jobs: {:?}", e))?;
+    if abandoned > 0 {
+        warn!("Push worker: dead-lettered {} abandoned jobs", abandoned);
     }
+
+    diesel::delete(push_jobs::table.filter(
+        push_jobs::dead_at.lt(now - chrono::Duration::days(PUSH_DEAD_LETTER_RETENTION_DAYS)),
+    ))
+    .execute(conn)
+    .map_err(|e| format!("Failed to delete expired push dead letters: {:?}", e))?;
+
+    let (pending, oldest_pending): (i64, Option<chrono::DateTime<chrono::Utc>>) = push_jobs::table
+        .filter(push_jobs::dead_at.is_null())
+        .select((
+            diesel::dsl::count_star(),
+            diesel::dsl::min(push_jobs::created_at),
+        ))
+        .first(conn)
+        .map_err(|e| format!("Failed to count pending push jobs: {:?}", e))?;
+    let dead: i64 = push_jobs::table
+        .filter(push_jobs::dead_at.is_not_null())
+        .count()
+        .get_result(conn)
+        .map_err(|e| format!("Failed to count dead push jobs: {:?}", e))?;
+    let oldest_pending_age = oldest_pending
+        .map(|created_at| (now - created_at).num_seconds().max(0))
+        .unwrap_or(0);
+    service
+        .metrics
+        .set_push_queue_stats(pending, dead, oldest_pending_age);
+
+    Ok(())
+}
+
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    let secs = PUSH_INITIAL_RETRY_DELAY_SECS
+        .saturating_mul(1_i64 << exponent)
+        .min(PUSH_MAX_RETRY_DELAY_SECS);
+    chrono::Duration::seconds(secs)
+}
+
+fn truncate_error(error: &str) -> String {
+    error.chars().take(PUSH_MAX_ERROR_CHARS).collect()
 }
 
 #[derive(Debug)]
 struct RecipientCandidate {
     uid: i32,
     muted_until: Option<chrono::DateTime<chrono::Utc>>,
+    topic_muted_until: Option<chrono::DateTime<chrono::Utc>>,
     chat_archived: bool,
     thread_state: ThreadPushState,
+    /// Per-chat override of the account notification level.
+    notification_level: Option<NotificationLevel>,
+}
+
+/// Subscriptions to deliver to now, the users among their owners in silent quiet hours,
+/// and the subscriptions held until quiet hours end.
+#[derive(Debug, Default)]
+struct TargetSubscriptions {
+    subscriptions: Vec<PushSubscription>,
+    silent_uids: HashSet<i32>,
+    held: HeldDeliveries,
 }
 
 #[derive(Debug)]
@@ -135,6 +440,21 @@ fn counted_failure_persistence_action(next_failure_count: i32) -> CountedFailure
     }
 }
 
+type ThreadCandidateRow = (
+    i32,
+    bool,
+    Option<chrono::DateTime<chrono::Utc>>,
+    bool,
+    Option<NotificationLevel>,
+);
+
+type MemberCandidateRow = (
+    i32,
+    Option<chrono::DateTime<chrono::Utc>>,
+    bool,
+    Option<NotificationLevel>,
+);
+
 fn load_recipient_candidates(
     conn: &mut PgConnection,
     job: &PushJob,
@@ -147,151 +467,343 @@ fn load_recipient_candidates(
     if let Some(thread_root_id) = job.thread_root_id {
         use crate::schema::thread_user_states::dsl as ts_dsl;
 
-        let rows: Vec<(i32, bool, Option<chrono::DateTime<chrono::Utc>>, bool)> =
-            ts_dsl::thread_user_states
-                .inner_join(
-                    group_membership::table.on(gm_dsl::chat_id
-                        .eq(ts_dsl::chat_id)
-                        .and(gm_dsl::uid.eq(ts_dsl::uid))),
-                )
-                .filter(ts_dsl::chat_id.eq(job.chat_id))
-                .filter(ts_dsl::thread_root_id.eq(thread_root_id))
-                .filter(ts_dsl::subscribed.eq(true))
-                .select((
-                    ts_dsl::uid,
-                    ts_dsl::archived,
-                    group_membership::muted_until,
-                    group_membership::archived,
-                ))
-                .load(conn)
-                .map_err(|e| format!("Failed to load thread subscriber candidates: {:?}", e))?;
+        let rows: Vec<ThreadCandidateRow> = ts_dsl::thread_user_states
+            .inner_join(
+                group_membership::table.on(gm_dsl::chat_id
+                    .eq(ts_dsl::chat_id)
+                    .and(gm_dsl::uid.eq(ts_dsl::uid))),
+            )
+            .filter(ts_dsl::chat_id.eq(job.chat_id))
+            .filter(ts_dsl::thread_root_id.eq(thread_root_id))
+            .filter(ts_dsl::subscribed.eq(true))
+            .select((
+                ts_dsl::uid,
+                ts_dsl::archived,
+                group_membership::muted_until,
+                group_membership::archived,
+                group_membership::notification_level,
+            ))
+            .load(conn)
+            .map_err(|e| format!("Failed to load thread subscriber candidates: {:?}", e))?;
 
-        for (uid, thread_archived, muted_until, chat_archived) in rows {
+        for (uid, thread_archived, muted_until, chat_archived, notification_level) in rows {
             candidates.insert(
                 uid,
                 RecipientCandidate {
                     uid,
                     muted_until,
+                    topic_muted_until: None,
                     chat_archived,
                     thread_state: if thread_archived {
                         ThreadPushState::ArchivedSubscription
                     } else {
                         ThreadPushState::ActiveSubscription
                     },
+                    notification_level,
                 },
             );
         }
 
-        if !job.mentioned_uids.is_empty() {
-            let rows: Vec<(i32, Option<chrono::DateTime<chrono::Utc>>, bool)> =
-                group_membership::table
-                    .filter(gm_dsl::chat_id.eq(job.chat_id))
-                    .filter(gm_dsl::uid.eq_any(&job.mentioned_uids))
-                    .select((
-                        group_membership::uid,
-                        group_membership::muted_until,
-                        group_membership::archived,
-                    ))
-                    .load(conn)
-                    .map_err(|e| format!("Failed to load mentioned member candidates: {:?}", e))?;
+        // Mentions and keyword matches reach members who do not follow the thread.
+        let mut unsubscribed_uids: Vec<i32> = job
+            .mentioned_uids
+            .iter()
+            .copied()
+            .filter(|uid| !candidates.contains_key(uid))
+            .collect();
+        let message_text = job.message_preview.message.as_deref().unwrap_or_default();
+        if !message_text.is_empty() {
+            use crate::schema::notification_preferences::dsl as np_dsl;
+
+            let keyword_rows: Vec<(i32, Vec<String>)> = np_dsl::notification_preferences
+                .filter(
+                    np_dsl::uid.eq_any(
+                        group_membership::table
+                            .filter(gm_dsl::chat_id.eq(job.chat_id))
+                            .select(gm_dsl::uid),
+                    ),
+                )
+                .filter(np_dsl::keywords.ne(Vec::<String>::new()))
+                .select((np_dsl::uid, np_dsl::keywords))
+                .load(conn)
+                .map_err(|e| format!("Failed to load keyword candidates: {:?}", e))?;
+            unsubscribed_uids.extend(
+                keyword_rows
+                    .into_iter()
+                    .filter(|(uid, keywords)| {
+                        !candidates.contains_key(uid) && matches_any_keyword(message_text, keywords)
+                    })
+                    .map(|(uid, _)| uid),
+            );
+        }
+
+        if !unsubscribed_uids.is_empty() {
+            let rows: Vec<MemberCandidateRow> = group_membership::table
+                .filter(gm_dsl::chat_id.eq(job.chat_id))
+                .filter(gm_dsl::uid.eq_any(&unsubscribed_uids))
+                .select((
+                    group_membership::uid,
+                    group_membership::muted_until,
+                    group_membership::archived,
+                    group_membership::notification_level,
+                ))
+                .load(conn)
+                .map_err(|e| format!("Failed to load unsubscribed member candidates: {:?}", e))?;
 
-            for (uid, muted_until, chat_archived) in rows {
+            for (uid, muted_until, chat_archived, notification_level) in rows {
                 candidates.entry(uid).or_insert(RecipientCandidate {
                     uid,
                     muted_until,
+                    topic_muted_until: None,
                     chat_archived,
                     thread_state: ThreadPushState::NoSubscription,
+                    notification_level,
                 });
             }
         }
     } else {
-        let rows: Vec<(i32, Option<chrono::DateTime<chrono::Utc>>, bool)> = group_membership::table
+        let rows: Vec<MemberCandidateRow> = group_membership::table
             .filter(gm_dsl::chat_id.eq(job.chat_id))
             .select((
                 group_membership::uid,
                 group_membership::muted_until,
                 group_membership::archived,
+                group_membership::notification_level,
             ))
             .load(conn)
             .map_err(|e| format!("Failed to load member candidates: {:?}", e))?;
 
-        for (uid, muted_until, chat_archived) in rows {
+        for (uid, muted_until, chat_archived, notification_level) in rows {
             candidates.insert(
                 uid,
                 RecipientCandidate {
                     uid,
                     muted_until,
+                    topic_muted_until: None,
                     chat_archived,
                     thread_state: ThreadPushState::NotThreadMessage,
+                    notification_level,
                 },
             );
         }
+
+        if let Some(topic_id) = job.topic_id {
+            let uids: Vec<i32> = candidates.keys().copied().collect();
+            let topic_mutes = crate::services::topics::load_topic_mutes(conn, topic_id, &uids)
+                .map_err(|e| format!("Failed to load topic mutes: {:?}", e))?;
+            for (uid, muted_until) in topic_mutes {
+                if let Some(candidate) = candidates.get_mut(&uid) {
+                    candidate.topic_muted_until = Some(muted_until);
+                }
+            }
+        }
     }
 
     Ok(candidates.into_values().collect())
 }
 
-/// Process a single push job: load subscriptions, filter online users, send, cleanup.
-async fn process_push_job(
-    service: &Arc<PushService>,
-    mut conn: diesel::r2d2::PooledConnection<ConnectionManager<PgConnection>>,
+/// Subscriptions of the chat members the policy lets this job notify. Deliveries held
+/// for quiet hours are returned for the caller to queue with the job's outcome.
+fn load_target_subscriptions(
+    service: &PushService,
+    conn: &mut PgConnection,
     ws_registry: &ConnectionRegistry,
     job: &PushJob,
-) -> Result<(), String> {
+) -> Result<TargetSubscriptions, String> {
     let now = chrono::Utc::now();
 
-    let candidates = load_recipient_candidates(&mut conn, job)?;
+    let candidates = load_recipient_candidates(conn, job)?;
+    let candidate_uids: Vec<i32> = candidates.iter().map(|candidate| candidate.uid).collect();
+    let preferences = load_preferences_for(conn, &candidate_uids)
+        .map_err(|e| format!("Failed to load notification preferences: {:?}", e))?;
+    let message_text = job.message_preview.message.as_deref().unwrap_or_default();
 
-    let mentioned_uids: std::collections::HashSet<i32> =
-        job.mentioned_uids.iter().copied().collect();
-    let target_uids: Vec<i32> = candidates
-        .into_iter()
-        .filter_map(|candidate| {
-            let has_active_presence =
-                ws_registry.should_suppress_push(candidate.uid, PUSH_SUPPRESSION_FRESHNESS_SECS);
-            let context = PushRecipientContext {
-                uid: candidate.uid,
-                is_sender: candidate.uid == job.sender_uid,
-                is_mentioned: mentioned_uids.contains(&candidate.uid),
-                is_reply_target: job.reply_target_uid == Some(candidate.uid),
-                chat_archived: candidate.chat_archived,
-                group_muted_until: candidate.muted_until,
-                thread_state: candidate.thread_state,
-                has_active_presence,
-            };
-
-            match should_send_push(&context, now) {
-                PushDecision::Send | PushDecision::SendOneOffMention => Some(candidate.uid),
-                PushDecision::Skip(PushSkipReason::ActivePresence) => {
-                    service.metrics.record_push_suppressed();
-                    None
-                }
-                PushDecision::Skip(_) => None,
+    let mentioned_uids: HashSet<i32> = job.mentioned_uids.iter().copied().collect();
+    let mut target_uids = Vec::new();
+    let mut silent_uids = HashSet::new();
+    let mut held_until = std::collections::HashMap::new();
+    for candidate in candidates {
+        let has_active_presence =
+            ws_registry.should_suppress_push(candidate.uid, PUSH_SUPPRESSION_FRESHNESS_SECS);
+        let preferences = preferences.get(&candidate.uid);
+        let context = PushRecipientContext {
+            uid: candidate.uid,
+            is_sender: candidate.uid == job.sender_uid,
+            is_mentioned: mentioned_uids.contains(&candidate.uid),
+            is_reply_target: job.reply_target_uid == Some(candidate.uid),
+            chat_archived: candidate.chat_archived,
+            group_muted_until: candidate.muted_until,
+            is_important_announcement: job.important && job.thread_root_id.is_none(),
+            topic_muted_until: candidate.topic_muted_until,
+            thread_state: candidate.thread_state,
+            has_active_presence,
+            notification_level: candidate
+                .notification_level
+                .or(preferences.map(|p| p.level))
+                .unwrap_or(NotificationLevel::All),
+            matches_keyword: preferences
+                .is_some_and(|p| matches_any_keyword(message_text, &p.keywords)),
+            quiet_hours: preferences.and_then(quiet_hours_from_preferences),
+        };
+
+        match should_send_push(&context, now) {
+            PushDecision::Send | PushDecision::SendOneOffMention => target_uids.push(candidate.uid),
+            PushDecision::SendSilently => {
+                target_uids.push(candidate.uid);
+                silent_uids.insert(candidate.uid);
             }
-        })
-        .collect();
+            PushDecision::Hold { until } => {
+                target_uids.push(candidate.uid);
+                held_until.insert(candidate.uid, until);
+            }
+            PushDecision::Skip(PushSkipReason::ActivePresence) => {
+                service.metrics.record_push_suppressed();
+            }
+            PushDecision::Skip(_) => {}
+        }
+    }
 
     if target_uids.is_empty() {
         debug!(
             "Push job: no offline recipients for message_id={}",
             job.message_id
         );
-        return Ok(());
+        return Ok(TargetSubscriptions::default());
     }
 
     // 3. Load push subscriptions for the target users.
     let subs: Vec<PushSubscription> = push_subscriptions::table
         .filter(push_subscriptions::dsl::user_id.eq_any(&target_uids))
         .select(PushSubscription::as_select())
-        .load(&mut conn)
+        .load(conn)
         .map_err(|e| format!("Failed to load push subscriptions: {:?}", e))?;
 
+    let mut held = HeldDeliveries::new();
+    let mut subscriptions = Vec::with_capacity(subs.len());
+    for sub in subs {
+        match held_until.get(&sub.user_id) {
+            Some(until) => held.entry(*until).or_default().push(sub.id),
+            None => subscriptions.push(sub),
+        }
+    }
+
+    Ok(TargetSubscriptions {
+        subscriptions,
+        silent_uids,
+        held,
+    })
+}
+
+fn quiet_hours_from_preferences(preferences: &NotificationPreferences) -> Option<QuietHours> {
+    let (start, end, timezone) = (
+        preferences.quiet_hours_start?,
+        preferences.quiet_hours_end?,
+        preferences.quiet_hours_timezone.as_deref()?,
+    );
+    let timezone = match chrono_tz::Tz::from_str(timezone) {
+        Ok(timezone) => timezone,
+        Err(_) => {
+            warn!(
+                "Push worker: ignoring quiet hours of uid={} with unknown timezone {}",
+                preferences.uid, timezone
+            );
+            return None;
+        }
+    };
+    Some(QuietHours {
+        start_minute: u16::try_from(start).ok()?,
+        end_minute: u16::try_from(end).ok()?,
+        timezone,
+        mode: preferences.quiet_hours_mode,
+    })
+}
+
+/// Queue a copy of job `job_id` per quiet-hours end, for the subscriptions held until then.
+fn hold_for_quiet_hours(
+    conn: &mut PgConnection,
+    job_id: i64,
+    held: HeldDeliveries,
+) -> QueryResult<()> {
+    for (until, subscription_ids) in held {
+        debug!(
+            "Push job: holding {} subscriptions of job {} until {}",
+            subscription_ids.len(),
+            job_id,
+            until
+        );
+        diesel::sql_query(
+            "INSERT INTO push_jobs (chat_id, message_id, payload, retry_subscription_ids, next_attempt_at)
+             SELECT chat_id, message_id, payload, $2, $3
+             FROM push_jobs
+             WHERE id = $1",
+        )
+        .bind::<diesel::sql_types::BigInt, _>(job_id)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::BigInt>, _>(subscription_ids)
+        .bind::<diesel::sql_types::Timestamptz, _>(until)
+        .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Subscriptions a retried job still owes a delivery, minus users who have come back online.
+fn load_retry_subscriptions(
+    service: &PushService,
+    conn: &mut PgConnection,
+    ws_registry: &ConnectionRegistry,
+    subscription_ids: &[i64],
+) -> Result<Vec<PushSubscription>, String> {
+    let subs: Vec<PushSubscription> = push_subscriptions::table
+        .filter(push_subscriptions::dsl::id.eq_any(subscription_ids))
+        .select(PushSubscription::as_select())
+        .load(conn)
+        .map_err(|e| format!("Failed to load push subscriptions for retry: {:?}", e))?;
+
+    Ok(subs
+        .into_iter()
+        .filter(|sub| {
+            let online =
+                ws_registry.should_suppress_push(sub.user_id, PUSH_SUPPRESSION_FRESHNESS_SECS);
+            if online {
+                service.metrics.record_push_suppressed();
+            }
+            !online
+        })
+        .collect())
+}
+
+/// Process a single push job: load subscriptions, filter online users, send, cleanup.
+///
+/// Returns the subscriptions to retry and the deliveries held for quiet hours.
+async fn process_push_job(
+    service: &Arc<PushService>,
+    mut conn: diesel::r2d2::PooledConnection<ConnectionManager<PgConnection>>,
+    ws_registry: &ConnectionRegistry,
+    job: &PushJob,
+    retry_subscription_ids: Option<&[i64]>,
+    retry_silent_uids: &[i32],
+) -> Result<PushAttempt, String> {
+    let TargetSubscriptions {
+        subscriptions: subs,
+        silent_uids,
+        held,
+    } = match retry_subscription_ids {
+        Some(ids) => TargetSubscriptions {
+            subscriptions: load_retry_subscriptions(service, &mut conn, ws_registry, ids)?,
+            silent_uids: retry_silent_uids.iter().copied().collect(),
+            held: HeldDeliveries::new(),
+        },
+        None => load_target_subscriptions(service, &mut conn, ws_registry, job)?,
+    };
+
     if subs.is_empty() {
         debug!(
             "Push job: no subscriptions for message_id={}",
             job.message_id
         );
-        return Ok(());
+        return Ok(PushAttempt {
+            held,
+            ..PushAttempt::default()
+        });
     }
 
     debug!(
@@ -324,13 +836,18 @@ async fn process_push_job(
             let service = service.clone();
 
             let unread = unread_counts.get(&sub.user_id).copied().unwrap_or(0);
-            let web_payload = serde_json::to_vec(&build_push_payload(job, unread, &body_text))
-                .unwrap_or_default();
-            let apns_notification = build_apns_notification(job, unread);
+            let silent = silent_uids.contains(&sub.user_id);
+            let mut web_payload = build_push_payload(job, unread, &body_text);
+            web_payload.silent = silent;
+            let web_payload = serde_json::to_vec(&web_payload).unwrap_or_default();
+            let mut apns_notification = build_apns_notification(job, unread);
+            apns_notification.silent = silent;
+            let mut fcm_notification = build_fcm_notification(job, unread, &body_text);
+            fcm_notification.silent = silent;
 
             async move {
                 match service
-                    .send_to_subscription(&sub, &web_payload, &apns_notification)
+                    .send_to_subscription(&sub, &web_payload, &apns_notification, &fcm_notification)
                     .await
                 {
                     Ok(()) => DeliveryAttemptResult::Success {
@@ -350,9 +867,39 @@ async fn process_push_job(
         .collect()
         .await;
 
-    apply_delivery_results(service, &mut conn, delivery_results)?;
+    let retry_ids = retryable_subscription_ids(&delivery_results);
+    let mut silent_uids: Vec<i32> = silent_uids.into_iter().collect();
+    silent_uids.sort_unstable();
+    // The notifications are out; failing the job here would send them all again.
+    if let Err(e) = apply_delivery_results(service, &mut conn, delivery_results) {
+        error!(
+            "Push job: failed to record delivery results for message_id={}: {}",
+            job.message_id, e
+        );
+    }
+
+    Ok(PushAttempt {
+        retry_ids,
+        silent_uids,
+        held,
+    })
+}
 
-    Ok(())
+/// Subscriptions that failed for a reason on the provider's side that may clear up,
+/// e.g. quota or availability.
+fn retryable_subscription_ids(results: &[DeliveryAttemptResult]) -> Vec<i64> {
+    results
+        .iter()
+        .filter_map(|result| match result {
+            DeliveryAttemptResult::Failure { failure, .. }
+                if failure.action == DeliveryFailureAction::None
+                    && failure.class == DeliveryFailureClass::ProviderTransient =>
+            {
+                Some(failure.subscription_id)
+            }
+            _ => None,
+        })
+        .collect()
 }
 
 fn apply_delivery_results(
@@ -490,7 +1037,37 @@ static TEST_PANIC_MESSAGE_ID: std::sync::atomic::AtomicI64 =
 
 #[cfg(test)]
 mod tests {
-    use super::{counted_failure_persistence_action, CountedFailurePersistenceAction};
+    use diesel::prelude::*;
+    use diesel_migrations::MigrationHarness;
+
+    use super::{
+        counted_failure_persistence_action, exhaust_retries, record_job_outcome, retry_delay,
+        retryable_subscription_ids, CountedFailurePersistenceAction, DeliveryAttemptResult,
+        HeldDeliveries, PushJobOutcome, PUSH_MAX_ATTEMPTS, PUSH_MAX_RETRY_DELAY_SECS,
+    };
+    use crate::models::PushProvider;
+    use crate::schema::push_jobs;
+    use crate::services::push::delivery::{
+        DeliveryFailure, DeliveryFailureAction, DeliveryFailureClass,
+    };
+
+    fn failure(
+        subscription_id: i64,
+        class: DeliveryFailureClass,
+        action: DeliveryFailureAction,
+    ) -> DeliveryAttemptResult {
+        DeliveryAttemptResult::Failure {
+            failure: DeliveryFailure {
+                subscription_id,
+                user_id: 1,
+                provider: PushProvider::Fcm,
+                class,
+                reason: "test".to_string(),
+                action,
+            },
+            next_failure_count: 1,
+        }
+    }
 
     #[test]
     fn first_counted_delivery_failure_is_recorded() {
@@ -515,4 +1092,125 @@ mod tests {
             CountedFailurePersistenceAction::Prune
         );
     }
+
+    #[test]
+    fn retry_delay_doubles_and_caps() {
+        assert_eq!(retry_delay(1).num_seconds(), 15);
+        assert_eq!(retry_delay(2).num_seconds(), 30);
+        assert_eq!(retry_delay(3).num_seconds(), 60);
+        assert_eq!(retry_delay(30).num_seconds(), PUSH_MAX_RETRY_DELAY_SECS);
+    }
+
+    #[test]
+    fn retry_on_final_attempt_is_dead_lettered() {
+        let retry = || PushJobOutcome::Retry {
+            subscription_ids: Some(vec![7]),
+            silent_uids: vec![3],
+            error: "quota".to_string(),
+        };
+
+        assert_eq!(exhaust_retries(retry(), PUSH_MAX_ATTEMPTS - 1), retry());
+        assert_eq!(
+            exhaust_retries(retry(), PUSH_MAX_ATTEMPTS),
+            PushJobOutcome::DeadLetter {
+                error: "quota".to_string()
+            }
+        );
+        assert_eq!(
+            exhaust_retries(PushJobOutcome::Delivered, PUSH_MAX_ATTEMPTS),
+            PushJobOutcome::Delivered
+        );
+    }
+
+    /// Runs against `TEST_DATABASE_URL` when set; there is no database otherwise.
+    #[test]
+    fn held_deliveries_are_queued_with_the_retry() {
+        let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+            return;
+        };
+        let conn = &mut diesel::PgConnection::establish(&database_url).unwrap();
+        conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+
+        let message_id = rand::random::<i64>().abs();
+        let payload = serde_json::json!({ "message_id": message_id });
+        let job_id: i64 = diesel::insert_into(push_jobs::table)
+            .values((
+                push_jobs::chat_id.eq(1_i64),
+                push_jobs::message_id.eq(message_id),
+                push_jobs::payload.eq(&payload),
+            ))
+            .returning(push_jobs::id)
+            .get_result(conn)
+            .unwrap();
+
+        let until = chrono::Utc::now() + chrono::Duration::hours(6);
+        let held = HeldDeliveries::from([(until, vec![11, 12])]);
+        record_job_outcome(
+            conn,
+            job_id,
+            1,
+            PushJobOutcome::Retry {
+                subscription_ids: Some(vec![13]),
+                silent_uids: vec![4],
+                error: "quota".to_string(),
+            },
+            held,
+        )
+        .unwrap();
+
+        let rows: Vec<(Option<Vec<i64>>, Vec<i32>)> = push_jobs::table
+            .filter(push_jobs::message_id.eq(message_id))
+            .order(push_jobs::id.asc())
+            .select((push_jobs::retry_subscription_ids, push_jobs::silent_uids))
+            .load(conn)
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![(Some(vec![13]), vec![4]), (Some(vec![11, 12]), Vec::new()),],
+            "the retry stays silent and the held copy follows it"
+        );
+        let held_payload: serde_json::Value = push_jobs::table
+            .filter(push_jobs::message_id.eq(message_id))
+            .filter(push_jobs::id.ne(job_id))
+            .select(push_jobs::payload)
+            .first(conn)
+            .unwrap();
+        assert_eq!(held_payload, payload);
+
+        diesel::delete(push_jobs::table.filter(push_jobs::message_id.eq(message_id)))
+            .execute(conn)
+            .unwrap();
+    }
+
+    #[test]
+    fn only_transient_provider_failures_are_retried() {
+        let results = vec![
+            DeliveryAttemptResult::Success {
+                subscription_id: 1,
+                should_reset_failure_state: false,
+            },
+            failure(
+                2,
+                DeliveryFailureClass::ProviderTransient,
+                DeliveryFailureAction::None,
+            ),
+            failure(
+                3,
+                DeliveryFailureClass::ProviderRejected,
+                DeliveryFailureAction::PruneImmediate,
+            ),
+            failure(
+                4,
+                DeliveryFailureClass::ProviderTransient,
+                DeliveryFailureAction::Counted,
+            ),
+            failure(
+                5,
+                DeliveryFailureClass::BackendConfig,
+                DeliveryFailureAction::None,
+            ),
+        ];
+
+        assert_eq!(retryable_subscription_ids(&results), vec![2]);
+    }
 }
@@ -0,0 +1,681 @@
+//! Token-bucket limits for abuse-prone API routes. Each route group has its own bucket
+//! per caller, keyed by uid, client id or IP. Buckets live in memory by default; set
+//! `RATE_LIMIT_STORE=postgres` to share them between nodes.
+//!
+//! A group is configured with `RATE_LIMIT_<GROUP>=<burst>/<seconds>[:uid|client|ip]`,
+//! e.g. `RATE_LIMIT_MESSAGES=20/20:uid` (bursts of 20, refilled at one per second), or
+//! `off` to disable it.
+//!
+//! Unauthenticated requests are always keyed by IP; a client-chosen header would give
+//! each request a fresh bucket. `RATE_LIMIT_TRUSTED_PROXIES` lists the proxies (IPs or
+//! CIDRs) whose `X-Forwarded-For` is believed.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, State},
+    http::{HeaderMap, Method, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::PgConnection;
+
+use crate::errors::AppError;
+use crate::metrics::Metrics;
+use crate::schema::rate_limit_buckets;
+use crate::utils::auth::{extract_auth_context, optional_client_id};
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+const RATE_LIMITED: &str = "Too many requests";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouteGroup {
+    Messages,
+    Reactions,
+    Uploads,
+    InviteRedeem,
+    UserSearch,
+}
+
+impl RouteGroup {
+    const ALL: [Self; 5] = [
+        Self::Messages,
+        Self::Reactions,
+        Self::Uploads,
+        Self::InviteRedeem,
+        Self::UserSearch,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Messages => "messages",
+            Self::Reactions => "reactions",
+            Self::Uploads => "uploads",
+            Self::InviteRedeem => "invite_redeem",
+            Self::UserSearch => "user_search",
+        }
+    }
+
+    fn env_var(self) -> &'static str {
+        match self {
+            Self::Messages => "RATE_LIMIT_MESSAGES",
+            Self::Reactions => "RATE_LIMIT_REACTIONS",
+            Self::Uploads => "RATE_LIMIT_UPLOADS",
+            Self::InviteRedeem => "RATE_LIMIT_INVITE_REDEEM",
+            Self::UserSearch => "RATE_LIMIT_USER_SEARCH",
+        }
+    }
+
+    fn default_rule(self) -> RateLimitRule {
+        let (burst, period_secs) = match self {
+            Self::Messages => (20, 20),
+            Self::Reactions => (30, 30),
+            Self::Uploads => (20, 60),
+            Self::InviteRedeem => (10, 60),
+            Self::UserSearch => (30, 60),
+        };
+        RateLimitRule {
+            burst,
+            period_secs,
+            key: KeyKind::Uid,
+        }
+    }
+
+    /// The group a matched route template belongs to, if any.
+    fn classify(method: &Method, route: &str) -> Option<Self> {
+        let route = route.trim_end_matches('/');
+        if *method == Method::POST
+            && (route == "/chats/{chat_id}/messages"
+                || route == "/chats/{chat_id}/threads/{thread_id}/messages")
+        {
+            return Some(Self::Messages);
+        }
+        if *method != Method::GET
+            && route.starts_with("/chats/{chat_id}/messages/{message_id}/reactions")
+        {
+            return Some(Self::Reactions);
+        }
+        match (method, route) {
+            (&Method::POST, "/attachments/upload-url") => Some(Self::Uploads),
+            (&Method::POST, "/invites/redeem") => Some(Self::InviteRedeem),
+            (&Method::GET, "/users/search") => Some(Self::UserSearch),
+            _ => None,
+        }
+    }
+}
+
+/// What a bucket is keyed by. `Uid` falls back to the client id and `ClientId` to the
+/// IP address when the request does not carry one; unauthenticated requests always
+/// use the IP address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyKind {
+    Uid,
+    ClientId,
+    Ip,
+}
+
+impl KeyKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Uid => "uid",
+            Self::ClientId => "client",
+            Self::Ip => "ip",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitRule {
+    /// Requests allowed in a burst.
+    burst: u32,
+    /// Seconds for an empty bucket to refill completely.
+    period_secs: u32,
+    key: KeyKind,
+}
+
+impl RateLimitRule {
+    fn refill_per_sec(&self) -> f64 {
+        f64::from(self.burst) / f64::from(self.period_secs)
+    }
+}
+
+/// Parse `<burst>/<seconds>[:uid|client|ip]`, or `off` for no limit.
+fn parse_rule(raw: &str) -> Result<Option<RateLimitRule>, String> {
+    let raw = raw.trim();
+    if raw.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    let (rate, key) = match raw.split_once(':') {
+        Some((rate, key)) => (rate, Some(key.trim())),
+        None => (raw, None),
+    };
+    let key = match key {
+        None | Some("uid") => KeyKind::Uid,
+        Some("client") => KeyKind::ClientId,
+        Some("ip") => KeyKind::Ip,
+        Some(other) => return Err(format!("unknown key `{other}`")),
+    };
+    let (burst, period_secs) = rate
+        .split_once('/')
+        .ok_or_else(|| format!("expected <burst>/<seconds>, got `{rate}`"))?;
+    let burst = burst
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|burst| *burst > 0)
+        .ok_or_else(|| format!("invalid burst `{burst}`"))?;
+    let period_secs = period_secs
+        .trim()
+        .trim_end_matches('s')
+        .parse::<u32>()
+        .ok()
+        .filter(|period| *period > 0)
+        .ok_or_else(|| format!("invalid period `{period_secs}`"))?;
+    Ok(Some(RateLimitRule {
+        burst,
+        period_secs,
+        key,
+    }))
+}
+
+fn refill(tokens: f64, elapsed_secs: f64, rule: &RateLimitRule) -> f64 {
+    (tokens + elapsed_secs.max(0.0) * rule.refill_per_sec()).min(f64::from(rule.burst))
+}
+
+/// Seconds until a bucket holding `tokens` has a whole token again.
+fn retry_after_secs(tokens: f64, rule: &RateLimitRule) -> u64 {
+    ((1.0 - tokens) / rule.refill_per_sec()).ceil().max(1.0) as u64
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+    /// When the bucket is full again and can be forgotten.
+    full_at: Instant,
+}
+
+enum BucketStore {
+    Memory(DashMap<String, TokenBucket>),
+    Postgres(Pool<ConnectionManager<PgConnection>>),
+}
+
+impl BucketStore {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Memory(_) => "memory",
+            Self::Postgres(_) => "postgres",
+        }
+    }
+}
+
+#[derive(diesel::QueryableByName)]
+struct BucketRow {
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    tokens: f64,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    allowed: bool,
+}
+
+pub struct RateLimiter {
+    rules: Vec<(RouteGroup, RateLimitRule)>,
+    store: BucketStore,
+    /// Proxies whose `X-Forwarded-For` entries are believed.
+    trusted_proxies: Vec<IpNetwork>,
+    metrics: Arc<Metrics>,
+}
+
+impl RateLimiter {
+    pub fn start(db: Pool<ConnectionManager<PgConnection>>, metrics: Arc<Metrics>) -> Arc<Self> {
+        let rules = RouteGroup::ALL
+            .into_iter()
+            .filter_map(|group| {
+                let rule = match std::env::var(group.env_var()) {
+                    Ok(raw) => parse_rule(&raw)
+                        .unwrap_or_else(|err| panic!("{} is invalid: {err}", group.env_var())),
+                    Err(_) => Some(group.default_rule()),
+                };
+                rule.map(|rule| (group, rule))
+            })
+            .collect::<Vec<_>>();
+        let store = match std::env::var("RATE_LIMIT_STORE").as_deref() {
+            Ok("postgres") => BucketStore::Postgres(db),
+            Ok("memory") | Err(_) => BucketStore::Memory(DashMap::new()),
+            Ok(other) => panic!("RATE_LIMIT_STORE must be memory or postgres, got {other}"),
+        };
+        let trusted_proxies = std::env::var("RATE_LIMIT_TRUSTED_PROXIES")
+            .map(|raw| {
+                parse_trusted_proxies(&raw)
+                    .unwrap_or_else(|err| panic!("RATE_LIMIT_TRUSTED_PROXIES is invalid: {err}"))
+            })
+            .unwrap_or_default();
+        tracing::info!(
+            store = store.label(),
+            groups = ?rules.iter().map(|(group, _)| group.as_str()).collect::<Vec<_>>(),
+            "API rate limits configured"
+        );
+
+        let limiter = Arc::new(Self {
+            rules,
+            store,
+            trusted_proxies,
+            metrics,
+        });
+
+        let pruner = limiter.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                pruner.prune().await;
+            }
+        });
+
+        limiter
+    }
+
+    fn rule_for(&self, group: RouteGroup) -> Option<RateLimitRule> {
+        self.rules
+            .iter()
+            .find(|(candidate, _)| *candidate == group)
+            .map(|(_, rule)| *rule)
+    }
+
+    /// Take one token from `key`'s bucket. Store failures let the request through.
+    async fn take(&self, key: String, rule: RateLimitRule) -> Result<(), u64> {
+        match &self.store {
+            BucketStore::Memory(buckets) => {
+                let now = Instant::now();
+                let mut bucket = buckets.entry(key).or_insert_with(|| TokenBucket {
+                    tokens: f64::from(rule.burst),
+                    updated_at: now,
+                    full_at: now,
+                });
+                let tokens = refill(
+                    bucket.tokens,
+                    (now - bucket.updated_at).as_secs_f64(),
+                    &rule,
+                );
+                bucket.updated_at = now;
+                let result = if tokens >= 1.0 {
+                    bucket.tokens = tokens - 1.0;
+                    Ok(())
+                } else {
+                    bucket.tokens = tokens;
+                    Err(retry_after_secs(tokens, &rule))
+                };
+                let missing = f64::from(rule.burst) - bucket.tokens;
+                bucket.full_at = now + Duration::from_secs_f64(missing / rule.refill_per_sec());
+                result
+            }
+            BucketStore::Postgres(db) => {
+                let db = db.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let mut conn = db.get().map_err(AppError::from)?;
+                    take_postgres(&mut conn, &key, &rule).map_err(AppError::from)
+                })
+                .await;
+                match result {
+                    Ok(Ok(row)) if row.allowed => Ok(()),
+                    Ok(Ok(row)) => Err(retry_after_secs(row.tokens, &rule)),
+                    Ok(Err(err)) => {
+                        tracing::warn!(?err, "rate limit store unavailable");
+                        self.metrics
+                            .record_rate_limit_store_error(self.store.label());
+                        Ok(())
+                    }
+                    Err(err) => {
+                        tracing::warn!(?err, "rate limit check panicked");
+                        self.metrics
+                            .record_rate_limit_store_error(self.store.label());
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Forget buckets that have refilled; they behave exactly like new ones.
+    async fn prune(&self) {
+        match &self.store {
+            BucketStore::Memory(buckets) => {
+                let now = Instant::now();
+                buckets.retain(|_, bucket| bucket.full_at > now);
+            }
+            BucketStore::Postgres(db) => {
+                let max_period_secs = self
+                    .rules
+                    .iter()
+                    .map(|(_, rule)| rule.period_secs)
+                    .max()
+                    .unwrap_or(0);
+                let db = db.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let mut conn = db.get().map_err(AppError::from)?;
+                    let cutoff =
+                        chrono::Utc::now() - chrono::Duration::seconds(i64::from(max_period_secs));
+                    diesel::delete(
+                        rate_limit_buckets::table.filter(rate_limit_buckets::updated_at.lt(cutoff)),
+                    )
+                    .execute(&mut conn)
+                    .map_err(AppError::from)
+                })
+                .await;
+                if !matches!(result, Ok(Ok(_))) {
+                    tracing::warn!(?result, "failed to prune rate limit buckets");
+                }
+            }
+        }
+    }
+
+    /// The bucket key for a request and the kind of key it ended up using.
+    fn subject(
+        &self,
+        kind: KeyKind,
+        headers: &HeaderMap,
+        state: &crate::AppState,
+        peer: Option<IpAddr>,
+    ) -> (KeyKind, String) {
+        let auth = match kind {
+            KeyKind::Ip => None,
+            KeyKind::Uid | KeyKind::ClientId => extract_auth_context(headers, state).ok(),
+        };
+        if let Some(auth) = auth {
+            if kind == KeyKind::Uid {
+                return (KeyKind::Uid, auth.uid.to_string());
+            }
+            let client_id = auth
+                .client_id
+                .or_else(|| optional_client_id(headers).ok().flatten());
+            if let Some(client_id) = client_id {
+                return (KeyKind::ClientId, client_id);
+            }
+        }
+        (
+            KeyKind::Ip,
+            client_ip(peer, headers, &self.trusted_proxies)
+                .map_or_else(|| "unknown".to_string(), |ip| ip.to_string()),
+        )
+    }
+}
+
+/// An address range in CIDR notation; a bare address is a single-host range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct IpNetwork {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        let (addr, prefix_len) = match raw.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (raw, None),
+        };
+        let addr: IpAddr = addr
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid address `{raw}`"))?;
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|len| *len <= max_len)
+                .ok_or_else(|| format!("invalid prefix length in `{raw}`"))?,
+            None => max_len,
+        };
+        Ok(Self { addr, prefix_len })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+            IpAddr::V4(_) => ip,
+        };
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => prefix_matches(
+                u128::from(u32::from(net)),
+                u128::from(u32::from(ip)),
+                32,
+                self.prefix_len,
+            ),
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                prefix_matches(u128::from(net), u128::from(ip), 128, self.prefix_len)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(net: u128, ip: u128, bits: u8, prefix_len: u8) -> bool {
+    if prefix_len == 0 {
+        return true;
+    }
+    let shift = u32::from(bits - prefix_len);
+    (net >> shift) == (ip >> shift)
+}
+
+/// Parse a comma-separated list of proxy addresses or CIDR ranges.
+fn parse_trusted_proxies(raw: &str) -> Result<Vec<IpNetwork>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(IpNetwork::parse)
+        .collect()
+}
+
+/// The caller's address. `X-Forwarded-For` is only read when the peer is a trusted
+/// proxy, and then from the right: the first hop not added by a trusted proxy is the
+/// client, since anything to its left is client-supplied.
+fn client_ip(
+    peer: Option<IpAddr>,
+    headers: &HeaderMap,
+    trusted_proxies: &[IpNetwork],
+) -> Option<IpAddr> {
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|net| net.contains(ip));
+    let peer = peer?;
+    if !is_trusted(peer) {
+        return Some(peer);
+    }
+    let hops: Vec<IpAddr> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|hop| hop.trim().parse::<IpAddr>())
+        .collect::<Result<_, _>>()
+        .unwrap_or_default();
+    Some(
+        hops.iter()
+            .rev()
+            .find(|hop| !is_trusted(**hop))
+            .or_else(|| hops.first())
+            .copied()
+            .unwrap_or(peer),
+    )
+}
+
+fn take_postgres(
+    conn: &mut PgConnection,
+    key: &str,
+    rule: &RateLimitRule,
+) -> QueryResult<BucketRow> {
+    diesel::sql_query(
+        "INSERT INTO rate_limit_buckets AS b (key, tokens, allowed, updated_at)
+         VALUES ($1, $2 - 1, TRUE, NOW())
+         ON CONFLICT (key) DO UPDATE SET
+           tokens = LEAST($2, b.tokens + EXTRACT(EPOCH FROM NOW() - b.updated_at)::float8 * $3)
+             - CASE
+                 WHEN LEAST($2, b.tokens + EXTRACT(EPOCH FROM NOW() - b.updated_at)::float8 * $3) >= 1
+                 THEN 1 ELSE 0
+               END,
+           allowed =
+             LEAST($2, b.tokens + EXTRACT(EPOCH FROM NOW() - b.updated_at)::float8 * $3) >= 1,
+           updated_at = NOW()
+         RETURNING tokens, allowed",
+    )
+    .bind::<diesel::sql_types::Text, _>(key)
+    .bind::<diesel::sql_types::Double, _>(f64::from(rule.burst))
+    .bind::<diesel::sql_types::Double, _>(rule.refill_per_sec())
+    .get_result(conn)
+}
+
+/// Middleware rejecting requests over their route group's limit with 429 and
+/// `Retry-After`. Routes outside every group pass straight through.
+pub async fn enforce_rate_limits(
+    State(state): State<crate::AppState>,
+    request: Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    let limiter = state.rate_limiter.clone();
+    let Some((group, rule)) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|route| RouteGroup::classify(request.method(), route.as_str()))
+        .and_then(|group| limiter.rule_for(group).map(|rule| (group, rule)))
+    else {
+        return next.run(request).await;
+    };
+
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let (kind, subject) = limiter.subject(rule.key, request.headers(), &state, peer);
+    let key = format!("{}:{}:{}", group.as_str(), kind.as_str(), subject);
+
+    if let Err(retry_after_secs) = limiter.take(key, rule).await {
+        limiter
+            .metrics
+            .record_rate_limit_throttled(group.as_str(), kind.as_str());
+        return AppError::TooManyRequests {
+            message: RATE_LIMITED,
+            retry_after_secs,
+        }
+        .into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderValue, Method};
+
+    use super::*;
+
+    fn rule(burst: u32, period_secs: u32) -> RateLimitRule {
+        RateLimitRule {
+            burst,
+            period_secs,
+            key: KeyKind::Uid,
+        }
+    }
+
+    #[test]
+    fn parse_rule_accepts_burst_period_and_key() {
+        assert_eq!(parse_rule("30/60").unwrap(), Some(rule(30, 60)));
+        assert_eq!(
+            parse_rule(" 5/10s:ip ").unwrap(),
+            Some(RateLimitRule {
+                burst: 5,
+                period_secs: 10,
+                key: KeyKind::Ip,
+            })
+        );
+        assert_eq!(parse_rule("off").unwrap(), None);
+        assert!(parse_rule("0/60").is_err());
+        assert!(parse_rule("10").is_err());
+        assert!(parse_rule("10/60:session").is_err());
+    }
+
+    #[test]
+    fn classify_matches_limited_routes_only() {
+        assert_eq!(
+            RouteGroup::classify(&Method::POST, "/chats/{chat_id}/messages/"),
+            Some(RouteGroup::Messages)
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::GET, "/chats/{chat_id}/messages"),
+            None
+        );
+        assert_eq!(
+            RouteGroup::classify(
+                &Method::DELETE,
+                "/chats/{chat_id}/messages/{message_id}/reactions/{emoji}"
+            ),
+            Some(RouteGroup::Reactions)
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::POST, "/invites/redeem"),
+            Some(RouteGroup::InviteRedeem)
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::GET, "/users/search"),
+            Some(RouteGroup::UserSearch)
+        );
+    }
+
+    #[test]
+    fn bucket_refills_up_to_burst() {
+        let rule = rule(10, 20);
+        assert_eq!(refill(0.0, 4.0, &rule), 2.0);
+        assert_eq!(refill(9.0, 60.0, &rule), 10.0);
+        assert_eq!(retry_after_secs(0.0, &rule), 2);
+        assert_eq!(retry_after_secs(0.9, &rule), 1);
+    }
+
+    fn forwarded(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static(value));
+        headers
+    }
+
+    fn ip(raw: &str) -> IpAddr {
+        raw.parse().unwrap()
+    }
+
+    #[test]
+    fn trusted_proxies_parse_addresses_and_ranges() {
+        let proxies = parse_trusted_proxies(" 10.0.0.0/8, 127.0.0.1 ,fd00::/8").unwrap();
+        assert!(proxies[0].contains(ip("10.20.30.40")));
+        assert!(!proxies[0].contains(ip("11.0.0.1")));
+        assert!(proxies[1].contains(ip("::ffff:127.0.0.1")));
+        assert!(!proxies[1].contains(ip("127.0.0.2")));
+        assert!(proxies[2].contains(ip("fd12::1")));
+        assert!(parse_trusted_proxies("").unwrap().is_empty());
+        assert!(parse_trusted_proxies("10.0.0.0/33").is_err());
+        assert!(parse_trusted_proxies("proxy.internal").is_err());
+    }
+
+    #[test]
+    fn client_ip_ignores_forwarded_for_from_untrusted_peers() {
+        let headers = forwarded("203.0.113.7");
+        assert_eq!(
+            client_ip(Some(ip("198.51.100.1")), &headers, &[]),
+            Some(ip("198.51.100.1"))
+        );
+    }
+
+    #[test]
+    fn client_ip_takes_the_first_untrusted_hop_from_the_right() {
+        let proxies = parse_trusted_proxies("10.0.0.0/8").unwrap();
+        // The client prepended a spoofed hop; the proxy appended the real address.
+        let headers = forwarded("1.2.3.4, 203.0.113.7, 10.0.0.2");
+        assert_eq!(
+            client_ip(Some(ip("10.0.0.1")), &headers, &proxies),
+            Some(ip("203.0.113.7"))
+        );
+        assert_eq!(
+            client_ip(Some(ip("10.0.0.1")), &HeaderMap::new(), &proxies),
+            Some(ip("10.0.0.1"))
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! Fixed one-minute request windows stored on the limited row, so every node shares them.
+//! Callers advance the window with an `UPDATE ... RETURNING window_started_at, window_count`
+//! and check the result here.
+
+use chrono::{DateTime, Utc};
+
+use crate::errors::AppError;
+
+pub const WINDOW_SECS: i64 = 60;
+
+#[derive(diesel::QueryableByName)]
+pub struct RateWindow {
+    #[diesel(sql_type = diesel::sql_types::Timestamptz)]
+    pub window_started_at: DateTime<Utc>,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub window_count: i32,
+}
+
+impl RateWindow {
+    /// Reject with 429 once the window holds more than `limit` requests.
+    pub fn check(&self, limit: i32, message: &'static str) -> Result<(), AppError> {
+        match retry_after_secs(self.window_started_at, self.window_count, limit, Utc::now()) {
+            Some(retry_after_secs) => Err(AppError::TooManyRequests {
+                message,
+                retry_after_secs,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn retry_after_secs(
+    window_started_at: DateTime<Utc>,
+    window_count: i32,
+    limit: i32,
+    now: DateTime<Utc>,
+) -> Option<u64> {
+    if window_count <= limit {
+        return None;
+    }
+    let elapsed = (now - window_started_at).num_seconds().max(0);
+    Some((WINDOW_SECS - elapsed).max(1) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::retry_after_secs;
+
+    #[test]
+    fn retry_after_counts_down_the_window() {
+        let started = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(retry_after_secs(started, 30, 30, started), None);
+        assert_eq!(
+            retry_after_secs(started, 31, 30, started + Duration::seconds(15)),
+            Some(45)
+        );
+        assert_eq!(
+            retry_after_secs(started, 31, 30, started + Duration::seconds(75)),
+            Some(1)
+        );
+    }
+}
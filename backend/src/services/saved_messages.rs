@@ -574,6 +574,8 @@ mod tests {
             sticker_id: None,
             is_published: true,
             transcode_status: TranscodeStatus::None,
+            topic_id: None,
+            is_important: false,
         }
     }
 
@@ -1,4 +1,13 @@
-use chrono::{Duration, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use axum::{
+    extract::{MatchedPath, State},
+    http::Request,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Duration, Utc};
 use constant_time_eq::constant_time_eq;
 use diesel::prelude::*;
 use diesel::PgConnection;
@@ -7,16 +16,20 @@ use rand::{rngs::OsRng, RngCore};
 use sha2::Sha256;
 
 use crate::errors::AppError;
-use crate::models::ServiceToken;
-use crate::schema::service_tokens;
+use crate::models::{NewServiceTokenUsage, ServiceToken, ServiceTokenUsage};
+use crate::schema::{service_token_usage, service_tokens};
+use crate::services::rate_windows::{self, RateWindow};
+use crate::utils::auth::bearer_token;
 
 type HmacSha256 = Hmac<Sha256>;
 
 pub const TOKEN_PREFIX: &str = "svc_";
 const TOKEN_BYTES: usize = 16;
 const SECRET_BYTES: usize = 32;
-#[allow(dead_code)]
 const LAST_USED_WRITE_INTERVAL_MINUTES: i64 = 5;
+const USAGE_RETENTION_DAYS: i64 = 14;
+const MAX_USAGE_ENDPOINT_CHARS: usize = 512;
+const RATE_LIMITED: &str = "Service token rate limit exceeded";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParsedCredential<'a> {
@@ -31,10 +44,24 @@ pub struct GeneratedCredential {
     pub secret_hash: String,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct AuthenticatedServiceToken {
     pub id: i64,
+    /// `None` when the token is not restricted to particular chats.
+    pub allowed_chat_ids: Option<Vec<i64>>,
+    pub rate_limit_per_minute: Option<i32>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Request extension the `Principal` extractor fills with the authenticated token id, so
+/// `track_service_token_usage` can log the request once its status is known.
+#[derive(Debug, Clone, Default)]
+pub struct UsageSlot(Arc<OnceLock<i64>>);
+
+impl UsageSlot {
+    pub fn record(&self, service_token_id: i64) {
+        let _ = self.0.set(service_token_id);
+    }
 }
 
 pub fn generate_credential(hash_key: &[u8]) -> Result<GeneratedCredential, AppError> {
@@ -87,6 +114,12 @@ pub fn authenticate(
     if row.revoked_at.is_some() {
         return Err(AppError::Unauthorized("Service token revoked"));
     }
+    if row
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(AppError::Unauthorized("Service token expired"));
+    }
 
     if !verify_secret_hash(hash_key, parsed.token, parsed.secret, &row.secret_hash)? {
         return Err(AppError::Unauthorized("Invalid service token"));
@@ -94,7 +127,40 @@ pub fn authenticate(
 
     maybe_touch_last_used_at(conn, &row)?;
 
-    Ok(AuthenticatedServiceToken { id: row.id })
+    Ok(AuthenticatedServiceToken {
+        id: row.id,
+        allowed_chat_ids: row.allowed_chat_ids,
+        rate_limit_per_minute: row.rate_limit_per_minute,
+        expires_at: row.expires_at,
+    })
+}
+
+/// Count one request against the token's one-minute window, if it has a limit.
+pub fn consume_rate_limit(
+    conn: &mut PgConnection,
+    token: &AuthenticatedServiceToken,
+) -> Result<(), AppError> {
+    let Some(limit) = token.rate_limit_per_minute else {
+        return Ok(());
+    };
+    let window: RateWindow = diesel::sql_query(
+        "UPDATE service_tokens
+         SET window_started_at = CASE
+               WHEN window_started_at <= NOW() - make_interval(secs => $2) THEN NOW()
+               ELSE window_started_at
+             END,
+             window_count = CASE
+               WHEN window_started_at <= NOW() - make_interval(secs => $2) THEN 1
+               ELSE window_count + 1
+             END
+         WHERE id = $1
+         RETURNING window_started_at, window_count",
+    )
+    .bind::<diesel::sql_types::BigInt, _>(token.id)
+    .bind::<diesel::sql_types::Double, _>(rate_windows::WINDOW_SECS as f64)
+    .get_result(conn)?;
+
+    window.check(limit, RATE_LIMITED)
 }
 
 pub fn hash_secret(hash_key: &[u8], token: &str, secret: &str) -> Result<String, AppError> {
@@ -120,7 +186,7 @@ fn verify_secret_hash(
     ))
 }
 
-#[allow(dead_code)]
+/// Write `last_used_at` at most every few minutes, pruning old usage rows at the same time.
 fn maybe_touch_last_used_at(conn: &mut PgConnection, row: &ServiceToken) -> Result<(), AppError> {
     let now = Utc::now();
     let should_touch = row
@@ -134,11 +200,107 @@ fn maybe_touch_last_used_at(conn: &mut PgConnection, row: &ServiceToken) -> Resu
         diesel::update(service_tokens::table.filter(service_tokens::id.eq(row.id)))
             .set(service_tokens::last_used_at.eq(now))
             .execute(conn)?;
+        diesel::delete(
+            service_token_usage::table
+                .filter(service_token_usage::service_token_id.eq(row.id))
+                .filter(
+                    service_token_usage::created_at.lt(now - Duration::days(USAGE_RETENTION_DAYS)),
+                ),
+        )
+        .execute(conn)?;
     }
 
     Ok(())
 }
 
+/// Middleware logging method, route and status of every request a service token
+/// authenticated. Requests that fail authentication are not logged.
+pub async fn track_service_token_usage(
+    State(state): State<crate::AppState>,
+    mut request: Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    let is_service_token = bearer_token(request.headers())
+        .ok()
+        .flatten()
+        .is_some_and(|token| token.starts_with(TOKEN_PREFIX));
+    if !is_service_token {
+        return next.run(request).await;
+    }
+
+    let slot = UsageSlot::default();
+    request.extensions_mut().insert(slot.clone());
+    let method = request.method().as_str().to_string();
+    let endpoint: String = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str)
+        .unwrap_or_else(|| request.uri().path())
+        .chars()
+        .take(MAX_USAGE_ENDPOINT_CHARS)
+        .collect();
+
+    let response = next.run(request).await;
+
+    if let Some(&service_token_id) = slot.0.get() {
+        let usage = NewServiceTokenUsage {
+            service_token_id,
+            method,
+            endpoint,
+            status: i32::from(response.status().as_u16()),
+        };
+        let db = state.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = db.get().map_err(AppError::from).and_then(|mut conn| {
+                diesel::insert_into(service_token_usage::table)
+                    .values(&usage)
+                    .execute(&mut conn)
+                    .map_err(AppError::from)
+            });
+            if let Err(err) = result {
+                tracing::warn!(
+                    service_token_id,
+                    ?err,
+                    "failed to record service token usage"
+                );
+            }
+        });
+    }
+
+    response
+}
+
+/// The latest `per_token` usage rows of each token, newest first.
+pub fn load_recent_usage(
+    conn: &mut PgConnection,
+    token_ids: &[i64],
+    per_token: i64,
+) -> QueryResult<HashMap<i64, Vec<ServiceTokenUsage>>> {
+    if token_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows: Vec<ServiceTokenUsage> = diesel::sql_query(
+        "SELECT service_token_id, method, endpoint, status, created_at
+         FROM (
+           SELECT u.*, ROW_NUMBER() OVER (PARTITION BY service_token_id ORDER BY id DESC) AS rn
+           FROM service_token_usage u
+           WHERE service_token_id = ANY($1)
+         ) ranked
+         WHERE rn <= $2
+         ORDER BY service_token_id, id DESC",
+    )
+    .bind::<diesel::sql_types::Array<diesel::sql_types::BigInt>, _>(token_ids)
+    .bind::<diesel::sql_types::BigInt, _>(per_token)
+    .load(conn)?;
+
+    let mut usage: HashMap<i64, Vec<ServiceTokenUsage>> = HashMap::new();
+    for row in rows {
+        usage.entry(row.service_token_id).or_default().push(row);
+    }
+    Ok(usage)
+}
+
 fn random_hex(byte_len: usize) -> String {
     let mut bytes = vec![0_u8; byte_len];
     OsRng.fill_bytes(&mut bytes);
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::PgConnection;
+
+use crate::constants::MAX_UNREAD_COUNT;
+use crate::dto::topics::TopicResponse;
+use crate::errors::AppError;
+use crate::models::{ChatTopic, TopicUserState};
+use crate::schema::{chat_topics, topic_user_states};
+
+pub const MAX_TOPICS_PER_CHAT: i64 = 100;
+pub const MAX_TOPIC_TITLE_LEN: usize = 128;
+pub const MAX_TOPIC_ICON_LEN: usize = 16;
+
+#[derive(QueryableByName)]
+struct TopicUnreadRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    topic_id: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    unread_count: i64,
+}
+
+pub fn normalize_topic_title(raw: &str) -> Result<String, AppError> {
+    let title = raw.trim();
+    if title.is_empty() {
+        return Err(AppError::BadRequest("Topic title cannot be empty"));
+    }
+    if title.chars().count() > MAX_TOPIC_TITLE_LEN {
+        return Err(AppError::BadRequest("Topic title is too long"));
+    }
+    Ok(title.to_string())
+}
+
+/// Blank icons mean "no icon".
+pub fn normalize_topic_icon(raw: Option<&str>) -> Result<Option<String>, AppError> {
+    let Some(icon) = raw.map(str::trim).filter(|icon| !icon.is_empty()) else {
+        return Ok(None);
+    };
+    if icon.chars().count() > MAX_TOPIC_ICON_LEN {
+        return Err(AppError::BadRequest("Topic icon is too long"));
+    }
+    Ok(Some(icon.to_string()))
+}
+
+pub fn load_chat_topic(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    topic_id: i64,
+) -> Result<ChatTopic, AppError> {
+    chat_topics::table
+        .filter(chat_topics::id.eq(topic_id))
+        .filter(chat_topics::chat_id.eq(chat_id))
+        .select(ChatTopic::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Topic not found"))
+}
+
+pub fn load_chat_topics(
+    conn: &mut PgConnection,
+    chat_id: i64,
+) -> Result<Vec<ChatTopic>, diesel::result::Error> {
+    chat_topics::table
+        .filter(chat_topics::chat_id.eq(chat_id))
+        .order((chat_topics::sort_order.asc(), chat_topics::id.asc()))
+        .select(ChatTopic::as_select())
+        .load(conn)
+}
+
+fn load_topic_user_states(
+    conn: &mut PgConnection,
+    uid: i32,
+    topic_ids: &[i64],
+) -> Result<HashMap<i64, TopicUserState>, diesel::result::Error> {
+    let rows = topic_user_states::table
+        .filter(topic_user_states::uid.eq(uid))
+        .filter(topic_user_states::topic_id.eq_any(topic_ids))
+        .select(TopicUserState::as_select())
+        .load::<TopicUserState>(conn)?;
+    Ok(rows.into_iter().map(|row| (row.topic_id, row)).collect())
+}
+
+/// Capped unread top-level message counts per topic. A topic's read pointer is
+/// the later of its own state and the chat-wide pointer, so reading the whole
+/// chat also clears every topic.
+pub fn count_topic_unreads(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    uid: i32,
+    topic_ids: &[i64],
+) -> Result<HashMap<i64, i64>, diesel::result::Error> {
+    if topic_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows: Vec<TopicUnreadRow> = sql_query(
+        "SELECT t.id AS topic_id,
+                COUNT(unread_messages.marker)::bigint AS unread_count
+         FROM chat_topics t
+         JOIN group_membership gm ON gm.chat_id = t.chat_id AND gm.uid = $2
+         LEFT JOIN topic_user_states ts ON ts.topic_id = t.id AND ts.uid = $2
+         LEFT JOIN LATERAL (
+             SELECT 1 AS marker
+             FROM messages m
+             WHERE m.topic_id = t.id
+               AND m.reply_root_id IS NULL
+               AND m.deleted_at IS NULL
+               AND m.is_published = TRUE
+               AND m.id > GREATEST(
+                   COALESCE(ts.last_read_message_id, 0),
+                   COALESCE(gm.last_read_message_id, 0)
+               )
+             LIMIT $4
+         ) AS unread_messages ON TRUE
+         WHERE t.chat_id = $1
+           AND t.id = ANY($3)
+         GROUP BY t.id",
+    )
+    .bind::<diesel::sql_types::BigInt, _>(chat_id)
+    .bind::<diesel::sql_types::Integer, _>(uid)
+    .bind::<diesel::sql_types::Array<diesel::sql_types::BigInt>, _>(topic_ids)
+    .bind::<diesel::sql_types::BigInt, _>(MAX_UNREAD_COUNT)
+    .load(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.topic_id, row.unread_count.min(MAX_UNREAD_COUNT)))
+        .collect())
+}
+
+/// Advance the caller's topic read pointer (never moves backwards).
+pub fn mark_topic_read(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    topic_id: i64,
+    uid: i32,
+    message_id: i64,
+) -> Result<Option<i64>, diesel::result::Error> {
+    #[derive(QueryableByName)]
+    struct LastReadRow {
+        #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+        last_read_message_id: Option<i64>,
+    }
+
+    let row: LastReadRow = sql_query(
+        "INSERT INTO topic_user_states (topic_id, uid, chat_id, last_read_message_id)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (topic_id, uid) DO UPDATE
+         SET last_read_message_id = GREATEST(
+             COALESCE(topic_user_states.last_read_message_id, 0),
+             EXCLUDED.last_read_message_id
+         )
+         RETURNING last_read_message_id",
+    )
+    .bind::<diesel::sql_types::BigInt, _>(topic_id)
+    .bind::<diesel::sql_types::Integer, _>(uid)
+    .bind::<diesel::sql_types::BigInt, _>(chat_id)
+    .bind::<diesel::sql_types::BigInt, _>(message_id)
+    .get_result(conn)?;
+    Ok(row.last_read_message_id)
+}
+
+/// Set or clear (`None`) the caller's push mute for one topic.
+pub fn set_topic_muted_until(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    topic_id: i64,
+    uid: i32,
+    muted_until: Option<DateTime<Utc>>,
+) -> Result<(), diesel::result::Error> {
+    diesel::insert_into(topic_user_states::table)
+        .values(TopicUserState {
+            topic_id,
+            uid,
+            chat_id,
+            last_read_message_id: None,
+            muted_until,
+        })
+        .on_conflict((topic_user_states::topic_id, topic_user_states::uid))
+        .do_update()
+        .set(topic_user_states::muted_until.eq(muted_until))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Topic mutes for the given recipients; only rows with a mute set are returned.
+pub fn load_topic_mutes(
+    conn: &mut PgConnection,
+    topic_id: i64,
+    uids: &[i32],
+) -> Result<HashMap<i32, DateTime<Utc>>, diesel::result::Error> {
+    let rows: Vec<(i32, Option<DateTime<Utc>>)> = topic_user_states::table
+        .filter(topic_user_states::topic_id.eq(topic_id))
+        .filter(topic_user_states::uid.eq_any(uids))
+        .filter(topic_user_states::muted_until.is_not_null())
+        .select((topic_user_states::uid, topic_user_states::muted_until))
+        .load(conn)?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(uid, muted_until)| muted_until.map(|until| (uid, until)))
+        .collect())
+}
+
+pub fn build_topic_response(
+    topic: ChatTopic,
+    state: Option<&TopicUserState>,
+    unread_count: i64,
+) -> TopicResponse {
+    TopicResponse {
+        id: topic.id,
+        chat_id: topic.chat_id,
+        title: topic.title,
+        icon_emoji: topic.icon_emoji,
+        closed: topic.closed,
+        sort_order: topic.sort_order,
+        unread_count,
+        last_read_message_id: state.and_then(|state| state.last_read_message_id),
+        muted_until: state.and_then(|state| state.muted_until),
+        created_at: topic.created_at,
+        updated_at: topic.updated_at,
+    }
+}
+
+pub fn load_topic_responses(
+    conn: &mut PgConnection,
+    chat_id: i64,
+    uid: i32,
+    topics: Vec<ChatTopic>,
+) -> Result<Vec<TopicResponse>, diesel::result::Error> {
+    let topic_ids: Vec<i64> = topics.iter().map(|topic| topic.id).collect();
+    let states = load_topic_user_states(conn, uid, &topic_ids)?;
+    let unread_counts = count_topic_unreads(conn, chat_id, uid, &topic_ids)?;
+
+    Ok(topics
+        .into_iter()
+        .map(|topic| {
+            let unread_count = unread_counts.get(&topic.id).copied().unwrap_or(0);
+            let state = states.get(&topic.id);
+            build_topic_response(topic, state, unread_count)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_title_is_trimmed_and_bounded() {
+        assert_eq!(normalize_topic_title("  Releases ").unwrap(), "Releases");
+        assert!(matches!(
+            normalize_topic_title("   "),
+            Err(AppError::BadRequest(_))
+        ));
+        let long = "a".repeat(MAX_TOPIC_TITLE_LEN + 1);
+        assert!(matches!(
+            normalize_topic_title(&long),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn blank_topic_icon_clears_it() {
+        assert_eq!(normalize_topic_icon(None).unwrap(), None);
+        assert_eq!(normalize_topic_icon(Some("  ")).unwrap(), None);
+        assert_eq!(
+            normalize_topic_icon(Some(" 🚀 ")).unwrap(),
+            Some("🚀".to_string())
+        );
+        assert!(normalize_topic_icon(Some(&"x".repeat(MAX_TOPIC_ICON_LEN + 1))).is_err());
+    }
+}
@@ -12,6 +12,9 @@ pub struct UserProfile {
     pub username: Option<String>,
     pub gender: i16,
     pub user_group: Option<UserGroupTagInfo>,
+    pub is_bot: bool,
+    /// Configured bot avatar; Discuz members resolve theirs via `lookup_user_avatars`.
+    pub avatar_url: Option<String>,
 }
 
 pub(crate) fn normalize_discuz_username(username: &str) -> String {
@@ -86,13 +89,18 @@ pub fn search_group_member_uids(
     let query = sql_query(
         "SELECT gm.uid
          FROM group_membership AS gm
-         JOIN discuz.common_member AS cm
+         LEFT JOIN discuz.common_member AS cm
            ON cm.uid = gm.uid
+         LEFT JOIN bots AS b
+           ON b.uid = gm.uid
+         LEFT JOIN incoming_webhooks AS iw
+           ON iw.uid = gm.uid
          WHERE gm.chat_id = $1
+           AND (cm.uid IS NOT NULL OR b.uid IS NOT NULL OR iw.uid IS NOT NULL)
            AND ($2::int4 IS NULL OR gm.uid > $2)
            AND (
              $3::text IS NULL
-             OR LOWER(BTRIM(cm.username::text)) LIKE LOWER($3) || '%'
+             OR LOWER(BTRIM(COALESCE(cm.username::text, b.name, iw.name))) LIKE LOWER($3) || '%'
              OR ($4::bool AND gm.uid = $5)
            )
          ORDER BY gm.uid ASC
@@ -177,6 +185,8 @@ pub fn lookup_user_profiles(
         return Ok(HashMap::new());
     }
 
+    let bots = crate::services::bots::lookup_bots(conn, uids)?;
+    let webhook_senders = crate::services::incoming_webhooks::lookup_webhook_senders(conn, uids)?;
     let rows = cm_dsl::common_member
         .left_join(cmp_dsl::common_member_profile.on(cm_dsl::uid.eq(cmp_dsl::uid)))
         .left_join(cug_dsl::common_usergroup.on(cm_dsl::groupid.eq(cug_dsl::groupid)))
@@ -193,24 +203,50 @@ pub fn lookup_user_profiles(
         ))
         .load::<DiscuzUserProfileRow>(conn)?;
 
-    Ok(rows
-        .into_iter()
-        .map(|row| {
-            (
-                row.uid,
-                UserProfile {
-                    username: Some(normalize_discuz_username(&row.username)),
-                    gender: row.gender.unwrap_or(0),
-                    user_group: Some(UserGroupTagInfo {
-                        group_id: row.group_id,
-                        name: row.group_name,
-                        chat_group_color: row.chat_group_color,
-                        chat_group_color_dark: row.chat_group_color_dark,
-                    }),
-                },
-            )
-        })
-        .collect())
+    let members = rows.into_iter().map(|row| {
+        (
+            row.uid,
+            UserProfile {
+                username: Some(normalize_discuz_username(&row.username)),
+                gender: row.gender.unwrap_or(0),
+                user_group: Some(UserGroupTagInfo {
+                    group_id: row.group_id,
+                    name: row.group_name,
+                    chat_group_color: row.chat_group_color,
+                    chat_group_color_dark: row.chat_group_color_dark,
+                }),
+                is_bot: false,
+                avatar_url: None,
+            },
+        )
+    });
+    let bots = bots.into_values().map(|bot| {
+        (
+            bot.uid,
+            UserProfile {
+                username: Some(bot.name),
+                gender: 0,
+                user_group: None,
+                is_bot: true,
+                avatar_url: bot.avatar_url,
+            },
+        )
+    });
+
+    let webhook_senders = webhook_senders.into_values().map(|webhook| {
+        (
+            webhook.uid,
+            UserProfile {
+                username: Some(webhook.name),
+                gender: 0,
+                user_group: None,
+                is_bot: true,
+                avatar_url: webhook.avatar_url,
+            },
+        )
+    });
+
+    Ok(members.chain(bots).chain(webhook_senders).collect())
 }
 
 #[cfg(test)]
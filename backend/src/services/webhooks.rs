@@ -0,0 +1,941 @@
+//! Outgoing webhooks: event fan-out into the durable `webhook_deliveries` queue and the
+//! worker that signs and delivers queued payloads with exponential backoff.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::PgConnection;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::sync::Notify;
+use tracing::warn;
+
+use crate::dto::webhooks::{WebhookDeliveryResponse, WebhookResponse};
+use crate::dto::ws::ServerWsMessage;
+use crate::errors::AppError;
+use crate::metrics::Metrics;
+use crate::models::{
+    GroupRole, NewWebhook, NewWebhookDelivery, Webhook, WebhookDelivery, WebhookDeliveryStatus,
+};
+use crate::schema::{service_tokens, webhook_deliveries, webhooks};
+use crate::services::outbound_http::{build_client, is_private_host};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "X-Wetty-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Wetty-Timestamp";
+const EVENT_HEADER: &str = "X-Wetty-Event";
+const DELIVERY_HEADER: &str = "X-Wetty-Delivery";
+const USER_AGENT: &str = "wetty-chat-webhooks/1";
+const SECRET_PREFIX: &str = "whsec_";
+const MAX_URL_LEN: usize = 2048;
+const MAX_WEBHOOKS_PER_OWNER: i64 = 10;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a claimed delivery stays hidden from other workers before it is retried.
+const CLAIM_LEASE_SECS: f64 = 60.0;
+const CLAIM_BATCH_SIZE: i64 = 20;
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_ATTEMPTS: i32 = 10;
+const INITIAL_RETRY_DELAY_SECS: i64 = 30;
+const MAX_RETRY_DELAY_SECS: i64 = 6 * 60 * 60;
+/// Failed attempts in a row, across all of a webhook's deliveries, before it is disabled.
+const AUTO_DISABLE_AFTER_FAILURES: i32 = 25;
+const MAX_ERROR_CHARS: usize = 500;
+const WORKER_RESTART_DELAY: Duration = Duration::from_secs(1);
+/// Succeeded and failed deliveries stay listed this long before the worker drops them.
+const DELIVERY_RETENTION_DAYS: i64 = 14;
+const RETENTION_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    MessageCreated,
+    MessageUpdated,
+    MessageDeleted,
+    ReactionUpdated,
+    MemberJoined,
+    MemberLeft,
+    PinAdded,
+    PinRemoved,
+    /// Only delivered to webhooks of the service token whose bot owns the command.
+    CommandInvoked,
+    /// Test delivery requested by the owner; always delivered, never subscribable.
+    Ping,
+}
+
+/// Event types a webhook may subscribe to.
+pub const SUBSCRIBABLE_EVENTS: [WebhookEvent; 9] = [
+    WebhookEvent::MessageCreated,
+    WebhookEvent::MessageUpdated,
+    WebhookEvent::MessageDeleted,
+    WebhookEvent::ReactionUpdated,
+    WebhookEvent::MemberJoined,
+    WebhookEvent::MemberLeft,
+    WebhookEvent::PinAdded,
+    WebhookEvent::PinRemoved,
+    WebhookEvent::CommandInvoked,
+];
+
+impl WebhookEvent {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::MessageCreated => "message.created",
+            Self::MessageUpdated => "message.updated",
+            Self::MessageDeleted => "message.deleted",
+            Self::ReactionUpdated => "reaction.updated",
+            Self::MemberJoined => "member.joined",
+            Self::MemberLeft => "member.left",
+            Self::PinAdded => "pin.added",
+            Self::PinRemoved => "pin.removed",
+            Self::CommandInvoked => "command.invoked",
+            Self::Ping => "ping",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        SUBSCRIBABLE_EVENTS
+            .into_iter()
+            .find(|event| event.as_str() == value)
+    }
+}
+
+/// Payload of `member.joined` and `member.left`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberEventData {
+    pub uid: i32,
+    pub role: GroupRole,
+    /// Admin who added or removed the member; `None` when they joined or left themselves.
+    pub actor_uid: Option<i32>,
+}
+
+/// Who a webhook belongs to: a chat (managed by its admins) or a service token, whose
+/// webhooks receive events from every chat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookOwner {
+    Chat(i64),
+    ServiceToken(i64),
+}
+
+/// Webhooks an event is queued for.
+#[derive(Debug, Clone, Copy)]
+enum Audience {
+    /// The chat's own webhooks plus every global webhook.
+    Chat(i64),
+    /// Only the global webhooks of one service token.
+    ServiceToken(i64),
+}
+
+#[derive(QueryableByName)]
+struct ClaimedDelivery {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    id: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    webhook_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    event_type: String,
+    #[diesel(sql_type = diesel::sql_types::Jsonb)]
+    payload: Value,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    attempts: i32,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    url: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    secret: String,
+}
+
+enum AttemptOutcome {
+    Succeeded { status: i32 },
+    Failed { status: Option<i32>, error: String },
+}
+
+pub struct WebhookService {
+    db: Pool<ConnectionManager<PgConnection>>,
+    client: reqwest::Client,
+    metrics: Arc<Metrics>,
+    wake: Notify,
+    allow_private_targets: bool,
+}
+
+impl WebhookService {
+    /// Create the service and spawn the delivery worker.
+    ///
+    /// `WEBHOOK_ALLOW_PRIVATE_TARGETS=true` permits loopback and private-network URLs,
+    /// e.g. for a local test receiver.
+    pub fn start(db: Pool<ConnectionManager<PgConnection>>, metrics: Arc<Metrics>) -> Arc<Self> {
+        let allow_private_targets = std::env::var("WEBHOOK_ALLOW_PRIVATE_TARGETS")
+            .map(|value| matches!(value.trim(), "1" | "true"))
+            .unwrap_or(false);
+        let client = build_client(REQUEST_TIMEOUT, USER_AGENT, allow_private_targets);
+
+        let service = Arc::new(Self {
+            db,
+            client,
+            metrics,
+            wake: Notify::new(),
+            allow_private_targets,
+        });
+
+        let worker_service = service.clone();
+        tokio::spawn(async move {
+            super::push::supervise_worker(
+                "webhook delivery worker",
+                WORKER_RESTART_DELAY,
+                move || {
+                    let worker_service = worker_service.clone();
+                    async move {
+                        worker_service.run_delivery_worker().await;
+                    }
+                },
+            )
+            .await;
+        });
+
+        service
+    }
+
+    pub fn validate_url(&self, raw: &str) -> Result<String, AppError> {
+        validate_webhook_url(raw, self.allow_private_targets)
+    }
+
+    /// Client that only connects to public addresses (unless private targets are
+    /// allowed) and re-checks redirects; pair it with `validate_url` for any
+    /// user-supplied target.
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Queue `event` for every enabled webhook of the chat and every global webhook
+    /// subscribed to it. Fire-and-forget; call after the change has been committed.
+    pub fn publish<T: Serialize>(self: &Arc<Self>, chat_id: i64, event: WebhookEvent, data: &T) {
+        self.publish_to(Audience::Chat(chat_id), chat_id, event, data);
+    }
+
+    /// Queue `event` for the webhooks of one service token only, e.g. a command invocation
+    /// meant for that token's bot.
+    pub fn publish_to_service_token<T: Serialize>(
+        self: &Arc<Self>,
+        service_token_id: i64,
+        chat_id: i64,
+        event: WebhookEvent,
+        data: &T,
+    ) {
+        self.publish_to(
+            Audience::ServiceToken(service_token_id),
+            chat_id,
+            event,
+            data,
+        );
+    }
+
+    fn publish_to<T: Serialize>(
+        self: &Arc<Self>,
+        audience: Audience,
+        chat_id: i64,
+        event: WebhookEvent,
+        data: &T,
+    ) {
+        let data = match serde_json::to_value(data) {
+            Ok(data) => data,
+            Err(err) => {
+                warn!(chat_id, event = event.as_str(), %err, "failed to serialize webhook event");
+                return;
+            }
+        };
+        let occurred_at = Utc::now();
+        let service = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = service.enqueue_event(audience, chat_id, event, data, occurred_at) {
+                warn!(
+                    chat_id,
+                    event = event.as_str(),
+                    ?err,
+                    "failed to enqueue webhook deliveries"
+                );
+            }
+        });
+    }
+
+    /// Publish the webhook event matching a WebSocket broadcast, if there is one.
+    pub fn publish_ws_message(self: &Arc<Self>, msg: &ServerWsMessage) {
+        match msg {
+            ServerWsMessage::Message(message) => {
+                self.publish(message.chat_id, WebhookEvent::MessageCreated, message)
+            }
+            ServerWsMessage::MessageUpdated(message) => {
+                self.publish(message.chat_id, WebhookEvent::MessageUpdated, message)
+            }
+            ServerWsMessage::MessageDeleted(message) => {
+                self.publish(message.chat_id, WebhookEvent::MessageDeleted, message)
+            }
+            ServerWsMessage::ReactionUpdated(payload) => {
+                self.publish(payload.chat_id, WebhookEvent::ReactionUpdated, payload)
+            }
+            ServerWsMessage::PinAdded(payload) => {
+                self.publish(payload.chat_id, WebhookEvent::PinAdded, payload)
+            }
+            ServerWsMessage::PinRemoved(payload) => {
+                self.publish(payload.chat_id, WebhookEvent::PinRemoved, payload)
+            }
+            _ => {}
+        }
+    }
+
+    fn enqueue_event(
+        &self,
+        audience: Audience,
+        chat_id: i64,
+        event: WebhookEvent,
+        data: Value,
+        occurred_at: DateTime<Utc>,
+    ) -> Result<usize, AppError> {
+        let conn = &mut self.db.get()?;
+
+        let live_tokens = service_tokens::table
+            .filter(service_tokens::revoked_at.is_null())
+            .filter(
+                service_tokens::expires_at
+                    .is_null()
+                    .or(service_tokens::expires_at.gt(diesel::dsl::now)),
+            );
+        let targets: Vec<(i64, Vec<String>)> =
+            match audience {
+                Audience::Chat(chat_id) => webhooks::table
+                    .filter(webhooks::enabled.eq(true))
+                    .filter(
+                        webhooks::chat_id
+                            .eq(chat_id)
+                            .or(webhooks::service_token_id.eq_any(
+                                live_tokens
+                                    .filter(service_tokens::allowed_chat_ids.is_null().or(
+                                        service_tokens::allowed_chat_ids.contains(vec![chat_id]),
+                                    ))
+                                    .select(service_tokens::id.nullable()),
+                            )),
+                    )
+                    .select((webhooks::id, webhooks::events))
+                    .load(conn)?,
+                Audience::ServiceToken(service_token_id) => webhooks::table
+                    .filter(webhooks::enabled.eq(true))
+                    .filter(webhooks::service_token_id.eq(service_token_id))
+                    .filter(
+                        webhooks::service_token_id
+                            .eq_any(live_tokens.select(service_tokens::id.nullable())),
+                    )
+                    .select((webhooks::id, webhooks::events))
+                    .load(conn)?,
+            };
+
+        let payload = envelope(event, Some(chat_id), occurred_at, data);
+        let rows: Vec<NewWebhookDelivery> = targets
+            .into_iter()
+            .filter(|(_, events)| subscribes_to(events, event))
+            .map(|(webhook_id, _)| NewWebhookDelivery {
+                webhook_id,
+                event_type: event.as_str().to_string(),
+                payload: payload.clone(),
+            })
+            .collect();
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        let inserted = diesel::insert_into(webhook_deliveries::table)
+            .values(&rows)
+            .execute(conn)?;
+        self.wake.notify_one();
+        Ok(inserted)
+    }
+
+    /// Queue a `ping` delivery for one webhook, regardless of its subscriptions.
+    pub fn enqueue_ping(
+        &self,
+        conn: &mut PgConnection,
+        webhook: &Webhook,
+    ) -> Result<WebhookDelivery, AppError> {
+        let payload = envelope(
+            WebhookEvent::Ping,
+            webhook.chat_id,
+            Utc::now(),
+            json!({ "webhookId": webhook.id.to_string() }),
+        );
+        let delivery = diesel::insert_into(webhook_deliveries::table)
+            .values(&NewWebhookDelivery {
+                webhook_id: webhook.id,
+                event_type: WebhookEvent::Ping.as_str().to_string(),
+                payload,
+            })
+            .returning(WebhookDelivery::as_returning())
+            .get_result(conn)?;
+        self.wake.notify_one();
+        Ok(delivery)
+    }
+
+    async fn run_delivery_worker(&self) {
+        let mut swept_at: Option<Instant> = None;
+        loop {
+            if swept_at.is_none_or(|at| at.elapsed() >= RETENTION_SWEEP_INTERVAL) {
+                let pruned = self.db.get().map_err(AppError::from).and_then(|mut conn| {
+                    prune_completed_deliveries(&mut conn, Utc::now()).map_err(AppError::from)
+                });
+                if let Err(err) = pruned {
+                    warn!(?err, "failed to prune webhook deliveries");
+                }
+                swept_at = Some(Instant::now());
+            }
+
+            let claimed = match self.deliver_due_batch().await {
+                Ok(claimed) => claimed,
+                Err(err) => {
+                    warn!(?err, "webhook delivery batch failed");
+                    0
+                }
+            };
+            if claimed < CLAIM_BATCH_SIZE as usize {
+                tokio::select! {
+                    _ = self.wake.notified() => {}
+                    _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                }
+            }
+        }
+    }
+
+    async fn deliver_due_batch(&self) -> Result<usize, AppError> {
+        let claimed = {
+            let conn = &mut self.db.get()?;
+            claim_due_deliveries(conn)?
+        };
+        let count = claimed.len();
+        futures::future::join_all(claimed.into_iter().map(|delivery| self.attempt(delivery))).await;
+        Ok(count)
+    }
+
+    async fn attempt(&self, delivery: ClaimedDelivery) {
+        let body = serde_json::to_vec(&delivery.payload).unwrap_or_default();
+        let timestamp = Utc::now().timestamp();
+        let signature = sign_payload(&delivery.secret, timestamp, &body);
+
+        let result = self
+            .client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .body(body)
+            .send()
+            .await;
+        let outcome = match result {
+            Ok(response) if response.status().is_success() => AttemptOutcome::Succeeded {
+                status: i32::from(response.status().as_u16()),
+            },
+            Ok(response) => AttemptOutcome::Failed {
+                status: Some(i32::from(response.status().as_u16())),
+                error: format!("Endpoint responded with {}", response.status()),
+            },
+            Err(err) => AttemptOutcome::Failed {
+                status: None,
+                error: truncate_error(&err.to_string()),
+            },
+        };
+        self.metrics.record_webhook_delivery(match outcome {
+            AttemptOutcome::Succeeded { .. } => "success",
+            AttemptOutcome::Failed { .. } => "failure",
+        });
+
+        let recorded = self
+            .db
+            .get()
+            .map_err(AppError::from)
+            .and_then(|mut conn| record_attempt(&mut conn, &delivery, outcome));
+        if let Err(err) = recorded {
+            warn!(
+                delivery_id = delivery.id,
+                webhook_id = delivery.webhook_id,
+                ?err,
+                "failed to record webhook delivery attempt"
+            );
+        }
+    }
+}
+
+fn claim_due_deliveries(conn: &mut PgConnection) -> QueryResult<Vec<ClaimedDelivery>> {
+    diesel::sql_query(
+        "UPDATE webhook_deliveries AS d
+         SET next_attempt_at = NOW() + make_interval(secs => $1)
+         FROM webhooks AS w
+         WHERE w.id = d.webhook_id
+           AND d.id IN (
+             SELECT due.id
+             FROM webhook_deliveries AS due
+             JOIN webhooks AS hook ON hook.id = due.webhook_id
+             WHERE due.status = 'pending'
+               AND due.next_attempt_at <= NOW()
+               AND hook.enabled
+             ORDER BY due.next_attempt_at
+             LIMIT $2
+             FOR UPDATE OF due SKIP LOCKED
+           )
+         RETURNING d.id, d.webhook_id, d.event_type, d.payload, d.attempts, w.url, w.secret",
+    )
+    .bind::<diesel::sql_types::Double, _>(CLAIM_LEASE_SECS)
+    .bind::<diesel::sql_types::BigInt, _>(CLAIM_BATCH_SIZE)
+    .load(conn)
+}
+
+/// Drop succeeded and failed deliveries that completed before the retention window.
+fn prune_completed_deliveries(conn: &mut PgConnection, now: DateTime<Utc>) -> QueryResult<usize> {
+    diesel::delete(
+        webhook_deliveries::table
+            .filter(webhook_deliveries::status.ne(WebhookDeliveryStatus::Pending))
+            .filter(
+                webhook_deliveries::completed_at
+                    .lt(now - chrono::Duration::days(DELIVERY_RETENTION_DAYS)),
+            ),
+    )
+    .execute(conn)
+}
+
+fn record_attempt(
+    conn: &mut PgConnection,
+    delivery: &ClaimedDelivery,
+    outcome: AttemptOutcome,
+) -> Result<(), AppError> {
+    let now = Utc::now();
+    let attempts = delivery.attempts + 1;
+    let delivery_row = webhook_deliveries::table.filter(webhook_deliveries::id.eq(delivery.id));
+    let webhook_row = webhooks::table.filter(webhooks::id.eq(delivery.webhook_id));
+
+    match outcome {
+        AttemptOutcome::Succeeded { status } => {
+            diesel::update(delivery_row)
+                .set((
+                    webhook_deliveries::status.eq(WebhookDeliveryStatus::Succeeded),
+                    webhook_deliveries::attempts.eq(attempts),
+                    webhook_deliveries::last_attempt_at.eq(Some(now)),
+                    webhook_deliveries::response_status.eq(Some(status)),
+                    webhook_deliveries::last_error.eq(None::<String>),
+                    webhook_deliveries::completed_at.eq(Some(now)),
+                ))
+                .execute(conn)?;
+            diesel::update(webhook_row.filter(webhooks::consecutive_failures.ne(0)))
+                .set(webhooks::consecutive_failures.eq(0))
+                .execute(conn)?;
+        }
+        AttemptOutcome::Failed { status, error } => {
+            let exhausted = attempts >= MAX_ATTEMPTS;
+            diesel::update(delivery_row)
+                .set((
+                    webhook_deliveries::status.eq(if exhausted {
+                        WebhookDeliveryStatus::Failed
+                    } else {
+                        WebhookDeliveryStatus::Pending
+                    }),
+                    webhook_deliveries::attempts.eq(attempts),
+                    webhook_deliveries::next_attempt_at.eq(now + retry_delay(attempts)),
+                    webhook_deliveries::last_attempt_at.eq(Some(now)),
+                    webhook_deliveries::response_status.eq(status),
+                    webhook_deliveries::last_error.eq(Some(error)),
+                    webhook_deliveries::completed_at.eq(exhausted.then_some(now)),
+                ))
+                .execute(conn)?;
+            let failures: i32 = diesel::update(webhook_row)
+                .set(webhooks::consecutive_failures.eq(webhooks::consecutive_failures + 1))
+                .returning(webhooks::consecutive_failures)
+                .get_result(conn)?;
+            if failures >= AUTO_DISABLE_AFTER_FAILURES {
+                let disabled = diesel::update(webhook_row.filter(webhooks::enabled.eq(true)))
+                    .set((
+                        webhooks::enabled.eq(false),
+                        webhooks::disabled_at.eq(Some(now)),
+                        webhooks::updated_at.eq(now),
+                    ))
+                    .execute(conn)?;
+                if disabled > 0 {
+                    warn!(
+                        webhook_id = delivery.webhook_id,
+                        failures, "webhook disabled after repeated delivery failures"
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn envelope(
+    event: WebhookEvent,
+    chat_id: Option<i64>,
+    occurred_at: DateTime<Utc>,
+    data: Value,
+) -> Value {
+    json!({
+        "type": event.as_str(),
+        "chatId": chat_id.map(|chat_id| chat_id.to_string()),
+        "occurredAt": occurred_at,
+        "data": data,
+    })
+}
+
+/// Whether the service token has an enabled webhook that would receive `event`.
+pub fn service_token_subscribes_to(
+    conn: &mut PgConnection,
+    service_token_id: i64,
+    event: WebhookEvent,
+) -> QueryResult<bool> {
+    let subscriptions: Vec<Vec<String>> = webhooks::table
+        .filter(webhooks::enabled.eq(true))
+        .filter(webhooks::service_token_id.eq(service_token_id))
+        .filter(
+            webhooks::service_token_id.eq_any(
+                service_tokens::table
+                    .filter(service_tokens::revoked_at.is_null())
+                    .filter(
+                        service_tokens::expires_at
+                            .is_null()
+                            .or(service_tokens::expires_at.gt(diesel::dsl::now)),
+                    )
+                    .select(service_tokens::id.nullable()),
+            ),
+        )
+        .select(webhooks::events)
+        .load(conn)?;
+    Ok(subscriptions
+        .iter()
+        .any(|events| subscribes_to(events, event)))
+}
+
+fn subscribes_to(events: &[String], event: WebhookEvent) -> bool {
+    events.is_empty() || events.iter().any(|subscribed| subscribed == event.as_str())
+}
+
+/// `sha256=<hex>` HMAC of `"<timestamp>.<body>"`, keyed with the webhook secret.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    let secs = INITIAL_RETRY_DELAY_SECS
+        .saturating_mul(1_i64 << exponent)
+        .min(MAX_RETRY_DELAY_SECS);
+    chrono::Duration::seconds(secs)
+}
+
+fn truncate_error(error: &str) -> String {
+    error.chars().take(MAX_ERROR_CHARS).collect()
+}
+
+fn validate_webhook_url(raw: &str, allow_private_targets: bool) -> Result<String, AppError> {
+    let raw = raw.trim();
+    if raw.len() > MAX_URL_LEN {
+        return Err(AppError::BadRequest("Webhook URL is too long"));
+    }
+    let url = reqwest::Url::parse(raw).map_err(|_| AppError::BadRequest("Invalid webhook URL"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AppError::BadRequest("Webhook URL must be http(s)"));
+    }
+    let host = url
+        .host_str()
+        .ok_or(AppError::BadRequest("Invalid webhook URL"))?;
+    if !allow_private_targets && is_private_host(host) {
+        return Err(AppError::BadRequest(
+            "Webhook URL must not target a private address",
+        ));
+    }
+    Ok(url.to_string())
+}
+
+/// Validate subscribed event types, returning them sorted and deduplicated.
+pub fn parse_event_types(events: &[String]) -> Result<Vec<String>, AppError> {
+    let mut parsed = events
+        .iter()
+        .map(|event| {
+            WebhookEvent::parse(event.trim())
+                .map(|event| event.as_str().to_string())
+                .ok_or(AppError::BadRequest("Unknown webhook event type"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    parsed.sort();
+    parsed.dedup();
+    Ok(parsed)
+}
+
+fn owned_by(owner: WebhookOwner) -> webhooks::BoxedQuery<'static, Pg> {
+    match owner {
+        WebhookOwner::Chat(chat_id) => webhooks::table
+            .filter(webhooks::chat_id.eq(chat_id))
+            .into_boxed(),
+        WebhookOwner::ServiceToken(token_id) => webhooks::table
+            .filter(webhooks::service_token_id.eq(token_id))
+            .into_boxed(),
+    }
+}
+
+pub fn list_webhooks(
+    conn: &mut PgConnection,
+    owner: WebhookOwner,
+) -> Result<Vec<Webhook>, AppError> {
+    Ok(owned_by(owner)
+        .order(webhooks::id.asc())
+        .select(Webhook::as_select())
+        .load(conn)?)
+}
+
+pub fn load_webhook(
+    conn: &mut PgConnection,
+    owner: WebhookOwner,
+    webhook_id: i64,
+) -> Result<Webhook, AppError> {
+    owned_by(owner)
+        .filter(webhooks::id.eq(webhook_id))
+        .select(Webhook::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("Webhook not found"))
+}
+
+/// Insert a webhook with a fresh signing secret.
+pub fn create_webhook(
+    conn: &mut PgConnection,
+    owner: WebhookOwner,
+    id: i64,
+    url: String,
+    events: Vec<String>,
+    created_by_uid: Option<i32>,
+) -> Result<Webhook, AppError> {
+    let existing: i64 = owned_by(owner).count().get_result(conn)?;
+    if existing >= MAX_WEBHOOKS_PER_OWNER {
+        return Err(AppError::BadRequest("Too many webhooks"));
+    }
+
+    let (chat_id, service_token_id) = match owner {
+        WebhookOwner::Chat(chat_id) => (Some(chat_id), None),
+        WebhookOwner::ServiceToken(token_id) => (None, Some(token_id)),
+    };
+    Ok(diesel::insert_into(webhooks::table)
+        .values(&NewWebhook {
+            id,
+            chat_id,
+            service_token_id,
+            url,
+            secret: format!(
+                "{SECRET_PREFIX}{}",
+                crate::services::service_tokens::generate_secret()
+            ),
+            events,
+            created_by_uid,
+        })
+        .returning(Webhook::as_returning())
+        .get_result(conn)?)
+}
+
+/// Apply changes to a webhook. Re-enabling clears its failure count.
+pub fn update_webhook(
+    conn: &mut PgConnection,
+    webhook: Webhook,
+    url: Option<String>,
+    events: Option<Vec<String>>,
+    enabled: Option<bool>,
+) -> Result<Webhook, AppError> {
+    let now = Utc::now();
+    let mut consecutive_failures = webhook.consecutive_failures;
+    let mut disabled_at = webhook.disabled_at;
+    match enabled {
+        Some(true) if !webhook.enabled => {
+            consecutive_failures = 0;
+            disabled_at = None;
+        }
+        Some(false) if webhook.enabled => disabled_at = Some(now),
+        _ => {}
+    }
+
+    Ok(
+        diesel::update(webhooks::table.filter(webhooks::id.eq(webhook.id)))
+            .set((
+                webhooks::url.eq(url.unwrap_or(webhook.url)),
+                webhooks::events.eq(events.unwrap_or(webhook.events)),
+                webhooks::enabled.eq(enabled.unwrap_or(webhook.enabled)),
+                webhooks::consecutive_failures.eq(consecutive_failures),
+                webhooks::disabled_at.eq(disabled_at),
+                webhooks::updated_at.eq(now),
+            ))
+            .returning(Webhook::as_returning())
+            .get_result(conn)?,
+    )
+}
+
+pub fn delete_webhook(
+    conn: &mut PgConnection,
+    owner: WebhookOwner,
+    webhook_id: i64,
+) -> Result<(), AppError> {
+    let webhook = load_webhook(conn, owner, webhook_id)?;
+    diesel::delete(webhooks::table.filter(webhooks::id.eq(webhook.id))).execute(conn)?;
+    Ok(())
+}
+
+/// Newest-first page of a webhook's deliveries and the cursor for the next page.
+pub fn list_deliveries(
+    conn: &mut PgConnection,
+    webhook_id: i64,
+    before: Option<i64>,
+    limit: i64,
+) -> Result<(Vec<WebhookDelivery>, Option<i64>), AppError> {
+    let mut query = webhook_deliveries::table
+        .filter(webhook_deliveries::webhook_id.eq(webhook_id))
+        .into_boxed();
+    if let Some(before) = before {
+        query = query.filter(webhook_deliveries::id.lt(before));
+    }
+    let mut rows: Vec<WebhookDelivery> = query
+        .order(webhook_deliveries::id.desc())
+        .limit(limit + 1)
+        .select(WebhookDelivery::as_select())
+        .load(conn)?;
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|row| row.id)
+    } else {
+        None
+    };
+    Ok((rows, next_cursor))
+}
+
+pub fn webhook_to_response(webhook: Webhook) -> WebhookResponse {
+    WebhookResponse {
+        id: webhook.id,
+        chat_id: webhook.chat_id,
+        service_token_id: webhook.service_token_id,
+        url: webhook.url,
+        events: webhook.events,
+        enabled: webhook.enabled,
+        consecutive_failures: webhook.consecutive_failures,
+        disabled_at: webhook.disabled_at,
+        created_by_uid: webhook.created_by_uid,
+        created_at: webhook.created_at,
+        updated_at: webhook.updated_at,
+    }
+}
+
+pub fn delivery_to_response(delivery: WebhookDelivery) -> WebhookDeliveryResponse {
+    WebhookDeliveryResponse {
+        id: delivery.id,
+        webhook_id: delivery.webhook_id,
+        event_type: delivery.event_type,
+        payload: delivery.payload,
+        next_attempt_at: (delivery.status == WebhookDeliveryStatus::Pending)
+            .then_some(delivery.next_attempt_at),
+        status: delivery.status,
+        attempts: delivery.attempts,
+        last_attempt_at: delivery.last_attempt_at,
+        response_status: delivery.response_status,
+        last_error: delivery.last_error,
+        created_at: delivery.created_at,
+        completed_at: delivery.completed_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_event_types, retry_delay, sign_payload, subscribes_to, validate_webhook_url,
+        WebhookEvent, SUBSCRIBABLE_EVENTS,
+    };
+    use crate::errors::AppError;
+
+    #[test]
+    fn event_types_round_trip() {
+        for event in SUBSCRIBABLE_EVENTS {
+            assert_eq!(WebhookEvent::parse(event.as_str()), Some(event));
+        }
+        assert_eq!(WebhookEvent::parse("ping"), None);
+    }
+
+    #[test]
+    fn parse_event_types_sorts_dedups_and_rejects_unknown() {
+        let parsed = parse_event_types(&[
+            "pin.added".to_string(),
+            "message.created".to_string(),
+            "pin.added".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(parsed, vec!["message.created", "pin.added"]);
+        assert!(matches!(
+            parse_event_types(&["message.sent".to_string()]),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn empty_subscription_receives_every_event() {
+        assert!(subscribes_to(&[], WebhookEvent::MemberLeft));
+        let events = vec!["message.created".to_string()];
+        assert!(subscribes_to(&events, WebhookEvent::MessageCreated));
+        assert!(!subscribes_to(&events, WebhookEvent::MessageDeleted));
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let signature = sign_payload("whsec_test", 1_700_000_000, br#"{"type":"ping"}"#);
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert_eq!(
+            signature,
+            sign_payload("whsec_test", 1_700_000_000, br#"{"type":"ping"}"#)
+        );
+        assert_ne!(
+            signature,
+            sign_payload("whsec_test", 1_700_000_001, br#"{"type":"ping"}"#)
+        );
+        assert_ne!(
+            signature,
+            sign_payload("whsec_other", 1_700_000_000, br#"{"type":"ping"}"#)
+        );
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_cap() {
+        assert_eq!(retry_delay(1).num_seconds(), 30);
+        assert_eq!(retry_delay(2).num_seconds(), 60);
+        assert_eq!(retry_delay(5).num_seconds(), 480);
+        assert_eq!(retry_delay(30).num_seconds(), 6 * 60 * 60);
+    }
+
+    #[test]
+    fn validate_webhook_url_blocks_private_targets_unless_allowed() {
+        assert_eq!(
+            validate_webhook_url(" https://hooks.example.com/in ", false).unwrap(),
+            "https://hooks.example.com/in"
+        );
+        for url in [
+            "http://localhost:8080/hook",
+            "http://127.0.0.1/hook",
+            "http://10.1.2.3/hook",
+            "http://[::1]/hook",
+            "http://169.254.169.254/latest",
+        ] {
+            assert!(
+                matches!(
+                    validate_webhook_url(url, false),
+                    Err(AppError::BadRequest(_))
+                ),
+                "{url}"
+            );
+        }
+        assert!(validate_webhook_url("http://127.0.0.1:9000/hook", true).is_ok());
+        assert!(validate_webhook_url("ftp://example.com/hook", true).is_err());
+        assert!(validate_webhook_url("not a url", true).is_err());
+    }
+}
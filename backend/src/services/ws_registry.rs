@@ -3,10 +3,11 @@
 
 use crate::dto::ws::{PresenceUpdatePayload, ServerWsMessage};
 use crate::metrics::Metrics;
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Notify};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
@@ -28,7 +29,11 @@ impl AppPresenceState {
 #[derive(Debug)]
 pub struct ConnectionEntry {
     pub conn_id: u64,
+    /// Client the connection authenticated as; `None` for tickets without a client.
+    pub client_id: Option<String>,
     pub tx: mpsc::Sender<Arc<ServerWsMessage>>,
+    /// Signalled when the connection must be closed, e.g. after its device was revoked.
+    pub close: Notify,
     /// Unix timestamp (seconds) when we last received a ping from the client.
     pub last_ping_at: AtomicU64,
     pub app_state: AtomicU8,
@@ -88,13 +93,16 @@ impl ConnectionRegistry {
     pub fn register(
         &self,
         uid: i32,
+        client_id: Option<String>,
     ) -> (Arc<ConnectionEntry>, mpsc::Receiver<Arc<ServerWsMessage>>) {
         let conn_id = next_conn_id();
         let (tx, rx) = mpsc::channel(256);
         let now = now_secs();
         let entry = Arc::new(ConnectionEntry {
             conn_id,
+            client_id,
             tx,
+            close: Notify::new(),
             last_ping_at: AtomicU64::new(now),
             app_state: AtomicU8::new(AppPresenceState::Active as u8),
             last_state_at: AtomicU64::new(now),
@@ -120,6 +128,39 @@ impl ConnectionRegistry {
         self.broadcast_presence_to_user(uid);
     }
 
+    /// Ask every connection of `uid` opened by `client_id` to close. Returns how many were signalled;
+    /// each socket task removes its own entry once it exits.
+    pub fn close_client_connections(&self, uid: i32, client_id: &str) -> usize {
+        let Some(vec) = self.inner.get(&uid) else {
+            return 0;
+        };
+        let mut closed = 0;
+        for entry in vec
+            .iter()
+            .filter(|entry| entry.client_id.as_deref() == Some(client_id))
+        {
+            entry.close.notify_one();
+            closed += 1;
+        }
+        closed
+    }
+
+    /// Client IDs with at least one live connection for `uid`.
+    pub fn connected_client_ids(&self, uid: i32) -> HashSet<String> {
+        self.inner
+            .get(&uid)
+            .map(|vec| {
+                vec.iter()
+                    .filter_map(|entry| entry.client_id.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn is_connected(&self, uid: i32) -> bool {
+        self.inner.get(&uid).is_some_and(|vec| !vec.is_empty())
+    }
+
     /// Broadcast a JSON string to all connections for the given user ids. Each uid may have multiple connections.
     /// Failures to send (e.g. full buffer) are logged but do not remove the connection here.
     pub fn broadcast_to_uids(&self, uids: &[i32], message: Arc<ServerWsMessage>) {
@@ -241,7 +282,7 @@ mod tests {
     #[test]
     fn suppresses_push_for_fresh_active_connection() {
         let registry = registry();
-        let (entry, _rx) = registry.register(7);
+        let (entry, _rx) = registry.register(7, None);
         entry.update_ping(AppPresenceState::Active);
 
         assert!(registry.should_suppress_push(7, 30));
@@ -250,7 +291,7 @@ mod tests {
     #[test]
     fn does_not_suppress_push_for_inactive_connection() {
         let registry = registry();
-        let (entry, _rx) = registry.register(7);
+        let (entry, _rx) = registry.register(7, None);
         entry.update_app_state(AppPresenceState::Inactive);
 
         assert!(!registry.should_suppress_push(7, 30));
@@ -259,7 +300,7 @@ mod tests {
     #[test]
     fn does_not_suppress_push_for_stale_connection() {
         let registry = registry();
-        let (entry, _rx) = registry.register(7);
+        let (entry, _rx) = registry.register(7, None);
         entry.update_ping(AppPresenceState::Active);
         entry
             .last_ping_at
@@ -271,11 +312,35 @@ mod tests {
     #[test]
     fn suppresses_push_when_any_connection_is_active() {
         let registry = registry();
-        let (inactive_entry, _rx1) = registry.register(7);
+        let (inactive_entry, _rx1) = registry.register(7, None);
         inactive_entry.update_app_state(AppPresenceState::Inactive);
-        let (active_entry, _rx2) = registry.register(7);
+        let (active_entry, _rx2) = registry.register(7, None);
         active_entry.update_ping(AppPresenceState::Active);
 
         assert!(registry.should_suppress_push(7, 30));
     }
+
+    #[tokio::test]
+    async fn closes_only_connections_of_the_revoked_client() {
+        let registry = registry();
+        let (phone, _rx1) = registry.register(7, Some("phone".to_string()));
+        let (laptop, _rx2) = registry.register(7, Some("laptop".to_string()));
+
+        assert_eq!(registry.close_client_connections(7, "phone"), 1);
+        assert_eq!(registry.close_client_connections(8, "phone"), 0);
+
+        tokio::time::timeout(std::time::Duration::from_secs(1), phone.close.notified())
+            .await
+            .expect("phone connection should be closed");
+        assert!(tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            laptop.close.notified()
+        )
+        .await
+        .is_err());
+        assert_eq!(
+            registry.connected_client_ids(7),
+            HashSet::from(["phone".to_string(), "laptop".to_string()])
+        );
+    }
 }
@@ -21,7 +21,10 @@ pub struct ClientId(pub String);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AuthSource {
+    /// Access token issued by an auth session.
     Jwt,
+    /// JWT handed out by the landing page, the primary credential under `JwtOnly`.
+    PrimaryJwt,
     Legacy,
 }
 
@@ -32,10 +35,23 @@ pub struct AuthContext {
     pub source: AuthSource,
 }
 
-#[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct ServiceTokenPrincipal {
     pub id: i64,
+    /// `None` when the token is not restricted to particular chats.
+    pub allowed_chat_ids: Option<Vec<i64>>,
+}
+
+impl ServiceTokenPrincipal {
+    /// Reject chats outside the token's allowlist, whatever its policies grant.
+    pub fn require_chat_allowed(&self, chat_id: i64) -> Result<(), AppError> {
+        match &self.allowed_chat_ids {
+            Some(allowed) if !allowed.contains(&chat_id) => Err(AppError::Forbidden(
+                "Service token is not allowed in this chat",
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -45,11 +61,30 @@ pub enum Principal {
     ServiceToken(ServiceTokenPrincipal),
 }
 
+/// `typ` of tokens issued by an auth session.
+pub const ACCESS_TOKEN_TYPE: &str = "access";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct AuthClaims {
     pub uid: i32,
     pub cid: String,
+    /// Session generation for `(uid, cid)`; tokens from an older generation are revoked.
+    #[serde(default)]
     pub gen: i32,
+    #[serde(default)]
+    pub iat: i64,
+    /// Required on access tokens; landing-page tokens may omit it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    /// [`ACCESS_TOKEN_TYPE`] on tokens issued by an auth session, absent on landing-page tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+}
+
+impl AuthClaims {
+    pub fn is_access_token(&self) -> bool {
+        self.typ.as_deref() == Some(ACCESS_TOKEN_TYPE)
+    }
 }
 
 impl fmt::Display for CurrentUid {
@@ -61,13 +96,13 @@ impl fmt::Display for CurrentUid {
 static JWT_CRYPTO_PROVIDER: Once = Once::new();
 
 fn jwt_validation() -> Validation {
+    // Validates `exp` when present; `decode_auth_token` requires it on access tokens.
     let mut validation = Validation::default();
-    validation.validate_exp = false;
     validation.required_spec_claims.clear();
     validation
 }
 
-fn ensure_jwt_crypto_provider() {
+pub(crate) fn ensure_jwt_crypto_provider() {
     JWT_CRYPTO_PROVIDER.call_once(|| {
         let _ = jsonwebtoken::crypto::rust_crypto::DEFAULT_PROVIDER.install_default();
     });
@@ -86,26 +121,27 @@ pub fn extract_auth_context(
 ) -> Result<AuthContext, (StatusCode, &'static str)> {
     if let Some(token) = bearer_token(headers)? {
         let claims = decode_auth_token(token, &state.jwt_signing_key)?;
-        return Ok(AuthContext {
-            uid: claims.uid,
-            client_id: Some(claims.cid),
-            source: AuthSource::Jwt,
-        });
+        state.auth_sessions.verify(&claims)?;
+        return Ok(AuthContext::from(claims));
     }
 
     extract_legacy_auth_context(headers, state)
 }
 
-#[allow(dead_code)]
 pub fn extract_principal(
     headers: &HeaderMap,
     state: &crate::AppState,
+    usage: Option<&service_tokens::UsageSlot>,
 ) -> Result<Principal, AppError> {
     if let Some(token) = bearer_token(headers).map_err(AppError::from)? {
         if token.starts_with(service_tokens::TOKEN_PREFIX) {
             let mut conn = state.db.get()?;
             let service_token =
                 service_tokens::authenticate(&mut conn, &state.service_token_hash_key, token)?;
+            if let Some(usage) = usage {
+                usage.record(service_token.id);
+            }
+            service_tokens::consume_rate_limit(&mut conn, &service_token)?;
             return Ok(Principal::ServiceToken(ServiceTokenPrincipal::from(
                 service_token,
             )));
@@ -144,7 +180,9 @@ fn extract_legacy_auth_context(
     }
 }
 
-fn bearer_token(headers: &HeaderMap) -> Result<Option<&str>, (StatusCode, &'static str)> {
+pub(crate) fn bearer_token(
+    headers: &HeaderMap,
+) -> Result<Option<&str>, (StatusCode, &'static str)> {
     let Some(value) = headers.get(AUTHORIZATION) else {
         return Ok(None);
     };
@@ -175,7 +213,9 @@ pub fn decode_auth_token(
         &jwt_validation(),
     )
     .map(|data| data.claims)
-    .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid auth token"))
+    .ok()
+    .filter(|claims| !claims.is_access_token() || claims.exp.is_some())
+    .ok_or((StatusCode::UNAUTHORIZED, "Invalid auth token"))
 }
 
 pub fn encode_auth_token(
@@ -258,7 +298,8 @@ impl FromRequestParts<crate::AppState> for Principal {
         parts: &mut Parts,
         state: &crate::AppState,
     ) -> Result<Self, Self::Rejection> {
-        extract_principal(&parts.headers, state)
+        let usage = parts.extensions.get::<service_tokens::UsageSlot>().cloned();
+        extract_principal(&parts.headers, state, usage.as_ref())
     }
 }
 
@@ -275,9 +316,26 @@ impl FromRequestParts<crate::AppState> for ClientId {
     }
 }
 
+impl From<AuthClaims> for AuthContext {
+    fn from(claims: AuthClaims) -> Self {
+        Self {
+            uid: claims.uid,
+            source: if claims.is_access_token() {
+                AuthSource::Jwt
+            } else {
+                AuthSource::PrimaryJwt
+            },
+            client_id: Some(claims.cid),
+        }
+    }
+}
+
 impl From<AuthenticatedServiceToken> for ServiceTokenPrincipal {
     fn from(value: AuthenticatedServiceToken) -> Self {
-        Self { id: value.id }
+        Self {
+            id: value.id,
+            allowed_chat_ids: value.allowed_chat_ids,
+        }
     }
 }
 
@@ -299,13 +357,21 @@ mod tests {
         );
     }
 
-    #[test]
-    fn auth_token_round_trip_preserves_claims() {
-        let claims = AuthClaims {
+    fn claims_expiring_in(secs: i64) -> AuthClaims {
+        let now = chrono::Utc::now().timestamp();
+        AuthClaims {
             uid: 42,
             cid: "client_123".to_string(),
             gen: 0,
-        };
+            iat: now,
+            exp: Some(now + secs),
+            typ: Some(ACCESS_TOKEN_TYPE.to_string()),
+        }
+    }
+
+    #[test]
+    fn auth_token_round_trip_preserves_claims() {
+        let claims = claims_expiring_in(900);
 
         let token = encode_auth_token(&claims, b"01234567890123456789012345678901").unwrap();
         let decoded = decode_auth_token(&token, b"01234567890123456789012345678901").unwrap();
@@ -315,14 +381,39 @@ mod tests {
 
     #[test]
     fn auth_token_rejects_wrong_key() {
+        let claims = claims_expiring_in(900);
+
+        let token = encode_auth_token(&claims, b"01234567890123456789012345678901").unwrap();
+        let result = decode_auth_token(&token, b"abcdefabcdefabcdefabcdefabcdefab");
+
+        assert_eq!(
+            result,
+            Err((StatusCode::UNAUTHORIZED, "Invalid auth token"))
+        );
+    }
+
+    #[test]
+    fn auth_token_rejects_expired_token() {
+        let claims = claims_expiring_in(-3600);
+
+        let token = encode_auth_token(&claims, b"01234567890123456789012345678901").unwrap();
+        let result = decode_auth_token(&token, b"01234567890123456789012345678901");
+
+        assert_eq!(
+            result,
+            Err((StatusCode::UNAUTHORIZED, "Invalid auth token"))
+        );
+    }
+
+    #[test]
+    fn access_token_without_exp_is_rejected() {
         let claims = AuthClaims {
-            uid: 42,
-            cid: "client_123".to_string(),
-            gen: 0,
+            exp: None,
+            ..claims_expiring_in(900)
         };
 
         let token = encode_auth_token(&claims, b"01234567890123456789012345678901").unwrap();
-        let result = decode_auth_token(&token, b"abcdefabcdefabcdefabcdefabcdefab");
+        let result = decode_auth_token(&token, b"01234567890123456789012345678901");
 
         assert_eq!(
             result,
@@ -342,4 +433,25 @@ mod tests {
             Err((StatusCode::BAD_REQUEST, "X-Client-Id is invalid"))
         );
     }
+
+    #[test]
+    fn chat_allowlist_only_applies_when_set() {
+        let unrestricted = ServiceTokenPrincipal {
+            id: 1,
+            allowed_chat_ids: None,
+        };
+        assert!(unrestricted.require_chat_allowed(10).is_ok());
+
+        let scoped = ServiceTokenPrincipal {
+            id: 1,
+            allowed_chat_ids: Some(vec![10, 20]),
+        };
+        assert!(scoped.require_chat_allowed(20).is_ok());
+        assert!(matches!(
+            scoped.require_chat_allowed(30),
+            Err(AppError::Forbidden(
+                "Service token is not allowed in this chat"
+            ))
+        ));
+    }
 }
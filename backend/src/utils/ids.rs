@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use ferroid::{
     define_snowflake_id,
     futures::SnowflakeGeneratorAsyncTokioExt,
@@ -40,6 +41,13 @@ pub fn new_generator() -> IdGen {
     LockSnowflakeGenerator::new(node_id, MonotonicClock::with_epoch(UNIX_EPOCH))
 }
 
+/// The lowest id the generator could have issued at `at`. Ids for past events (imported
+/// history) count up from here so they sort among live ids by time.
+pub fn first_id_at(at: DateTime<Utc>) -> i64 {
+    let timestamp = u64::try_from(at.timestamp_millis()).unwrap_or(0);
+    WettyChatId::from_components(timestamp, 0, 0).to_raw() as i64
+}
+
 /// Generate next snowflake id as i64 (for gid, message id, attachment_id).
 pub async fn next_id(gen: &IdGen) -> Result<i64, ferroid::generator::Error> {
     let id: WettyChatId = gen.try_next_id_async().await?;